@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, HashSet}, io::Cursor, path::{Path, PathBuf}, sync::Arc, time::{Duration, SystemTime}
+    collections::{HashMap, HashSet}, io::Cursor, path::{Path, PathBuf}, sync::Arc, time::{Duration, Instant, SystemTime}
 };
 
 use auth::{
@@ -10,7 +10,7 @@ use auth::{
     serve_redirect::{self, ProcessAuthorizationError},
 };
 use bridge::{
-    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceWorldSummary, WorldDatapackSummary}, message::{EmbeddedOrRaw, MessageToBackend, MessageToFrontend}, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+    handle::{BackendHandle, BackendReceiver, FrontendHandle}, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, InstanceContentID, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceWorldSummary, WorldDatapackSummary}, java::JavaRuntimeInfo, message::{EmbeddedOrRaw, MessageToBackend, MessageToFrontend}, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
 };
 use image::ImageFormat;
 use indexmap::IndexSet;
@@ -22,8 +22,9 @@ use schema::{
     backend_config::{BackendConfig, ProxyConfig, SyncTargets},
     content::ContentSource,
     curseforge::{CachedCurseforgeFileInfo, CurseforgeGetFilesRequest},
-    instance::InstanceConfiguration,
+    instance::{InstanceConfiguration, InstanceLaunchProfile, InstanceSnapshotReason, LaunchPhase},
     loader::Loader,
+    modification::ModrinthModpackFileDownload,
     modrinth::ModrinthSideRequirement,
 };
 use sha1::{Digest, Sha1};
@@ -36,10 +37,13 @@ use ustr::Ustr;
 use crate::{
     account::{BackendAccountInfo, MinecraftLoginInfo},
     directories::LauncherDirectories,
+    download::DownloadManager,
     id_slab::IdSlab,
     instance::{ContentFolder, Instance},
     launch::Launcher,
+    launch_timing::LaunchTimer,
     metadata::{items::{CurseforgeGetFilesMetadataItem, MinecraftVersionManifestMetadataItem}, manager::MetadataManager},
+    mirror::MirrorTable,
     mod_metadata::ModMetadataManager,
     persistent::Persistent,
 };
@@ -54,7 +58,10 @@ fn build_http_clients(user_agent: &str, proxy_config: &ProxyConfig, proxy_passwo
         .use_rustls_tls()
         .user_agent(user_agent);
 
+    // Stalled downloads need to time out so that they can be resumed
     let mut redirecting_builder = reqwest::ClientBuilder::new()
+        .connect_timeout(Duration::from_secs(30))
+        .read_timeout(Duration::from_secs(30))
         .use_rustls_tls()
         .user_agent(user_agent);
 
@@ -142,8 +149,11 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
 
     let (http_client, redirecting_http_client) = build_http_clients(&user_agent, &proxy_config, proxy_password.as_deref());
 
+    let mirrors = Arc::new(MirrorTable::new(&config.get().mirrors));
+
     let meta = Arc::new(MetadataManager::new(
         http_client.clone(),
+        Arc::clone(&mirrors),
         directories.metadata_dir.clone(),
     ));
 
@@ -177,6 +187,9 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
 
     let (profile_reload_tx, profile_reload_rx) = tokio::sync::mpsc::channel(1);
 
+    let downloads = Arc::new(DownloadManager::new(redirecting_http_client.clone(), Arc::clone(&mirrors), &config.get().downloads));
+    let config = Arc::new(RwLock::new(config));
+
     let mut state = BackendState {
         self_handle,
         send: send.clone(),
@@ -186,13 +199,18 @@ pub fn start(launcher_dir: PathBuf, send: FrontendHandle, self_handle: BackendHa
         instance_state: Arc::new(RwLock::new(state_instances)),
         file_watching: Arc::new(RwLock::new(state_file_watching)),
         directories: Arc::clone(&directories),
-        launcher: Launcher::new(meta, directories, send),
+        launcher: Launcher::new(meta, directories, send, Arc::clone(&config), Arc::clone(&downloads)),
         mod_metadata_manager: Arc::new(mod_metadata_manager),
         account_info: Arc::new(RwLock::new(account_info)),
-        config: Arc::new(RwLock::new(config)),
+        config,
         secret_storage: Arc::new(OnceCell::new()),
         head_cache: Default::default(),
         profile_reload_tx,
+        next_background_update_check: Arc::new(RwLock::new(Instant::now() + crate::content_update::BACKGROUND_UPDATE_CHECK_DELAY)),
+        java_runtimes: Default::default(),
+        downloads,
+        mirrors,
+        last_connectivity_probe: Default::default(),
     };
 
     log::debug!("Doing initial backend load");
@@ -251,6 +269,12 @@ pub struct BackendState {
     pub secret_storage: Arc<OnceCell<Result<PlatformSecretStorage, SecretStorageError>>>,
     pub head_cache: Arc<RwLock<FxHashMap<Arc<str>, HeadCacheEntry>>>,
     pub profile_reload_tx: tokio::sync::mpsc::Sender<()>,
+    pub next_background_update_check: Arc<RwLock<Instant>>,
+    pub java_runtimes: Arc<RwLock<Option<Arc<[JavaRuntimeInfo]>>>>,
+    pub downloads: Arc<DownloadManager>,
+    pub mirrors: Arc<MirrorTable>,
+    /// When connectivity was last probed and whether the network could be reached
+    pub last_connectivity_probe: Arc<RwLock<Option<(Instant, bool)>>>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize, Default)]
@@ -291,7 +315,12 @@ impl BackendState {
     ) {
         log::info!("Starting backend");
 
-        tokio::task::spawn(crate::update::check_for_updates(self.redirecting_http_client.clone(), self.send.clone()));
+        let backend = self.clone();
+        tokio::task::spawn(async move {
+            if !backend.update_offline_mode().await {
+                crate::update::check_for_updates(backend.redirecting_http_client.clone(), backend.send.clone()).await;
+            }
+        });
 
         // Pre-fetch version manifest
         self.meta.load(&MinecraftVersionManifestMetadataItem).await;
@@ -424,10 +453,13 @@ impl BackendState {
                 root_path: instance.resolve_real_root_path(),
                 dot_minecraft_folder: instance.dot_minecraft_path.clone(),
                 configuration: instance.configuration.get().clone(),
+                stats: instance.stats.get().clone(),
                 worlds_state: Arc::clone(&instance.worlds_state),
                 servers_state: Arc::clone(&instance.servers_state),
                 mods_state: Arc::clone(&instance.content_state[ContentFolder::Mods].load_state),
                 resource_packs_state: Arc::clone(&instance.content_state[ContentFolder::ResourcePacks].load_state),
+                shader_packs_state: Arc::clone(&instance.content_state[ContentFolder::ShaderPacks].load_state),
+                datapacks_state: Arc::clone(&instance.content_state[ContentFolder::Datapacks].load_state),
             };
             self.send.send(message);
             if instance.status() == bridge::instance::InstanceStatus::Running {
@@ -486,21 +518,54 @@ impl BackendState {
         self.meta.expire().await;
         self.mod_metadata_manager.write_changes();
 
+        if self.config.write().get().background_update_checks && !self.is_offline() {
+            let now = Instant::now();
+            let mut next_background_update_check = self.next_background_update_check.write();
+            if now >= *next_background_update_check {
+                *next_background_update_check = now + crate::content_update::BACKGROUND_UPDATE_CHECK_INTERVAL;
+                drop(next_background_update_check);
+                tokio::task::spawn(self.clone().run_background_update_check());
+            }
+        }
+
+        let mut exited = Vec::new();
         let mut instance_state = self.instance_state.write();
         for instance in instance_state.instances.iter_mut() {
-            if let Some(child) = &mut instance.child
-                && !matches!(child.try_wait(), Ok(None))
-            {
-                log::debug!("Child process is no longer alive");
-                instance.child = None;
-                instance.clear_running_pid();
-                self.send.send(instance.create_modify_message());
-                continue;
+            let session_started_unix_ms = instance.session_started_unix_ms();
+            if let Some(child) = &mut instance.child {
+                let status = child.try_wait();
+                if !matches!(status, Ok(None)) {
+                    log::debug!("Child process is no longer alive");
+                    let exit_code = status.ok().flatten().and_then(|status| status.code());
+                    instance.child = None;
+                    instance.clear_running_pid(exit_code);
+                    instance.run_post_exit_hook(&self.send);
+                    self.send.send(instance.create_modify_message());
+                    if exit_code != Some(0) && let Some(started_unix_ms) = session_started_unix_ms {
+                        exited.push((instance.id, started_unix_ms, exit_code));
+                    }
+                    continue;
+                }
             }
 
             if instance.child.is_none() && instance.refresh_running_pid() {
+                instance.run_post_exit_hook(&self.send);
                 self.send.send(instance.create_modify_message());
+                if let Some(started_unix_ms) = session_started_unix_ms {
+                    exited.push((instance.id, started_unix_ms, None));
+                }
+            }
+        }
+        drop(instance_state);
+
+        for (id, started_unix_ms, exit_code) in exited {
+            if exit_code.is_some() {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.on_bisect_instance_crashed(id).await;
+                });
             }
+            tokio::task::spawn(self.clone().analyze_crash(id, started_unix_ms, exit_code));
         }
     }
 
@@ -779,9 +844,41 @@ impl BackendState {
         });
     }
 
-    pub async fn prelaunch(&self, id: InstanceID, modal_action: &ModalAction) -> Vec<PathBuf> {
-        self.apply_syncing_to_instance(id);
-        self.prelaunch_apply_modpacks(id, modal_action).await
+    pub async fn prelaunch(&self, id: InstanceID, modal_action: &ModalAction, timer: &LaunchTimer) -> Vec<PathBuf> {
+        {
+            let _timer = timer.start(LaunchPhase::FileSyncing);
+            self.apply_syncing_to_instance(id);
+        }
+        timer.time(LaunchPhase::ModpackApplication, self.prelaunch_apply_modpacks(id, modal_action)).await
+    }
+
+    pub fn set_content_enabled(&self, id: InstanceID, content_ids: Vec<InstanceContentID>, enabled: bool) {
+        let mut instance_state = self.instance_state.write();
+        let Some(instance) = instance_state.instances.get_mut(id) else {
+            return;
+        };
+
+        let mut reload = FxHashSet::default();
+
+        for content_id in content_ids {
+            if let Some((instance_mod, folder)) = instance.try_get_content(content_id) {
+                if instance_mod.enabled == enabled {
+                    continue;
+                }
+
+                let mut new_path = instance_mod.path.to_path_buf();
+                if instance_mod.enabled {
+                    new_path.add_extension("disabled");
+                } else {
+                    new_path.set_extension("");
+                };
+
+                let _ = std::fs::rename(&instance_mod.path, new_path);
+                reload.insert((id, folder));
+            }
+        }
+
+        instance_state.reload_immediately.extend(reload);
     }
 
     pub fn apply_syncing_to_instance(&self, id: InstanceID) {
@@ -798,6 +895,52 @@ impl BackendState {
         }
     }
 
+    pub fn is_modpack_download_enabled(&self, summary: &InstanceContentSummary, download: &ModrinthModpackFileDownload) -> bool {
+        if let Some(env) = download.env {
+            if env.client == ModrinthSideRequirement::Unsupported {
+                return false;
+            }
+        }
+
+        if let Some(metadata) = self.mod_metadata_manager.get_cached_by_sha1(&*download.hashes.sha1) {
+            if let Some(id) = &metadata.id && summary.disabled_children.disabled_ids.contains(id) {
+                return false;
+            }
+            if let Some(name) = &metadata.name && summary.disabled_children.disabled_names.contains(name) {
+                return false;
+            }
+        }
+
+        !summary.disabled_children.disabled_filenames.contains(&download.path)
+            && !summary.disabled_children.deleted_filenames.contains(&download.path)
+    }
+
+    /// Same as `is_modpack_download_enabled`, for the file at `index` of a CurseForge modpack
+    pub fn is_curseforge_modpack_file_enabled(&self, summary: &InstanceContentSummary, index: usize) -> bool {
+        let ContentType::CurseforgeModpack { summaries, .. } = &summary.content_summary.extra else {
+            return true;
+        };
+        let Some((content_summary, file_info)) = summaries.get(index) else {
+            return true;
+        };
+        let disabled_children = &summary.disabled_children;
+
+        if let Some(content_summary) = content_summary {
+            if let Some(id) = &content_summary.id && disabled_children.disabled_ids.contains(id) {
+                return false;
+            }
+            if let Some(name) = &content_summary.name && disabled_children.disabled_names.contains(name) {
+                return false;
+            }
+        }
+
+        let Some(file_info) = file_info else {
+            return true;
+        };
+        !disabled_children.disabled_filenames.contains(&file_info.filename)
+            && !disabled_children.deleted_filenames.contains(&file_info.filename)
+    }
+
     pub async fn prelaunch_apply_modpacks(&self, id: InstanceID, modal_action: &ModalAction) -> Vec<PathBuf> {
         let (loader, minecraft_version, mod_dir) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
             let configuration = instance.configuration.get();
@@ -810,6 +953,9 @@ impl BackendState {
             return Vec::new();
         };
 
+        // Files that aren't in the content library yet can't be downloaded, only the ones that are get linked
+        let offline = self.is_offline();
+
         struct HashedDownload {
             sha1: Arc<str>,
             path: Arc<str>,
@@ -846,25 +992,7 @@ impl BackendState {
             if let ContentType::ModrinthModpack { downloads, overrides, .. } = &summary.content_summary.extra {
                 let downloads = downloads.clone();
 
-                let filtered_downloads = downloads.iter().filter(|dl| {
-                    if let Some(env) = dl.env {
-                        if env.client == ModrinthSideRequirement::Unsupported {
-                            return false;
-                        }
-                    }
-
-                    if let Some(metadata) = self.mod_metadata_manager.get_cached_by_sha1(&*dl.hashes.sha1) {
-                        if let Some(id) = &metadata.id && summary.disabled_children.disabled_ids.contains(id) {
-                            return false;
-                        }
-                        if let Some(name) = &metadata.name && summary.disabled_children.disabled_names.contains(name) {
-                            return false;
-                        }
-                    }
-
-                    !summary.disabled_children.disabled_filenames.contains(&dl.path)
-                        && !summary.disabled_children.deleted_filenames.contains(&dl.path)
-                });
+                let filtered_downloads = downloads.iter().filter(|dl| self.is_modpack_download_enabled(summary, dl));
 
                 let content_install = ContentInstall {
                     target: bridge::install::InstallTarget::Library,
@@ -886,7 +1014,9 @@ impl BackendState {
                     }).collect(),
                 };
 
-                self.install_content(content_install, modal_action.clone()).await;
+                if !offline {
+                    self.install_content(content_install, modal_action.clone()).await;
+                }
 
                 modpack_installs.push(ModpackInstall {
                     hashed_downloads: filtered_downloads.map(|download| {
@@ -964,7 +1094,7 @@ impl BackendState {
                             });
                         }
 
-                        if !files_to_install.is_empty() {
+                        if !files_to_install.is_empty() && !offline {
                             self.install_content(ContentInstall {
                                 target: bridge::install::InstallTarget::Library,
                                 loader_hint: loader,
@@ -990,6 +1120,9 @@ impl BackendState {
             return Vec::new();
         };
 
+        // Snapshot before the first override replaces a file, most launches don't change anything
+        let mut snapshotted = false;
+
         for modpack_install in modpack_installs {
             let overrides = modpack_install.overrides;
             let content_library_dir = &self.directories.content_library_dir.clone();
@@ -1055,6 +1188,10 @@ impl BackendState {
                 };
 
                 let path = crate::create_content_library_path(content_library_dir, expected_hash, dest_path.extension());
+                if offline && !path.is_file() {
+                    log::warn!("Skipping modpack file {} while offline, it hasn't been downloaded yet", file.path);
+                    continue;
+                }
 
                 if file.add_content_folder_to_path {
                     let Some(summary) = self.mod_metadata_manager.get_path(&path) else {
@@ -1062,6 +1199,7 @@ impl BackendState {
                     };
                     match summary.extra {
                         ContentType::Fabric
+                        | ContentType::Quilt
                         | ContentType::Forge
                         | ContentType::LegacyForge
                         | ContentType::NeoForge
@@ -1073,6 +1211,12 @@ impl BackendState {
                         ContentType::ResourcePack => {
                             dest_path = SafePath::new(&format!("resourcepacks/{}", dest_path.as_str())).unwrap();
                         }
+                        ContentType::ShaderPack => {
+                            dest_path = SafePath::new(&format!("shaderpacks/{}", dest_path.as_str())).unwrap();
+                        }
+                        ContentType::Datapack => {
+                            dest_path = SafePath::new(&format!("datapacks/{}", dest_path.as_str())).unwrap();
+                        }
                     }
                 }
 
@@ -1090,6 +1234,10 @@ impl BackendState {
                             aux.applied_overrides.filename_to_hash.insert(file.path.clone(), file.sha1.clone());
                             aux_changed = true;
                         }
+                        if !snapshotted {
+                            self.snapshot_instance(id, InstanceSnapshotReason::Modpack).await;
+                            snapshotted = true;
+                        }
 
                         let _ = std::fs::create_dir_all(dest_path.parent().unwrap());
                         let _ = std::fs::copy(path, dest_path);
@@ -1131,6 +1279,10 @@ impl BackendState {
                                 aux.applied_overrides.filename_to_hash.insert(rel_path.as_str().into(), sha1.into());
                                 aux_changed = true;
                             }
+                            if !snapshotted {
+                                self.snapshot_instance(id, InstanceSnapshotReason::Modpack).await;
+                                snapshotted = true;
+                            }
 
                             let _ = std::fs::create_dir_all(dest_path.parent().unwrap());
                             let _ = std::fs::copy(path, dest_path);
@@ -1203,6 +1355,18 @@ impl BackendState {
                         resource_packs: Arc::clone(&content)
                     });
                 },
+                ContentFolder::ShaderPacks => {
+                    self.send.send(MessageToFrontend::InstanceShaderPacksUpdated {
+                        id,
+                        shader_packs: Arc::clone(&content)
+                    });
+                },
+                ContentFolder::Datapacks => {
+                    self.send.send(MessageToFrontend::InstanceDatapacksUpdated {
+                        id,
+                        datapacks: Arc::clone(&content)
+                    });
+                },
             }
         }
 
@@ -1396,12 +1560,22 @@ impl BackendState {
 
     }
 
-    pub async fn get_login_info(&self, modal_action: &ModalAction) -> Option<MinecraftLoginInfo> {
+    pub fn get_launch_profile(&self, id: InstanceID, name: &str) -> Option<InstanceLaunchProfile> {
+        let mut instance_state = self.instance_state.write();
+        let instance = instance_state.instances.get_mut(id)?;
+        instance.configuration.get().launch_profile(name).cloned()
+    }
+
+    /// Logs in with `account`, or the selected account if that's None
+    pub async fn get_login_info(&self, account: Option<Uuid>, modal_action: &ModalAction) -> Option<MinecraftLoginInfo> {
         let selected_account = {
             let mut account_info = self.account_info.write();
             let account_info = account_info.get();
 
-            let mut selected_account = account_info.selected_account;
+            if let Some(uuid) = account && !account_info.accounts.contains_key(&uuid) {
+                log::warn!("Account {uuid} no longer exists, using the selected account instead");
+            }
+            let mut selected_account = account.filter(|uuid| account_info.accounts.contains_key(uuid)).or(account_info.selected_account);
 
             if let Some(uuid) = selected_account {
                 if let Some(account) = account_info.accounts.get(&uuid) {
@@ -1420,6 +1594,18 @@ impl BackendState {
             selected_account
         };
 
+        if self.is_offline() {
+            let login_info = match selected_account {
+                Some(uuid) => self.offline_login_info(uuid).await,
+                None => None,
+            };
+            if login_info.is_none() {
+                modal_action.set_error_message("Unable to log in while offline, select an account that has logged in before".into());
+                modal_action.set_finished();
+            }
+            return login_info;
+        }
+
         let Some((profile, access_token)) = self.login_flow(modal_action, selected_account).await else {
             return None;
         };
@@ -2,25 +2,23 @@ use std::{io::{BufRead, Read}, sync::Arc, time::{Duration, SystemTime}};
 
 use auth::{credentials::AccountCredentials, models::{MinecraftAccessToken, MinecraftProfileResponse}, secret::PlatformSecretStorage};
 use bridge::{
-    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{InstanceStatus, ContentType, ContentSummary}, message::{BackendConfigWithPassword, LogFiles, MessageToBackend, MessageToFrontend, MinecraftCapeInfo, MinecraftProfileInfo, MinecraftSkinInfo}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath, serial::AtomicOptionSerial
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{InstanceStatus, ContentType}, message::{BackendConfigWithPassword, LogFiles, MessageToBackend, MessageToFrontend, MinecraftCapeInfo, MinecraftProfileInfo, MinecraftSkinInfo, QuickPlayLaunch}, meta::MetadataResult, modal_action::{ModalAction, ModalActionVisitUrl, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath, serial::AtomicOptionSerial
 };
-use futures::TryFutureExt;
 use reqwest::StatusCode;
 use rustc_hash::FxHashSet;
 use schema::{
     auxiliary::AuxiliaryContentMeta,
     content::ContentSource,
-    curseforge::{CachedCurseforgeFileInfo, CurseforgeGetFilesRequest, CurseforgeGetModFilesRequest, CurseforgeModLoaderType},
-    modrinth::ModrinthLoader,
+    curseforge::{CachedCurseforgeFileInfo, CurseforgeGetFilesRequest},
+    instance::{InstanceSnapshotReason, LaunchPhase},
     version::{LaunchArgument, LaunchArgumentValue},
 };
 use serde::Deserialize;
-use strum::IntoEnumIterator;
-use tokio::{io::AsyncBufReadExt, sync::Semaphore};
+use tokio::io::AsyncBufReadExt;
 use ustr::Ustr;
 
 use crate::{
-    BackendState, LoginError, account::BackendAccount, arcfactory::ArcStrFactory, instance::ContentFolder, launch::{ArgumentExpansionKey, LaunchError}, log_reader, metadata::{items::{AssetsIndexMetadataItem, CurseforgeGetFilesMetadataItem, CurseforgeGetModFilesMetadataItem, CurseforgeSearchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::MetaLoadError}, mod_metadata::{ContentUpdateAction, ContentUpdateKey}
+    BackendState, LoginError, account::BackendAccount, arcfactory::ArcStrFactory, instance::ContentFolder, launch::{ArgumentExpansionKey, LaunchError}, launch_cache::LaunchCache, launch_timing::LaunchTimer, log_reader, metadata::items::{AssetsIndexMetadataItem, CurseforgeGetFilesMetadataItem, CurseforgeGetModFilesMetadataItem, CurseforgeSearchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, ModrinthProjectMetadataItem, ModrinthProjectVersionsMetadataItem, ModrinthSearchMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem, QuiltLoaderManifestMetadataItem}, mod_metadata::{ContentUpdateAction, ContentUpdateKey}
 };
 
 /// Extract stable texture key from skin URL (last path segment). Used for deduplication.
@@ -848,6 +846,10 @@ impl BackendState {
                             let (result, handle) = meta.fetch_with_keepalive(&FabricLoaderManifestMetadataItem, force_reload).await;
                             (result.map(MetadataResult::FabricLoaderManifest), handle)
                         },
+                        bridge::meta::MetadataRequest::QuiltLoaderManifest => {
+                            let (result, handle) = meta.fetch_with_keepalive(&QuiltLoaderManifestMetadataItem, force_reload).await;
+                            (result.map(MetadataResult::QuiltLoaderManifest), handle)
+                        },
                         bridge::meta::MetadataRequest::ForgeMavenManifest => {
                             let (result, handle) = meta.fetch_with_keepalive(&ForgeInstallerMavenMetadataItem, force_reload).await;
                             (result.map(MetadataResult::ForgeMavenManifest), handle)
@@ -860,6 +862,10 @@ impl BackendState {
                             let (result, handle) = meta.fetch_with_keepalive(&ModrinthSearchMetadataItem(search), force_reload).await;
                             (result.map(MetadataResult::ModrinthSearchResult), handle)
                         },
+                        bridge::meta::MetadataRequest::ModrinthProject(ref project) => {
+                            let (result, handle) = meta.fetch_with_keepalive(&ModrinthProjectMetadataItem(project), force_reload).await;
+                            (result.map(MetadataResult::ModrinthProjectResult), handle)
+                        },
                         bridge::meta::MetadataRequest::ModrinthProjectVersions(ref project_versions) => {
                             let (result, handle) = meta.fetch_with_keepalive(&ModrinthProjectVersionsMetadataItem(project_versions), force_reload).await;
                             (result.map(MetadataResult::ModrinthProjectVersionsResult), handle)
@@ -937,6 +943,12 @@ impl BackendState {
             MessageToBackend::RequestLoadResourcePacks { id } => {
                 tokio::task::spawn(self.clone().load_instance_content(id, ContentFolder::ResourcePacks));
             },
+            MessageToBackend::RequestLoadShaderPacks { id } => {
+                tokio::task::spawn(self.clone().load_instance_content(id, ContentFolder::ShaderPacks));
+            },
+            MessageToBackend::RequestLoadDatapacks { id } => {
+                tokio::task::spawn(self.clone().load_instance_content(id, ContentFolder::Datapacks));
+            },
             MessageToBackend::CreateInstance { name, version, loader, icon } => {
                 self.create_instance(&name, &version, loader, icon).await;
             },
@@ -983,6 +995,11 @@ impl BackendState {
                 }
             },
             MessageToBackend::SetInstanceMinecraftVersion { id, version } => {
+                let changed = self.instance_state.write().instances.get_mut(id)
+                    .is_some_and(|instance| instance.configuration.get().minecraft_version != version);
+                if changed {
+                    self.snapshot_instance(id, InstanceSnapshotReason::MinecraftVersion).await;
+                }
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
                         configuration.minecraft_version = version;
@@ -990,6 +1007,11 @@ impl BackendState {
                 }
             },
             MessageToBackend::SetInstanceLoader { id, loader } => {
+                let changed = self.instance_state.write().instances.get_mut(id)
+                    .is_some_and(|instance| instance.configuration.get().loader != loader);
+                if changed {
+                    self.snapshot_instance(id, InstanceSnapshotReason::Loader).await;
+                }
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
                         configuration.loader = loader;
@@ -1026,6 +1048,56 @@ impl BackendState {
                     });
                 }
             },
+            MessageToBackend::SetInstanceWindow { id, window } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.window = Some(window);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceGameArguments { id, game_arguments } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.game_arguments = Some(game_arguments);
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceDemo { id, demo } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.demo = demo;
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceAutoJoinServer { id, server } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.auto_join_server = server;
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceLaunchProfiles { id, launch_profiles } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.launch_profiles = launch_profiles;
+                    });
+                    LaunchCache::invalidate(&instance.root_path);
+                }
+            },
+            MessageToBackend::SetInstanceEnvironment { id, environment } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.environment = environment;
+                    });
+                }
+            },
+            MessageToBackend::SetInstanceHooks { id, hooks } => {
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    instance.configuration.modify(|configuration| {
+                        configuration.hooks = Some(hooks);
+                    });
+                }
+            },
             MessageToBackend::SetInstanceJvmFlags { id, jvm_flags } => {
                 if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     instance.configuration.modify(|configuration| {
@@ -1038,6 +1110,7 @@ impl BackendState {
                     instance.configuration.modify(|configuration| {
                         configuration.jvm_binary = Some(jvm_binary);
                     });
+                    LaunchCache::invalidate(&instance.root_path);
                 }
             },
             MessageToBackend::SetInstanceLinuxWrapper { id, linux_wrapper } => {
@@ -1055,23 +1128,36 @@ impl BackendState {
                 }
             },
             MessageToBackend::KillInstance { id } => {
-                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                let mut instance_state = self.instance_state.write();
+                if let Some(instance) = instance_state.instances.get_mut(id) {
                     if let Some(mut child) = instance.child.take() {
                         let result = child.kill();
-                        instance.clear_running_pid();
+                        instance.clear_running_pid(None);
+                        let post_exit_hook = instance.post_exit_hook.take();
                         if result.is_err() {
                             self.send.send_error("Failed to kill instance");
                             log::error!("Failed to kill instance: {:?}", result.unwrap_err());
                         }
 
                         self.send.send(instance.create_modify_message());
+                        drop(instance_state);
+
+                        // Waiting for the process to exit can block, so it's done without holding the instance state
+                        let send = self.send.clone();
+                        tokio::task::spawn_blocking(move || {
+                            _ = child.wait();
+                            if let Some(hook) = post_exit_hook {
+                                hook.run_post_exit(&send);
+                            }
+                        });
                     } else if let Some(pid) = instance.running_pid {
                         let result = crate::instance::Instance::kill_pid(pid);
                         if result.is_err() {
                             self.send.send_error("Failed to kill instance");
                             log::error!("Failed to kill instance PID {}: {:?}", pid, result.unwrap_err());
                         } else {
-                            instance.clear_running_pid();
+                            instance.clear_running_pid(None);
+                            instance.run_post_exit_hook(&self.send);
                             self.send.send(instance.create_modify_message());
                         }
                     } else {
@@ -1085,28 +1171,49 @@ impl BackendState {
             MessageToBackend::StartInstance {
                 id,
                 quick_play,
+                profile,
                 allow_running_instance,
                 modal_action,
             } => {
-                let Some(login_info) = self.get_login_info(&modal_action).await else {
+                self.update_offline_mode_if_stale().await;
+
+                let profile = if let Some(profile) = profile {
+                    let Some(found) = self.get_launch_profile(id, &profile) else {
+                        self.send.send_error(format!("Can't launch instance, unknown launch profile {profile}"));
+                        modal_action.set_error_message(format!("Can't launch instance, unknown launch profile {profile}").into());
+                        modal_action.set_finished();
+                        return;
+                    };
+                    Some(found)
+                } else {
+                    None
+                };
+                let quick_play = quick_play.or_else(|| profile.as_ref()?.quick_play.as_ref().map(QuickPlayLaunch::from));
+
+                let timer = LaunchTimer::default();
+
+                let login_info = timer.time(LaunchPhase::Login, self.get_login_info(profile.as_ref().and_then(|profile| profile.account), &modal_action)).await;
+                let Some(login_info) = login_info else {
                     return;
                 };
 
                 let add_mods = tokio::select! {
-                    add_mods = self.prelaunch(id, &modal_action) => add_mods,
+                    add_mods = self.prelaunch(id, &modal_action, &timer) => add_mods,
                     _ = modal_action.request_cancel.cancelled() => {
+                        self.store_launch_timings(id, &timer);
                         self.send.send(MessageToFrontend::CloseModal);
                         return;
                     }
                 };
 
                 if modal_action.error.read().is_some() {
+                    self.store_launch_timings(id, &timer);
                     modal_action.set_finished();
                     self.send.send(MessageToFrontend::Refresh);
                     return;
                 }
 
-                let (dot_minecraft, configuration) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                let (name, root_path, dot_minecraft, configuration) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     if (instance.child.is_some() || instance.running_pid.is_some()) && !allow_running_instance {
                         self.send.send_warning("Can't launch instance, already running");
                         modal_action.set_error_message("Can't launch instance, already running".into());
@@ -1119,7 +1226,11 @@ impl BackendState {
                     });
                     self.send.send(instance.create_modify_message_with_status(InstanceStatus::Launching));
 
-                    (instance.dot_minecraft_path.clone(), instance.configuration.get().clone())
+                    let configuration = match &profile {
+                        Some(profile) => instance.configuration.get().with_launch_profile(profile),
+                        None => instance.configuration.get().clone(),
+                    };
+                    (instance.name, instance.root_path.clone(), instance.dot_minecraft_path.clone(), configuration)
                 } else {
                     self.send.send_error("Can't launch instance, unknown id");
                     modal_action.set_error_message("Can't launch instance, unknown id".into());
@@ -1127,10 +1238,13 @@ impl BackendState {
                     return;
                 };
 
+                let mods = self.clone().load_instance_content(id, ContentFolder::Mods).await.unwrap_or_else(|| Arc::from([]));
+
                 let launch_tracker = ProgressTracker::new(Arc::from("Launching"), self.send.clone());
                 modal_action.trackers.push(launch_tracker.clone());
 
-                let result = self.launcher.launch(&self.redirecting_http_client, dot_minecraft, configuration, quick_play, login_info, add_mods, &launch_tracker, &modal_action).await;
+                let result = self.launcher.launch(name, root_path, dot_minecraft, configuration, profile.map(|profile| profile.name), quick_play, login_info, add_mods, &mods, &launch_tracker, &modal_action, &timer).await;
+                self.store_launch_timings(id, &timer);
 
                 if matches!(result, Err(LaunchError::CancelledByUser)) {
                     self.send.send(MessageToFrontend::CloseModal);
@@ -1142,7 +1256,7 @@ impl BackendState {
 
                 let is_err = result.is_err();
                 match result {
-                    Ok(mut child) => {
+                    Ok((mut child, post_exit_hook)) => {
                         let pid = child.id();
                         if !self.config.write().get().dont_open_game_output_when_launching {
                             if let Some(stdout) = child.stdout.take() {
@@ -1156,7 +1270,7 @@ impl BackendState {
                         child.stdout.take();
 
                         if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                            instance.set_running_pid(pid);
+                            instance.set_running_pid(pid, post_exit_hook);
                             instance.child = Some(child);
                         }
                     },
@@ -1178,32 +1292,7 @@ impl BackendState {
 
             },
             MessageToBackend::SetContentEnabled { id, content_ids: mod_ids, enabled } => {
-                let mut instance_state = self.instance_state.write();
-                let Some(instance) = instance_state.instances.get_mut(id) else {
-                    return;
-                };
-
-                let mut reload = FxHashSet::default();
-
-                for mod_id in mod_ids {
-                    if let Some((instance_mod, folder)) = instance.try_get_content(mod_id) {
-                        if instance_mod.enabled == enabled {
-                            return;
-                        }
-
-                        let mut new_path = instance_mod.path.to_path_buf();
-                        if instance_mod.enabled {
-                            new_path.add_extension("disabled");
-                        } else {
-                            new_path.set_extension("");
-                        };
-
-                        let _ = std::fs::rename(&instance_mod.path, new_path);
-                        reload.insert((id, folder));
-                    }
-                }
-
-                instance_state.reload_immediately.extend(reload);
+                self.set_content_enabled(id, mod_ids, enabled);
             },
             MessageToBackend::SetContentChildEnabled { id, content_id: mod_id, child_id, child_name, child_filename, enabled, delete } => {
                 let mut instance_state = self.instance_state.write();
@@ -2028,286 +2117,9 @@ impl BackendState {
                 instance_state.reload_immediately.extend(reload);
             },
             MessageToBackend::UpdateCheck { instance: id, modal_action } => {
-                let (loader, version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                    let configuration = instance.configuration.get();
-                    (configuration.loader, configuration.minecraft_version)
-                } else {
-                    self.send.send_error("Can't update instance, unknown id");
-                    modal_action.set_error_message("Can't update instance, unknown id".into());
-                    modal_action.set_finished();
-                    return;
-                };
-
-                let mut content = Vec::new();
-                for folder in ContentFolder::iter() {
-                    let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
-                        modal_action.set_finished();
-                        return;
-                    };
-                    content.extend_from_slice(&*summaries);
-                }
-
-                let modrinth_loader = loader.as_modrinth_loader();
-                if modrinth_loader == ModrinthLoader::Unknown {
-                    modal_action.set_error_message("Unable to update instance, unsupported loader".into());
-                    modal_action.set_finished();
-                    return;
-                }
-
-                let tracker = ProgressTracker::new("Checking content".into(), self.send.clone());
-                tracker.set_total(content.len());
-                modal_action.trackers.push(tracker.clone());
-
-                let semaphore = Semaphore::new(8);
-
-                let mod_params = &VersionUpdateParameters {
-                    loaders: [modrinth_loader].into(),
-                    game_versions: [version].into(),
-                };
-
-                let fabric_mod_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::Fabric].into(),
-                    game_versions: [version].into(),
-                };
-
-                let forge_mod_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::Forge].into(),
-                    game_versions: [version].into(),
-                };
-
-                let neoforge_mod_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::NeoForge].into(),
-                    game_versions: [version].into(),
-                };
-
-                let resourcepack_params = &VersionUpdateParameters {
-                    loaders: [ModrinthLoader::Minecraft].into(),
-                    game_versions: [version].into(),
-                };
-
-                let modrinth_modpack_params = &VersionV3UpdateParameters {
-                    loaders: ["mrpack".into()].into(),
-                    loader_fields: VersionV3LoaderFields {
-                        mrpack_loaders: [modrinth_loader].into(),
-                        game_versions: [version].into(),
-                    },
-                };
-
-                let meta = self.meta.clone();
-
-                let mut futures = Vec::new();
-
-                struct UpdateResult {
-                    mod_summary: Arc<ContentSummary>,
-                    action: ContentUpdateAction,
-                }
-
-                { // Scope is needed so await doesn't complain about the non-send RwLockReadGuard
-                    let sources = self.mod_metadata_manager.read_content_sources();
-                    for summary in content.iter() {
-                        let source = sources.get(&summary.content_summary.hash).unwrap_or(ContentSource::Manual);
-                        let semaphore = &semaphore;
-                        let meta = &meta;
-                        let tracker = &tracker;
-                        futures.push(async move {
-                            match source {
-                                ContentSource::Manual => {
-                                    tracker.add_count(1);
-                                    tracker.notify();
-                                    Ok(ContentUpdateAction::ManualInstall)
-                                },
-                                ContentSource::ModrinthUnknown | ContentSource::ModrinthProject { .. } => {
-                                    let permit = semaphore.acquire().await.unwrap();
-                                    let result = match summary.content_summary.extra {
-                                        ContentType::Fabric => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: fabric_mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::Forge | ContentType::LegacyForge => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: forge_mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::NeoForge => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: neoforge_mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::CurseforgeModpack { .. } => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::JavaModule => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: mod_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::ModrinthModpack { .. } => {
-                                            meta.fetch(&ModrinthV3VersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: modrinth_modpack_params.clone()
-                                            }).await
-                                        },
-                                        ContentType::ResourcePack => {
-                                            meta.fetch(&ModrinthVersionUpdateMetadataItem {
-                                                sha1: hex::encode(summary.content_summary.hash).into(),
-                                                params: resourcepack_params.clone()
-                                            }).await
-                                        },
-                                    };
-                                    drop(permit);
-
-                                    tracker.add_count(1);
-                                    tracker.notify();
-
-                                    if let Err(MetaLoadError::NonOK(404)) = result {
-                                        return Ok(ContentUpdateAction::ErrorNotFound);
-                                    }
-
-                                    let result = result?;
-
-                                    if let ContentSource::ModrinthProject { ref project } = source {
-                                        if &result.0.project_id != project {
-                                            log::error!("Refusing to update {:?}, mismatched project ids: expected {}, got {}",
-                                                summary.content_summary.hash, project, &result.0.project_id);
-                                            return Ok(ContentUpdateAction::ErrorNotFound);
-                                        }
-                                    }
-
-                                    let install_file = result
-                                        .0
-                                        .files
-                                        .iter()
-                                        .find(|file| file.primary)
-                                        .unwrap_or(result.0.files.first().unwrap());
-
-                                    let mut latest_hash = [0u8; 20];
-                                    let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut latest_hash) else {
-                                        return Ok(ContentUpdateAction::ErrorInvalidHash);
-                                    };
-
-                                    if latest_hash == summary.content_summary.hash {
-                                        Ok(ContentUpdateAction::AlreadyUpToDate)
-                                    } else {
-                                        Ok(ContentUpdateAction::Modrinth {
-                                            file: install_file.clone(),
-                                            project_id: result.0.project_id.clone(),
-                                        })
-                                    }
-                                },
-                                ContentSource::CurseforgeProject { project_id } => {
-                                    let permit = semaphore.acquire().await.unwrap();
-
-                                    let mod_loader_type = match summary.content_summary.extra {
-                                        ContentType::Fabric => {
-                                            Some(CurseforgeModLoaderType::Fabric as u32)
-                                        },
-                                        ContentType::Forge | ContentType::LegacyForge => {
-                                            Some(CurseforgeModLoaderType::Forge as u32)
-                                        },
-                                        ContentType::NeoForge => {
-                                            Some(CurseforgeModLoaderType::NeoForge as u32)
-                                        },
-                                        _ => None
-                                    };
-
-                                    let result = self.meta.fetch(&CurseforgeGetModFilesMetadataItem(&CurseforgeGetModFilesRequest {
-                                        mod_id: project_id,
-                                        game_version: Some(version),
-                                        mod_loader_type,
-                                        page_size: Some(1)
-                                    })).await;
-
-                                    drop(permit);
-
-                                    tracker.add_count(1);
-                                    tracker.notify();
-
-                                    if let Err(MetaLoadError::NonOK(404)) = result {
-                                        return Ok(ContentUpdateAction::ErrorNotFound);
-                                    }
-
-                                    let result = result?;
-
-                                    let Some(file) = result.data.first() else {
-                                        return Ok(ContentUpdateAction::ErrorNotFound);
-                                    };
-
-                                    if file.mod_id != project_id {
-                                        log::error!("Refusing to update {:?}, mismatched project ids: expected {}, got {}",
-                                            summary.content_summary.hash, project_id, file.mod_id);
-                                        return Ok(ContentUpdateAction::ErrorNotFound);
-                                    }
-
-                                    let sha1 = file.hashes.iter()
-                                        .find(|hash| hash.algo == 1).map(|hash| &hash.value);
-                                    let Some(sha1) = sha1 else {
-                                        return Ok(ContentUpdateAction::ErrorInvalidHash);
-                                    };
-
-                                    let mut latest_hash = [0u8; 20];
-                                    let Ok(_) = hex::decode_to_slice(&**sha1, &mut latest_hash) else {
-                                        return Ok(ContentUpdateAction::ErrorInvalidHash);
-                                    };
-
-                                    if latest_hash == summary.content_summary.hash {
-                                        Ok(ContentUpdateAction::AlreadyUpToDate)
-                                    } else {
-                                        Ok(ContentUpdateAction::Curseforge {
-                                            file: file.clone(),
-                                            project_id,
-                                        })
-                                    }
-                                }
-                            }
-                        }.map_ok(|action| UpdateResult {
-                            mod_summary: summary.content_summary.clone(),
-                            action,
-                        }));
-                    }
-                }
-
-                let results: Result<Vec<UpdateResult>, MetaLoadError> = futures::future::try_join_all(futures).await;
-
-                match results {
-                    Ok(updates) => {
-                        let mut meta_updates = self.mod_metadata_manager.updates.write();
-
-                        for update in updates {
-                            meta_updates.insert(ContentUpdateKey {
-                                hash: update.mod_summary.hash,
-                                loader,
-                                version,
-                            }, update.action);
-                        }
-
-                        drop(meta_updates);
-
-                        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
-                            for (_, state) in &mut instance.content_state {
-                                state.mark_dirty(None);
-                            }
-                        }
-                    },
-                    Err(error) => {
-                        tracker.set_finished(ProgressTrackerFinishType::Error);
-                        modal_action.set_error_message(format!("Error checking for updates: {}", error).into());
-                        modal_action.set_finished();
-                        return;
-                    },
-                }
-
-                tracker.set_finished(ProgressTrackerFinishType::Normal);
-                modal_action.set_finished();
+                self.check_content_updates(id, &modal_action).await;
             },
-            MessageToBackend::UpdateContent { instance: id, content_id: mod_id, modal_action } => {
+            MessageToBackend::UpdateContent { instance: id, content_id: mod_id, snapshot, modal_action } => {
                 let content_install = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
                     let configuration = instance.configuration.get();
                     let (loader, minecraft_version) = (configuration.loader, configuration.minecraft_version);
@@ -2415,6 +2227,9 @@ impl BackendState {
                     return;
                 };
 
+                if snapshot {
+                    self.snapshot_instance(id, InstanceSnapshotReason::UpdateContent).await;
+                }
                 self.install_content(content_install, modal_action.clone()).await;
                 modal_action.set_finished();
                 self.send.send(MessageToFrontend::Refresh);
@@ -2422,6 +2237,9 @@ impl BackendState {
             MessageToBackend::Sleep5s => {
                 tokio::time::sleep(Duration::from_secs(5)).await;
             },
+            MessageToBackend::Flush { channel } => {
+                _ = channel.send(());
+            },
             MessageToBackend::ReadLog { path, send } => {
                 let frontend = self.send.clone();
                 let serial = AtomicOptionSerial::default();
@@ -2689,6 +2507,114 @@ impl BackendState {
 
                 self.send.send_success(format!("Deleted {} files", deleted));
             },
+            MessageToBackend::ExportInstance { id, format, name, version, overrides, output, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.export_instance(id, format, name, version, overrides, output, &modal_action).await {
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::CreateInstanceSnapshot { id } => {
+                self.snapshot_instance(id, InstanceSnapshotReason::Manual).await;
+            },
+            MessageToBackend::GetInstanceSnapshots { id, channel } => {
+                _ = channel.send(self.list_instance_snapshots(id));
+            },
+            MessageToBackend::RestoreInstanceSnapshot { id, snapshot, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn_blocking(move || {
+                    if let Err(error) = backend.restore_instance_snapshot(id, &snapshot) {
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::GenerateInstanceLock { id, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.generate_instance_lock(id, &modal_action).await {
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::RestoreInstanceFromLock { id, remove_extra, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.restore_instance_from_lock(id, remove_extra, &modal_action).await {
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                    backend.send.send(MessageToFrontend::Refresh);
+                });
+            },
+            MessageToBackend::StartBisect { id } => {
+                if let Err(error) = self.start_bisect(id).await {
+                    self.send.send_error(format!("Unable to start bisecting: {error}"));
+                }
+            },
+            MessageToBackend::ReportBisectResult { id, step, good } => {
+                if let Err(error) = self.report_bisect_result(id, step, good).await {
+                    self.send.send_error(format!("Unable to continue bisecting: {error}"));
+                }
+            },
+            MessageToBackend::StopBisect { id } => {
+                if let Err(error) = self.stop_bisect(id).await {
+                    self.send.send_error(format!("Unable to stop bisecting: {error}"));
+                }
+            },
+            MessageToBackend::RequestBisectStatus { id } => {
+                self.request_bisect_status(id).await;
+            },
+            MessageToBackend::VerifyInstance { id, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.verify_instance(id, &modal_action).await {
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::ExportLaunchCommand { id, quick_play, profile, export, modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.export_launch_command(id, quick_play, profile, export, &modal_action).await {
+                        log::error!("Failed to export launch command: {error}");
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::VerifyAllInstances { modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.verify_all_instances(&modal_action).await;
+                    modal_action.set_finished();
+                });
+            },
+            MessageToBackend::GetDiskUsage { channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    match backend.scan_disk_usage().await {
+                        Ok(report) => _ = channel.send(Some(Arc::new(report))),
+                        Err(error) => {
+                            backend.send.send_error(format!("Unable to scan disk usage: {error}"));
+                            _ = channel.send(None);
+                        },
+                    }
+                });
+            },
+            MessageToBackend::CleanupDiskUsage { modal_action } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    if let Err(error) = backend.cleanup_disk_usage(&modal_action).await {
+                        modal_action.set_error_message(Arc::from(format!("{}", error)));
+                    }
+                    modal_action.set_finished();
+                });
+            },
             MessageToBackend::UploadLogFile { path, modal_action } => {
                 let file = match std::fs::File::open(path) {
                     Ok(file) => file,
@@ -2867,6 +2793,36 @@ impl BackendState {
                     config.dont_open_game_output_when_launching = !value;
                 });
             },
+            MessageToBackend::SetBackgroundUpdateChecks { value } => {
+                self.config.write().modify(|config| {
+                    config.background_update_checks = value;
+                });
+            },
+            MessageToBackend::SetOfflineMode { value } => {
+                self.config.write().modify(|config| {
+                    config.offline_mode = value;
+                });
+
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    backend.update_offline_mode().await;
+                });
+            },
+            MessageToBackend::GetJavaRuntimes { rescan, channel } => {
+                let backend = self.clone();
+                tokio::task::spawn(async move {
+                    _ = channel.send(backend.get_java_runtimes(rescan).await);
+                });
+            },
+            MessageToBackend::SetDefaultJavaRuntime { major_version, path } => {
+                self.config.write().modify(|config| {
+                    if let Some(path) = path {
+                        config.java_defaults.insert(major_version, path);
+                    } else {
+                        config.java_defaults.remove(&major_version);
+                    }
+                });
+            },
             MessageToBackend::SetProxyConfiguration { config, password } => {
                 self.config.write().modify(|backend_config| {
                     backend_config.proxy = config;
@@ -2895,6 +2851,18 @@ impl BackendState {
                 // Notify user that restart is required for proxy changes to take effect
                 self.send.send_info("Proxy settings saved. Restart the launcher to apply changes.");
             },
+            MessageToBackend::SetDownloadConfiguration { config } => {
+                self.config.write().modify(|backend_config| {
+                    backend_config.downloads = config;
+                });
+                self.downloads.apply_config(&config);
+            },
+            MessageToBackend::SetDownloadMirrors { mirrors } => {
+                self.mirrors.set(&mirrors);
+                self.config.write().modify(|backend_config| {
+                    backend_config.mirrors = mirrors;
+                });
+            },
             MessageToBackend::RelocateInstance { id, path } => {
                 if path.exists() {
                     self.send.send_warning("Cannot relocate instance: path already exists");
@@ -3030,7 +2998,7 @@ impl BackendState {
                 }
             },
             MessageToBackend::InstallUpdate { update, modal_action } => {
-                tokio::task::spawn(crate::update::install_update(self.redirecting_http_client.clone(), self.directories.clone(), self.send.clone(), update, modal_action));
+                tokio::task::spawn(crate::update::install_update(self.downloads.clone(), self.directories.clone(), self.send.clone(), update, modal_action));
             },
             MessageToBackend::ImportFromOtherLauncher { launcher, import_accounts, import_instances, modal_action } => {
                 let Some(base_dirs) = directories::BaseDirs::new() else {
@@ -0,0 +1,448 @@
+use std::{collections::BTreeMap, path::PathBuf, sync::Arc};
+
+use bridge::{
+    dependency::ContentDependencyKind, instance::{InstanceBisectStatus, InstanceContentSummary, InstanceID, InstanceStatus}, message::MessageToFrontend
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use serde::{Deserialize, Serialize};
+
+use crate::{BackendState, IoOrSerializationError, instance::ContentFolder};
+
+const BISECT_STATE_FILENAME: &str = "pandora.bisect.json";
+
+#[derive(thiserror::Error, Debug)]
+pub enum BisectError {
+    #[error("Unable to find instance")]
+    UnknownInstance,
+    #[error("Unable to bisect while the instance is running")]
+    InstanceRunning,
+    #[error("Already bisecting this instance")]
+    AlreadyBisecting,
+    #[error("Not currently bisecting this instance")]
+    NotBisecting,
+    #[error("At least two mods need to be enabled to bisect")]
+    NotEnoughMods,
+    #[error("Unable to load mods")]
+    UnableToLoadMods,
+    #[error("Unable to read bisect state:\n{0}")]
+    UnableToReadState(#[from] IoOrSerializationError),
+    #[error("Failed to serialize data:\n{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Persisted so the original state can still be restored after the launcher is restarted
+#[derive(Debug, Serialize, Deserialize)]
+struct BisectState {
+    /// Whether each mod was enabled when bisecting started, keyed by filename without `.disabled`
+    original: BTreeMap<Arc<str>, bool>,
+    /// Mods that may still be causing the problem
+    candidates: Vec<Arc<str>>,
+    /// Mods enabled for the current step
+    #[serde(default)]
+    enabled: Vec<Arc<str>>,
+    #[serde(default)]
+    step: usize,
+    #[serde(default)]
+    result: Option<Vec<Arc<str>>>,
+}
+
+impl BackendState {
+    pub async fn start_bisect(&self, id: InstanceID) -> Result<(), BisectError> {
+        let path = self.bisect_state_path(id)?;
+        if path.exists() {
+            return Err(BisectError::AlreadyBisecting);
+        }
+
+        let mods = self.load_bisect_mods(id).await?;
+
+        let original: BTreeMap<Arc<str>, bool> = mods.iter().map(|summary| (base_filename(summary), summary.enabled)).collect();
+        let candidates: Vec<Arc<str>> = mods.iter().filter(|summary| summary.enabled).map(base_filename).collect();
+        if candidates.len() < 2 {
+            return Err(BisectError::NotEnoughMods);
+        }
+
+        let mut state = BisectState {
+            original,
+            candidates,
+            enabled: Vec::new(),
+            step: 0,
+            result: None,
+        };
+        next_step(&mut state, &mods);
+
+        crate::write_safe(&path, &serde_json::to_vec(&state)?)?;
+        self.apply_bisect_state(id, &mods, &state);
+        self.send_bisect_status(id, Some(&state), &mods);
+
+        Ok(())
+    }
+
+    /// Records whether the problem still happened with the mods of `step` and moves on to the next step
+    pub async fn report_bisect_result(&self, id: InstanceID, step: usize, good: bool) -> Result<(), BisectError> {
+        let path = self.bisect_state_path(id)?;
+        if !path.exists() {
+            return Err(BisectError::NotBisecting);
+        }
+        let mut state: BisectState = crate::read_json(&path)?;
+        if state.result.is_some() {
+            return Ok(());
+        }
+        // A crash already moved on to the next step, don't count the same step twice
+        if state.step != step {
+            log::debug!("Ignoring bisect result for step {step}, currently at step {}", state.step);
+            return Ok(());
+        }
+
+        let mods = self.load_bisect_mods(id).await?;
+
+        let enabled: FxHashSet<&Arc<str>> = state.enabled.iter().collect();
+        let (tested, untested): (Vec<Arc<str>>, Vec<Arc<str>>) = state.candidates.iter().cloned()
+            .partition(|candidate| enabled.contains(candidate));
+        state.candidates = if good { untested } else { tested };
+
+        next_step(&mut state, &mods);
+
+        crate::write_safe(&path, &serde_json::to_vec(&state)?)?;
+        self.apply_bisect_state(id, &mods, &state);
+        self.send_bisect_status(id, Some(&state), &mods);
+
+        Ok(())
+    }
+
+    /// Stops bisecting, restoring every mod to the state it was in when bisecting started
+    pub async fn stop_bisect(&self, id: InstanceID) -> Result<(), BisectError> {
+        let path = self.bisect_state_path(id)?;
+        if !path.exists() {
+            self.send.send(MessageToFrontend::InstanceBisectUpdated { id, status: None });
+            return Ok(());
+        }
+        let mut state: BisectState = crate::read_json(&path)?;
+
+        let mods = self.load_bisect_mods(id).await?;
+        if state.result.is_none() {
+            state.result = Some(Vec::new());
+        }
+        self.apply_bisect_state(id, &mods, &state);
+
+        std::fs::remove_file(&path)?;
+        self.send.send(MessageToFrontend::InstanceBisectUpdated { id, status: None });
+
+        Ok(())
+    }
+
+    pub async fn request_bisect_status(&self, id: InstanceID) {
+        let Ok(path) = self.bisect_state_path(id) else {
+            return;
+        };
+        let Ok(state) = crate::read_json::<BisectState>(&path) else {
+            self.send.send(MessageToFrontend::InstanceBisectUpdated { id, status: None });
+            return;
+        };
+        let mods = self.clone().load_instance_content(id, ContentFolder::Mods).await.unwrap_or_else(|| Arc::from([]));
+        self.send_bisect_status(id, Some(&state), &mods);
+    }
+
+    /// Crashing counts as the problem still happening
+    pub async fn on_bisect_instance_crashed(&self, id: InstanceID) {
+        let Ok(path) = self.bisect_state_path(id) else {
+            return;
+        };
+        let Ok(state) = crate::read_json::<BisectState>(&path) else {
+            return;
+        };
+
+        log::info!("Instance crashed while bisecting, marking step as bad");
+        if let Err(error) = self.report_bisect_result(id, state.step, false).await {
+            self.send.send_error(format!("Unable to continue bisecting: {error}"));
+        }
+    }
+
+    fn bisect_state_path(&self, id: InstanceID) -> Result<PathBuf, BisectError> {
+        let instance_state = self.instance_state.read();
+        let Some(instance) = instance_state.instances.get(id) else {
+            return Err(BisectError::UnknownInstance);
+        };
+        Ok(instance.root_path.join(BISECT_STATE_FILENAME))
+    }
+
+    async fn load_bisect_mods(&self, id: InstanceID) -> Result<Arc<[InstanceContentSummary]>, BisectError> {
+        if self.instance_state.read().instances.get(id).is_some_and(|instance| instance.status() == InstanceStatus::Running) {
+            return Err(BisectError::InstanceRunning);
+        }
+        self.clone().load_instance_content(id, ContentFolder::Mods).await.ok_or(BisectError::UnableToLoadMods)
+    }
+
+    /// Enables exactly the mods of the current step, or restores the original state once finished
+    fn apply_bisect_state(&self, id: InstanceID, mods: &[InstanceContentSummary], state: &BisectState) {
+        let enabled: FxHashSet<&Arc<str>> = state.enabled.iter().collect();
+
+        let mut enable = Vec::new();
+        let mut disable = Vec::new();
+        for summary in mods {
+            let filename = base_filename(summary);
+            let Some(originally_enabled) = state.original.get(&filename) else {
+                continue;
+            };
+            let should_enable = if state.result.is_some() {
+                *originally_enabled
+            } else {
+                *originally_enabled && enabled.contains(&filename)
+            };
+            if should_enable && !summary.enabled {
+                enable.push(summary.id);
+            } else if !should_enable && summary.enabled {
+                disable.push(summary.id);
+            }
+        }
+
+        if !enable.is_empty() {
+            self.set_content_enabled(id, enable, true);
+        }
+        if !disable.is_empty() {
+            self.set_content_enabled(id, disable, false);
+        }
+    }
+
+    fn send_bisect_status(&self, id: InstanceID, state: Option<&BisectState>, mods: &[InstanceContentSummary]) {
+        let status = state.map(|state| {
+            let names: FxHashMap<Arc<str>, Arc<str>> = mods.iter().map(|summary| {
+                let name = summary.content_summary.name.clone().unwrap_or_else(|| summary.filename.clone());
+                (base_filename(summary), name)
+            }).collect();
+            let display = |filenames: &[Arc<str>]| -> Arc<[Arc<str>]> {
+                filenames.iter().map(|filename| names.get(filename).cloned().unwrap_or_else(|| filename.clone())).collect()
+            };
+
+            InstanceBisectStatus {
+                step: state.step,
+                candidates: state.candidates.len(),
+                enabled: display(&state.enabled),
+                result: state.result.as_deref().map(display),
+            }
+        });
+        self.send.send(MessageToFrontend::InstanceBisectUpdated { id, status });
+    }
+}
+
+/// Picks the mods to enable for the next step, finishing once the problem can't be narrowed down further
+fn next_step(state: &mut BisectState, mods: &[InstanceContentSummary]) {
+    if state.candidates.len() <= 1 {
+        state.result = Some(state.candidates.clone());
+        state.enabled.clear();
+        return;
+    }
+
+    let dependencies = DependencyClosure::new(mods, &state.original);
+    let half = state.candidates.len() / 2;
+    for test in [&state.candidates[..half], &state.candidates[half..]] {
+        let enabled = dependencies.close(test);
+        // Required dependencies may pull in every candidate, in which case this split tells us nothing
+        if state.candidates.iter().any(|candidate| !enabled.contains(candidate)) {
+            state.enabled = enabled.into_iter().collect();
+            state.enabled.sort();
+            state.step += 1;
+            return;
+        }
+    }
+
+    state.result = Some(state.candidates.clone());
+    state.enabled.clear();
+}
+
+struct DependencyClosure<'a> {
+    providers: FxHashMap<&'a str, Vec<Arc<str>>>,
+    required: FxHashMap<Arc<str>, Vec<&'a str>>,
+}
+
+impl<'a> DependencyClosure<'a> {
+    fn new(mods: &'a [InstanceContentSummary], original: &BTreeMap<Arc<str>, bool>) -> Self {
+        let mut providers: FxHashMap<&'a str, Vec<Arc<str>>> = FxHashMap::default();
+        let mut required: FxHashMap<Arc<str>, Vec<&'a str>> = FxHashMap::default();
+
+        for summary in mods {
+            let filename = base_filename(summary);
+            if original.get(&filename) != Some(&true) {
+                continue;
+            }
+            if let Some(id) = summary.content_summary.id.as_deref() {
+                providers.entry(id).or_default().push(filename.clone());
+            }
+            let Some(info) = summary.content_summary.dependency_info.as_deref() else {
+                continue;
+            };
+            for provided in info.provides.iter() {
+                providers.entry(&provided.id).or_default().push(filename.clone());
+            }
+            let ids = info.dependencies.iter()
+                .filter(|dependency| dependency.kind == ContentDependencyKind::Required)
+                .map(|dependency| &*dependency.id);
+            required.entry(filename).or_default().extend(ids);
+        }
+
+        Self { providers, required }
+    }
+
+    /// Returns the given mods together with every mod they require, directly or indirectly
+    fn close(&self, mods: &[Arc<str>]) -> FxHashSet<Arc<str>> {
+        let mut enabled: FxHashSet<Arc<str>> = mods.iter().cloned().collect();
+        let mut queue: Vec<Arc<str>> = mods.to_vec();
+
+        while let Some(filename) = queue.pop() {
+            let Some(required) = self.required.get(&filename) else {
+                continue;
+            };
+            for id in required {
+                let Some(providers) = self.providers.get(id) else {
+                    continue;
+                };
+                if providers.iter().any(|provider| enabled.contains(provider)) {
+                    continue;
+                }
+                let provider = providers[0].clone();
+                enabled.insert(provider.clone());
+                queue.push(provider);
+            }
+        }
+
+        enabled
+    }
+}
+
+fn base_filename(summary: &InstanceContentSummary) -> Arc<str> {
+    match summary.filename.strip_suffix(".disabled") {
+        Some(filename) => filename.into(),
+        None => summary.filename.clone(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{collections::BTreeMap, path::Path, sync::Arc};
+
+    use bridge::{
+        dependency::{ContentDependency, ContentDependencyInfo, ContentDependencyKind, ProvidedMod, VersionRequirement}, instance::{ContentSummary, ContentType, ContentUpdateContext, ContentUpdateStatus, InstanceContentID, InstanceContentSummary}
+    };
+    use schema::loader::Loader;
+
+    use super::{BisectState, DependencyClosure, next_step};
+
+    fn summary(filename: &str, id: &str, provides: &[&str], requires: &[&str]) -> InstanceContentSummary {
+        let dependency_info = ContentDependencyInfo {
+            version: None,
+            provides: provides.iter().map(|id| ProvidedMod { id: (*id).into(), version: None }).collect(),
+            dependencies: requires.iter().map(|id| ContentDependency {
+                id: (*id).into(),
+                kind: ContentDependencyKind::Required,
+                versions: VersionRequirement::Any,
+            }).collect(),
+        };
+        InstanceContentSummary {
+            content_summary: Arc::new(ContentSummary {
+                id: Some(id.into()),
+                hash: [0; 20],
+                name: None,
+                version_str: "1.0.0".into(),
+                authors: "".into(),
+                png_icon: None,
+                extra: ContentType::Fabric,
+                dependency_info: Some(Arc::new(dependency_info)),
+            }),
+            id: InstanceContentID::dangling(),
+            filename: filename.into(),
+            lowercase_search_keys: Arc::from([]),
+            filename_hash: 0,
+            path: Path::new(filename).into(),
+            enabled: true,
+            content_source: Default::default(),
+            update: ContentUpdateContext::new(ContentUpdateStatus::Unknown, Loader::Fabric, "1.21.1".into()),
+            disabled_children: Default::default(),
+        }
+    }
+
+    fn state(mods: &[InstanceContentSummary]) -> BisectState {
+        BisectState {
+            original: mods.iter().map(|summary| (summary.filename.clone(), true)).collect::<BTreeMap<_, _>>(),
+            candidates: mods.iter().map(|summary| summary.filename.clone()).collect(),
+            enabled: Vec::new(),
+            step: 0,
+            result: None,
+        }
+    }
+
+    fn names(filenames: &[Arc<str>]) -> Vec<&str> {
+        filenames.iter().map(|filename| &**filename).collect()
+    }
+
+    #[test]
+    fn test_next_step_halves_candidates() {
+        let mods = [
+            summary("a.jar", "a", &[], &[]),
+            summary("b.jar", "b", &[], &[]),
+            summary("c.jar", "c", &[], &[]),
+            summary("d.jar", "d", &[], &[]),
+        ];
+        let mut state = state(&mods);
+
+        next_step(&mut state, &mods);
+        assert_eq!(names(&state.enabled), ["a.jar", "b.jar"]);
+        assert_eq!(state.step, 1);
+        assert!(state.result.is_none());
+
+        state.candidates = vec!["c.jar".into()];
+        next_step(&mut state, &mods);
+        assert_eq!(state.result.as_deref().map(names), Some(vec!["c.jar"]));
+        assert!(state.enabled.is_empty());
+    }
+
+    #[test]
+    fn test_next_step_enables_required_dependencies() {
+        let mods = [
+            summary("a.jar", "a", &[], &["library"]),
+            summary("b.jar", "b", &[], &[]),
+            summary("c.jar", "c", &[], &[]),
+            summary("library.jar", "library", &[], &[]),
+        ];
+        let mut state = state(&mods);
+
+        next_step(&mut state, &mods);
+        assert_eq!(names(&state.enabled), ["a.jar", "b.jar", "library.jar"]);
+        assert!(state.result.is_none());
+    }
+
+    #[test]
+    fn test_next_step_finishes_when_dependencies_cover_every_candidate() {
+        let mods = [
+            summary("a.jar", "a", &[], &["b"]),
+            summary("b.jar", "b", &[], &["a"]),
+        ];
+        let mut state = state(&mods);
+
+        next_step(&mut state, &mods);
+        assert_eq!(state.result.as_deref().map(names), Some(vec!["a.jar", "b.jar"]));
+        assert!(state.enabled.is_empty());
+        assert_eq!(state.step, 0);
+    }
+
+    #[test]
+    fn test_dependency_closure_uses_provides() {
+        let mods = [
+            summary("a.jar", "a", &[], &["fabric-api-base"]),
+            summary("fabric-api.jar", "fabric-api", &["fabric-api-base"], &[]),
+            summary("other.jar", "other", &[], &[]),
+        ];
+        let state = state(&mods);
+        let closure = DependencyClosure::new(&mods, &state.original);
+
+        let enabled = closure.close(&["a.jar".into()]);
+        assert_eq!(enabled.len(), 2);
+        assert!(enabled.contains("fabric-api.jar"));
+
+        // Mods that were disabled before bisecting never provide anything
+        let mut original = state.original.clone();
+        original.insert("fabric-api.jar".into(), false);
+        let closure = DependencyClosure::new(&mods, &original);
+        assert_eq!(closure.close(&["a.jar".into()]).len(), 1);
+    }
+}
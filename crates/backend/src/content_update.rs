@@ -0,0 +1,457 @@
+use std::{sync::Arc, time::Duration};
+
+use bridge::{
+    instance::{ContentSummary, ContentType, InstanceContentSummary, InstanceID}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use futures::TryFutureExt;
+use rustc_hash::FxHashSet;
+use schema::{
+    content::ContentSource,
+    curseforge::{CachedCurseforgeFileInfo, CurseforgeFingerprintsRequest, CurseforgeGetModFilesRequest, CurseforgeModLoaderType},
+    loader::Loader,
+    modrinth::ModrinthLoader,
+};
+use strum::IntoEnumIterator;
+use tokio::sync::Semaphore;
+
+use crate::{
+    BackendState, instance::ContentFolder, metadata::{items::{CurseforgeFingerprintsMetadataItem, CurseforgeGetModFilesMetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem, VersionUpdateParameters, VersionV3LoaderFields, VersionV3UpdateParameters}, manager::MetaLoadError}, mod_metadata::{ContentUpdateAction, ContentUpdateKey}
+};
+
+/// Delay after startup before the first background update check
+pub const BACKGROUND_UPDATE_CHECK_DELAY: Duration = Duration::from_secs(2 * 60);
+pub const BACKGROUND_UPDATE_CHECK_INTERVAL: Duration = Duration::from_secs(6 * 60 * 60);
+
+impl BackendState {
+    /// Checks every instance one after another, sending a single notification if anything can be updated
+    pub async fn run_background_update_check(self) {
+        log::info!("Running background update check");
+
+        let instances: Vec<InstanceID> = self.instance_state.read().instances.iter().map(|instance| instance.id).collect();
+
+        let mut available = 0;
+        let mut outdated_instances = 0;
+        for id in instances {
+            let modal_action = ModalAction::default();
+            match self.check_content_updates(id, &modal_action).await {
+                Some(0) => {},
+                Some(count) => {
+                    available += count;
+                    outdated_instances += 1;
+                },
+                None => {
+                    if let Some(error) = &*modal_action.error.read() {
+                        log::warn!("Background update check failed for {id:?}: {error}");
+                    }
+                },
+            }
+        }
+
+        if available > 0 {
+            self.send.send_info(format!("{available} content update(s) available across {outdated_instances} instance(s)"));
+        }
+    }
+
+    /// Checks every piece of content in an instance for updates, returning how many can be updated
+    pub async fn check_content_updates(&self, id: InstanceID, modal_action: &ModalAction) -> Option<usize> {
+        let (loader, version) = if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            let configuration = instance.configuration.get();
+            (configuration.loader, configuration.minecraft_version)
+        } else {
+            self.send.send_error("Can't update instance, unknown id");
+            modal_action.set_error_message("Can't update instance, unknown id".into());
+            modal_action.set_finished();
+            return None;
+        };
+
+        let mut content = Vec::new();
+        for folder in ContentFolder::iter() {
+            let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                modal_action.set_finished();
+                return None;
+            };
+            content.extend_from_slice(&*summaries);
+        }
+
+        // Vanilla has no Modrinth loader, but its resource packs, shaders and datapacks can still be updated
+        let modrinth_loader = loader.as_modrinth_loader();
+        if loader == Loader::Unknown {
+            modal_action.set_error_message("Unable to update instance, unsupported loader".into());
+            modal_action.set_finished();
+            return None;
+        }
+
+        let tracker = ProgressTracker::new("Checking content".into(), self.send.clone());
+        tracker.set_total(content.len());
+        modal_action.trackers.push(tracker.clone());
+
+        self.identify_curseforge_content(&content).await;
+
+        let semaphore = Semaphore::new(8);
+
+        let mod_params = &VersionUpdateParameters {
+            loaders: [modrinth_loader].into(),
+            game_versions: [version].into(),
+        };
+
+        let fabric_mod_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::Fabric].into(),
+            game_versions: [version].into(),
+        };
+
+        let quilt_mod_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::Quilt].into(),
+            game_versions: [version].into(),
+        };
+
+        let forge_mod_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::Forge].into(),
+            game_versions: [version].into(),
+        };
+
+        let neoforge_mod_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::NeoForge].into(),
+            game_versions: [version].into(),
+        };
+
+        let resourcepack_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::Minecraft].into(),
+            game_versions: [version].into(),
+        };
+
+        let shaderpack_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::Iris, ModrinthLoader::Optifine, ModrinthLoader::Canvas].into(),
+            game_versions: [version].into(),
+        };
+
+        let datapack_params = &VersionUpdateParameters {
+            loaders: [ModrinthLoader::Datapack].into(),
+            game_versions: [version].into(),
+        };
+
+        let modrinth_modpack_params = &VersionV3UpdateParameters {
+            loaders: ["mrpack".into()].into(),
+            loader_fields: VersionV3LoaderFields {
+                mrpack_loaders: [modrinth_loader].into(),
+                game_versions: [version].into(),
+            },
+        };
+
+        let meta = self.meta.clone();
+
+        let mut futures = Vec::new();
+
+        struct UpdateResult {
+            mod_summary: Arc<ContentSummary>,
+            action: ContentUpdateAction,
+        }
+
+        { // Scope is needed so await doesn't complain about the non-send RwLockReadGuard
+            let sources = self.mod_metadata_manager.read_content_sources();
+            for summary in content.iter() {
+                let source = sources.get(&summary.content_summary.hash).unwrap_or(ContentSource::Manual);
+                let semaphore = &semaphore;
+                let meta = &meta;
+                let tracker = &tracker;
+                futures.push(async move {
+                    match source {
+                        ContentSource::Manual => {
+                            tracker.add_count(1);
+                            tracker.notify();
+                            Ok(ContentUpdateAction::ManualInstall)
+                        },
+                        ContentSource::ModrinthUnknown | ContentSource::ModrinthProject { .. } => {
+                            let permit = semaphore.acquire().await.unwrap();
+                            let result = match summary.content_summary.extra {
+                                ContentType::Fabric => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: fabric_mod_params.clone()
+                                    }).await
+                                },
+                                ContentType::Quilt => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: quilt_mod_params.clone()
+                                    }).await
+                                },
+                                ContentType::Forge | ContentType::LegacyForge => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: forge_mod_params.clone()
+                                    }).await
+                                },
+                                ContentType::NeoForge => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: neoforge_mod_params.clone()
+                                    }).await
+                                },
+                                ContentType::CurseforgeModpack { .. } => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: mod_params.clone()
+                                    }).await
+                                },
+                                ContentType::JavaModule => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: mod_params.clone()
+                                    }).await
+                                },
+                                ContentType::ModrinthModpack { .. } => {
+                                    meta.fetch(&ModrinthV3VersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: modrinth_modpack_params.clone()
+                                    }).await
+                                },
+                                ContentType::ResourcePack => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: resourcepack_params.clone()
+                                    }).await
+                                },
+                                ContentType::ShaderPack => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: shaderpack_params.clone()
+                                    }).await
+                                },
+                                ContentType::Datapack => {
+                                    meta.fetch(&ModrinthVersionUpdateMetadataItem {
+                                        sha1: hex::encode(summary.content_summary.hash).into(),
+                                        params: datapack_params.clone()
+                                    }).await
+                                },
+                            };
+                            drop(permit);
+
+                            tracker.add_count(1);
+                            tracker.notify();
+
+                            if let Err(MetaLoadError::NonOK(404)) = result {
+                                return Ok(ContentUpdateAction::ErrorNotFound);
+                            }
+
+                            let result = result?;
+
+                            if let ContentSource::ModrinthProject { ref project } = source {
+                                if &result.0.project_id != project {
+                                    log::error!("Refusing to update {:?}, mismatched project ids: expected {}, got {}",
+                                        summary.content_summary.hash, project, &result.0.project_id);
+                                    return Ok(ContentUpdateAction::ErrorNotFound);
+                                }
+                            }
+
+                            let install_file = result
+                                .0
+                                .files
+                                .iter()
+                                .find(|file| file.primary)
+                                .unwrap_or(result.0.files.first().unwrap());
+
+                            let mut latest_hash = [0u8; 20];
+                            let Ok(_) = hex::decode_to_slice(&*install_file.hashes.sha1, &mut latest_hash) else {
+                                return Ok(ContentUpdateAction::ErrorInvalidHash);
+                            };
+
+                            if latest_hash == summary.content_summary.hash {
+                                Ok(ContentUpdateAction::AlreadyUpToDate)
+                            } else {
+                                Ok(ContentUpdateAction::Modrinth {
+                                    file: install_file.clone(),
+                                    project_id: result.0.project_id.clone(),
+                                })
+                            }
+                        },
+                        ContentSource::CurseforgeProject { project_id } => {
+                            let permit = semaphore.acquire().await.unwrap();
+
+                            let mod_loader_type = match summary.content_summary.extra {
+                                ContentType::Fabric => {
+                                    Some(CurseforgeModLoaderType::Fabric as u32)
+                                },
+                                ContentType::Quilt => {
+                                    Some(CurseforgeModLoaderType::Quilt as u32)
+                                },
+                                ContentType::Forge | ContentType::LegacyForge => {
+                                    Some(CurseforgeModLoaderType::Forge as u32)
+                                },
+                                ContentType::NeoForge => {
+                                    Some(CurseforgeModLoaderType::NeoForge as u32)
+                                },
+                                _ => None
+                            };
+
+                            let result = self.meta.fetch(&CurseforgeGetModFilesMetadataItem(&CurseforgeGetModFilesRequest {
+                                mod_id: project_id,
+                                game_version: Some(version),
+                                mod_loader_type,
+                                page_size: Some(1)
+                            })).await;
+
+                            drop(permit);
+
+                            tracker.add_count(1);
+                            tracker.notify();
+
+                            if let Err(MetaLoadError::NonOK(404)) = result {
+                                return Ok(ContentUpdateAction::ErrorNotFound);
+                            }
+
+                            let result = result?;
+
+                            let Some(file) = result.data.first() else {
+                                return Ok(ContentUpdateAction::ErrorNotFound);
+                            };
+
+                            if file.mod_id != project_id {
+                                log::error!("Refusing to update {:?}, mismatched project ids: expected {}, got {}",
+                                    summary.content_summary.hash, project_id, file.mod_id);
+                                return Ok(ContentUpdateAction::ErrorNotFound);
+                            }
+
+                            let sha1 = file.hashes.iter()
+                                .find(|hash| hash.algo == 1).map(|hash| &hash.value);
+                            let Some(sha1) = sha1 else {
+                                return Ok(ContentUpdateAction::ErrorInvalidHash);
+                            };
+
+                            let mut latest_hash = [0u8; 20];
+                            let Ok(_) = hex::decode_to_slice(&**sha1, &mut latest_hash) else {
+                                return Ok(ContentUpdateAction::ErrorInvalidHash);
+                            };
+
+                            if latest_hash == summary.content_summary.hash {
+                                Ok(ContentUpdateAction::AlreadyUpToDate)
+                            } else {
+                                Ok(ContentUpdateAction::Curseforge {
+                                    file: file.clone(),
+                                    project_id,
+                                })
+                            }
+                        }
+                    }
+                }.map_ok(|action| UpdateResult {
+                    mod_summary: summary.content_summary.clone(),
+                    action,
+                }));
+            }
+        }
+
+        let results: Result<Vec<UpdateResult>, MetaLoadError> = futures::future::try_join_all(futures).await;
+
+        let available;
+
+        match results {
+            Ok(updates) => {
+                available = updates.iter()
+                    .filter(|update| matches!(update.action, ContentUpdateAction::Modrinth { .. } | ContentUpdateAction::Curseforge { .. }))
+                    .count();
+
+                let mut meta_updates = self.mod_metadata_manager.updates.write();
+
+                for update in updates {
+                    meta_updates.insert(ContentUpdateKey {
+                        hash: update.mod_summary.hash,
+                        loader,
+                        version,
+                    }, update.action);
+                }
+
+                drop(meta_updates);
+
+                if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+                    for (_, state) in &mut instance.content_state {
+                        state.mark_dirty(None);
+                    }
+                }
+            },
+            Err(error) => {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                modal_action.set_error_message(format!("Error checking for updates: {}", error).into());
+                modal_action.set_finished();
+                return None;
+            },
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        modal_action.set_finished();
+
+        Some(available)
+    }
+
+    /// Matches manually installed content against CurseForge fingerprints, so that content downloaded
+    /// from CurseForge outside of the launcher can be updated
+    async fn identify_curseforge_content(&self, content: &[InstanceContentSummary]) {
+        let unknown: Vec<&InstanceContentSummary> = {
+            let sources = self.mod_metadata_manager.read_content_sources();
+            content.iter()
+                .filter(|summary| sources.get(&summary.content_summary.hash).unwrap_or(ContentSource::Manual) == ContentSource::Manual)
+                .filter(|summary| !self.mod_metadata_manager.is_unmatched_curseforge_fingerprint(&summary.content_summary.hash))
+                .collect()
+        };
+        if unknown.is_empty() {
+            return;
+        }
+
+        let files: Vec<_> = unknown.iter().map(|summary| (summary.path.clone(), summary.content_summary.hash)).collect();
+        let (mut fingerprints, mut unknown_hashes) = tokio::task::spawn_blocking(move || {
+            let mut fingerprints = Vec::with_capacity(files.len());
+            let mut unknown_hashes = FxHashSet::default();
+            for (path, hash) in files {
+                match std::fs::read(&path) {
+                    Ok(bytes) => {
+                        fingerprints.push(crate::curseforge_fingerprint(&bytes));
+                        unknown_hashes.insert(hash);
+                    },
+                    Err(error) => log::warn!("Unable to read {:?} to compute fingerprint: {error}", path),
+                }
+            }
+            (fingerprints, unknown_hashes)
+        }).await.unwrap();
+        fingerprints.sort_unstable();
+        fingerprints.dedup();
+
+        let result = self.meta.fetch(&CurseforgeFingerprintsMetadataItem(&CurseforgeFingerprintsRequest {
+            fingerprints,
+        })).await;
+        let result = match result {
+            Ok(result) => result,
+            Err(error) => {
+                log::warn!("Unable to match CurseForge fingerprints: {error}");
+                return;
+            },
+        };
+
+        let mut sources = Vec::new();
+        for exact_match in result.data.exact_matches.iter() {
+            let file = &exact_match.file;
+            let Some(sha1) = file.hashes.iter().find(|hash| hash.algo == 1) else {
+                continue;
+            };
+            let mut hash = [0u8; 20];
+            let Ok(_) = hex::decode_to_slice(&*sha1.value, &mut hash) else {
+                continue;
+            };
+            if !unknown_hashes.remove(&hash) {
+                continue;
+            }
+
+            self.mod_metadata_manager.set_cached_curseforge_info(file.id, CachedCurseforgeFileInfo {
+                hash,
+                filename: file.file_name.clone(),
+                disabled_third_party_downloads: file.download_url.is_none(),
+            });
+            sources.push((hash, ContentSource::CurseforgeProject { project_id: exact_match.id }));
+        }
+
+        // Don't read and look up the files CurseForge doesn't know again on every check
+        self.mod_metadata_manager.set_unmatched_curseforge_fingerprints(unknown_hashes.into_iter());
+
+        if !sources.is_empty() {
+            log::info!("Identified {} file(s) from CurseForge by fingerprint", sources.len());
+            self.mod_metadata_manager.set_content_sources(sources.into_iter());
+        }
+    }
+}
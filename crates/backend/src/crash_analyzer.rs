@@ -0,0 +1,346 @@
+use std::{
+    collections::BTreeSet, io::{Read, Seek, SeekFrom}, path::{Path, PathBuf}, sync::Arc, time::SystemTime
+};
+
+use bridge::{
+    crash::{CrashAnalysis, CrashReportKind, CrashSuspect, CrashSuspectReason}, instance::{InstanceContentSummary, InstanceID}, message::MessageToFrontend
+};
+use once_cell::sync::Lazy;
+use rc_zip_sync::ReadZip;
+use regex::Regex;
+use rustc_hash::FxHashMap;
+
+use crate::{BackendState, instance::ContentFolder};
+
+/// Only the end of very large logs is analyzed
+const MAX_REPORT_SIZE: u64 = 8 * 1024 * 1024;
+const MAX_SUSPECTS: usize = 5;
+const MAX_STACK_TRACE_SCORE: u32 = 10;
+
+static NAMED_MOD: Lazy<[Regex; 4]> = Lazy::new(|| [
+    // Forge/NeoForge: -- Mod loading issue for: examplemod --
+    Regex::new(r"Mod loading issue for: ([\w\-]+)").unwrap(),
+    Regex::new(r"Failed to create mod instance\. ModID: ([\w\-]+)").unwrap(),
+    // Fabric loader: Mod 'Example Mod' (examplemod) 1.0.0 requires ...
+    Regex::new(r"Mod '[^']*' \(([\w\-]+)\)").unwrap(),
+    Regex::new(r"Caught exception during mod initialization for mod '?([\w\-]+)'?").unwrap(),
+]);
+static SUSPECTED_MODS: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?m)^\s*Suspected Mods?:(.*(?:\n[ \t]+\S.*)*)").unwrap());
+static PARENTHESIZED_ID: Lazy<Regex> = Lazy::new(|| Regex::new(r"\(([\w\-]+)\)").unwrap());
+static MOD_FILE: Lazy<Regex> = Lazy::new(|| Regex::new(r"Mod File: .*?([^/\\]+\.jar)").unwrap());
+static MIXIN_MOD: Lazy<Regex> = Lazy::new(|| Regex::new(r"Mixin (?:apply|transformation)[^\n]*?(?:for|from) mod \(?([\w\-]+)\)?").unwrap());
+static MIXIN_CONFIG: Lazy<Regex> = Lazy::new(|| Regex::new(r"(?:Mixin|mixin) \[?([\w\-.]+\.json)").unwrap());
+// Mixin injector names include the owning mod, e.g. handler$zza000$examplemod$onTick
+static MIXIN_HANDLER: Lazy<Regex> = Lazy::new(|| Regex::new(r"\$[a-z]{3}\d{3}\$([A-Za-z0-9_]+)\$").unwrap());
+static STACK_FRAME: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^\s*(?:at\s+(?:[\w.\-]+/)*|[jJ]\s+(?:\d+\s+c\d\s+)?)([\w$.]+)\.[\w$<>]+\(").unwrap()
+});
+static FRAME_JAR: Lazy<Regex> = Lazy::new(|| Regex::new(r"\[([^\[\]/\\%:]+\.jar)").unwrap());
+static EXCEPTION_LINE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?:Caused by: )?[\w$.]+(?:Exception|Error)\b").unwrap());
+
+struct CrashReport {
+    path: PathBuf,
+    kind: CrashReportKind,
+    text: String,
+}
+
+#[derive(Default)]
+struct ModIndex<'a> {
+    enabled: Vec<&'a InstanceContentSummary>,
+    by_id: FxHashMap<String, usize>,
+    by_filename: FxHashMap<String, usize>,
+    /// Package prefixes, None if more than one mod contains the package
+    by_package: FxHashMap<String, Option<usize>>,
+    by_mixin_config: FxHashMap<String, usize>,
+}
+
+#[derive(Default, Clone)]
+struct SuspectScore {
+    score: u32,
+    stack_trace_score: u32,
+    reasons: BTreeSet<CrashSuspectReason>,
+}
+
+impl BackendState {
+    /// Looks for crash reports written during the last session and tells the frontend which mods are most likely responsible
+    pub async fn analyze_crash(self, id: InstanceID, session_started_unix_ms: i64, exit_code: Option<i32>) {
+        let Some((dot_minecraft_path, instance_name)) = self.instance_state.read().instances.get(id)
+            .map(|instance| (instance.dot_minecraft_path.clone(), instance.name))
+        else {
+            return;
+        };
+
+        // The game log is only worth analyzing if the game is known to have exited with an error
+        let allow_game_log = exit_code.is_some();
+        let Some(report) = find_crash_report(&dot_minecraft_path, session_started_unix_ms, allow_game_log) else {
+            return;
+        };
+
+        log::info!("Analyzing crash report {:?}", report.path);
+
+        let mods = self.clone().load_instance_content(id, ContentFolder::Mods).await.unwrap_or_else(|| Arc::from([]));
+
+        let result = tokio::task::spawn_blocking(move || {
+            let description = describe_report(&report);
+            let suspects = find_suspects(&report.text, &mods);
+            (report, description, suspects)
+        }).await;
+
+        let Ok((report, description, suspects)) = result else {
+            return;
+        };
+
+        if report.kind == CrashReportKind::GameLog && suspects.is_empty() {
+            return;
+        }
+
+        self.send.send(MessageToFrontend::CrashAnalyzed {
+            analysis: Arc::new(CrashAnalysis {
+                instance: id,
+                instance_name,
+                report_path: report.path.into(),
+                report_kind: report.kind,
+                description,
+                suspects: suspects.into(),
+            }),
+        });
+    }
+}
+
+fn find_crash_report(dot_minecraft_path: &Path, session_started_unix_ms: i64, allow_game_log: bool) -> Option<CrashReport> {
+    let started = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(session_started_unix_ms.max(0) as u64);
+
+    let newest_matching = |dir: &Path, matches: &dyn Fn(&str) -> bool| -> Option<PathBuf> {
+        std::fs::read_dir(dir).ok()?.flatten()
+            .filter(|entry| entry.file_name().to_str().is_some_and(matches))
+            .filter_map(|entry| Some((entry.metadata().ok()?.modified().ok()?, entry.path())))
+            .filter(|(modified, _)| *modified >= started)
+            .max_by_key(|(modified, _)| *modified)
+            .map(|(_, path)| path)
+    };
+
+    let (path, kind) = if let Some(path) = newest_matching(&dot_minecraft_path.join("crash-reports"), &|name| name.ends_with(".txt")) {
+        (path, CrashReportKind::CrashReport)
+    } else if let Some(path) = newest_matching(dot_minecraft_path, &|name| name.starts_with("hs_err_pid") && name.ends_with(".log")) {
+        (path, CrashReportKind::JvmFatalError)
+    } else if allow_game_log && let Some(path) = newest_matching(&dot_minecraft_path.join("logs"), &|name| name == "latest.log") {
+        (path, CrashReportKind::GameLog)
+    } else {
+        return None;
+    };
+
+    let text = read_report(&path).inspect_err(|error| {
+        log::error!("Unable to read crash report {:?}: {error}", path);
+    }).ok()?;
+
+    Some(CrashReport { path, kind, text })
+}
+
+fn read_report(path: &Path) -> std::io::Result<String> {
+    let mut file = std::fs::File::open(path)?;
+    let len = file.metadata()?.len();
+    if len > MAX_REPORT_SIZE {
+        file.seek(SeekFrom::Start(len - MAX_REPORT_SIZE))?;
+    }
+    let mut bytes = Vec::new();
+    file.take(MAX_REPORT_SIZE).read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn describe_report(report: &CrashReport) -> Option<Arc<str>> {
+    let mut lines = report.text.lines().map(str::trim_end);
+
+    let description = match report.kind {
+        CrashReportKind::CrashReport => {
+            let description = lines.find_map(|line| line.strip_prefix("Description: "))?;
+            match lines.find(|line| !line.trim().is_empty()) {
+                Some(exception) => format!("{description}\n{exception}"),
+                None => description.to_string(),
+            }
+        },
+        CrashReportKind::JvmFatalError => {
+            lines.find(|line| line.starts_with("# Problematic frame:"))?;
+            lines.next()?.trim_start_matches('#').trim().to_string()
+        },
+        CrashReportKind::GameLog => {
+            lines.find(|line| EXCEPTION_LINE.is_match(line.trim_start()) || line.contains("Incompatible mods found"))?.trim().to_string()
+        },
+    };
+
+    let description = description.trim();
+    if description.is_empty() {
+        return None;
+    }
+    Some(crate::log_reader::replace(description).into())
+}
+
+fn find_suspects(text: &str, mods: &[InstanceContentSummary]) -> Vec<CrashSuspect> {
+    let index = ModIndex::new(mods);
+    let mut scores = vec![SuspectScore::default(); index.enabled.len()];
+
+    let mut add = |mod_index: Option<usize>, reason: CrashSuspectReason, score: u32| {
+        let Some(mod_index) = mod_index else {
+            return;
+        };
+        let entry = &mut scores[mod_index];
+        if reason == CrashSuspectReason::StackTrace {
+            let score = score.min(MAX_STACK_TRACE_SCORE - entry.stack_trace_score.min(MAX_STACK_TRACE_SCORE));
+            entry.stack_trace_score += score;
+            entry.score += score;
+        } else {
+            entry.score += score;
+        }
+        entry.reasons.insert(reason);
+    };
+
+    for regex in NAMED_MOD.iter() {
+        for captures in regex.captures_iter(text) {
+            add(index.find_id(&captures[1]), CrashSuspectReason::NamedInReport, 10);
+        }
+    }
+    for captures in SUSPECTED_MODS.captures_iter(text) {
+        for id in PARENTHESIZED_ID.captures_iter(&captures[1]) {
+            add(index.find_id(&id[1]), CrashSuspectReason::NamedInReport, 10);
+        }
+    }
+    for captures in MOD_FILE.captures_iter(text) {
+        add(index.find_filename(&captures[1]), CrashSuspectReason::NamedInReport, 10);
+    }
+
+    for captures in MIXIN_MOD.captures_iter(text) {
+        add(index.find_id(&captures[1]), CrashSuspectReason::MixinFailure, 6);
+    }
+    for captures in MIXIN_CONFIG.captures_iter(text) {
+        add(index.by_mixin_config.get(&captures[1]).copied(), CrashSuspectReason::MixinFailure, 6);
+    }
+
+    // The first frame of each exception that belongs to a mod is the most relevant
+    let mut first_frame = true;
+    for line in text.lines() {
+        let Some(captures) = STACK_FRAME.captures(line) else {
+            if !line.trim().is_empty() {
+                first_frame = true;
+            }
+            continue;
+        };
+
+        let mut found = index.find_class(&captures[1]);
+        if found.is_none() && let Some(jar) = FRAME_JAR.captures(line) {
+            found = index.find_filename(&jar[1]);
+        }
+        if found.is_none() && let Some(handler) = MIXIN_HANDLER.captures(line) {
+            found = index.find_id(&handler[1]);
+        }
+
+        if found.is_some() {
+            add(found, CrashSuspectReason::StackTrace, if first_frame { 3 } else { 1 });
+            first_frame = false;
+        }
+    }
+
+    let mut ranked: Vec<(usize, SuspectScore)> = scores.into_iter().enumerate().filter(|(_, score)| score.score >= 2).collect();
+    ranked.sort_by_key(|(_, score)| std::cmp::Reverse(score.score));
+
+    ranked.into_iter().take(MAX_SUSPECTS).map(|(mod_index, score)| {
+        let summary = index.enabled[mod_index];
+        CrashSuspect {
+            content_id: summary.id,
+            name: summary.content_summary.name.clone()
+                .or_else(|| summary.content_summary.id.clone())
+                .unwrap_or_else(|| summary.filename.clone()),
+            filename: summary.filename.clone(),
+            reasons: score.reasons.into_iter().collect(),
+        }
+    }).collect()
+}
+
+impl<'a> ModIndex<'a> {
+    fn new(mods: &'a [InstanceContentSummary]) -> Self {
+        let mut index = Self::default();
+
+        for summary in mods.iter().filter(|summary| summary.enabled) {
+            let mod_index = index.enabled.len();
+            index.enabled.push(summary);
+
+            if let Some(id) = &summary.content_summary.id {
+                index.by_id.insert(normalize_id(id), mod_index);
+            }
+            if let Some(info) = &summary.content_summary.dependency_info {
+                for provided in info.provides.iter() {
+                    index.by_id.entry(normalize_id(&provided.id)).or_insert(mod_index);
+                }
+            }
+            index.by_filename.insert(summary.filename.to_lowercase(), mod_index);
+
+            index.index_archive(&summary.path, mod_index);
+        }
+
+        // Packages used by more than one mod are usually shaded libraries and say nothing about the culprit
+        index.by_package.retain(|_, mod_index| mod_index.is_some());
+
+        index
+    }
+
+    fn index_archive(&mut self, path: &Path, mod_index: usize) {
+        let Ok(file) = std::fs::File::open(path) else {
+            return;
+        };
+        let Ok(archive) = file.read_zip() else {
+            return;
+        };
+
+        for entry in archive.entries() {
+            let name = entry.name.as_str();
+            if name.starts_with("META-INF/") {
+                continue;
+            }
+            if let Some(class) = name.strip_suffix(".class") {
+                let mut segments: Vec<&str> = class.split('/').collect();
+                segments.pop();
+                if segments.len() >= 2 {
+                    let package = segments[..segments.len().min(3)].join(".");
+                    match self.by_package.get(&package) {
+                        Some(Some(existing)) if *existing != mod_index => {
+                            self.by_package.insert(package, None);
+                        },
+                        Some(_) => {},
+                        None => {
+                            self.by_package.insert(package, Some(mod_index));
+                        },
+                    }
+                }
+            } else if !name.contains('/') && name.ends_with(".json") && name.contains("mixin") {
+                self.by_mixin_config.insert(name.to_string(), mod_index);
+            }
+        }
+    }
+
+    fn find_id(&self, id: &str) -> Option<usize> {
+        self.by_id.get(&normalize_id(id)).copied()
+    }
+
+    fn find_filename(&self, filename: &str) -> Option<usize> {
+        let filename = filename.to_lowercase();
+        self.by_filename.get(&filename)
+            .or_else(|| self.by_filename.get(&format!("{filename}.disabled")))
+            .copied()
+    }
+
+    fn find_class(&self, class: &str) -> Option<usize> {
+        let segments: Vec<&str> = class.split('.').collect();
+        if segments.len() < 3 {
+            return None;
+        }
+        let package = &segments[..segments.len() - 1];
+        for len in (2..=package.len().min(3)).rev() {
+            if let Some(Some(mod_index)) = self.by_package.get(&package[..len].join(".")) {
+                return Some(*mod_index);
+            }
+        }
+        None
+    }
+}
+
+fn normalize_id(id: &str) -> String {
+    id.chars().filter(|c| *c != '-' && *c != '_').flat_map(char::to_lowercase).collect()
+}
@@ -0,0 +1,365 @@
+use std::{path::Path, sync::Arc, time::{Duration, SystemTime}};
+
+use bridge::{
+    disk_usage::{DiskUsageEntry, DiskUsageReport, InstanceDiskUsage}, instance::{ContentType, InstanceContentSummary, InstanceID, InstanceStatus}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use rustc_hash::{FxHashMap, FxHashSet};
+use sha1::{Digest, Sha1};
+use strum::IntoEnumIterator;
+use ustr::Ustr;
+
+use crate::{BackendState, directories::LauncherDirectories, instance::ContentFolder};
+
+/// Extracted natives that haven't been touched for this long are considered old
+const OLD_NATIVES_AGE: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+/// Library files modified more recently than this may belong to an install that is still in progress
+const RECENT_LIBRARY_FILE_AGE: Duration = Duration::from_secs(60 * 60);
+
+#[derive(thiserror::Error, Debug)]
+pub enum DiskUsageError {
+    #[error("Unable to load the content of {0}")]
+    UnableToLoadContent(Ustr),
+    #[error("Failed to move files to the trash:\n{0}")]
+    TrashError(#[from] trash::Error),
+}
+
+/// Files are identified by inode where possible so that hard links are only counted once
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+enum FileKey {
+    #[cfg(unix)]
+    Inode(u64, u64),
+    #[cfg(not(unix))]
+    Path(std::path::PathBuf),
+    #[cfg(not(unix))]
+    Library([u8; 20]),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FileOwner {
+    Library,
+    Instance(usize),
+    Shared,
+}
+
+struct ReferencedContent {
+    hashes: FxHashSet<[u8; 20]>,
+    hash_by_path: FxHashMap<Arc<Path>, [u8; 20]>,
+}
+
+struct DiskUsageScan {
+    report: DiskUsageReport,
+    /// Hashes that will still be in the content library or an instance after cleaning up
+    kept_hashes: FxHashSet<[u8; 20]>,
+}
+
+impl BackendState {
+    pub async fn scan_disk_usage(&self) -> Result<DiskUsageReport, DiskUsageError> {
+        let scan = self.run_disk_usage_scan().await?;
+        Ok(scan.report)
+    }
+
+    /// Moves orphaned content library files and old natives to the trash and forgets about content that no longer exists
+    pub async fn cleanup_disk_usage(&self, modal_action: &ModalAction) -> Result<(), DiskUsageError> {
+        let tracker = ProgressTracker::new("Scanning disk usage".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let scan = self.run_disk_usage_scan().await?;
+        tracker.set_finished(ProgressTrackerFinishType::Fast);
+        tracker.notify();
+
+        let paths: Vec<Arc<Path>> = scan.report.orphaned_library.iter().chain(scan.report.old_natives.iter())
+            .map(|entry| entry.path.clone())
+            .collect();
+        let files = paths.len();
+        let bytes = scan.report.reclaimable_bytes();
+
+        if !paths.is_empty() {
+            let tracker = ProgressTracker::new("Moving files to the trash".into(), self.send.clone());
+            modal_action.trackers.push(tracker.clone());
+            tracker.set_total(files);
+            tracker.notify();
+
+            tokio::task::spawn_blocking(move || trash::delete_all(paths)).await.unwrap()?;
+
+            tracker.set_count(files);
+            tracker.set_finished(ProgressTrackerFinishType::Normal);
+            tracker.notify();
+        }
+
+        let kept_hashes = scan.kept_hashes;
+        let meta_entries = self.mod_metadata_manager.remove_orphaned_meta(|hash| kept_hashes.contains(hash));
+
+        self.send.send_success(format!("Moved {files} file(s) ({} MiB) to the trash and removed {meta_entries} unused content source(s)", bytes / (1024 * 1024)));
+        Ok(())
+    }
+
+    async fn run_disk_usage_scan(&self) -> Result<DiskUsageScan, DiskUsageError> {
+        let referenced = self.load_referenced_content().await?;
+
+        let (instances, any_running) = {
+            let instance_state = self.instance_state.read();
+            let instances: Vec<(InstanceID, Ustr, Arc<Path>)> = instance_state.instances.iter()
+                .map(|instance| (instance.id, instance.name, instance.root_path.clone()))
+                .collect();
+            let any_running = instance_state.instances.iter().any(|instance| instance.status() != InstanceStatus::NotRunning);
+            (instances, any_running)
+        };
+
+        let directories = self.directories.clone();
+        let mut scan = tokio::task::spawn_blocking(move || {
+            scan_directories(&directories, &instances, &referenced, any_running)
+        }).await.unwrap();
+
+        let kept_hashes = &scan.kept_hashes;
+        scan.report.orphaned_meta_entries = self.mod_metadata_manager.count_orphaned_meta(|hash| kept_hashes.contains(hash));
+
+        Ok(scan)
+    }
+
+    /// Collects the hash of every file used by an instance, including the files that modpacks install when launching
+    async fn load_referenced_content(&self) -> Result<ReferencedContent, DiskUsageError> {
+        let instances: Vec<(InstanceID, Ustr)> = self.instance_state.read().instances.iter()
+            .map(|instance| (instance.id, instance.name))
+            .collect();
+
+        let mut referenced = ReferencedContent {
+            hashes: FxHashSet::default(),
+            hash_by_path: FxHashMap::default(),
+        };
+        for (id, name) in instances {
+            for folder in ContentFolder::iter() {
+                let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                    return Err(DiskUsageError::UnableToLoadContent(name));
+                };
+                for summary in summaries.iter() {
+                    referenced.hash_by_path.insert(summary.path.clone(), summary.content_summary.hash);
+                    insert_content_hashes(summary, &mut referenced.hashes);
+                }
+            }
+        }
+
+        Ok(referenced)
+    }
+}
+
+/// Inserts the hash of the content itself, as well as every file a modpack installs when launching
+pub(crate) fn insert_content_hashes(summary: &InstanceContentSummary, hashes: &mut FxHashSet<[u8; 20]>) {
+    hashes.insert(summary.content_summary.hash);
+
+    let overrides = match &summary.content_summary.extra {
+        ContentType::ModrinthModpack { downloads, overrides, .. } => {
+            for download in downloads.iter() {
+                let mut hash = [0_u8; 20];
+                if hex::decode_to_slice(&*download.hashes.sha1, &mut hash).is_ok() {
+                    hashes.insert(hash);
+                }
+            }
+            overrides
+        },
+        ContentType::CurseforgeModpack { summaries, overrides, .. } => {
+            for (child, cached) in summaries.iter() {
+                if let Some(child) = child {
+                    hashes.insert(child.hash);
+                }
+                if let Some(cached) = cached {
+                    hashes.insert(cached.hash);
+                }
+            }
+            overrides
+        },
+        _ => return,
+    };
+
+    for (_, data) in overrides.iter() {
+        let mut hasher = Sha1::new();
+        hasher.update(data);
+        hashes.insert(hasher.finalize().into());
+    }
+}
+
+fn scan_directories(
+    directories: &LauncherDirectories,
+    instances: &[(InstanceID, Ustr, Arc<Path>)],
+    referenced: &ReferencedContent,
+    any_running: bool,
+) -> DiskUsageScan {
+    let now = SystemTime::now();
+    let mut files: FxHashMap<FileKey, (u64, FileOwner)> = FxHashMap::default();
+
+    let mut content_library_files = 0;
+    let mut content_library_bytes = 0;
+    let mut library_hashes = FxHashSet::default();
+    let mut orphaned_library = Vec::new();
+    let mut kept_hashes = referenced.hashes.clone();
+
+    for entry in walkdir::WalkDir::new(&directories.content_library_dir).into_iter().filter_map(Result::ok) {
+        let Ok(metadata) = entry.metadata() else {
+            continue;
+        };
+        if !metadata.is_file() {
+            continue;
+        }
+        let path = entry.path();
+        let bytes = metadata.len();
+
+        content_library_files += 1;
+        content_library_bytes += bytes;
+
+        let hash = library_file_hash(path);
+        if let Some(hash) = hash {
+            library_hashes.insert(hash);
+        }
+        files.insert(file_key(path, &metadata, hash), (bytes, FileOwner::Library));
+
+        // Anything that isn't named after its hash (e.g. lock files) isn't ours to clean up
+        let Some(hash) = hash else {
+            continue;
+        };
+        let recently_modified = metadata.modified().ok()
+            .and_then(|modified| now.duration_since(modified).ok())
+            .is_none_or(|age| age < RECENT_LIBRARY_FILE_AGE);
+        if referenced.hashes.contains(&hash) || recently_modified || has_other_links(path, &metadata) {
+            kept_hashes.insert(hash);
+        } else {
+            orphaned_library.push(DiskUsageEntry { path: path.into(), bytes });
+        }
+    }
+
+    let mut instance_usage = Vec::with_capacity(instances.len());
+    for (index, (id, name, root_path)) in instances.iter().enumerate() {
+        let mut seen = FxHashSet::default();
+        let mut total_bytes = 0;
+
+        for entry in walkdir::WalkDir::new(root_path).into_iter().filter_map(Result::ok) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let path = entry.path();
+            let hash = referenced.hash_by_path.get(path).copied().filter(|hash| library_hashes.contains(hash));
+            let key = file_key(path, &metadata, hash);
+            if !seen.insert(key.clone()) {
+                continue;
+            }
+
+            let bytes = metadata.len();
+            total_bytes += bytes;
+            files.entry(key)
+                .and_modify(|(_, owner)| *owner = FileOwner::Shared)
+                .or_insert((bytes, FileOwner::Instance(index)));
+        }
+
+        instance_usage.push(InstanceDiskUsage {
+            id: *id,
+            name: *name,
+            total_bytes,
+            unique_bytes: 0,
+        });
+    }
+
+    let mut total_bytes = 0;
+    for (bytes, owner) in files.values() {
+        total_bytes += bytes;
+        if let FileOwner::Instance(index) = owner {
+            instance_usage[*index].unique_bytes += bytes;
+        }
+    }
+
+    let content_meta_bytes = directory_size(&directories.content_meta_dir);
+    total_bytes += content_meta_bytes;
+
+    // Natives of a running instance may still be in use
+    let mut old_natives = Vec::new();
+    if !any_running && let Ok(read_dir) = std::fs::read_dir(&directories.temp_natives_base_dir) {
+        for entry in read_dir.filter_map(Result::ok) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            let is_old = metadata.modified().ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .is_some_and(|age| age >= OLD_NATIVES_AGE);
+            if metadata.is_dir() && is_old {
+                let path = entry.path();
+                let bytes = directory_size(&path);
+                old_natives.push(DiskUsageEntry { path: path.into(), bytes });
+            }
+        }
+    }
+
+    instance_usage.sort_by(|a, b| b.total_bytes.cmp(&a.total_bytes));
+    orphaned_library.sort_by(|a, b| b.bytes.cmp(&a.bytes));
+
+    DiskUsageScan {
+        report: DiskUsageReport {
+            instances: instance_usage.into(),
+            content_library_files,
+            content_library_bytes,
+            content_meta_bytes,
+            total_bytes,
+            orphaned_library: orphaned_library.into(),
+            orphaned_meta_entries: 0,
+            old_natives: old_natives.into(),
+        },
+        kept_hashes,
+    }
+}
+
+/// Library files are stored as `<first two hex digits>/<sha1>[.extension]`
+pub(crate) fn library_file_hash(path: &Path) -> Option<[u8; 20]> {
+    let stem = path.file_name()?.to_str()?.split('.').next()?;
+    let mut hash = [0_u8; 20];
+    hex::decode_to_slice(stem, &mut hash).ok()?;
+    Some(hash)
+}
+
+#[cfg(unix)]
+fn file_key(_path: &Path, metadata: &std::fs::Metadata, _hash: Option<[u8; 20]>) -> FileKey {
+    use std::os::unix::fs::MetadataExt;
+    FileKey::Inode(metadata.dev(), metadata.ino())
+}
+
+/// Without inodes, content files are matched to the content library by their hash instead
+#[cfg(not(unix))]
+fn file_key(path: &Path, _metadata: &std::fs::Metadata, hash: Option<[u8; 20]>) -> FileKey {
+    match hash {
+        Some(hash) => FileKey::Library(hash),
+        None => FileKey::Path(path.to_path_buf()),
+    }
+}
+
+#[cfg(unix)]
+fn has_other_links(_path: &Path, metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    metadata.nlink() > 1
+}
+
+/// The link count isn't part of the metadata std returns on Windows, so it's read from the file handle
+#[cfg(windows)]
+fn has_other_links(path: &Path, _metadata: &std::fs::Metadata) -> bool {
+    use std::os::windows::io::AsRawHandle;
+    use windows::Win32::{Foundation::HANDLE, Storage::FileSystem::{BY_HANDLE_FILE_INFORMATION, GetFileInformationByHandle}};
+
+    let Ok(file) = std::fs::File::open(path) else {
+        return false;
+    };
+    let mut info = BY_HANDLE_FILE_INFORMATION::default();
+    let result = unsafe { GetFileInformationByHandle(HANDLE(file.as_raw_handle()), &mut info) };
+    result.is_ok() && info.nNumberOfLinks > 1
+}
+
+#[cfg(not(any(unix, windows)))]
+fn has_other_links(_path: &Path, _metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+fn directory_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path).into_iter()
+        .filter_map(Result::ok)
+        .filter_map(|entry| entry.metadata().ok())
+        .filter(|metadata| metadata.is_file())
+        .map(|metadata| metadata.len())
+        .sum()
+}
@@ -0,0 +1,324 @@
+use std::{path::Path, sync::{Arc, atomic::{AtomicU64, Ordering}}, time::Duration};
+
+use bridge::modal_action::ProgressTracker;
+use futures::StreamExt;
+use reqwest::{StatusCode, header::RANGE};
+use schema::backend_config::DownloadConfig;
+use sha1::{Digest, Sha1};
+use tokio::{io::AsyncWriteExt, sync::Semaphore, time::Instant};
+
+use crate::mirror::MirrorTable;
+
+const MAX_ATTEMPTS: u32 = 5;
+/// A mirror that isn't working shouldn't hold up every download for long before falling back to the origin
+const MIRROR_ATTEMPTS: u32 = 2;
+const INITIAL_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+#[derive(thiserror::Error, Debug)]
+pub enum DownloadError {
+    #[error("Failed to download remote content:\n{0}")]
+    Reqwest(#[from] reqwest::Error),
+    #[error("Remote server returned non-success status code: {0}")]
+    NotOK(StatusCode),
+    #[error("Downloaded file had the wrong size. Expected {0}, got {1}")]
+    WrongSize(usize, usize),
+    #[error("Downloaded file had the wrong hash")]
+    WrongHash,
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+impl DownloadError {
+    fn is_retryable(&self) -> bool {
+        match self {
+            DownloadError::Reqwest(error) => error.is_timeout() || error.is_connect() || error.is_request() || error.is_body() || error.is_decode(),
+            DownloadError::NotOK(status) => status.is_server_error() || *status == StatusCode::TOO_MANY_REQUESTS || *status == StatusCode::RANGE_NOT_SATISFIABLE,
+            _ => false,
+        }
+    }
+}
+
+/// Shared by every download in the launcher so that the concurrency and bandwidth limits apply to all of them together
+pub struct DownloadManager {
+    http_client: reqwest::Client,
+    mirrors: Arc<MirrorTable>,
+    connections: Arc<Semaphore>,
+    max_concurrent: parking_lot::Mutex<usize>,
+    /// Bytes per second, 0 if unlimited
+    bandwidth_limit: AtomicU64,
+    next_chunk_at: parking_lot::Mutex<Instant>,
+}
+
+#[derive(Default)]
+struct DownloadState {
+    hasher: Sha1,
+    received: usize,
+}
+
+enum DownloadSink {
+    Memory(Vec<u8>),
+    File(tokio::fs::File),
+}
+
+impl DownloadSink {
+    async fn write(&mut self, bytes: &[u8]) -> std::io::Result<()> {
+        match self {
+            DownloadSink::Memory(vec) => {
+                vec.extend_from_slice(bytes);
+                Ok(())
+            },
+            DownloadSink::File(file) => file.write_all(bytes).await,
+        }
+    }
+
+    async fn truncate(&mut self) -> std::io::Result<()> {
+        match self {
+            DownloadSink::Memory(vec) => {
+                vec.clear();
+                Ok(())
+            },
+            DownloadSink::File(file) => file.set_len(0).await,
+        }
+    }
+}
+
+impl DownloadManager {
+    pub fn new(http_client: reqwest::Client, mirrors: Arc<MirrorTable>, config: &DownloadConfig) -> Self {
+        let max_concurrent = config.max_concurrent();
+        Self {
+            http_client,
+            mirrors,
+            connections: Arc::new(Semaphore::new(max_concurrent)),
+            max_concurrent: parking_lot::Mutex::new(max_concurrent),
+            bandwidth_limit: AtomicU64::new(config.bandwidth_limit_bytes()),
+            next_chunk_at: parking_lot::Mutex::new(Instant::now()),
+        }
+    }
+
+    pub fn apply_config(&self, config: &DownloadConfig) {
+        self.bandwidth_limit.store(config.bandwidth_limit_bytes(), Ordering::Relaxed);
+
+        let new_max_concurrent = config.max_concurrent();
+        let mut max_concurrent = self.max_concurrent.lock();
+        if new_max_concurrent > *max_concurrent {
+            self.connections.add_permits(new_max_concurrent - *max_concurrent);
+        } else if new_max_concurrent < *max_concurrent {
+            // Permits can only be removed once the downloads holding them are done
+            let connections = Arc::clone(&self.connections);
+            let excess = (*max_concurrent - new_max_concurrent) as u32;
+            tokio::task::spawn(async move {
+                if let Ok(permits) = connections.acquire_many_owned(excess).await {
+                    permits.forget();
+                }
+            });
+        }
+        *max_concurrent = new_max_concurrent;
+    }
+
+    /// Downloads into `<path>.part` and moves it to `path` once it has the expected size and hash.
+    /// A partial file left behind by an earlier attempt is resumed instead of starting over
+    pub async fn download_to_file(&self, url: &str, path: &Path, size: Option<usize>, sha1: Option<[u8; 20]>, tracker: Option<&ProgressTracker>) -> Result<(), DownloadError> {
+        let part_path = path.with_added_extension("part");
+
+        // Tokio doesn't have lock, so we use std temporarily to lock it
+        let file = std::fs::OpenOptions::new().read(true).append(true).create(true).open(&part_path)?;
+        _ = file.lock();
+
+        let (file, mut state) = tokio::task::spawn_blocking(move || {
+            let mut file = file;
+            let mut state = DownloadState::default();
+            state.received = std::io::copy(&mut file, &mut state.hasher)? as usize;
+            Ok::<_, std::io::Error>((file, state))
+        }).await.unwrap()?;
+
+        let mut sink = DownloadSink::File(tokio::fs::File::from_std(file));
+        if size.is_some_and(|size| state.received > size) {
+            sink.truncate().await?;
+            state = DownloadState::default();
+        }
+
+        if state.received > 0 {
+            log::info!("Resuming download of {url} from {} bytes", state.received);
+        }
+
+        let result = self.download(url, &mut sink, state, size, sha1, tracker).await;
+        let DownloadSink::File(mut file) = sink else {
+            unreachable!();
+        };
+
+        if let Err(error) = result {
+            // Whatever was downloaded so far is useless if it doesn't match, everything else can be resumed later
+            if matches!(error, DownloadError::WrongSize(..) | DownloadError::WrongHash) {
+                drop(file);
+                let _ = tokio::fs::remove_file(&part_path).await;
+            }
+            return Err(error);
+        }
+
+        file.flush().await?;
+        drop(file);
+
+        // Overwrite in place instead of replacing the file so that hard links into instances are kept
+        if tokio::fs::try_exists(path).await.unwrap_or(false) {
+            tokio::fs::copy(&part_path, path).await?;
+            let _ = tokio::fs::remove_file(&part_path).await;
+        } else {
+            tokio::fs::rename(&part_path, path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Downloads into memory, resuming from the bytes received so far if the connection drops
+    pub async fn download_bytes(&self, url: &str, size: Option<usize>, sha1: Option<[u8; 20]>, tracker: Option<&ProgressTracker>) -> Result<Vec<u8>, DownloadError> {
+        let mut sink = DownloadSink::Memory(Vec::with_capacity(size.unwrap_or(0)));
+        self.download(url, &mut sink, DownloadState::default(), size, sha1, tracker).await?;
+        let DownloadSink::Memory(bytes) = sink else {
+            unreachable!();
+        };
+        Ok(bytes)
+    }
+
+    async fn download(
+        &self,
+        url: &str,
+        sink: &mut DownloadSink,
+        mut state: DownloadState,
+        size: Option<usize>,
+        sha1: Option<[u8; 20]>,
+        tracker: Option<&ProgressTracker>,
+    ) -> Result<(), DownloadError> {
+        let _permit = self.connections.acquire().await.unwrap();
+
+        if state.received > 0 && let Some(tracker) = tracker {
+            tracker.add_count(state.received);
+            tracker.notify();
+        }
+
+        if let Some(mirror_url) = self.mirrors.rewrite(url) {
+            match self.download_from(&mirror_url, MIRROR_ATTEMPTS, sink, &mut state, size, sha1, tracker).await {
+                Ok(()) => return Ok(()),
+                Err(error) => {
+                    log::warn!("Download from mirror {mirror_url} failed, falling back to {url}:\n{error}");
+                    // Whatever the mirror sent can't be trusted
+                    Self::restart(sink, &mut state, tracker).await?;
+                },
+            }
+        }
+
+        self.download_from(url, MAX_ATTEMPTS, sink, &mut state, size, sha1, tracker).await
+    }
+
+    async fn download_from(
+        &self,
+        url: &str,
+        attempts: u32,
+        sink: &mut DownloadSink,
+        state: &mut DownloadState,
+        size: Option<usize>,
+        sha1: Option<[u8; 20]>,
+        tracker: Option<&ProgressTracker>,
+    ) -> Result<(), DownloadError> {
+        let mut attempt = 1;
+        loop {
+            match self.fetch(url, sink, state, size, tracker).await {
+                Ok(()) => break,
+                Err(error) if attempt < attempts && error.is_retryable() => {
+                    let delay = INITIAL_RETRY_DELAY * 2_u32.pow(attempt - 1);
+                    log::warn!("Download of {url} failed, retrying in {}s:\n{error}", delay.as_secs());
+                    tokio::time::sleep(delay).await;
+                    attempt += 1;
+                },
+                Err(error) => return Err(error),
+            }
+        }
+
+        if let Some(size) = size && state.received != size {
+            return Err(DownloadError::WrongSize(size, state.received));
+        }
+        if let Some(sha1) = sha1 && *state.hasher.clone().finalize() != sha1 {
+            return Err(DownloadError::WrongHash);
+        }
+
+        Ok(())
+    }
+
+    async fn fetch(
+        &self,
+        url: &str,
+        sink: &mut DownloadSink,
+        state: &mut DownloadState,
+        size: Option<usize>,
+        tracker: Option<&ProgressTracker>,
+    ) -> Result<(), DownloadError> {
+        if state.received > 0 && size == Some(state.received) {
+            return Ok(());
+        }
+
+        let mut request = self.http_client.get(url);
+        if state.received > 0 {
+            request = request.header(RANGE, format!("bytes={}-", state.received));
+        }
+        let response = request.send().await?;
+
+        match response.status() {
+            StatusCode::PARTIAL_CONTENT if state.received > 0 => {},
+            StatusCode::OK => {
+                if state.received > 0 {
+                    log::debug!("{url} doesn't support resuming downloads, starting over");
+                    Self::restart(sink, state, tracker).await?;
+                }
+            },
+            status => {
+                if status == StatusCode::RANGE_NOT_SATISFIABLE {
+                    Self::restart(sink, state, tracker).await?;
+                }
+                return Err(DownloadError::NotOK(status));
+            },
+        }
+
+        let mut stream = response.bytes_stream();
+        while let Some(chunk) = stream.next().await {
+            let chunk = chunk?;
+            self.throttle(chunk.len()).await;
+
+            state.hasher.update(&chunk);
+            sink.write(&chunk).await?;
+            state.received += chunk.len();
+
+            if let Some(tracker) = tracker {
+                tracker.add_count(chunk.len());
+                tracker.notify();
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn restart(sink: &mut DownloadSink, state: &mut DownloadState, tracker: Option<&ProgressTracker>) -> std::io::Result<()> {
+        sink.truncate().await?;
+        // Progress can't go backwards, so the bytes that are downloaded again are added to the total instead
+        if let Some(tracker) = tracker && state.received > 0 {
+            tracker.add_total(state.received);
+            tracker.notify();
+        }
+        *state = DownloadState::default();
+        Ok(())
+    }
+
+    /// Spaces out chunks so that all downloads together stay under the bandwidth limit
+    async fn throttle(&self, bytes: usize) {
+        let limit = self.bandwidth_limit.load(Ordering::Relaxed);
+        if limit == 0 {
+            return;
+        }
+
+        let start = {
+            let mut next_chunk_at = self.next_chunk_at.lock();
+            let start = (*next_chunk_at).max(Instant::now());
+            *next_chunk_at = start + Duration::from_secs_f64(bytes as f64 / limit as f64);
+            start
+        };
+        tokio::time::sleep_until(start).await;
+    }
+}
@@ -0,0 +1,466 @@
+use std::{collections::{HashMap, HashSet}, io::{BufReader, BufWriter}, path::Path, sync::Arc};
+
+use bridge::{
+    export::InstanceExportFormat, instance::{ContentType, InstanceContentSummary, InstanceID}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+};
+use indexmap::IndexMap;
+use relative_path::{RelativePath, RelativePathBuf};
+use schema::{
+    content::ContentSource,
+    curseforge::{CurseforgeModpackFile, CurseforgeModpackManifestJsonOutput, CurseforgeModpackMinecraft, CurseforgeModpackModLoader},
+    instance::InstanceConfiguration,
+    loader::Loader,
+    modification::ModrinthModpackFileDownload,
+    modrinth::{ModrinthHashAlgorithm, ModrinthVersionFilesRequest},
+    mrpack::ModrinthIndexJsonOutput,
+};
+use strum::IntoEnumIterator;
+use ustr::Ustr;
+
+use crate::{
+    BackendState, instance::ContentFolder, launch::LaunchError, metadata::{items::ModrinthVersionFilesMetadataItem, manager::MetaLoadError}, zip_writer::ZipWriter
+};
+
+const MODRINTH_VERSION_FILES_CHUNK_SIZE: usize = 100;
+
+#[derive(thiserror::Error, Debug)]
+pub enum ExportError {
+    #[error("Unable to find instance")]
+    UnknownInstance,
+    #[error("Unable to load instance content")]
+    UnableToLoadContent,
+    #[error("Failed to resolve loader version:\n{0}")]
+    LaunchError(#[from] LaunchError),
+    #[error("Failed to load metadata:\n{0}")]
+    MetaLoadError(#[from] MetaLoadError),
+    #[error("Failed to serialize data:\n{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to wait for task:\n{0}")]
+    JoinError(#[from] tokio::task::JoinError),
+    #[error("Cancelled by user")]
+    CancelledByUser,
+}
+
+pub(crate) enum ExportFileSource {
+    File(Arc<Path>),
+    Bytes(Arc<[u8]>),
+}
+
+/// Files that will be written into the `overrides/` folder of the exported pack
+#[derive(Default)]
+pub(crate) struct ExportOverrides {
+    files: Vec<(RelativePathBuf, ExportFileSource)>,
+    seen: HashSet<RelativePathBuf>,
+}
+
+impl ExportOverrides {
+    pub fn add(&mut self, path: RelativePathBuf, source: ExportFileSource) {
+        if self.seen.insert(path.clone()) {
+            self.files.push((path, source));
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    fn collect_selected(&mut self, dot_minecraft: &Path, selected: &[SafePath]) {
+        for selected in selected {
+            // Content folders are handled separately
+            if ContentFolder::iter().any(|folder| selected.starts_with(folder.path())) {
+                continue;
+            }
+
+            let path = selected.to_path(dot_minecraft);
+            for entry in walkdir::WalkDir::new(&path).follow_links(true) {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                if !entry.file_type().is_file() {
+                    continue;
+                }
+
+                let Some(filename) = entry.file_name().to_str() else {
+                    continue;
+                };
+                if filename.starts_with("pandora.") || filename.ends_with(".aux.json") {
+                    continue;
+                }
+
+                let Ok(relative) = entry.path().strip_prefix(dot_minecraft) else {
+                    continue;
+                };
+                let Ok(relative) = RelativePathBuf::from_path(relative) else {
+                    continue;
+                };
+
+                self.add(relative, ExportFileSource::File(entry.path().into()));
+            }
+        }
+    }
+}
+
+pub(crate) struct ExportInstanceInfo {
+    pub dot_minecraft: Arc<Path>,
+    pub configuration: InstanceConfiguration,
+    pub content: Vec<(ContentFolder, Arc<[InstanceContentSummary]>)>,
+}
+
+impl BackendState {
+    pub async fn export_instance(
+        &self,
+        id: InstanceID,
+        format: InstanceExportFormat,
+        name: Arc<str>,
+        version: Arc<str>,
+        overrides: Arc<[SafePath]>,
+        output: Arc<Path>,
+        modal_action: &ModalAction,
+    ) -> Result<(), ExportError> {
+        let (dot_minecraft, configuration) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(ExportError::UnknownInstance);
+            };
+            (instance.dot_minecraft_path.clone(), instance.configuration.get().clone())
+        };
+
+        let mut content = Vec::new();
+        for folder in ContentFolder::iter() {
+            let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                return Err(ExportError::UnableToLoadContent);
+            };
+            content.push((folder, summaries));
+        }
+
+        let info = ExportInstanceInfo {
+            dot_minecraft,
+            configuration,
+            content,
+        };
+
+        let result = match format {
+            InstanceExportFormat::Modrinth => self.export_mrpack(info, name, version, overrides, &output, modal_action).await,
+            InstanceExportFormat::Curseforge => self.export_curseforge(info, name, version, overrides, &output, modal_action).await,
+        };
+
+        if result.is_err() {
+            _ = std::fs::remove_file(&output);
+        }
+
+        result
+    }
+
+    async fn export_curseforge(
+        &self,
+        info: ExportInstanceInfo,
+        name: Arc<str>,
+        version: Arc<str>,
+        selected_overrides: Arc<[SafePath]>,
+        output: &Path,
+        modal_action: &ModalAction,
+    ) -> Result<(), ExportError> {
+        let tracker = ProgressTracker::new("Resolving content".into(), self.send.clone());
+        tracker.set_total(1);
+        tracker.notify();
+        modal_action.trackers.push(tracker.clone());
+
+        let mut mod_loaders = Vec::new();
+        if let Some(loader_version) = self.launcher.resolve_loader_version(&info.configuration).await? {
+            let id = match info.configuration.loader {
+                Loader::Fabric => Some(format!("fabric-{}", loader_version)),
+                Loader::Forge => Some(format!("forge-{}", strip_minecraft_version_prefix(&info.configuration, loader_version))),
+                Loader::NeoForge => Some(format!("neoforge-{}", loader_version)),
+                Loader::Quilt => Some(format!("quilt-{}", loader_version)),
+                Loader::Vanilla | Loader::Unknown => None,
+            };
+            if let Some(id) = id {
+                mod_loaders.push(CurseforgeModpackModLoader {
+                    id: id.into(),
+                    primary: true,
+                });
+            }
+        }
+
+        let mut files = Vec::new();
+        let mut overrides = ExportOverrides::default();
+
+        for (folder, summaries) in &info.content {
+            for summary in summaries.iter() {
+                let pack_path = folder.path().join(&*summary.filename);
+
+                if !summary.enabled {
+                    overrides.add(pack_path, ExportFileSource::File(summary.path.clone()));
+                    continue;
+                }
+
+                match &summary.content_summary.extra {
+                    ContentType::CurseforgeModpack { files: modpack_files, overrides: modpack_overrides, .. } => {
+                        for (index, file) in modpack_files.iter().enumerate() {
+                            if self.is_curseforge_modpack_file_enabled(summary, index) {
+                                files.push(file.clone());
+                            }
+                        }
+                        for (path, data) in modpack_overrides.iter() {
+                            overrides.add(RelativePath::new(path.as_str()).to_relative_path_buf(), ExportFileSource::Bytes(data.clone()));
+                        }
+                        continue;
+                    },
+                    ContentType::ModrinthModpack { downloads, overrides: modpack_overrides, .. } => {
+                        // Modrinth downloads can't be referenced from a CurseForge manifest, bundle the files instead
+                        for download in downloads.iter() {
+                            if !self.is_modpack_download_enabled(summary, download) {
+                                continue;
+                            }
+
+                            let mut hash = [0u8; 20];
+                            if hex::decode_to_slice(&*download.hashes.sha1, &mut hash).is_err() {
+                                continue;
+                            }
+                            let extension = RelativePath::new(&*download.path).extension();
+                            let library_path = crate::create_content_library_path(&self.directories.content_library_dir, hash, extension);
+                            if !library_path.exists() {
+                                log::warn!("Unable to export {}, file hasn't been downloaded", download.path);
+                                continue;
+                            }
+                            overrides.add(RelativePath::new(&*download.path).to_relative_path_buf(), ExportFileSource::File(library_path.into()));
+                        }
+                        for (path, data) in modpack_overrides.iter() {
+                            overrides.add(RelativePath::new(path.as_str()).to_relative_path_buf(), ExportFileSource::Bytes(data.clone()));
+                        }
+                        continue;
+                    },
+                    _ => {},
+                }
+
+                let curseforge_file = match &summary.content_source {
+                    ContentSource::CurseforgeProject { project_id } => {
+                        self.mod_metadata_manager.get_cached_curseforge_file_id(&summary.content_summary.hash)
+                            .map(|file_id| (*project_id, file_id))
+                    },
+                    _ => None,
+                };
+
+                if let Some((project_id, file_id)) = curseforge_file {
+                    files.push(CurseforgeModpackFile {
+                        project_id,
+                        file_id,
+                        required: true,
+                    });
+                } else {
+                    overrides.add(pack_path, ExportFileSource::File(summary.path.clone()));
+                }
+            }
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        let manifest = CurseforgeModpackManifestJsonOutput {
+            minecraft: CurseforgeModpackMinecraft {
+                version: Some(info.configuration.minecraft_version.as_str().into()),
+                mod_loaders: mod_loaders.into(),
+                recommended_ram: None,
+            },
+            manifest_type: "minecraftModpack",
+            manifest_version: 1,
+            name,
+            version,
+            author: "".into(),
+            files,
+            overrides: "overrides",
+        };
+        let manifest = serde_json::to_vec_pretty(&manifest)?;
+
+        self.write_pack("manifest.json", manifest, overrides, info.dot_minecraft, selected_overrides, output, modal_action).await
+    }
+
+    async fn export_mrpack(
+        &self,
+        info: ExportInstanceInfo,
+        name: Arc<str>,
+        version: Arc<str>,
+        selected_overrides: Arc<[SafePath]>,
+        output: &Path,
+        modal_action: &ModalAction,
+    ) -> Result<(), ExportError> {
+        let tracker = ProgressTracker::new("Resolving content".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+
+        let mut dependencies: IndexMap<&'static str, Arc<str>> = IndexMap::new();
+        dependencies.insert("minecraft", Arc::from(info.configuration.minecraft_version.as_str()));
+
+        if let Some(loader_version) = self.launcher.resolve_loader_version(&info.configuration).await? {
+            match info.configuration.loader {
+                Loader::Fabric => {
+                    dependencies.insert("fabric-loader", Arc::from(loader_version.as_str()));
+                },
+                Loader::Forge => {
+                    dependencies.insert("forge", strip_minecraft_version_prefix(&info.configuration, loader_version));
+                },
+                Loader::NeoForge => {
+                    dependencies.insert("neoforge", Arc::from(loader_version.as_str()));
+                },
+                Loader::Quilt => {
+                    dependencies.insert("quilt-loader", Arc::from(loader_version.as_str()));
+                },
+                Loader::Vanilla | Loader::Unknown => {},
+            }
+        }
+
+        let mut files = Vec::new();
+        let mut overrides = ExportOverrides::default();
+        let mut candidates = Vec::new();
+
+        for (folder, summaries) in &info.content {
+            for summary in summaries.iter() {
+                let pack_path = folder.path().join(&*summary.filename);
+
+                if !summary.enabled {
+                    overrides.add(pack_path, ExportFileSource::File(summary.path.clone()));
+                    continue;
+                }
+
+                if let ContentType::ModrinthModpack { downloads, overrides: modpack_overrides, .. } = &summary.content_summary.extra {
+                    for download in downloads.iter() {
+                        if self.is_modpack_download_enabled(summary, download) {
+                            files.push(download.clone());
+                        }
+                    }
+                    for (path, data) in modpack_overrides.iter() {
+                        overrides.add(RelativePath::new(path.as_str()).to_relative_path_buf(), ExportFileSource::Bytes(data.clone()));
+                    }
+                    continue;
+                }
+
+                candidates.push((pack_path, summary));
+            }
+        }
+
+        tracker.set_total(candidates.len().div_ceil(MODRINTH_VERSION_FILES_CHUNK_SIZE).max(1));
+        tracker.notify();
+
+        let mut versions = HashMap::new();
+        for chunk in candidates.chunks(MODRINTH_VERSION_FILES_CHUNK_SIZE) {
+            if modal_action.has_requested_cancel() {
+                return Err(ExportError::CancelledByUser);
+            }
+
+            let hashes = chunk.iter().map(|(_, summary)| Arc::<str>::from(hex::encode(summary.content_summary.hash))).collect();
+            let result = self.meta.fetch(&ModrinthVersionFilesMetadataItem(&ModrinthVersionFilesRequest {
+                hashes,
+                algorithm: ModrinthHashAlgorithm::Sha1,
+            })).await?;
+            versions.extend(result.0.iter().map(|(hash, version)| (hash.clone(), version.clone())));
+
+            tracker.add_count(1);
+            tracker.notify();
+        }
+
+        for (pack_path, summary) in candidates {
+            let sha1 = hex::encode(summary.content_summary.hash);
+
+            let file = versions.get(sha1.as_str()).and_then(|version| {
+                if let ContentSource::ModrinthProject { project } = &summary.content_source && *project != version.project_id {
+                    log::warn!("Project id mismatch for {}, expected {} got {}", summary.filename, project, version.project_id);
+                    return None;
+                }
+                version.files.iter().find(|file| *file.hashes.sha1 == *sha1)
+            });
+
+            if let Some(file) = file && file.hashes.sha512.is_some() {
+                files.push(ModrinthModpackFileDownload {
+                    path: pack_path.as_str().into(),
+                    hashes: file.hashes.clone(),
+                    env: None,
+                    downloads: [file.url.clone()].into(),
+                    file_size: file.size,
+                });
+            } else {
+                overrides.add(pack_path, ExportFileSource::File(summary.path.clone()));
+            }
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        let index = ModrinthIndexJsonOutput {
+            format_version: 1,
+            game: "minecraft",
+            version_id: version,
+            name,
+            summary: None,
+            files,
+            dependencies,
+        };
+        let index = serde_json::to_vec_pretty(&index)?;
+
+        self.write_pack("modrinth.index.json", index, overrides, info.dot_minecraft, selected_overrides, output, modal_action).await
+    }
+
+    async fn write_pack(
+        &self,
+        manifest_name: &'static str,
+        manifest: Vec<u8>,
+        mut overrides: ExportOverrides,
+        dot_minecraft: Arc<Path>,
+        selected_overrides: Arc<[SafePath]>,
+        output: &Path,
+        modal_action: &ModalAction,
+    ) -> Result<(), ExportError> {
+        let write_tracker = ProgressTracker::new("Writing modpack".into(), self.send.clone());
+        modal_action.trackers.push(write_tracker.clone());
+
+        let output = output.to_path_buf();
+        let modal_action = modal_action.clone();
+        tokio::task::spawn_blocking(move || {
+            overrides.collect_selected(&dot_minecraft, &selected_overrides);
+
+            write_tracker.set_total(overrides.len() + 1);
+            write_tracker.notify();
+
+            let file = std::fs::File::create(&output)?;
+            let mut zip = ZipWriter::new(BufWriter::new(file));
+
+            zip.add_file(manifest_name, manifest.as_slice(), manifest.len() as u64)?;
+            write_tracker.add_count(1);
+            write_tracker.notify();
+
+            for (path, source) in &overrides.files {
+                if modal_action.has_requested_cancel() {
+                    write_tracker.set_finished(ProgressTrackerFinishType::Error);
+                    write_tracker.notify();
+                    return Err(ExportError::CancelledByUser);
+                }
+
+                let name = format!("overrides/{}", path);
+                match source {
+                    ExportFileSource::File(path) => {
+                        let file = std::fs::File::open(path)?;
+                        let size = file.metadata()?.len();
+                        zip.add_file(&name, BufReader::new(file), size)?
+                    },
+                    ExportFileSource::Bytes(bytes) => zip.add_file(&name, &bytes[..], bytes.len() as u64)?,
+                }
+
+                write_tracker.add_count(1);
+                write_tracker.notify();
+            }
+
+            zip.finish()?;
+
+            write_tracker.set_finished(ProgressTrackerFinishType::Normal);
+            write_tracker.notify();
+
+            Ok(())
+        }).await?
+    }
+}
+
+/// Forge maven versions are prefixed with the Minecraft version, modpack formats expect only the forge version
+fn strip_minecraft_version_prefix(configuration: &InstanceConfiguration, loader_version: Ustr) -> Arc<str> {
+    let prefix = format!("{}-", configuration.minecraft_version);
+    loader_version.as_str().strip_prefix(&prefix).unwrap_or(loader_version.as_str()).into()
+}
@@ -1,9 +1,8 @@
-use std::{ffi::{OsStr, OsString}, io::Write, path::{Path, PathBuf}, sync::Arc};
+use std::{ffi::{OsStr, OsString}, path::{Path, PathBuf}, sync::Arc};
 
 use bridge::{
     install::{ContentInstall, ContentInstallFile, ContentInstallPath}, instance::{ContentType, ContentSummary}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
 };
-use reqwest::StatusCode;
 use schema::{
     content::ContentSource,
     curseforge::{
@@ -14,10 +13,10 @@ use schema::{
     modrinth::{ModrinthDependency, ModrinthDependencyType, ModrinthLoader, ModrinthProjectVersion, ModrinthProjectVersionsRequest},
 };
 use sha1::{Digest, Sha1};
-use tokio::io::AsyncWriteExt;
 
 use crate::{
     BackendState,
+    download::DownloadError,
     lockfile::Lockfile,
     metadata::{
         items::{
@@ -37,14 +36,8 @@ pub enum ContentInstallError {
     UnableToDetermineContentType(Arc<str>),
     #[error("Invalid filename: {0}")]
     InvalidFilename(Arc<str>),
-    #[error("Failed to download remote content")]
-    Reqwest(#[from] reqwest::Error),
-    #[error("Remote server returned non-200 status code: {0}")]
-    NotOK(StatusCode),
-    #[error("Downloaded file had the wrong size")]
-    WrongFilesize,
-    #[error("Downloaded file had the wrong hash")]
-    WrongHash,
+    #[error("Failed to download remote content:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Missing required sha1 hash")]
     MissingHash,
     #[error("Hash isn't a valid sha1 hash:\n{0}")]
@@ -107,8 +100,6 @@ impl BackendState {
             ..content
         };
 
-        let semaphore = tokio::sync::Semaphore::new(8);
-
         let mut tasks = Vec::new();
 
         for content_file in content.files.iter() {
@@ -186,7 +177,7 @@ impl BackendState {
                             };
 
                             let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                                (&safe_filename).into(), url, sha1, size, &semaphore).await?;
+                                (&safe_filename).into(), url, sha1, size).await?;
 
                             let install_path = match &content_file.path {
                                 ContentInstallPath::Raw(path) => path.clone(),
@@ -204,6 +195,7 @@ impl BackendState {
                                     } else if let Some(mod_summary) = &mod_summary {
                                         match mod_summary.extra {
                                             ContentType::Fabric
+                                            | ContentType::Quilt
                                             | ContentType::Forge
                                             | ContentType::LegacyForge
                                             | ContentType::NeoForge
@@ -215,6 +207,12 @@ impl BackendState {
                                             ContentType::ResourcePack => {
                                                 PathBuf::from("resourcepacks")
                                             }
+                                            ContentType::ShaderPack => {
+                                                PathBuf::from("shaderpacks")
+                                            }
+                                            ContentType::Datapack => {
+                                                PathBuf::from("datapacks")
+                                            }
                                         }
                                     } else if let Some(loaders) = &version.loaders {
                                         let mut base = None;
@@ -258,6 +256,7 @@ impl BackendState {
                                 Loader::Fabric => Some(CurseforgeModLoaderType::Fabric as u32),
                                 Loader::Forge => Some(CurseforgeModLoaderType::Forge as u32),
                                 Loader::NeoForge => Some(CurseforgeModLoaderType::NeoForge as u32),
+                                Loader::Quilt => Some(CurseforgeModLoaderType::Quilt as u32),
                                 Loader::Unknown => None,
                             },
                             page_size: Some(1)
@@ -291,7 +290,7 @@ impl BackendState {
                         };
 
                         let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                            (&safe_filename).into(), url, sha1, size, &semaphore).await?;
+                            (&safe_filename).into(), url, sha1, size).await?;
 
                         let install_path = match &content_file.path {
                             ContentInstallPath::Raw(path) => path.clone(),
@@ -300,6 +299,7 @@ impl BackendState {
                                 let base = if let Some(mod_summary) = &mod_summary {
                                     match mod_summary.extra {
                                         ContentType::Fabric
+                                        | ContentType::Quilt
                                         | ContentType::Forge
                                         | ContentType::LegacyForge
                                         | ContentType::NeoForge
@@ -311,6 +311,12 @@ impl BackendState {
                                         ContentType::ResourcePack => {
                                             Path::new("resourcepacks")
                                         }
+                                        ContentType::ShaderPack => {
+                                            Path::new("shaderpacks")
+                                        }
+                                        ContentType::Datapack => {
+                                            Path::new("datapacks")
+                                        }
                                     }
                                 } else {
                                     return Err(ContentInstallError::UnableToDetermineContentType(file.file_name.clone()))
@@ -337,7 +343,7 @@ impl BackendState {
                         };
 
                         let (path, hash, mod_summary) = self.download_file_into_library(&modal_action,
-                            name, url, sha1, size, &semaphore).await?;
+                            name, url, sha1, size).await?;
 
                         let install_path = match &content_file.path {
                             ContentInstallPath::Raw(path) => path.clone(),
@@ -418,11 +424,13 @@ impl BackendState {
 
                                 let base = if let Some(mod_summary) = &mod_summary {
                                     match &mod_summary.extra {
-                                        ContentType::Fabric | ContentType::Forge | ContentType::LegacyForge
+                                        ContentType::Fabric | ContentType::Quilt | ContentType::Forge | ContentType::LegacyForge
                                             | ContentType::NeoForge | ContentType::JavaModule
                                             | ContentType::ModrinthModpack { .. }
                                             | ContentType::CurseforgeModpack { .. } => Path::new("mods"),
                                         ContentType::ResourcePack => Path::new("resourcepacks"),
+                                        ContentType::ShaderPack => Path::new("shaderpacks"),
+                                        ContentType::Datapack => Path::new("datapacks"),
                                     }
                                 } else {
                                     return Err(ContentInstallError::UnableToDetermineContentType(
@@ -550,12 +558,13 @@ impl BackendState {
                     if let Some(instance_id) = instance_id_for_reload {
                         use crate::instance::ContentFolder;
                         use std::collections::HashSet;
+                        use strum::IntoEnumIterator;
                         let mut affected = HashSet::new();
                         for install in &files {
-                            if install.install_path.starts_with(std::path::Path::new("mods")) {
-                                affected.insert(ContentFolder::Mods);
-                            } else if install.install_path.starts_with(std::path::Path::new("resourcepacks")) {
-                                affected.insert(ContentFolder::ResourcePacks);
+                            for folder in ContentFolder::iter() {
+                                if install.install_path.starts_with(folder.path().as_str()) {
+                                    affected.insert(folder);
+                                }
                             }
                         }
                         if let Some(instance) = self.instance_state.write().instances.get_mut(instance_id) {
@@ -755,6 +764,7 @@ impl BackendState {
                 Loader::Fabric => Some(CurseforgeModLoaderType::Fabric as u32),
                 Loader::Forge => Some(CurseforgeModLoaderType::Forge as u32),
                 Loader::NeoForge => Some(CurseforgeModLoaderType::NeoForge as u32),
+                Loader::Quilt => Some(CurseforgeModLoaderType::Quilt as u32),
                 Loader::Unknown => None,
             },
             page_size: Some(1),
@@ -828,8 +838,8 @@ impl BackendState {
         }
     }
 
-    async fn download_file_into_library(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, semaphore: &tokio::sync::Semaphore) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
-        let mut result = self.download_file_into_library_inner(modal_action, name, url, sha1, size, semaphore).await?;
+    async fn download_file_into_library(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
+        let mut result = self.download_file_into_library_inner(modal_action, name, url, sha1, size).await?;
 
         if let Some(summary) = &result.2 {
             if let ContentType::ModrinthModpack { downloads, .. } = &summary.extra {
@@ -846,7 +856,7 @@ impl BackendState {
                     };
 
                     tasks.push(self.download_file_into_library_inner(modal_action, name,
-                        &download.downloads[0], &download.hashes.sha1, download.file_size, semaphore));
+                        &download.downloads[0], &download.hashes.sha1, download.file_size));
                 }
 
                 _ = futures::future::try_join_all(tasks).await;
@@ -903,7 +913,6 @@ impl BackendState {
                                 download_url,
                                 sha1,
                                 file.file_length as usize,
-                                semaphore,
                             ));
                         }
 
@@ -917,7 +926,7 @@ impl BackendState {
         Ok(result)
     }
 
-    async fn download_file_into_library_inner(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize, semaphore: &tokio::sync::Semaphore) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
+    async fn download_file_into_library_inner(&self, modal_action: &ModalAction, name: FilenameAndExtension, url: &Arc<str>, sha1: &Arc<str>, size: usize) -> Result<(PathBuf, [u8; 20], Option<Arc<ContentSummary>>), ContentInstallError> {
         let mut expected_hash = [0u8; 20];
         let Ok(_) = hex::decode_to_slice(&**sha1, &mut expected_hash) else {
             log::warn!("Content install has invalid sha1: {}", sha1);
@@ -937,8 +946,6 @@ impl BackendState {
 
         let lockfile = Lockfile::create(path.with_added_extension("lock").into()).await;
 
-        let _permit = semaphore.acquire().await.unwrap();
-
         let file_name = name.filename.clone();
 
         let title = format!("Downloading {}", file_name.as_deref().map(|s| s.to_string_lossy()).unwrap_or(std::borrow::Cow::Borrowed("???")));
@@ -963,55 +970,12 @@ impl BackendState {
             return Ok((path, expected_hash, summary));
         }
 
-        let response = self.redirecting_http_client.get(&**url).send().await?;
-
-        if response.status() != StatusCode::OK {
-            return Err(ContentInstallError::NotOK(response.status()));
-        }
-
-        // Tokio doesn't have lock, so we use std temporarily to lock it
-        let file = std::fs::File::create(&path)?;
-        _ = file.lock();
-
-        let mut file = tokio::fs::File::from_std(file);
-
-        use futures::StreamExt;
-        let mut stream = response.bytes_stream();
-
-        let mut total_bytes = 0;
-
-        let mut hasher = Sha1::new();
-        while let Some(item) = stream.next().await {
-            let item = item?;
-
-            total_bytes += item.len();
-            tracker.add_count(item.len());
-            tracker.notify();
-
-            hasher.write_all(&item)?;
-            file.write_all(&item).await?;
-        }
-
-        tracker.set_finished(ProgressTrackerFinishType::Fast);
-
-        let actual_hash = hasher.finalize();
-
-        let wrong_hash = *actual_hash != expected_hash;
-        let wrong_size = total_bytes != size;
+        let result = self.downloads.download_to_file(url, &path, Some(size), Some(expected_hash), Some(&tracker)).await;
 
-        if wrong_hash || wrong_size {
-            let _ = file.set_len(0).await;
-            drop(file);
-            let _ = tokio::fs::remove_file(&path).await;
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
 
-            if wrong_hash {
-                return Err(ContentInstallError::WrongHash);
-            } else if wrong_size {
-                return Err(ContentInstallError::WrongFilesize);
-            } else {
-                unreachable!();
-            }
-        }
+        result?;
 
         drop(lockfile);
 
@@ -1025,6 +989,7 @@ fn determine_loader_from_content(content: &[InstallFromContentLibrary]) -> Optio
         if let Some(summary) = &content.mod_summary {
             match &summary.extra {
                 ContentType::Fabric => return Some(Loader::Fabric),
+                ContentType::Quilt => return Some(Loader::Quilt),
                 ContentType::LegacyForge => return Some(Loader::Forge),
                 ContentType::Forge => return Some(Loader::Forge),
                 ContentType::NeoForge => return Some(Loader::NeoForge),
@@ -1035,6 +1000,7 @@ fn determine_loader_from_content(content: &[InstallFromContentLibrary]) -> Optio
                             "forge" => return Some(Loader::Forge),
                             "neoforge" => return Some(Loader::NeoForge),
                             "fabric-loader" => return Some(Loader::Fabric),
+                            "quilt-loader" => return Some(Loader::Quilt),
                             _ => {},
                         }
                     }
@@ -1044,7 +1010,7 @@ fn determine_loader_from_content(content: &[InstallFromContentLibrary]) -> Optio
                         return Some(loader);
                     }
                 },
-                ContentType::ResourcePack => {},
+                ContentType::ResourcePack | ContentType::ShaderPack | ContentType::Datapack => {},
             }
         }
     }
@@ -1056,6 +1022,7 @@ fn determine_minecraft_version_from_content(content: &[InstallFromContentLibrary
         if let Some(summary) = &content.mod_summary {
             match &summary.extra {
                 ContentType::Fabric => {},
+                ContentType::Quilt => {},
                 ContentType::LegacyForge => {},
                 ContentType::Forge => {},
                 ContentType::NeoForge => {},
@@ -1072,7 +1039,7 @@ fn determine_minecraft_version_from_content(content: &[InstallFromContentLibrary
                         return Some(version.clone());
                     }
                 },
-                ContentType::ResourcePack => {},
+                ContentType::ResourcePack | ContentType::ShaderPack | ContentType::Datapack => {},
             }
         }
     }
@@ -7,32 +7,29 @@ use std::{
 use anyhow::Context;
 use base64::Engine;
 use bridge::{
-    instance::{
+    handle::FrontendHandle, instance::{
         ContentSummary, ContentUpdateContext, ContentUpdateStatus, InstanceContentID, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceStatus, InstanceWorldSummary, WorldDatapackSummary
     }, message::{AtomicBridgeDataLoadState, BridgeDataLoadState, MessageToFrontend}, notify_signal::{KeepAliveNotifySignal, KeepAliveNotifySignalHandle}
 };
 use parking_lot::RwLock;
 use relative_path::RelativePath;
 use serde::{Deserialize, Serialize};
-use schema::{auxiliary::{AuxDisabledChildren, AuxiliaryContentMeta}, instance::InstanceConfiguration, loader::Loader};
+use schema::{auxiliary::{AuxDisabledChildren, AuxiliaryContentMeta}, instance::{InstanceConfiguration, InstanceSession, InstanceStats}, loader::Loader};
 use strum::IntoEnumIterator;
 use thiserror::Error;
 
 use ustr::Ustr;
 
-use crate::{BackendStateFileWatching, BackendStateInstances, IoOrSerializationError, WatchTarget, id_slab::{GetId, Id}, launcher_import, mod_metadata::{ContentUpdateAction, ContentUpdateKey, ModMetadataManager}, persistent::Persistent};
+use crate::{BackendStateFileWatching, BackendStateInstances, IoOrSerializationError, WatchTarget, id_slab::{GetId, Id}, launch::LaunchHook, launcher_import, mod_metadata::{ContentUpdateAction, ContentUpdateKey, ModMetadataManager}, persistent::Persistent};
 
 #[derive(Debug, Serialize, Deserialize)]
 struct RunningInstanceState {
     pid: u32,
-}
-
-#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
-pub struct InstanceStats {
-    pub total_playtime_secs: u64,
-    pub session_count: u64,
     #[serde(default)]
-    pub last_played_unix_ms: Option<i64>,
+    started_unix_ms: Option<i64>,
+    /// Kept so the hook still runs when the game exits after the launcher was restarted, without its environment
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    post_exit_hook: Option<LaunchHook>,
 }
 
 #[derive(Debug)]
@@ -45,9 +42,12 @@ pub struct Instance {
     pub name: Ustr,
     pub icon: Option<Arc<[u8]>>,
     pub configuration: Persistent<InstanceConfiguration>,
+    pub stats: Persistent<InstanceStats>,
 
     pub child: Option<Child>,
+    pub post_exit_hook: Option<LaunchHook>,
     pub running_pid: Option<u32>,
+    session_started_unix_ms: Option<i64>,
 
     pub worlds_state: Arc<AtomicBridgeDataLoadState>,
     dirty_worlds: HashSet<Arc<Path>>,
@@ -80,6 +80,8 @@ pub struct ContentFolderState {
 pub enum ContentFolder {
     Mods,
     ResourcePacks,
+    ShaderPacks,
+    Datapacks,
 }
 
 impl ContentFolder {
@@ -87,6 +89,8 @@ impl ContentFolder {
         match self {
             ContentFolder::Mods => RelativePath::new("mods"),
             ContentFolder::ResourcePacks => RelativePath::new("resourcepacks"),
+            ContentFolder::ShaderPacks => RelativePath::new("shaderpacks"),
+            ContentFolder::Datapacks => RelativePath::new("datapacks"),
         }
     }
 }
@@ -239,7 +243,7 @@ impl Instance {
         }
     }
 
-    fn load_running_pid(path: &Path) -> Option<u32> {
+    fn load_running_state(path: &Path) -> Option<RunningInstanceState> {
         let running_state_path = Self::running_state_path(path);
         let Ok(data) = std::fs::read(&running_state_path) else {
             return None;
@@ -249,24 +253,53 @@ impl Instance {
             return None;
         };
         if Self::process_exists(state.pid) {
-            Some(state.pid)
+            Some(state)
         } else {
             _ = std::fs::remove_file(running_state_path);
             None
         }
     }
 
-    pub fn set_running_pid(&mut self, pid: u32) {
+    pub fn set_running_pid(&mut self, pid: u32, post_exit_hook: Option<LaunchHook>) {
+        let now = chrono::Utc::now().timestamp_millis();
+
         self.running_pid = Some(pid);
-        let state = RunningInstanceState { pid };
+        self.session_started_unix_ms = Some(now);
+        self.stats.modify(|stats| stats.start_session(now));
+
+        let state = RunningInstanceState { pid, started_unix_ms: Some(now), post_exit_hook };
         if let Ok(bytes) = serde_json::to_vec(&state) {
             _ = crate::write_safe(&Self::running_state_path(&self.root_path), &bytes);
         }
+        self.post_exit_hook = state.post_exit_hook;
     }
 
-    pub fn clear_running_pid(&mut self) {
+    pub fn session_started_unix_ms(&self) -> Option<i64> {
+        self.session_started_unix_ms
+    }
+
+    pub fn clear_running_pid(&mut self, exit_code: Option<i32>) {
         self.running_pid = None;
         _ = std::fs::remove_file(Self::running_state_path(&self.root_path));
+
+        if let Some(started_unix_ms) = self.session_started_unix_ms.take() {
+            let now = chrono::Utc::now().timestamp_millis();
+            let duration_secs = (now - started_unix_ms).max(0) as u64 / 1000;
+            self.stats.modify(|stats| stats.end_session(InstanceSession {
+                started_unix_ms,
+                duration_secs,
+                exit_code,
+            }));
+        }
+    }
+
+    /// Runs the post-exit hook of the last launch in the background, if it had one
+    pub fn run_post_exit_hook(&mut self, sender: &FrontendHandle) {
+        if let Some(mut hook) = self.post_exit_hook.take() {
+            hook.restore_env(self.configuration.get(), self.name.as_str(), &self.root_path, &self.dot_minecraft_path);
+            let sender = sender.clone();
+            tokio::task::spawn_blocking(move || hook.run_post_exit(&sender));
+        }
     }
 
     pub fn refresh_running_pid(&mut self) -> bool {
@@ -276,7 +309,7 @@ impl Instance {
         if Self::process_exists(pid) {
             false
         } else {
-            self.clear_running_pid();
+            self.clear_running_pid(None);
             true
         }
     }
@@ -287,6 +320,7 @@ impl Instance {
         self.name = path.file_name().unwrap().to_string_lossy().into_owned().into();
         self.root_path = path.into();
         self.configuration = Persistent::load_or(path.join("info_v1.json").into(), self.configuration.get().clone());
+        self.stats = Persistent::load_or(path.join("stats_v1.json").into(), self.stats.get().clone());
 
         let mut dot_minecraft_path = path.to_owned();
         dot_minecraft_path.push(".minecraft");
@@ -808,9 +842,13 @@ impl Instance {
             ContentFolderState::new(content_type.path().to_path(&dot_minecraft_path).into())
         });
 
+        let stats = Persistent::load(path.join("stats_v1.json").into());
+
         let icon_path = path.join("icon.png");
         let icon = std::fs::read(icon_path).ok().map(|v| v.into());
 
+        let mut running_state = Self::load_running_state(path);
+
         Ok(Self {
             id: InstanceID::dangling(),
             root_path: path.into(),
@@ -820,9 +858,12 @@ impl Instance {
             name: path.file_name().unwrap().to_string_lossy().into_owned().into(),
             icon,
             configuration: instance_info,
+            stats,
 
             child: None,
-            running_pid: Self::load_running_pid(path),
+            post_exit_hook: running_state.as_mut().and_then(|state| state.post_exit_hook.take()),
+            running_pid: running_state.as_ref().map(|state| state.pid),
+            session_started_unix_ms: running_state.and_then(|state| state.started_unix_ms),
 
             worlds_state: Arc::new(AtomicBridgeDataLoadState::new(BridgeDataLoadState::Unloaded)),
             dirty_worlds: HashSet::new(),
@@ -880,6 +921,7 @@ impl Instance {
         self.root_path = new.root_path;
         self.name = new.name;
         self.configuration = new.configuration;
+        self.stats = new.stats;
     }
 
     pub fn status(&self) -> InstanceStatus {
@@ -915,6 +957,7 @@ impl Instance {
             root_path: self.resolve_real_root_path(),
             dot_minecraft_folder: self.dot_minecraft_path.clone(),
             configuration: self.configuration.get().clone(),
+            stats: self.stats.get().clone(),
             status,
         }
     }
@@ -0,0 +1,387 @@
+use std::{collections::HashMap, path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{InstanceContentID, InstanceContentSummary, InstanceID}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}, safe_path::SafePath
+};
+use schema::{
+    auxiliary::AuxiliaryContentMeta,
+    curseforge::CurseforgeGetFilesRequest,
+    instance::InstanceSnapshotReason,
+    instance_lock::{InstanceLock, InstanceLockFile},
+    modrinth::{ModrinthHashAlgorithm, ModrinthVersionFilesRequest},
+};
+use strum::IntoEnumIterator;
+
+use crate::{
+    BackendState, IoOrSerializationError, instance::ContentFolder, launch::LaunchError, metadata::{items::{CurseforgeGetFilesMetadataItem, ModrinthVersionFilesMetadataItem}, manager::MetaLoadError}
+};
+
+pub const INSTANCE_LOCK_FILENAME: &str = "pandora.lock.json";
+
+const MODRINTH_VERSION_FILES_CHUNK_SIZE: usize = 100;
+
+#[derive(thiserror::Error, Debug)]
+pub enum InstanceLockError {
+    #[error("Unable to find instance")]
+    UnknownInstance,
+    #[error("Unable to load instance content")]
+    UnableToLoadContent,
+    #[error("Unable to read {INSTANCE_LOCK_FILENAME}:\n{0}")]
+    UnableToReadLock(#[from] IoOrSerializationError),
+    #[error("Invalid path in lockfile: {0}")]
+    InvalidPath(Arc<str>),
+    #[error("Invalid sha1 hash in lockfile: {0}")]
+    InvalidHash(Arc<str>),
+    #[error("Unable to find a download for {0}")]
+    UnableToFindDownload(Arc<str>),
+    #[error("Unable to install {0}")]
+    UnableToInstall(Arc<str>),
+    #[error("Failed to install content:\n{0}")]
+    InstallError(Arc<str>),
+    #[error("Failed to resolve loader version:\n{0}")]
+    LaunchError(#[from] LaunchError),
+    #[error("Failed to load metadata:\n{0}")]
+    MetaLoadError(#[from] MetaLoadError),
+    #[error("Failed to serialize data:\n{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+    #[error("Failed to move files to the trash:\n{0}")]
+    TrashError(#[from] trash::Error),
+    #[error("Cancelled by user")]
+    CancelledByUser,
+}
+
+struct LockEntry<'a> {
+    file: &'a InstanceLockFile,
+    path: SafePath,
+    hash: [u8; 20],
+}
+
+impl BackendState {
+    /// Writes `pandora.lock.json` into the instance folder, describing every content file
+    pub async fn generate_instance_lock(&self, id: InstanceID, modal_action: &ModalAction) -> Result<(), InstanceLockError> {
+        let tracker = ProgressTracker::new("Generating lockfile".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.set_total(2);
+        tracker.notify();
+
+        let (root_path, configuration) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(InstanceLockError::UnknownInstance);
+            };
+            (instance.root_path.clone(), instance.configuration.get().clone())
+        };
+
+        let loader_version = self.launcher.resolve_loader_version(&configuration).await?;
+
+        let content = self.load_all_instance_content(id).await?;
+
+        tracker.set_count(1);
+        tracker.notify();
+
+        let mut files = Vec::new();
+        for (folder, summaries) in &content {
+            for summary in summaries.iter() {
+                let hash = summary.content_summary.hash;
+                files.push(InstanceLockFile {
+                    path: lock_path_for_content(*folder, summary).into(),
+                    sha1: hex::encode(hash).into(),
+                    size: std::fs::metadata(&summary.path)?.len(),
+                    source: summary.content_source.clone(),
+                    enabled: summary.enabled,
+                    curseforge_file_id: self.mod_metadata_manager.get_cached_curseforge_file_id(&hash),
+                    disabled_children: (*summary.disabled_children).clone(),
+                });
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let lock = InstanceLock {
+            minecraft_version: configuration.minecraft_version,
+            loader: configuration.loader,
+            loader_version,
+            files,
+        };
+
+        let bytes = serde_json::to_vec_pretty(&lock)?;
+        crate::write_safe(&root_path.join(INSTANCE_LOCK_FILENAME), &bytes)?;
+
+        tracker.set_count(2);
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        Ok(())
+    }
+
+    /// Makes the instance match its `pandora.lock.json`, downloading missing files and
+    /// disabling (or trashing, if `remove_extra` is set) anything not in the lockfile. The instance is
+    /// snapshotted first so that a restore that fails partway through can be undone
+    pub async fn restore_instance_from_lock(&self, id: InstanceID, remove_extra: bool, modal_action: &ModalAction) -> Result<(), InstanceLockError> {
+        let root_path = {
+            let instance_state = self.instance_state.read();
+            let Some(instance) = instance_state.instances.get(id) else {
+                return Err(InstanceLockError::UnknownInstance);
+            };
+            instance.root_path.clone()
+        };
+
+        let lock: InstanceLock = crate::read_json(&root_path.join(INSTANCE_LOCK_FILENAME))?;
+
+        let mut entries = Vec::with_capacity(lock.files.len());
+        for file in &lock.files {
+            let Some(path) = SafePath::new(&file.path) else {
+                return Err(InstanceLockError::InvalidPath(file.path.clone()));
+            };
+            if !ContentFolder::iter().any(|folder| path.starts_with(folder.path())) {
+                return Err(InstanceLockError::InvalidPath(file.path.clone()));
+            }
+            let Some(hash) = hex::decode(&*file.sha1).ok().and_then(|hash| <[u8; 20]>::try_from(hash).ok()) else {
+                return Err(InstanceLockError::InvalidHash(file.sha1.clone()));
+            };
+            entries.push(LockEntry { file, path, hash });
+        }
+
+        self.snapshot_instance(id, InstanceSnapshotReason::Lockfile).await;
+
+        let content = self.load_all_instance_content(id).await?;
+
+        let mut existing_by_path = HashMap::new();
+        let mut existing_by_hash = HashMap::new();
+        for (folder, summaries) in &content {
+            for summary in summaries.iter() {
+                existing_by_path.insert(lock_path_for_content(*folder, summary), summary.content_summary.hash);
+                existing_by_hash.insert(summary.content_summary.hash, summary.path.clone());
+            }
+        }
+
+        let missing: Vec<&LockEntry> = entries.iter()
+            .filter(|entry| existing_by_path.get(entry.path.as_str()) != Some(&entry.hash))
+            .collect();
+
+        if !missing.is_empty() {
+            let files = self.resolve_lock_downloads(&missing, &existing_by_hash, modal_action).await?;
+
+            self.install_content(ContentInstall {
+                target: InstallTarget::Instance(id),
+                loader_hint: lock.loader,
+                version_hint: Some(lock.minecraft_version.as_str().into()),
+                datapack_world: None,
+                files: files.into(),
+            }, modal_action.clone()).await;
+
+            if let Some(error) = modal_action.error.write().take() {
+                return Err(InstanceLockError::InstallError(error));
+            }
+        }
+
+        if modal_action.has_requested_cancel() {
+            return Err(InstanceLockError::CancelledByUser);
+        }
+
+        let tracker = ProgressTracker::new("Applying lockfile".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            for folder in ContentFolder::iter() {
+                instance.content_state[folder].mark_dirty(None);
+            }
+        }
+        let content = self.load_all_instance_content(id).await?;
+
+        let mut wanted: HashMap<&str, &LockEntry> = entries.iter().map(|entry| (entry.path.as_str(), entry)).collect();
+
+        let mut to_trash: Vec<PathBuf> = Vec::new();
+        let mut to_enable: Vec<InstanceContentID> = Vec::new();
+        let mut to_disable: Vec<InstanceContentID> = Vec::new();
+
+        for (folder, summaries) in &content {
+            for summary in summaries.iter() {
+                let lock_path = lock_path_for_content(*folder, summary);
+                let entry = wanted.get(lock_path.as_str()).copied().filter(|entry| entry.hash == summary.content_summary.hash);
+
+                let Some(entry) = entry else {
+                    if remove_extra {
+                        if let Some(aux_path) = crate::pandora_aux_path_for_content(summary) && aux_path.exists() {
+                            to_trash.push(aux_path);
+                        }
+                        to_trash.push(summary.path.to_path_buf());
+                    } else if summary.enabled {
+                        to_disable.push(summary.id);
+                    }
+                    continue;
+                };
+                wanted.remove(lock_path.as_str());
+
+                if *summary.disabled_children != entry.file.disabled_children
+                    && let Some(aux_path) = crate::pandora_aux_path_for_content(summary)
+                {
+                    let mut aux: AuxiliaryContentMeta = crate::read_json(&aux_path).unwrap_or_default();
+                    aux.disabled_children = entry.file.disabled_children.clone();
+                    crate::write_safe(&aux_path, &serde_json::to_vec(&aux)?)?;
+                }
+
+                if summary.enabled != entry.file.enabled {
+                    if entry.file.enabled {
+                        to_enable.push(summary.id);
+                    } else {
+                        to_disable.push(summary.id);
+                    }
+                }
+            }
+        }
+
+        self.set_content_enabled(id, to_enable, true);
+        self.set_content_enabled(id, to_disable, false);
+        if !to_trash.is_empty() {
+            tokio::task::spawn_blocking(move || trash::delete_all(to_trash)).await.unwrap()?;
+        }
+
+        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            for folder in ContentFolder::iter() {
+                instance.content_state[folder].mark_dirty(None);
+            }
+        }
+        for folder in ContentFolder::iter() {
+            tokio::task::spawn(self.clone().load_instance_content(id, folder));
+        }
+
+        if let Some(entry) = wanted.values().next() {
+            tracker.set_finished(ProgressTrackerFinishType::Error);
+            tracker.notify();
+            return Err(InstanceLockError::UnableToInstall(entry.file.path.clone()));
+        }
+
+        // Only switch versions once every locked file is installed with the right hash
+        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            instance.configuration.modify(|configuration| {
+                configuration.minecraft_version = lock.minecraft_version;
+                configuration.loader = lock.loader;
+                configuration.preferred_loader_version = lock.loader_version;
+            });
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        Ok(())
+    }
+
+    async fn load_all_instance_content(&self, id: InstanceID) -> Result<Vec<(ContentFolder, Arc<[InstanceContentSummary]>)>, InstanceLockError> {
+        let mut content = Vec::new();
+        for folder in ContentFolder::iter() {
+            let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                return Err(InstanceLockError::UnableToLoadContent);
+            };
+            content.push((folder, summaries));
+        }
+        Ok(content)
+    }
+
+    async fn resolve_lock_downloads(
+        &self,
+        missing: &[&LockEntry<'_>],
+        existing_by_hash: &HashMap<[u8; 20], Arc<Path>>,
+        modal_action: &ModalAction,
+    ) -> Result<Vec<ContentInstallFile>, InstanceLockError> {
+        let tracker = ProgressTracker::new("Resolving content".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.set_total(missing.len().div_ceil(MODRINTH_VERSION_FILES_CHUNK_SIZE) + 1);
+        tracker.notify();
+
+        let mut downloads: Vec<Option<ContentDownload>> = Vec::with_capacity(missing.len());
+        for entry in missing {
+            let library_path = crate::create_content_library_path(&self.directories.content_library_dir, entry.hash, entry.path.extension());
+            if library_path.exists() {
+                downloads.push(Some(ContentDownload::File { path: library_path }));
+            } else if let Some(existing) = existing_by_hash.get(&entry.hash) {
+                downloads.push(Some(ContentDownload::File { path: PathBuf::from(&**existing) }));
+            } else {
+                downloads.push(None);
+            }
+        }
+
+        let unresolved: Vec<usize> = (0..missing.len()).filter(|index| downloads[*index].is_none()).collect();
+        for chunk in unresolved.chunks(MODRINTH_VERSION_FILES_CHUNK_SIZE) {
+            if modal_action.has_requested_cancel() {
+                return Err(InstanceLockError::CancelledByUser);
+            }
+
+            let hashes = chunk.iter().map(|index| missing[*index].file.sha1.clone()).collect();
+            let result = self.meta.fetch(&ModrinthVersionFilesMetadataItem(&ModrinthVersionFilesRequest {
+                hashes,
+                algorithm: ModrinthHashAlgorithm::Sha1,
+            })).await?;
+
+            for index in chunk {
+                let sha1 = &missing[*index].file.sha1;
+                let file = result.0.get(sha1).and_then(|version| version.files.iter().find(|file| file.hashes.sha1 == *sha1));
+                if let Some(file) = file {
+                    downloads[*index] = Some(ContentDownload::Url {
+                        url: file.url.clone(),
+                        sha1: sha1.clone(),
+                        size: file.size,
+                    });
+                }
+            }
+
+            tracker.add_count(1);
+            tracker.notify();
+        }
+
+        let file_ids: Vec<u32> = (0..missing.len())
+            .filter(|index| downloads[*index].is_none())
+            .filter_map(|index| missing[index].file.curseforge_file_id)
+            .collect();
+        if !file_ids.is_empty() {
+            let result = self.meta.fetch(&CurseforgeGetFilesMetadataItem(&CurseforgeGetFilesRequest { file_ids })).await?;
+            for (index, entry) in missing.iter().enumerate() {
+                if downloads[index].is_some() {
+                    continue;
+                }
+                let file = result.data.iter().find(|file| Some(file.id) == entry.file.curseforge_file_id);
+                let Some(file) = file else {
+                    continue;
+                };
+                let matches_hash = file.hashes.iter().any(|hash| hash.algo == 1 && hash.value == entry.file.sha1);
+                if let Some(url) = &file.download_url && matches_hash {
+                    downloads[index] = Some(ContentDownload::Url {
+                        url: url.clone(),
+                        sha1: entry.file.sha1.clone(),
+                        size: file.file_length as usize,
+                    });
+                }
+            }
+        }
+
+        tracker.add_count(1);
+        tracker.notify();
+
+        let mut files = Vec::with_capacity(missing.len());
+        for (entry, download) in missing.iter().zip(downloads) {
+            let Some(download) = download else {
+                tracker.set_finished(ProgressTrackerFinishType::Error);
+                tracker.notify();
+                return Err(InstanceLockError::UnableToFindDownload(entry.file.path.clone()));
+            };
+            files.push(ContentInstallFile {
+                replace_old: None,
+                path: ContentInstallPath::Safe(entry.path.clone()),
+                download,
+                content_source: entry.file.source.clone(),
+            });
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::Normal);
+        tracker.notify();
+
+        Ok(files)
+    }
+}
+
+fn lock_path_for_content(folder: ContentFolder, summary: &InstanceContentSummary) -> String {
+    let filename = summary.filename.strip_suffix(".disabled").unwrap_or(&summary.filename);
+    folder.path().join(filename).into_string()
+}
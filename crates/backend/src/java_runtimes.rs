@@ -0,0 +1,114 @@
+use std::{path::{Path, PathBuf}, sync::Arc};
+
+use bridge::java::JavaRuntimeInfo;
+use rustc_hash::FxHashSet;
+
+use crate::{BackendState, launch::Launcher};
+
+impl BackendState {
+    /// Returns every Java runtime found on this machine, scanning for them if that hasn't been done yet
+    pub async fn get_java_runtimes(&self, rescan: bool) -> Arc<[JavaRuntimeInfo]> {
+        if !rescan && let Some(runtimes) = &*self.java_runtimes.read() {
+            return runtimes.clone();
+        }
+
+        let launcher = self.launcher.clone();
+        let search_paths = self.java_search_paths();
+        let runtimes: Arc<[JavaRuntimeInfo]> = tokio::task::spawn_blocking(move || {
+            scan_java_runtimes(&launcher, search_paths)
+        }).await.unwrap().into();
+
+        log::info!("Found {} Java runtimes", runtimes.len());
+        *self.java_runtimes.write() = Some(runtimes.clone());
+        runtimes
+    }
+
+    fn java_search_paths(&self) -> Vec<PathBuf> {
+        let mut paths = Vec::new();
+
+        if let Some(java_home) = std::env::var_os("JAVA_HOME") {
+            paths.push(PathBuf::from(java_home));
+        }
+        if let Some(path) = std::env::var_os("PATH") {
+            paths.extend(std::env::split_paths(&path));
+        }
+
+        // Directories that contain one runtime per subdirectory
+        let mut parents: Vec<PathBuf> = Vec::new();
+        if cfg!(target_os = "linux") {
+            parents.push("/usr/lib/jvm".into());
+            parents.push("/usr/lib64/jvm".into());
+        } else if cfg!(target_os = "macos") {
+            parents.push("/Library/Java/JavaVirtualMachines".into());
+        } else if cfg!(target_os = "windows") {
+            for vendor in ["Java", "Eclipse Adoptium", "Microsoft", "Zulu", "Amazon Corretto", "BellSoft"] {
+                parents.push(Path::new("C:\\Program Files").join(vendor));
+            }
+        }
+        if let Some(sdkman_dir) = std::env::var_os("SDKMAN_DIR") {
+            parents.push(Path::new(&sdkman_dir).join("candidates").join("java"));
+        }
+        if let Some(base_dirs) = directories::BaseDirs::new() {
+            let home = base_dirs.home_dir();
+            parents.push(home.join(".sdkman").join("candidates").join("java"));
+            parents.push(home.join(".jdks"));
+            if cfg!(target_os = "macos") {
+                parents.push(home.join("Library").join("Java").join("JavaVirtualMachines"));
+            }
+        }
+
+        for parent in parents {
+            let Ok(read_dir) = std::fs::read_dir(&parent) else {
+                continue;
+            };
+            for entry in read_dir.filter_map(Result::ok) {
+                let path = entry.path();
+                let macos_home = path.join("Contents").join("Home");
+                paths.push(if macos_home.is_dir() { macos_home } else { path });
+            }
+        }
+
+        // Mojang runtimes are stored as <component>/<platform>
+        if let Ok(read_dir) = std::fs::read_dir(&self.directories.runtime_base_dir) {
+            for component in read_dir.filter_map(Result::ok) {
+                let Ok(read_dir) = std::fs::read_dir(component.path()) else {
+                    continue;
+                };
+                paths.extend(read_dir.filter_map(Result::ok).map(|platform| platform.path()));
+            }
+        }
+
+        paths
+    }
+}
+
+fn scan_java_runtimes(launcher: &Launcher, search_paths: Vec<PathBuf>) -> Vec<JavaRuntimeInfo> {
+    let mut seen = FxHashSet::default();
+    let mut runtimes = Vec::new();
+
+    for path in search_paths {
+        let Some(binary) = Launcher::search_for_java_binary(&path) else {
+            continue;
+        };
+        // PATH and JAVA_HOME usually point at a runtime that's also found in one of the install directories
+        let binary = binary.canonicalize().unwrap_or(binary);
+        if !seen.insert(binary.clone()) {
+            continue;
+        }
+
+        let Some(properties) = launcher.get_java_properties(&binary) else {
+            log::debug!("Unable to determine Java version of {:?}", binary);
+            continue;
+        };
+
+        runtimes.push(JavaRuntimeInfo {
+            path: binary.into(),
+            major_version: properties.major_version,
+            version: properties.version,
+            vendor: properties.vendor,
+        });
+    }
+
+    runtimes.sort_by(|a, b| b.major_version.cmp(&a.major_version).then_with(|| a.path.cmp(&b.path)));
+    runtimes
+}
@@ -1,28 +1,29 @@
 use std::{
-    borrow::Cow, cmp::Ordering, collections::{BTreeSet, HashMap, HashSet}, ffi::{OsStr, OsString}, fs::File, io::Write, path::{Path, PathBuf}, process::{Child, Stdio}, sync::{Arc, OnceLock, atomic::AtomicBool}
+    borrow::Cow, cmp::Ordering, collections::{BTreeSet, HashMap, HashSet}, ffi::{OsStr, OsString}, fs::File, io::Write, path::{Path, PathBuf}, process::{Child, Output, Stdio}, sync::{Arc, OnceLock, atomic::AtomicBool}
 };
 
 use bridge::{
-    handle::FrontendHandle, message::{MessageToFrontend, QuickPlayLaunch}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType, ProgressTrackers}, safe_path::SafePath
+    handle::FrontendHandle, instance::InstanceContentSummary, message::{MessageToFrontend, QuickPlayLaunch}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType, ProgressTrackers}, safe_path::SafePath
 };
 use futures::{FutureExt, TryFutureExt};
 use rand::seq::SliceRandom;
 use rc_zip_sync::{ArchiveHandle, ReadZip};
 use regex::Regex;
+use parking_lot::RwLock;
 use rustc_hash::FxHashMap;
 use schema::{
-    assets_index::AssetsIndex, fabric_launch::FabricLaunch, forge::{ForgeInstallProfile, ForgeInstallProfileLegacy, ForgeSide, VersionFragment}, instance::{AUTO_LIBRARY_PATH_GLFW, AUTO_LIBRARY_PATH_OPENAL, InstanceConfiguration, InstanceWrapperCommandConfiguration}, java_runtime_component::{JavaRuntimeComponentFile, JavaRuntimeComponentManifest}, loader::Loader, maven::MavenCoordinate, version::{
+    assets_index::AssetsIndex, backend_config::BackendConfig, fabric_launch::FabricLaunch, forge::{ForgeInstallProfile, ForgeInstallProfileLegacy, ForgeSide, VersionFragment}, instance::{AUTO_LIBRARY_PATH_GLFW, AUTO_LIBRARY_PATH_OPENAL, InstanceConfiguration, InstanceWrapperCommandConfiguration, LaunchPhase}, java_runtime_component::{JavaRuntimeComponentFile, JavaRuntimeComponentManifest}, loader::Loader, maven::MavenCoordinate, quilt_launch::QuiltLaunch, version::{
         GameLibrary, GameLibraryArtifact, GameLibraryDownloads, GameLibraryExtractOptions, GameLogging, LaunchArgument, LaunchArgumentValue, MinecraftVersion, OsArch, OsName, PartialMinecraftVersion, Rule, RuleAction
     }, version_manifest::MinecraftVersionManifest
 };
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use sha1::{Digest, Sha1};
 use ustr::Ustr;
 
 use crate::{
-    account::MinecraftLoginInfo, directories::LauncherDirectories, launch_wrapper, metadata::{items::{AssetsIndexMetadataItem, FabricLaunchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem}, manager::{
+    account::MinecraftLoginInfo, directories::LauncherDirectories, download::{DownloadError, DownloadManager}, launch_cache::{self, LaunchCache, LaunchCacheKey}, launch_timing::LaunchTimer, launch_wrapper, metadata::{items::{AssetsIndexMetadataItem, FabricLaunchMetadataItem, FabricLoaderManifestMetadataItem, ForgeInstallerMavenMetadataItem, MetadataItem, MinecraftVersionManifestMetadataItem, MinecraftVersionMetadataItem, MojangJavaRuntimeComponentMetadataItem, MojangJavaRuntimesMetadataItem, NeoforgeInstallerMavenMetadataItem, QuiltLaunchMetadataItem, QuiltLoaderManifestMetadataItem}, manager::{
         MetaLoadError, MetadataManager,
-    }}
+    }}, persistent::Persistent, verify::{RepairKind, RepairLog}
 };
 
 #[derive(Clone)]
@@ -31,6 +32,10 @@ pub struct Launcher {
     directories: Arc<LauncherDirectories>,
     launch_wrapper: Arc<Path>,
     sender: FrontendHandle,
+    config: Arc<RwLock<Persistent<BackendConfig>>>,
+    downloads: Arc<DownloadManager>,
+    /// Major versions of custom Java binaries, with the modification time of the binary they were read from
+    java_versions: RwLock<FxHashMap<PathBuf, (std::time::SystemTime, u32)>>,
 }
 
 #[derive(thiserror::Error, Debug)]
@@ -61,6 +66,10 @@ pub enum LaunchError {
     CancelledByUser,
     #[error("Loader supports the wrong version of Minecraft: {0}")]
     MismatchedLoaderVersions(Arc<str>),
+    #[error("Failed to run pre-launch command:\n{0}")]
+    PreLaunchHookError(std::io::Error),
+    #[error("Pre-launch command failed ({0}):\n{1}")]
+    PreLaunchHookFailed(std::process::ExitStatus, String),
 }
 
 #[derive(PartialEq, Eq)]
@@ -70,151 +79,273 @@ pub enum AddVanillaJar {
 }
 
 impl Launcher {
-    pub fn new(meta: Arc<MetadataManager>, directories: Arc<LauncherDirectories>, sender: FrontendHandle) -> Self {
+    pub fn new(meta: Arc<MetadataManager>, directories: Arc<LauncherDirectories>, sender: FrontendHandle, config: Arc<RwLock<Persistent<BackendConfig>>>, downloads: Arc<DownloadManager>) -> Self {
         let launch_wrapper = launch_wrapper::create_wrapper(&directories.temp_dir).into();
         Self {
             meta,
             directories,
             launch_wrapper,
             sender,
+            config,
+            downloads,
+            java_versions: Default::default(),
         }
     }
 
     pub async fn launch(
         &self,
-        http_client: &reqwest::Client,
+        instance_name: Ustr,
+        instance_dir: Arc<Path>,
         dot_minecraft_path: Arc<Path>,
         instance_info: InstanceConfiguration,
+        launch_profile: Option<Arc<str>>,
         quick_play: Option<QuickPlayLaunch>,
         login_info: MinecraftLoginInfo,
         add_mods: Vec<PathBuf>,
+        mods: &[InstanceContentSummary],
         launch_tracker: &ProgressTracker,
         modal_action: &ModalAction,
-    ) -> Result<Child, LaunchError> {
-        log::info!("Launching {:?}", dot_minecraft_path);
-
-        launch_tracker.set_total(6);
-
-        log::debug!("Creating launch version");
-
-        let (version_info, add_vanilla_jar) = tokio::select! {
-            result = self.create_launch_version(http_client, &modal_action.trackers, launch_tracker, &instance_info) => result?,
-            _ = modal_action.request_cancel.cancelled() => {
-                self.sender.send(MessageToFrontend::CloseModal);
-                return Err(LaunchError::CancelledByUser);
+        timer: &LaunchTimer,
+    ) -> Result<(Child, Option<LaunchHook>), LaunchError> {
+        let (launch_context, version_info) = self.prepare_launch(instance_name, instance_dir, dot_minecraft_path,
+            instance_info, launch_profile, quick_play, login_info, add_mods, mods, launch_tracker, modal_action, timer).await?;
+
+        let hooks = launch_context.configuration.hooks.clone().unwrap_or_default();
+        if let Some(hook) = launch_context.hook(&hooks.pre_launch) {
+            log::info!("Running pre-launch command");
+            let output = timer.time(LaunchPhase::PreLaunchCommand, hook.run_cancellable(modal_action)).await?;
+            if !output.status.success() {
+                return Err(LaunchError::PreLaunchHookFailed(output.status, LaunchHook::output_tail(&output)));
             }
+        }
+        let post_exit_hook = launch_context.hook(&hooks.post_exit);
+
+        log::info!("Launching game process");
+        let child = {
+            let _timer = timer.start(LaunchPhase::ProcessStart);
+            launch_context.launch(&version_info)?
         };
 
         launch_tracker.add_count(1);
-        launch_tracker.notify();
+
+        Ok((child, post_exit_hook))
+    }
+
+    /// Downloads and resolves everything needed to launch, but returns the command instead of starting the game
+    pub async fn dry_run(
+        &self,
+        instance_name: Ustr,
+        instance_dir: Arc<Path>,
+        dot_minecraft_path: Arc<Path>,
+        instance_info: InstanceConfiguration,
+        launch_profile: Option<Arc<str>>,
+        quick_play: Option<QuickPlayLaunch>,
+        login_info: MinecraftLoginInfo,
+        add_mods: Vec<PathBuf>,
+        mods: &[InstanceContentSummary],
+        launch_tracker: &ProgressTracker,
+        modal_action: &ModalAction,
+    ) -> Result<ResolvedLaunchCommand, LaunchError> {
+        let (launch_context, version_info) = self.prepare_launch(instance_name, instance_dir, dot_minecraft_path,
+            instance_info, launch_profile, quick_play, login_info, add_mods, mods, launch_tracker, modal_action, &LaunchTimer::default()).await?;
+
+        Ok(launch_context.resolve(&version_info, false))
+    }
+
+    async fn prepare_launch(
+        &self,
+        instance_name: Ustr,
+        instance_dir: Arc<Path>,
+        dot_minecraft_path: Arc<Path>,
+        instance_info: InstanceConfiguration,
+        launch_profile: Option<Arc<str>>,
+        quick_play: Option<QuickPlayLaunch>,
+        login_info: MinecraftLoginInfo,
+        add_mods: Vec<PathBuf>,
+        mods: &[InstanceContentSummary],
+        launch_tracker: &ProgressTracker,
+        modal_action: &ModalAction,
+        timer: &LaunchTimer,
+    ) -> Result<(LaunchContext, Arc<MinecraftVersion>), LaunchError> {
+        log::info!("Launching {:?}", dot_minecraft_path);
+
+        launch_tracker.set_total(6);
+
+        self.check_mod_dependencies(&instance_info, mods).await;
 
         let _ = std::fs::create_dir_all(&dot_minecraft_path);
 
+        let window = instance_info.window.unwrap_or_default();
+        let auto_join_server = instance_info.auto_join_server.as_ref()
+            .filter(|server| !server.trim().is_empty())
+            .map(|server| QuickPlayLaunch::Multiplayer(OsString::from(server.trim())));
         let launch_rule_context = LaunchRuleContext {
-            is_demo_user: false,
-            custom_resolution: None,
-            quick_play,
+            is_demo_user: instance_info.demo,
+            custom_resolution: window.custom_size.then_some((window.width, window.height)),
+            quick_play: quick_play.or(auto_join_server),
         };
 
-        let mut artifacts = Vec::new();
-        let mut natives_to_extract = HashMap::new();
-        launch_rule_context.collect_libraries(&version_info.libraries, &mut artifacts, &mut natives_to_extract);
+        let (cache_key, cache) = {
+            let _timer = timer.start(LaunchPhase::LaunchCache);
+            match self.launch_cache_key(&instance_info, launch_profile).await {
+                Some(cache_key) => {
+                    let instance_dir = instance_dir.clone();
+                    tokio::task::spawn_blocking(move || {
+                        let cache = LaunchCache::load(&instance_dir, &cache_key);
+                        (Some(cache_key), cache)
+                    }).await.unwrap()
+                },
+                None => (None, None),
+            }
+        };
 
-        // Compute natives path based on combined hash of all libraries
-        let natives_dir = self.directories.temp_natives_base_dir.join(calculate_natives_dirname(&artifacts));
-        let _ = std::fs::create_dir_all(&natives_dir);
+        let (version_info, java_path, natives_dir, assets_index_name, classpath, log_configuration) = if let Some(cache) = cache {
+            log::info!("Nothing changed since the last launch, using the cached launch");
 
-        if add_vanilla_jar == AddVanillaJar::Yes {
-            let client_download = &version_info.downloads.client;
-            artifacts.push(GameLibraryArtifact {
-                path: format!("net/minecraft/{0}/minecraft-client-{0}.jar", instance_info.minecraft_version).into(),
-                sha1: Some(client_download.sha1),
-                size: Some(client_download.size),
-                url: client_download.url,
-            });
-        }
+            launch_tracker.add_count(2);
+            launch_tracker.notify();
 
-        let mojang_java_binary_future = self.load_mojang_java_binary(
-            &self.meta,
-            http_client,
-            &instance_info,
-            &version_info,
-            &modal_action.trackers,
-            launch_tracker,
-        );
-        let load_assets_future =
-            self.load_assets(&self.meta, http_client, &dot_minecraft_path, &version_info, &modal_action.trackers, launch_tracker);
-        let load_libraries_future =
-            self.load_libraries(http_client, &artifacts, &modal_action.trackers, launch_tracker);
-        let load_log_configuration = self.load_log_configuration(http_client, version_info.logging.as_ref());
+            (Arc::new(cache.version), cache.java_path, cache.natives_dir, cache.assets_index_name, cache.classpath, cache.log_configuration)
+        } else {
+            let repairs = RepairLog::default();
 
-        log::debug!("Loading java, assets, libraries and log configuration");
+            log::debug!("Creating launch version");
 
-        let joined = futures::future::try_join4(
-            mojang_java_binary_future.map_err(LaunchError::from),
-            load_assets_future.map_err(LaunchError::from),
-            load_libraries_future.map_err(LaunchError::from),
-            load_log_configuration.map(Ok),
-        );
+            let (version_info, add_vanilla_jar) = tokio::select! {
+                result = self.create_launch_version(&modal_action.trackers, launch_tracker, &instance_info, &repairs, timer) => result?,
+                _ = modal_action.request_cancel.cancelled() => {
+                    self.sender.send(MessageToFrontend::CloseModal);
+                    return Err(LaunchError::CancelledByUser);
+                }
+            };
 
-        let (java_path, assets_index_name, library_paths, log_configuration) = tokio::select! {
-            result = joined => result?,
-            _ = modal_action.request_cancel.cancelled() => {
-                self.sender.send(MessageToFrontend::CloseModal);
-                return Err(LaunchError::CancelledByUser);
+            launch_tracker.add_count(1);
+            launch_tracker.notify();
+
+            let mut artifacts = Vec::new();
+            let mut natives_to_extract = HashMap::new();
+            launch_rule_context.collect_libraries(&version_info.libraries, &mut artifacts, &mut natives_to_extract);
+
+            // Compute natives path based on combined hash of all libraries
+            let natives_dir = self.directories.temp_natives_base_dir.join(calculate_natives_dirname(&artifacts));
+            let _ = std::fs::create_dir_all(&natives_dir);
+
+            if add_vanilla_jar == AddVanillaJar::Yes {
+                artifacts.push(client_jar_artifact(&version_info, instance_info.minecraft_version));
             }
-        };
 
-        launch_tracker.add_count(1);
-        launch_tracker.notify();
+            let mojang_java_binary_future = self.load_mojang_java_binary(
+                &self.meta,
+                &instance_info,
+                &version_info,
+                &modal_action.trackers,
+                launch_tracker,
+                &repairs,
+            );
+            let load_assets_future =
+                self.load_assets(&self.meta, &dot_minecraft_path, &version_info, &modal_action.trackers, launch_tracker, &repairs);
+            let load_libraries_future =
+                self.load_libraries(&artifacts, &modal_action.trackers, launch_tracker, &repairs);
+            let load_log_configuration = self.load_log_configuration(version_info.logging.as_ref());
+
+            log::debug!("Loading java, assets, libraries and log configuration");
+
+            let joined = futures::future::try_join4(
+                timer.time_concurrent(LaunchPhase::JavaRuntime, mojang_java_binary_future.map_err(LaunchError::from)),
+                timer.time_concurrent(LaunchPhase::Assets, load_assets_future.map_err(LaunchError::from)),
+                timer.time_concurrent(LaunchPhase::Libraries, load_libraries_future.map_err(LaunchError::from)),
+                load_log_configuration.map(Ok),
+            );
+
+            let (java_path, (assets_index_name, assets_dir), library_paths, log_configuration) = tokio::select! {
+                result = joined => result?,
+                _ = modal_action.request_cancel.cancelled() => {
+                    self.sender.send(MessageToFrontend::CloseModal);
+                    return Err(LaunchError::CancelledByUser);
+                }
+            };
 
-        let mut classpath = Vec::new();
-        for (raw_path, library_path) in library_paths {
-            if let Some(extract_options) = natives_to_extract.get(&raw_path) {
-                let Ok(file) = std::fs::File::open(library_path) else {
-                    continue;
-                };
-                let Ok(archive) = file.read_zip() else {
-                    continue;
-                };
-                for file in archive.entries() {
-                    let Some(path) = SafePath::new(&file.name) else {
+            launch_tracker.add_count(1);
+            launch_tracker.notify();
+
+            let repaired = repairs.take();
+            if !repaired.is_empty() {
+                log::warn!("Downloaded {} missing or corrupt game files", repaired.len());
+            }
+
+            let natives_timer = timer.start(LaunchPhase::NativesExtraction);
+            let mut classpath = Vec::new();
+            let mut natives = Vec::new();
+            for (raw_path, library_path) in library_paths {
+                if let Some(extract_options) = natives_to_extract.get(&raw_path) {
+                    let Ok(file) = std::fs::File::open(library_path) else {
                         continue;
                     };
-
-                    if let Some(exclude) = &extract_options.exclude {
-                        let mut skip = false;
-                        for to_exclude in exclude.iter() {
-                            if path.starts_with(to_exclude) {
-                                skip = true;
-                                break;
+                    let Ok(archive) = file.read_zip() else {
+                        continue;
+                    };
+                    for file in archive.entries() {
+                        let Some(path) = SafePath::new(&file.name) else {
+                            continue;
+                        };
+
+                        if let Some(exclude) = &extract_options.exclude {
+                            let mut skip = false;
+                            for to_exclude in exclude.iter() {
+                                if path.starts_with(to_exclude) {
+                                    skip = true;
+                                    break;
+                                }
+                            }
+                            if skip {
+                                continue;
                             }
                         }
-                        if skip {
-                            continue;
+
+                        let output_path = path.to_path(&natives_dir);
+                        match file.kind() {
+                            rc_zip_sync::rc_zip::EntryKind::Directory => {
+                                let _ = std::fs::create_dir(output_path);
+                            },
+                            rc_zip_sync::rc_zip::EntryKind::File => {
+                                let Ok(mut outfile) = std::fs::File::create(&output_path) else {
+                                    continue;
+                                };
+                                let _ = std::io::copy(&mut file.reader(), &mut outfile);
+                                natives.push(output_path);
+                            },
+                            rc_zip_sync::rc_zip::EntryKind::Symlink => {},
                         }
                     }
+                } else {
+                    classpath.push(library_path.into_os_string());
+                }
+            }
+            drop(natives_timer);
 
-                    let output_path = path.to_path(&natives_dir);
-                    match file.kind() {
-                        rc_zip_sync::rc_zip::EntryKind::Directory => {
-                            let _ = std::fs::create_dir(output_path);
-                        },
-                        rc_zip_sync::rc_zip::EntryKind::File => {
-                            let Ok(mut outfile) = std::fs::File::create(&output_path) else {
-                                continue;
-                            };
-                            let _ = std::io::copy(&mut file.reader(), &mut outfile);
-                        },
-                        rc_zip_sync::rc_zip::EntryKind::Symlink => {},
-                    }
+            if let Some(cache_key) = cache_key {
+                let assets_index = self.directories.assets_index_dir.join(format!("{}.json", &assets_index_name));
+                let mut dependencies = Vec::new();
+                if let Some(loader_metadata) = self.loader_metadata_file(&cache_key) {
+                    dependencies.push(loader_metadata);
+                }
+                if log_configuration.is_some() && let Some(client) = version_info.logging.as_ref().and_then(|logging| logging.client.as_ref()) {
+                    dependencies.push(self.directories.log_configs_dir.join(client.file.id.as_str()));
+                }
+                let cache = LaunchCache::new(cache_key, (*version_info).clone(), java_path.clone(), natives_dir.clone(),
+                    assets_index_name.clone(), classpath.clone(), log_configuration.clone(), assets_index, assets_dir.to_path_buf(),
+                    natives, dependencies);
+                if let Some(cache) = cache {
+                    cache.save(&instance_dir);
                 }
-            } else {
-                classpath.push(library_path.into_os_string());
             }
-        }
+
+            (version_info, java_path, natives_dir, assets_index_name, classpath, log_configuration)
+        };
 
         let launch_context = LaunchContext {
+            instance_name,
+            instance_dir,
             launch_wrapper_path: self.launch_wrapper.clone(),
             java_path,
             natives_dir,
@@ -235,23 +366,185 @@ impl Launcher {
             return Err(LaunchError::CancelledByUser);
         }
 
-        log::info!("Launching game process");
-        let child = launch_context.launch(&version_info)?;
+        Ok((launch_context, version_info))
+    }
 
-        launch_tracker.add_count(1);
+    /// The inputs the launch cache of an instance depends on, None if the loader version can't be resolved
+    async fn launch_cache_key(&self, instance_info: &InstanceConfiguration, launch_profile: Option<Arc<str>>) -> Option<LaunchCacheKey> {
+        let loader_version = match self.resolve_loader_version(instance_info).await {
+            Ok(loader_version) => loader_version,
+            Err(error) => {
+                log::debug!("Unable to resolve loader version, not using the launch cache: {error}");
+                return None;
+            },
+        };
 
-        Ok(child)
+        let versions = self.meta.fetch(&MinecraftVersionManifestMetadataItem).await.ok()?;
+        let version_sha1 = versions.versions.iter().find(|version| version.id == instance_info.minecraft_version)?.sha1;
+
+        Some(LaunchCacheKey {
+            minecraft_version: instance_info.minecraft_version,
+            version_sha1,
+            loader: instance_info.loader,
+            loader_version,
+            launch_profile,
+            java_defaults: self.config.write().get().java_defaults.clone(),
+            force_external_java: std::env::var_os("FORCE_EXTERNAL_JAVA"),
+        })
+    }
+
+    /// The file the loader's libraries and arguments were resolved from, the launch cache is invalidated when it changes
+    fn loader_metadata_file(&self, cache_key: &LaunchCacheKey) -> Option<PathBuf> {
+        let minecraft_version = cache_key.minecraft_version;
+        let loader_version = cache_key.loader_version?;
+        match cache_key.loader {
+            Loader::Fabric => FabricLaunchMetadataItem { minecraft_version, loader_version }.cache_file(&self.meta).map(|path| path.as_ref().to_path_buf()),
+            Loader::Quilt => QuiltLaunchMetadataItem { minecraft_version, loader_version }.cache_file(&self.meta).map(|path| path.as_ref().to_path_buf()),
+            Loader::Forge => Some(self.directories.libraries_dir.join(FORGE_INSTALLER_PATH.replace("{0}", &loader_version))),
+            Loader::NeoForge => Some(self.directories.libraries_dir.join(NEOFORGE_INSTALLER_PATH.replace("{0}", &loader_version))),
+            Loader::Vanilla | Loader::Unknown => None,
+        }
+    }
+
+    /// Checks the Java runtime, assets, libraries and client jar needed by an instance, downloading any that are missing or corrupt
+    pub async fn verify_game_files(
+        &self,
+        dot_minecraft_path: &Arc<Path>,
+        instance_info: &InstanceConfiguration,
+        verify_tracker: &ProgressTracker,
+        progress_trackers: &ProgressTrackers,
+        repairs: &RepairLog,
+    ) -> Result<(), LaunchError> {
+        verify_tracker.set_total(4);
+
+        let (version_info, add_vanilla_jar) = self.create_launch_version(progress_trackers, verify_tracker, instance_info, repairs,
+            &LaunchTimer::default()).await?;
+
+        verify_tracker.add_count(1);
+        verify_tracker.notify();
+
+        let launch_rule_context = LaunchRuleContext {
+            is_demo_user: false,
+            custom_resolution: None,
+            quick_play: None,
+        };
+
+        let mut artifacts = Vec::new();
+        let mut natives_to_extract = HashMap::new();
+        launch_rule_context.collect_libraries(&version_info.libraries, &mut artifacts, &mut natives_to_extract);
+
+        if add_vanilla_jar == AddVanillaJar::Yes {
+            artifacts.push(client_jar_artifact(&version_info, instance_info.minecraft_version));
+        }
+
+        futures::future::try_join3(
+            self.load_mojang_java_binary(&self.meta, instance_info, &version_info, progress_trackers, verify_tracker, repairs)
+                .map_err(LaunchError::from),
+            self.load_assets(&self.meta, dot_minecraft_path, &version_info, progress_trackers, verify_tracker, repairs)
+                .map_err(LaunchError::from),
+            self.load_libraries(&artifacts, progress_trackers, verify_tracker, repairs)
+                .map_err(LaunchError::from),
+        ).await?;
+
+        Ok(())
+    }
+
+    /// Warns about missing dependencies and incompatibilities, the launch continues regardless
+    async fn check_mod_dependencies(&self, instance_info: &InstanceConfiguration, mods: &[InstanceContentSummary]) {
+        if mods.is_empty() {
+            return;
+        }
+
+        let loader_version = self.resolve_loader_version(instance_info).await.ok().flatten();
+        let issues: Vec<_> = bridge::dependency::analyze_content(
+            mods,
+            instance_info.minecraft_version.as_str(),
+            instance_info.loader,
+            loader_version.as_ref().map(|version| version.as_str()),
+        ).into_iter().filter(|issue| issue.is_error()).collect();
+
+        if issues.is_empty() {
+            return;
+        }
+
+        for issue in &issues {
+            log::warn!("{issue}");
+        }
+
+        const MAX_SHOWN: usize = 5;
+        let mut message = String::from("Possible problems with installed mods:");
+        for issue in issues.iter().take(MAX_SHOWN) {
+            message.push_str("\n- ");
+            message.push_str(&issue.to_string());
+        }
+        if issues.len() > MAX_SHOWN {
+            message.push_str(&format!("\n...and {} more", issues.len() - MAX_SHOWN));
+        }
+        self.sender.send_warning(message);
+    }
+
+    pub async fn resolve_loader_version(&self, instance_info: &InstanceConfiguration) -> Result<Option<Ustr>, LaunchError> {
+        match instance_info.loader {
+            Loader::Vanilla | Loader::Unknown => Ok(None),
+            Loader::Fabric => {
+                if let Some(preferred_version) = instance_info.preferred_loader_version {
+                    return Ok(Some(preferred_version));
+                }
+
+                let manifest = self.meta.fetch(&FabricLoaderManifestMetadataItem).await?;
+
+                let mut latest_loader_version = manifest.0.iter().find(|v| v.stable);
+                if latest_loader_version.is_none() {
+                    latest_loader_version = manifest.0.first();
+                }
+                Ok(latest_loader_version.map(|v| v.version))
+            },
+            Loader::Quilt => {
+                if let Some(preferred_version) = instance_info.preferred_loader_version {
+                    return Ok(Some(preferred_version));
+                }
+
+                let manifest = self.meta.fetch(&QuiltLoaderManifestMetadataItem).await?;
+
+                let mut latest_loader_version = manifest.0.iter().find(|v| v.is_stable());
+                if latest_loader_version.is_none() {
+                    latest_loader_version = manifest.0.first();
+                }
+                Ok(latest_loader_version.map(|v| v.version))
+            },
+            Loader::Forge | Loader::NeoForge => {
+                if let Some(preferred_version) = instance_info.preferred_loader_version {
+                    return Ok(Some(preferred_version));
+                }
+
+                let latest_loader_version = if instance_info.loader == Loader::Forge {
+                    let loader_versions = self.meta.fetch(&ForgeInstallerMavenMetadataItem).await?;
+                    find_latest_forgelike_version(instance_info.minecraft_version, &loader_versions.0, false)
+                } else {
+                    let loader_versions = self.meta.fetch(&NeoforgeInstallerMavenMetadataItem).await?;
+                    find_latest_forgelike_version(instance_info.minecraft_version, &loader_versions.0, true)
+                };
+
+                let Some(latest_loader_version) = latest_loader_version else {
+                    return Err(LaunchError::CantFindVersion(instance_info.minecraft_version.as_str()));
+                };
+                Ok(Some(latest_loader_version))
+            },
+        }
     }
 
     async fn create_launch_version(
         &self,
-        http_client: &reqwest::Client,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
+        repairs: &RepairLog,
+        timer: &LaunchTimer,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         match instance_info.loader {
             Loader::Vanilla => {
+                let _timer = timer.start(LaunchPhase::VersionResolution);
+
                 launch_tracker.add_total(1);
                 launch_tracker.notify();
 
@@ -323,8 +616,10 @@ impl Launcher {
                     Ok(value)
                 });
 
-                let (version, fabric_launch): (Arc<MinecraftVersion>, Arc<FabricLaunch>) =
-                    futures::future::try_join(version, fabric_launch).await?;
+                let (version, fabric_launch): (Arc<MinecraftVersion>, Arc<FabricLaunch>) = futures::future::try_join(
+                    timer.time_concurrent(LaunchPhase::VersionResolution, version),
+                    timer.time_concurrent(LaunchPhase::LoaderInstall, fabric_launch),
+                ).await?;
 
                 let mut version: MinecraftVersion = (*version).clone();
 
@@ -393,24 +688,113 @@ impl Launcher {
 
                 Ok((Arc::new(version), AddVanillaJar::Yes))
             },
+            Loader::Quilt => {
+                let versions = self.meta.fetch(&MinecraftVersionManifestMetadataItem).map_err(LaunchError::from);
+
+                let quilt_loader_version = async move {
+                    if let Some(preferred_version) = instance_info.preferred_loader_version {
+                        Ok(preferred_version)
+                    } else {
+                        let manifest = self.meta.fetch(&QuiltLoaderManifestMetadataItem).map_err(LaunchError::from).await?;
+
+                        let mut latest_loader_version = manifest.0.iter().find(|v| v.is_stable());
+                        if latest_loader_version.is_none() {
+                            latest_loader_version = manifest.0.first();
+                        }
+                        let Some(latest_loader_version) = latest_loader_version else {
+                            return Err(LaunchError::CantFindVersion(instance_info.minecraft_version.as_str()));
+                        };
+                        Ok(latest_loader_version.version)
+                    }
+                };
+
+                launch_tracker.add_total(4);
+                launch_tracker.notify();
+
+                let launch_tracker2 = launch_tracker.clone();
+                let meta2 = Arc::clone(&self.meta);
+                let minecraft_version = instance_info.minecraft_version;
+                let quilt_launch = quilt_loader_version.and_then(async move |loader_version| {
+                    launch_tracker2.add_count(1);
+                    launch_tracker2.notify();
+
+                    let value = meta2.fetch(&QuiltLaunchMetadataItem {
+                        minecraft_version,
+                        loader_version,
+                    }).await?;
+
+                    launch_tracker2.add_count(1);
+                    launch_tracker2.notify();
+
+                    Ok(value)
+                });
+
+                let launch_tracker3 = launch_tracker.clone();
+                let meta3 = Arc::clone(&self.meta);
+                let instance_version = instance_info.minecraft_version;
+                let version = versions.and_then(async move |versions| {
+                    launch_tracker3.add_count(1);
+                    launch_tracker3.notify();
+
+                    let Some(version) = versions.versions.iter().find(|v| v.id == instance_version) else {
+                        return Err(LaunchError::CantFindVersion(instance_version.as_str()));
+                    };
+
+                    let value = meta3.fetch(&MinecraftVersionMetadataItem(version)).await?;
+
+                    launch_tracker3.add_count(1);
+                    launch_tracker3.notify();
+
+                    Ok(value)
+                });
+
+                let (version, quilt_launch): (Arc<MinecraftVersion>, Arc<QuiltLaunch>) = futures::future::try_join(
+                    timer.time_concurrent(LaunchPhase::VersionResolution, version),
+                    timer.time_concurrent(LaunchPhase::LoaderInstall, quilt_launch),
+                ).await?;
+
+                let mut version: MinecraftVersion = (*version).clone();
+
+                if let Some(loader) = &quilt_launch.loader {
+                    version.libraries.push(create_maven_library(loader.maven, "https://maven.quiltmc.org/repository/release/"));
+                }
+
+                // Quilt loader can use either intermediary or hashed mappings, prefer intermediary like the official launcher profile
+                if let Some(intermediary) = &quilt_launch.intermediary {
+                    version.libraries.push(create_maven_library(intermediary.maven, "https://maven.fabricmc.net/"));
+                } else if let Some(hashed) = &quilt_launch.hashed {
+                    version.libraries.push(create_maven_library(hashed.maven, "https://maven.quiltmc.org/repository/release/"));
+                }
+
+                let libraries = &quilt_launch.launcher_meta.libraries;
+                for library in libraries.common.iter().chain(libraries.client.iter()) {
+                    version.libraries.push(create_maven_library(library.name, library.url.as_str()));
+                }
+
+                version.main_class = quilt_launch.launcher_meta.main_class.client;
+
+                Ok((Arc::new(version), AddVanillaJar::Yes))
+            },
             Loader::Forge => {
                 launch_tracker.add_total(7);
                 launch_tracker.notify();
 
                 // Download Minecraft manifest and neoforge installer maven
                 let (minecraft_versions, loader_versions) = futures::future::try_join(
-                    self.meta.fetch(&MinecraftVersionManifestMetadataItem),
-                    self.meta.fetch(&ForgeInstallerMavenMetadataItem)
+                    timer.time_concurrent(LaunchPhase::VersionResolution, self.meta.fetch(&MinecraftVersionManifestMetadataItem)),
+                    timer.time_concurrent(LaunchPhase::LoaderInstall, self.meta.fetch(&ForgeInstallerMavenMetadataItem))
                 ).await?;
 
-                self.create_forgelike_launch_version(http_client, progress_trackers, launch_tracker, instance_info,
+                self.create_forgelike_launch_version(progress_trackers, launch_tracker, instance_info,
                     minecraft_versions,
                     &loader_versions.0,
                     "https://maven.minecraftforge.net/net/minecraftforge/forge/{0}/forge-{0}-installer.jar.sha1",
-                    "net/minecraftforge/forge/{0}/forge-{0}-installer.jar",
+                    FORGE_INSTALLER_PATH,
                     "https://maven.minecraftforge.net/net/minecraftforge/forge/{0}/forge-{0}-installer.jar",
                     true,
-                    false
+                    false,
+                    repairs,
+                    timer
                 ).await
             },
             Loader::NeoForge => {
@@ -419,18 +803,20 @@ impl Launcher {
 
                 // Download Minecraft manifest and neoforge installer maven
                 let (minecraft_versions, loader_versions) = futures::future::try_join(
-                    self.meta.fetch(&MinecraftVersionManifestMetadataItem),
-                    self.meta.fetch(&NeoforgeInstallerMavenMetadataItem)
+                    timer.time_concurrent(LaunchPhase::VersionResolution, self.meta.fetch(&MinecraftVersionManifestMetadataItem)),
+                    timer.time_concurrent(LaunchPhase::LoaderInstall, self.meta.fetch(&NeoforgeInstallerMavenMetadataItem))
                 ).await?;
 
-                self.create_forgelike_launch_version(http_client, progress_trackers, launch_tracker, instance_info,
+                self.create_forgelike_launch_version(progress_trackers, launch_tracker, instance_info,
                     minecraft_versions,
                     &loader_versions.0,
                     "https://maven.neoforged.net/releases/net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar.sha1",
-                    "net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar",
+                    NEOFORGE_INSTALLER_PATH,
                     "https://maven.neoforged.net/releases/net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar",
                     false,
-                    true
+                    true,
+                    repairs,
+                    timer
                 ).await
             },
             Loader::Unknown => todo!(),
@@ -439,7 +825,6 @@ impl Launcher {
 
     async fn create_forgelike_launch_version(
         &self,
-        http_client: &reqwest::Client,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
@@ -450,6 +835,8 @@ impl Launcher {
         installer_url: &'static str,
         check_mirrors: bool,
         neoforge_versioning: bool,
+        repairs: &RepairLog,
+        timer: &LaunchTimer,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
         launch_tracker.add_count(1);
         launch_tracker.notify();
@@ -461,37 +848,7 @@ impl Launcher {
         let loader_version = if let Some(preferred_loader_version) = instance_info.preferred_loader_version {
             preferred_loader_version
         } else {
-            let mut minecraft_version_parts = VersionFragment::string_to_parts(instance_info.minecraft_version.as_str());
-            if neoforge_versioning {
-                // 1.21.5 -> 21.5
-                // 25w14craftmine -> 0.25w14craftmine
-                // 1.21 -> 21.0
-                // 26.1 -> 26.1.0
-                if minecraft_version_parts[0] == VersionFragment::String("25w14craftmine".into()) {
-                    minecraft_version_parts.insert(0, VersionFragment::Number(0))
-                } else {
-                    if minecraft_version_parts.len() < 3 {
-                        minecraft_version_parts.push(VersionFragment::Number(0))
-                    }
-                    if minecraft_version_parts[0] == VersionFragment::Number(1) {
-                        minecraft_version_parts.remove(0);
-                    }
-                }
-            }
-
-            let mut latest_loader_version = None;
-            let mut latest_loader_version_parts = Vec::new();
-            for version in loader_versions.iter() {
-                let parts = VersionFragment::string_to_parts(version);
-
-                if parts.starts_with(&minecraft_version_parts) {
-                    if parts > latest_loader_version_parts {
-                        latest_loader_version_parts = parts;
-                        latest_loader_version = Some(version.clone());
-                    }
-                }
-            }
-            let Some(latest_loader_version) = latest_loader_version else {
+            let Some(latest_loader_version) = find_latest_forgelike_version(instance_info.minecraft_version, loader_versions, neoforge_versioning) else {
                 return Err(LaunchError::CantFindVersion(instance_info.minecraft_version.as_str()));
             };
 
@@ -501,11 +858,13 @@ impl Launcher {
         // Download base Minecraft version and neoforge installer hash
         let installer_hash_url = installer_hash_url.replace("{0}", &loader_version);
         let (base_version, installer_sha1) = futures::future::join(
-            self.meta.fetch(&MinecraftVersionMetadataItem(version_link)),
-            Self::download_sha1(http_client, &installer_hash_url)
+            timer.time_concurrent(LaunchPhase::VersionResolution, self.meta.fetch(&MinecraftVersionMetadataItem(version_link))),
+            timer.time_concurrent(LaunchPhase::LoaderInstall, self.download_sha1(&installer_hash_url))
         ).await;
         let base_version = base_version?;
 
+        let loader_install_timer = timer.start(LaunchPhase::LoaderInstall);
+
         launch_tracker.add_count(1);
         launch_tracker.notify();
 
@@ -527,13 +886,13 @@ impl Launcher {
 
         let mojang_java_binary_future = self.load_mojang_java_binary(
             &self.meta,
-            http_client,
             instance_info,
             &base_version,
             progress_trackers,
             launch_tracker,
+            repairs,
         );
-        let load_installer_library_future = self.load_libraries(http_client, artifacts, progress_trackers, launch_tracker);
+        let load_installer_library_future = self.load_libraries(artifacts, progress_trackers, launch_tracker, repairs);
 
         let (artifact_load_result, java_load_result) = futures::future::try_join(
             load_installer_library_future.map_err(LaunchError::from),
@@ -555,18 +914,20 @@ impl Launcher {
 
         let install_profile = serde_json::from_slice(&install_profile_bytes);
 
+        drop(loader_install_timer);
+
         if install_profile.is_err() {
             if let Ok(install_profile_legacy) = serde_json::from_slice(&install_profile_bytes) {
                 launch_tracker.add_count(1);
                 let ret = self.create_forgelike_install_version_legacy(install_profile_legacy, installer_zip,
-                    base_version, http_client, progress_trackers, launch_tracker, instance_info, check_mirrors).await;
+                    base_version, progress_trackers, launch_tracker, instance_info, check_mirrors, repairs, timer).await;
                 return ret;
             }
         }
 
         self.create_forgelike_install_version_modern(install_profile?, installer_zip,
-            installer_path, minecraft_jar_path, &java_load_result, base_version, http_client,
-            progress_trackers, launch_tracker, instance_info, check_mirrors).await
+            installer_path, minecraft_jar_path, &java_load_result, base_version,
+            progress_trackers, launch_tracker, instance_info, check_mirrors, repairs, timer).await
     }
 
     async fn create_forgelike_install_version_modern(
@@ -577,12 +938,15 @@ impl Launcher {
         minecraft_jar_path: &PathBuf,
         java_path: &PathBuf,
         base_version: Arc<MinecraftVersion>,
-        http_client: &reqwest::Client,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
         check_mirrors: bool,
+        repairs: &RepairLog,
+        timer: &LaunchTimer,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
+        let loader_install_timer = timer.start(LaunchPhase::LoaderInstall);
+
         if &*install_profile.minecraft != instance_info.minecraft_version.as_str() {
             return Err(LaunchError::MismatchedLoaderVersions(install_profile.minecraft.clone()));
         }
@@ -599,7 +963,7 @@ impl Launcher {
 
         // Download mirror list
         let mirror = if check_mirrors {
-            Self::download_random_mirror(http_client, &install_profile.mirror_list).await
+            self.download_random_mirror(&install_profile.mirror_list).await
         } else {
             None
         };
@@ -618,7 +982,7 @@ impl Launcher {
             Some(artifact)
         }).collect::<Vec<_>>();
 
-        self.load_libraries(http_client, &libraries, progress_trackers, launch_tracker).await?;
+        self.load_libraries(&libraries, progress_trackers, launch_tracker, repairs).await?;
 
         let forge_temp = self.directories.temp_dir.join("forge_installer");
 
@@ -668,6 +1032,9 @@ impl Launcher {
         data.insert("INSTALLER".into(), installer_path.as_os_str().to_os_string());
         data.insert("LIBRARY_DIR".into(), self.directories.libraries_dir.as_os_str().to_os_string());
 
+        drop(loader_install_timer);
+        let _timer = timer.start(LaunchPhase::ForgeProcessors);
+
         let processor_tracker = ProgressTracker::new("Forge Post Processors".into(), self.sender.clone());
         progress_trackers.push(processor_tracker.clone());
 
@@ -790,12 +1157,15 @@ impl Launcher {
         install_profile: ForgeInstallProfileLegacy,
         installer_zip: ArchiveHandle<'_, File>,
         base_version: Arc<MinecraftVersion>,
-        http_client: &reqwest::Client,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
         instance_info: &InstanceConfiguration,
         check_mirrors: bool,
+        repairs: &RepairLog,
+        timer: &LaunchTimer,
     ) -> Result<(Arc<MinecraftVersion>, AddVanillaJar), LaunchError> {
+        let _timer = timer.start(LaunchPhase::LoaderInstall);
+
         if &*install_profile.install.minecraft != instance_info.minecraft_version.as_str() {
             return Err(LaunchError::MismatchedLoaderVersions(install_profile.install.minecraft.clone()));
         }
@@ -816,7 +1186,7 @@ impl Launcher {
 
         // Download mirror list
         let mirror = if check_mirrors {
-            Self::download_random_mirror(http_client, &install_profile.install.mirror_list).await
+            self.download_random_mirror(&install_profile.install.mirror_list).await
         } else {
             None
         };
@@ -836,32 +1206,28 @@ impl Launcher {
                 Some(artifact)
             }).collect::<Vec<_>>();
 
-            self.load_libraries(http_client, &libraries, progress_trackers, launch_tracker).await?;
+            self.load_libraries(&libraries, progress_trackers, launch_tracker, repairs).await?;
         }
 
         Ok((Arc::new(version.apply_to(&base_version)), AddVanillaJar::Yes))
     }
 
-    async fn download_sha1(http_client: &reqwest::Client, url: &str) -> Option<Ustr> {
-        let response = http_client
-            .get(url)
-            .send().await.ok()?;
-
-        let bytes = response.bytes().await.ok()?;
-
-        if bytes.len() != 40 {
+    async fn download_sha1(&self, url: &str) -> Option<Ustr> {
+        if self.meta.is_offline() {
             return None;
         }
 
+        let bytes = self.downloads.download_bytes(url, Some(40), None, None).await.ok()?;
+
         Some(str::from_utf8(&bytes).ok()?.into())
     }
 
-    async fn download_random_mirror(http_client: &reqwest::Client, url: &str) -> Option<Arc<str>> {
-        let response = http_client
-            .get(url)
-            .send().await.ok()?;
+    async fn download_random_mirror(&self, url: &str) -> Option<Arc<str>> {
+        if self.meta.is_offline() {
+            return None;
+        }
 
-        let bytes = response.bytes().await.ok()?;
+        let bytes = self.downloads.download_bytes(url, None, None, None).await.ok()?;
 
         #[derive(Deserialize)]
         struct Mirror {
@@ -878,15 +1244,22 @@ impl Launcher {
     async fn load_mojang_java_binary(
         &self,
         meta: &MetadataManager,
-        http_client: &reqwest::Client,
         configuration: &InstanceConfiguration,
         version_info: &MinecraftVersion,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
+        repairs: &RepairLog,
     ) -> Result<PathBuf, LoadJavaRuntimeError> {
+        let needed_version = if let Some(java_version) = &version_info.java_version {
+            java_version.major_version
+        } else {
+            8
+        };
+
         if let Some(jvm_binary) = &configuration.jvm_binary {
             if jvm_binary.enabled && let Some(path) = &jvm_binary.path {
                 if let Some(binary) = Self::search_for_java_binary(&path) {
+                    self.warn_if_mismatched_java(&binary, needed_version, configuration.minecraft_version).await;
                     return Ok(binary);
                 }
             }
@@ -897,12 +1270,6 @@ impl Launcher {
 
             let mut found_versions = BTreeSet::new();
 
-            let needed_version = if let Some(java_version) = &version_info.java_version {
-                java_version.major_version
-            } else {
-                8
-            };
-
             for path in paths {
                 let Some(binary) = Self::search_for_java_binary(&path) else {
                     continue;
@@ -922,6 +1289,14 @@ impl Launcher {
             return Err(LoadJavaRuntimeError::UnableToFindExternalBinary(needed_version, found_versions.into_iter().collect()));
         }
 
+        let default_java = self.config.write().get().java_defaults.get(&needed_version).cloned();
+        if let Some(path) = default_java {
+            if let Some(binary) = Self::search_for_java_binary(&path) {
+                return Ok(binary);
+            }
+            log::warn!("Default runtime for Java {needed_version} at {:?} no longer exists, using Mojang's runtime instead", path);
+        }
+
         let mut platform: Ustr = match (std::env::consts::OS, std::env::consts::ARCH) {
             ("linux", "x86_64") => "linux".into(),
             ("linux", "x86") => "linux-i386".into(),
@@ -989,7 +1364,7 @@ impl Launcher {
         progress_trackers.push(java_runtime_tracker.clone());
         java_runtime_tracker.notify();
 
-        let result = do_java_runtime_load(http_client, runtime_component_dir, fresh_install, runtime, &java_runtime_tracker).await;
+        let result = do_java_runtime_load(&self.downloads, self.meta.is_offline(), runtime_component_dir, fresh_install, runtime, &java_runtime_tracker, repairs).await;
 
         java_runtime_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         java_runtime_tracker.notify();
@@ -1003,12 +1378,12 @@ impl Launcher {
     async fn load_assets(
         &self,
         meta: &MetadataManager,
-        http_client: &reqwest::Client,
         game_dir: &Arc<Path>,
         version_info: &MinecraftVersion,
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
-    ) -> Result<String, LoadAssetObjectsError> {
+        repairs: &RepairLog,
+    ) -> Result<(String, Arc<Path>), LoadAssetObjectsError> {
         let asset_index = format!("{}", version_info.assets);
 
         let assets_index = meta.fetch(&AssetsIndexMetadataItem {
@@ -1030,7 +1405,13 @@ impl Launcher {
             self.directories.assets_objects_dir.clone()
         };
 
-        let result = do_asset_objects_load(http_client, assets_index, assets_dir, &assets_tracker).await;
+        let marker = launch_cache::assets_complete_marker(&assets_dir, &asset_index);
+        _ = std::fs::remove_file(&marker);
+
+        let result = do_asset_objects_load(&self.downloads, self.meta.is_offline(), assets_index, assets_dir.clone(), &assets_tracker, repairs).await;
+        if result.is_ok() && let Err(error) = std::fs::write(&marker, []) {
+            log::warn!("Unable to mark assets as complete: {error}");
+        }
 
         assets_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         assets_tracker.notify();
@@ -1040,15 +1421,15 @@ impl Launcher {
 
         result?;
 
-        Ok(asset_index)
+        Ok((asset_index, assets_dir))
     }
 
     async fn load_libraries(
         &self,
-        http_client: &reqwest::Client,
         artifacts: &[GameLibraryArtifact],
         progress_trackers: &ProgressTrackers,
         launch_tracker: &ProgressTracker,
+        repairs: &RepairLog,
     ) -> Result<Vec<(Ustr, PathBuf)>, LoadLibrariesError> {
         let initial_title = Arc::from("Verifying integrity of game libraries");
         let libraries_tracker = ProgressTracker::new(initial_title, self.sender.clone());
@@ -1056,7 +1437,7 @@ impl Launcher {
         libraries_tracker.notify();
 
         let result =
-            do_libraries_load(http_client, artifacts, self.directories.libraries_dir.clone(), &libraries_tracker).await;
+            do_libraries_load(&self.downloads, self.meta.is_offline(), artifacts, self.directories.libraries_dir.clone(), &libraries_tracker, repairs).await;
 
         libraries_tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
         libraries_tracker.notify();
@@ -1069,7 +1450,6 @@ impl Launcher {
 
     async fn load_log_configuration(
         &self,
-        http_client: &reqwest::Client,
         logging: Option<&GameLogging>,
     ) -> Option<OsString> {
         let Some(logging) = logging else {
@@ -1104,43 +1484,16 @@ impl Launcher {
             return Some(expand_logging_argument(client.argument.as_str(), &path));
         }
 
-        let Ok(response) = http_client.get(client.file.url.as_str()).send().await else {
-            log::error!("Failed to make request to download log configuration");
-            return None;
-        };
-        let Ok(bytes) = response.bytes().await else {
-            log::error!("Failed to download log configuration");
-            return None;
-        };
-        let bytes = Arc::new(bytes);
-
-        if bytes.len() != client.file.size as usize {
-            log::error!("Rejecting log configuration because invalid size");
+        if self.meta.is_offline() {
+            log::warn!("Log configuration isn't downloaded, launching without it while offline");
             return None;
         }
 
-        let correct_hash = {
-            let bytes = Arc::clone(&bytes);
-
-            tokio::task::spawn_blocking(move || {
-                let mut hasher = Sha1::new();
-                hasher.update(&*bytes);
-                let actual_hash = hasher.finalize();
-
-                expected_hash == *actual_hash
-            }).await.unwrap()
-        };
-
-        if !correct_hash {
-            log::error!("Log configuration has incorrect hash");
+        if let Err(error) = self.downloads.download_to_file(client.file.url.as_str(), &path, Some(client.file.size as usize), Some(expected_hash), None).await {
+            log::error!("Failed to download log configuration: {error}");
             return None;
         }
 
-        let Ok(_) = tokio::fs::write(path.clone(), &*bytes).await else {
-            log::error!("Failed to write log configuration to disk");
-            return None;
-        };
-
         Some(expand_logging_argument(client.argument.as_str(), &path))
     }
 
@@ -1259,7 +1612,7 @@ impl Launcher {
         }
     }
 
-    fn search_for_java_binary(path: &Path) -> Option<PathBuf> {
+    pub(crate) fn search_for_java_binary(path: &Path) -> Option<PathBuf> {
         if path.is_file() {
             return Some(path.to_path_buf());
         }
@@ -1295,33 +1648,88 @@ impl Launcher {
     }
 
     fn get_major_java_version(&self, binary: &Path) -> Option<u32> {
-        let mut command = std::process::Command::new(binary);
-        command.arg("-jar");
-        command.arg(self.launch_wrapper.as_os_str().to_os_string());
-        command.stdin(Stdio::piped());
-        command.stdout(Stdio::piped());
+        self.get_java_properties(binary).map(|properties| properties.major_version)
+    }
 
-        let mut process = command.spawn().ok()?;
+    pub(crate) fn get_java_properties(&self, binary: &Path) -> Option<JavaProperties> {
+        java_properties(binary, &self.launch_wrapper)
+    }
 
-        let mut stdin = process.stdin.take().unwrap();
-        stdin.write_all(b"printproperty\njava.specification.version\nexit\n").ok()?;
-        stdin.flush().ok()?;
+    /// Same as `get_major_java_version` without blocking, the version is remembered until the binary is modified
+    async fn get_cached_major_java_version(&self, binary: &Path) -> Option<u32> {
+        let modified = std::fs::metadata(binary).and_then(|metadata| metadata.modified()).ok()?;
+        if let Some((cached_modified, major_version)) = self.java_versions.read().get(binary) && *cached_modified == modified {
+            return Some(*major_version);
+        }
 
-        let output = process.wait_with_output().ok()?;
+        let binary = binary.to_path_buf();
+        let launch_wrapper = Arc::clone(&self.launch_wrapper);
+        let (binary, properties) = tokio::task::spawn_blocking(move || {
+            let properties = java_properties(&binary, &launch_wrapper);
+            (binary, properties)
+        }).await.unwrap();
 
-        if !output.status.success() {
-            return None;
-        }
+        let major_version = properties?.major_version;
+        self.java_versions.write().insert(binary, (modified, major_version));
+        Some(major_version)
+    }
 
-        let output = output.stdout.trim_ascii();
-        let mut output = str::from_utf8(output).ok()?;
-        if output.starts_with("1.") {
-            output = &output[2..];
+    async fn warn_if_mismatched_java(&self, binary: &Path, needed_version: u32, minecraft_version: Ustr) {
+        match self.get_cached_major_java_version(binary).await {
+            Some(major_version) if major_version != needed_version => {
+                log::warn!("Selected Java runtime {:?} is Java {major_version}, but Java {needed_version} is needed", binary);
+                self.sender.send_warning(format!("The selected Java runtime is Java {major_version}, but Minecraft {minecraft_version} needs Java {needed_version}. The game might not start"));
+            },
+            Some(_) => {},
+            None => {
+                log::warn!("Unable to determine the version of the selected Java runtime {:?}", binary);
+                self.sender.send_warning("Unable to determine the version of the selected Java runtime, the game might not start");
+            },
         }
-        output.parse().ok()
     }
 }
 
+pub(crate) struct JavaProperties {
+    pub major_version: u32,
+    pub version: Arc<str>,
+    pub vendor: Arc<str>,
+}
+
+/// Runs the binary to find out which version of Java it is and who built it
+fn java_properties(binary: &Path, launch_wrapper: &Path) -> Option<JavaProperties> {
+    let mut command = std::process::Command::new(binary);
+    command.arg("-jar");
+    command.arg(launch_wrapper.as_os_str().to_os_string());
+    command.stdin(Stdio::piped());
+    command.stdout(Stdio::piped());
+
+    let mut process = command.spawn().ok()?;
+
+    let mut stdin = process.stdin.take().unwrap();
+    stdin.write_all(b"printproperty\njava.specification.version\nprintproperty\njava.version\nprintproperty\njava.vendor\nexit\n").ok()?;
+    stdin.flush().ok()?;
+
+    let output = process.wait_with_output().ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let output = str::from_utf8(output.stdout.trim_ascii()).ok()?;
+    let mut lines = output.lines().map(str::trim_ascii);
+
+    let mut specification_version = lines.next()?;
+    if specification_version.starts_with("1.") {
+        specification_version = &specification_version[2..];
+    }
+
+    Some(JavaProperties {
+        major_version: specification_version.parse().ok()?,
+        version: lines.next().unwrap_or("unknown").into(),
+        vendor: lines.next().unwrap_or("Unknown vendor").into(),
+    })
+}
+
 fn expand_logging_argument(argument: &str, path: &Path) -> OsString {
     let mut dollar_last = false;
     let mut builder = OsString::new();
@@ -1349,6 +1757,70 @@ fn expand_logging_argument(argument: &str, path: &Path) -> OsString {
     builder
 }
 
+fn create_maven_library(name: Ustr, repository: &str) -> GameLibrary {
+    let coordinate = MavenCoordinate::create(&name);
+    let artifact_path = coordinate.artifact_path();
+    GameLibrary {
+        downloads: GameLibraryDownloads {
+            artifact: Some(GameLibraryArtifact {
+                url: format!("{}{}", repository, &artifact_path).into(),
+                path: artifact_path.into(),
+                sha1: None,
+                size: None,
+            }),
+            classifiers: None,
+        },
+        name,
+        rules: None,
+        natives: None,
+        extract: None,
+    }
+}
+
+fn find_latest_forgelike_version(minecraft_version: Ustr, loader_versions: &[Ustr], neoforge_versioning: bool) -> Option<Ustr> {
+    let mut minecraft_version_parts = VersionFragment::string_to_parts(minecraft_version.as_str());
+    if neoforge_versioning {
+        // 1.21.5 -> 21.5
+        // 25w14craftmine -> 0.25w14craftmine
+        // 1.21 -> 21.0
+        // 26.1 -> 26.1.0
+        if minecraft_version_parts[0] == VersionFragment::String("25w14craftmine".into()) {
+            minecraft_version_parts.insert(0, VersionFragment::Number(0))
+        } else {
+            if minecraft_version_parts.len() < 3 {
+                minecraft_version_parts.push(VersionFragment::Number(0))
+            }
+            if minecraft_version_parts[0] == VersionFragment::Number(1) {
+                minecraft_version_parts.remove(0);
+            }
+        }
+    }
+
+    let mut latest_loader_version = None;
+    let mut latest_loader_version_parts = Vec::new();
+    for version in loader_versions.iter() {
+        let parts = VersionFragment::string_to_parts(version);
+
+        if parts.starts_with(&minecraft_version_parts) {
+            if parts > latest_loader_version_parts {
+                latest_loader_version_parts = parts;
+                latest_loader_version = Some(version.clone());
+            }
+        }
+    }
+    latest_loader_version
+}
+
+fn client_jar_artifact(version_info: &MinecraftVersion, minecraft_version: Ustr) -> GameLibraryArtifact {
+    let client_download = &version_info.downloads.client;
+    GameLibraryArtifact {
+        path: format!("net/minecraft/{0}/minecraft-client-{0}.jar", minecraft_version).into(),
+        sha1: Some(client_download.sha1),
+        size: Some(client_download.size),
+        url: client_download.url,
+    }
+}
+
 fn calculate_natives_dirname(artifacts: &[GameLibraryArtifact]) -> String {
     let mut hashes = HashSet::new();
 
@@ -1374,7 +1846,7 @@ fn calculate_natives_dirname(artifacts: &[GameLibraryArtifact]) -> String {
 #[derive(thiserror::Error, Debug)]
 pub enum LoadJavaRuntimeError {
     #[error("Failed to load remote content:\n{0}")]
-    Reqwest(#[from] reqwest::Error),
+    Download(#[from] DownloadError),
     #[error("Failed to perform I/O operation:\n{0}")]
     IoError(#[from] std::io::Error),
     #[error("Failed to load metadata:\n{0}")]
@@ -1387,8 +1859,6 @@ pub enum LoadJavaRuntimeError {
     UnknownComponentForPlatform,
     #[error("Mojang runtime path is invalid")]
     InvalidComponentPath,
-    #[error("Downloaded file had wrong response size. Expected {0}, got {1}")]
-    WrongResponseSize(usize, usize),
     #[error("Downloaded file had wrong raw size")]
     WrongRawSize,
     #[error("Failed to decompress file")]
@@ -1399,19 +1869,21 @@ pub enum LoadJavaRuntimeError {
     UnableToFindBinary,
     #[error("Unable to find external binary, needed Java {0}, got Java {1:?}")]
     UnableToFindExternalBinary(u32, Vec<u32>),
+    #[error("{0:?} is missing or corrupt and can't be downloaded while offline")]
+    UnavailableOffline(PathBuf),
 }
 
 async fn do_java_runtime_load(
-    http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
+    offline: bool,
     runtime_component_dir: PathBuf,
     fresh_install: bool,
     runtime: Arc<JavaRuntimeComponentManifest>,
     java_runtime_tracker: &ProgressTracker,
+    repairs: &RepairLog,
 ) -> Result<PathBuf, LoadJavaRuntimeError> {
     let mut links = HashMap::new();
 
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
     let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(fresh_install);
 
@@ -1439,7 +1911,6 @@ async fn do_java_runtime_load(
                 total_size += downloads.raw.size;
 
                 let started_downloading = &started_downloading;
-                let download_semaphore = &download_semaphore;
                 let disk_semaphore = &disk_semaphore;
 
                 let task = async move {
@@ -1459,6 +1930,12 @@ async fn do_java_runtime_load(
                         return Ok(());
                     }
 
+                    if offline {
+                        return Err(LoadJavaRuntimeError::UnavailableOffline(path));
+                    }
+
+                    let repair_kind = RepairKind::of(&path);
+
                     let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
                     if !was_downloading {
                         java_runtime_tracker.set_title(Arc::from("Downloading Java Runtime"));
@@ -1470,14 +1947,7 @@ async fn do_java_runtime_load(
                         (false, downloads.raw.size as usize, &downloads.raw)
                     };
 
-                    let permit = download_semaphore.acquire().await.unwrap();
-                    let response = http_client.get(download.url.as_str()).send().await?;
-                    let bytes = response.bytes().await?;
-                    drop(permit);
-
-                    if bytes.len() != size {
-                        return Err(LoadJavaRuntimeError::WrongResponseSize(size, bytes.len()));
-                    }
+                    let bytes = download_manager.download_bytes(download.url.as_str(), Some(size), None, None).await?;
 
                     let decompressed_or_raw = if lzma {
                         let result = tokio::task::spawn_blocking(move || {
@@ -1500,7 +1970,7 @@ async fn do_java_runtime_load(
 
                     let bytes = match &*decompressed_or_raw {
                         Ok(vec) => vec.as_slice(),
-                        Err(bytes) => bytes,
+                        Err(bytes) => bytes.as_slice(),
                     };
 
                     if bytes.len() != downloads.raw.size as usize {
@@ -1512,7 +1982,7 @@ async fn do_java_runtime_load(
                         tokio::task::spawn_blocking(move || {
                             let bytes = match &*decompressed_or_raw {
                                 Ok(vec) => vec.as_slice(),
-                                Err(bytes) => bytes,
+                                Err(bytes) => bytes.as_slice(),
                             };
 
                             let mut hasher = Sha1::new();
@@ -1538,6 +2008,10 @@ async fn do_java_runtime_load(
                         let _ = tokio::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755)).await;
                     }
 
+                    if !fresh_install {
+                        repairs.push(&path, repair_kind);
+                    }
+
                     java_runtime_tracker.add_count(downloads.raw.size as usize);
                     java_runtime_tracker.notify();
                     Ok(())
@@ -1596,28 +2070,26 @@ async fn do_java_runtime_load(
 
 #[derive(thiserror::Error, Debug)]
 pub enum LoadAssetObjectsError {
-    #[error("Failed to load remote content")]
-    Reqwest(#[from] reqwest::Error),
+    #[error("Failed to load remote content:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Failed to perform I/O operation")]
     IoError(#[from] std::io::Error),
     #[error("Hash isn't a valid sha1 hash\n{0}")]
     InvalidHash(Ustr),
-    #[error("Downloaded file had wrong response size. Expected {0}, got {1}")]
-    WrongResponseSize(usize, usize),
-    #[error("Downloaded file had the wrong hash")]
-    WrongHash,
     #[error("Failed to load metadata:\n{0}")]
     MetaLoadError(#[from] MetaLoadError),
+    #[error("{0:?} is missing or corrupt and can't be downloaded while offline")]
+    UnavailableOffline(PathBuf),
 }
 
 async fn do_asset_objects_load(
-    http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
+    offline: bool,
     assets_index: Arc<AssetsIndex>,
     assets_objects_dir: Arc<Path>,
     assets_tracker: &ProgressTracker,
+    repairs: &RepairLog,
 ) -> Result<(), LoadAssetObjectsError> {
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
     let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(false);
 
@@ -1640,7 +2112,6 @@ async fn do_asset_objects_load(
         total_size += asset.size;
 
         let started_downloading = &started_downloading;
-        let download_semaphore = &download_semaphore;
         let disk_semaphore = &disk_semaphore;
 
         let url = format!("https://resources.download.minecraft.net/{}/{}", &asset.hash[..2], &asset.hash);
@@ -1662,37 +2133,21 @@ async fn do_asset_objects_load(
                 return Ok(());
             }
 
-            let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
-            if !was_downloading {
-                assets_tracker.set_title(Arc::from("Downloading game assets"));
+            if offline {
+                return Err(LoadAssetObjectsError::UnavailableOffline(path));
             }
 
-            let permit = download_semaphore.acquire().await.unwrap();
-            let response = http_client.get(&url).send().await?;
-            let bytes = Arc::new(response.bytes().await?);
-            drop(permit);
+            let repair_kind = RepairKind::of(&path);
 
-            if bytes.len() != asset.size as usize {
-                return Err(LoadAssetObjectsError::WrongResponseSize(asset.size as usize, bytes.len()));
+            let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
+            if !was_downloading {
+                assets_tracker.set_title(Arc::from("Downloading game assets"));
             }
 
-            let correct_hash = {
-                let bytes = Arc::clone(&bytes);
-
-                tokio::task::spawn_blocking(move || {
-                    let mut hasher = Sha1::new();
-                    hasher.update(&*bytes);
-                    let actual_hash = hasher.finalize();
-
-                    expected_hash == *actual_hash
-                }).await.unwrap()
-            };
-
-            if !correct_hash {
-                return Err(LoadAssetObjectsError::WrongHash);
-            }
+            let bytes = download_manager.download_bytes(&url, Some(asset.size as usize), Some(expected_hash), None).await?;
 
-            tokio::fs::write(path.clone(), &*bytes).await?;
+            tokio::fs::write(path.clone(), &bytes).await?;
+            repairs.push(&path, repair_kind);
             assets_tracker.add_count(asset.size as usize);
             assets_tracker.notify();
             Ok(())
@@ -1710,28 +2165,26 @@ async fn do_asset_objects_load(
 
 #[derive(thiserror::Error, Debug)]
 pub enum LoadLibrariesError {
-    #[error("Failed to load remote content")]
-    Reqwest(#[from] reqwest::Error),
+    #[error("Failed to load remote content:\n{0}")]
+    Download(#[from] DownloadError),
     #[error("Failed to perform I/O operation")]
     IoError(#[from] std::io::Error),
     #[error("Hash isn't a valid sha1 hash\n{0}")]
     InvalidHash(Ustr),
-    #[error("Downloaded file had wrong response size. Expected {0}, got {1}")]
-    WrongResponseSize(usize, usize),
-    #[error("Downloaded file had the wrong hash")]
-    WrongHash,
     #[error("Illegal library path {0}, directory traversal?")]
     IllegalLibraryPath(Ustr),
+    #[error("{0:?} is missing or corrupt and can't be downloaded while offline")]
+    UnavailableOffline(PathBuf),
 }
 
 async fn do_libraries_load(
-    http_client: &reqwest::Client,
+    download_manager: &DownloadManager,
+    offline: bool,
     artifacts: &[GameLibraryArtifact],
     libraries_dir: Arc<Path>,
     libraries_tracker: &ProgressTracker,
+    repairs: &RepairLog,
 ) -> Result<Vec<(Ustr, PathBuf)>, LoadLibrariesError> {
-    // Limit max concurrent connections to 8 to avoid ratelimiting issues
-    let download_semaphore = tokio::sync::Semaphore::new(8);
     let disk_semaphore = tokio::sync::Semaphore::new(32);
     let started_downloading = AtomicBool::new(false);
 
@@ -1766,7 +2219,6 @@ async fn do_libraries_load(
         total_size += tracker_size;
 
         let started_downloading = &started_downloading;
-        let download_semaphore = &download_semaphore;
         let disk_semaphore = &disk_semaphore;
 
         let task = async move {
@@ -1788,41 +2240,21 @@ async fn do_libraries_load(
                 return Ok((artifact.path, artifact_path));
             }
 
-            let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
-            if !was_downloading {
-                libraries_tracker.set_title(Arc::from("Downloading game libraries"));
+            if offline {
+                return Err(LoadLibrariesError::UnavailableOffline(artifact_path));
             }
 
-            let permit = download_semaphore.acquire().await.unwrap();
-            let response = http_client.get(artifact.url.as_str()).send().await?;
-            let bytes = Arc::new(response.bytes().await?);
-            drop(permit);
+            let repair_kind = RepairKind::of(&artifact_path);
 
-            if let Some(artifact_size) = artifact.size && bytes.len() != artifact_size as usize {
-                return Err(LoadLibrariesError::WrongResponseSize(artifact_size as usize, bytes.len()));
+            let was_downloading = started_downloading.swap(true, std::sync::atomic::Ordering::Relaxed);
+            if !was_downloading {
+                libraries_tracker.set_title(Arc::from("Downloading game libraries"));
             }
 
-            let correct_hash = {
-                if let Some(expected_hash) = expected_hash {
-                    let bytes = Arc::clone(&bytes);
-
-                    tokio::task::spawn_blocking(move || {
-                        let mut hasher = Sha1::new();
-                        hasher.update(&*bytes);
-                        let actual_hash = hasher.finalize();
-
-                        expected_hash == *actual_hash
-                    }).await.unwrap()
-                } else {
-                    true
-                }
-            };
-
-            if !correct_hash {
-                return Err(LoadLibrariesError::WrongHash);
-            }
+            let bytes = download_manager.download_bytes(artifact.url.as_str(), artifact.size.map(|size| size as usize), expected_hash, None).await?;
 
-            tokio::fs::write(artifact_path.clone(), &*bytes).await?;
+            tokio::fs::write(artifact_path.clone(), &bytes).await?;
+            repairs.push(&artifact_path, repair_kind);
             libraries_tracker.add_count(tracker_size as usize);
             libraries_tracker.notify();
             Ok((artifact.path, artifact_path))
@@ -2052,7 +2484,188 @@ impl LaunchRuleContext {
     }
 }
 
+/// A user command run before the game starts or after it exits. Like the hooks in Prism Launcher and MultiMC,
+/// it gets the INST_* environment variables, which can also be used in its arguments
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LaunchHook {
+    arguments: Vec<String>,
+    /// Not saved since the instance's environment variables can contain secrets, see `restore_env`
+    #[serde(skip)]
+    env: Vec<(OsString, OsString)>,
+    working_dir: Arc<Path>,
+}
+
+impl LaunchHook {
+    const MAX_OUTPUT_LINES: usize = 20;
+
+    pub fn run(&self) -> std::io::Result<Output> {
+        let (program, args) = self.arguments.split_first().expect("hook has a program");
+        std::process::Command::new(program)
+            .args(args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .output()
+    }
+
+    /// Runs the hook without blocking, it's killed if the launch is cancelled while it's running
+    pub async fn run_cancellable(&self, modal_action: &ModalAction) -> Result<Output, LaunchError> {
+        let (program, args) = self.arguments.split_first().expect("hook has a program");
+        let child = tokio::process::Command::new(program)
+            .args(args)
+            .envs(self.env.iter().map(|(key, value)| (key, value)))
+            .current_dir(&self.working_dir)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .kill_on_drop(true)
+            .spawn()
+            .map_err(LaunchError::PreLaunchHookError)?;
+
+        tokio::select! {
+            output = child.wait_with_output() => output.map_err(LaunchError::PreLaunchHookError),
+            _ = modal_action.request_cancel.cancelled() => {
+                log::info!("Launch cancelled, killing pre-launch command");
+                Err(LaunchError::CancelledByUser)
+            },
+        }
+    }
+
+    /// Rebuilds the environment of a hook that was loaded from disk from the instance's configuration.
+    /// INST_JAVA isn't known anymore at that point, so it's left out
+    pub fn restore_env(&mut self, configuration: &InstanceConfiguration, instance_name: &str, instance_dir: &Path, game_dir: &Path) {
+        if self.env.is_empty() {
+            self.env = hook_env(configuration, hook_variables(instance_name, instance_dir, game_dir, None));
+        }
+    }
+
+    /// Runs the hook after the game has exited, nothing is waiting on it so failures are only reported
+    pub fn run_post_exit(&self, sender: &FrontendHandle) {
+        log::info!("Running post-exit command");
+        match self.run() {
+            Ok(output) if output.status.success() => {},
+            Ok(output) => {
+                log::warn!("Post-exit command failed ({}):\n{}", output.status, Self::output_tail(&output));
+                sender.send_warning(format!("Post-exit command failed ({})", output.status));
+            },
+            Err(error) => {
+                log::warn!("Failed to run post-exit command: {error}");
+                sender.send_warning(format!("Failed to run post-exit command: {error}"));
+            },
+        }
+    }
+
+    /// The last lines the hook printed to stdout and stderr
+    pub fn output_tail(output: &Output) -> String {
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let lines: Vec<&str> = stdout.lines().chain(stderr.lines()).filter(|line| !line.trim().is_empty()).collect();
+        let skip = lines.len().saturating_sub(Self::MAX_OUTPUT_LINES);
+        lines[skip..].join("\n")
+    }
+}
+
+const FORGE_INSTALLER_PATH: &str = "net/minecraftforge/forge/{0}/forge-{0}-installer.jar";
+const NEOFORGE_INSTALLER_PATH: &str = "net/neoforged/neoforge/{0}/neoforge-{0}-installer.jar";
+const LAUNCH_WRAPPER_MAIN_CLASS: &str = "com.moulberry.pandora.LaunchWrapper";
+const ACCESS_TOKEN_VARIABLE: &str = "ACCESS_TOKEN";
+
+/// The fully resolved command that starts the game. The launch wrapper receives the game arguments, system properties
+/// and main class over stdin, without it they're passed on the command line instead
+#[derive(Debug)]
+pub struct ResolvedLaunchCommand {
+    pub working_dir: PathBuf,
+    pub env: Vec<(OsString, OsString)>,
+    pub program: OsString,
+    pub args: Vec<OsString>,
+    pub properties: Vec<(String, OsString)>,
+    pub main_class: String,
+    pub game_args: Vec<OsString>,
+    access_token: Option<String>,
+}
+
+impl ResolvedLaunchCommand {
+    /// The command on a single line, with the access token replaced by $ACCESS_TOKEN
+    pub fn to_command_line(&self) -> String {
+        let mut words = vec![self.quote(&self.program)];
+        words.extend(self.args.iter().map(|arg| self.quote(arg)));
+        for (property, value) in &self.properties {
+            let mut arg = OsString::from(format!("-D{property}="));
+            arg.push(value);
+            words.push(self.quote(&arg));
+        }
+        words.push(self.quote(OsStr::new(&self.main_class)));
+        words.extend(self.game_args.iter().map(|arg| self.quote(arg)));
+        words.join(" ")
+    }
+
+    /// A shell script that runs the game the same way the launcher does, the access token has to be provided
+    /// in the ACCESS_TOKEN environment variable
+    pub fn to_shell_script(&self) -> String {
+        let mut script = String::from("#!/bin/sh\n");
+        script.push_str("# Exported by Pandora Launcher\n");
+        if self.access_token.is_some() {
+            script.push_str(&format!(": \"${{{ACCESS_TOKEN_VARIABLE}:?Set {ACCESS_TOKEN_VARIABLE} to a valid Minecraft access token}}\"\n"));
+        }
+        script.push('\n');
+        script.push_str(&format!("cd {} || exit 1\n", self.quote(self.working_dir.as_os_str())));
+        for (key, value) in &self.env {
+            script.push_str(&format!("export {}={}\n", key.to_string_lossy(), self.quote(value)));
+        }
+        script.push('\n');
+        script.push_str("exec ");
+        script.push_str(&self.to_command_line());
+        script.push('\n');
+        script
+    }
+
+    fn quote(&self, arg: &OsStr) -> String {
+        let arg = arg.to_string_lossy();
+        let Some(token) = self.access_token.as_deref().filter(|token| !token.is_empty() && arg.contains(*token)) else {
+            return shell_words::quote(&arg).into_owned();
+        };
+
+        let variable = format!("\"${ACCESS_TOKEN_VARIABLE}\"");
+        arg.split(token)
+            .map(|part| if part.is_empty() { String::new() } else { shell_words::quote(part).into_owned() })
+            .collect::<Vec<_>>()
+            .join(&variable)
+    }
+}
+
+fn hook_variables(instance_name: &str, instance_dir: &Path, game_dir: &Path, java_path: Option<&Path>) -> Vec<(&'static str, OsString)> {
+    let mut variables = vec![
+        ("INST_NAME", OsString::from(instance_name)),
+        ("INST_ID", instance_dir.file_name().unwrap_or_default().to_os_string()),
+        ("INST_DIR", instance_dir.as_os_str().to_os_string()),
+        ("INST_MC_DIR", game_dir.as_os_str().to_os_string()),
+    ];
+    if let Some(java_path) = java_path {
+        variables.push(("INST_JAVA", java_path.as_os_str().to_os_string()));
+    }
+    variables
+}
+
+fn hook_env(configuration: &InstanceConfiguration, variables: Vec<(&'static str, OsString)>) -> Vec<(OsString, OsString)> {
+    let mut env: Vec<(OsString, OsString)> = configuration.environment.iter()
+        .map(|(key, value)| (OsString::from(&**key), OsString::from(&**value)))
+        .collect();
+    env.extend(variables.into_iter().map(|(name, value)| (OsString::from(name), value)));
+    env
+}
+
+fn substitute_hook_variables(argument: &str, variables: &[(&str, OsString)]) -> String {
+    let mut argument = argument.to_string();
+    for (name, value) in variables {
+        let value = value.to_string_lossy();
+        argument = argument.replace(&format!("${{{name}}}"), &value).replace(&format!("${name}"), &value);
+    }
+    argument
+}
+
 pub struct LaunchContext {
+    pub instance_name: Ustr,
+    pub instance_dir: Arc<Path>,
     pub launch_wrapper_path: Arc<Path>,
     pub java_path: PathBuf,
     pub natives_dir: PathBuf,
@@ -2069,7 +2682,71 @@ pub struct LaunchContext {
 }
 
 impl LaunchContext {
-    pub fn launch(mut self, version_info: &MinecraftVersion) -> std::io::Result<std::process::Child> {
+    /// Creates a hook for one of the commands in the instance's hook configuration, if it isn't empty
+    pub fn hook(&self, command: &str) -> Option<LaunchHook> {
+        let arguments = match shell_words::split(command) {
+            Ok(split) => split,
+            Err(_) => command.split_whitespace().map(|f| f.to_string()).collect(),
+        };
+        if arguments.is_empty() {
+            return None;
+        }
+
+        let variables = hook_variables(self.instance_name.as_str(), &self.instance_dir, &self.game_dir, Some(&self.java_path));
+        let arguments = arguments.iter().map(|argument| substitute_hook_variables(argument, &variables)).collect();
+
+        Some(LaunchHook {
+            arguments,
+            env: hook_env(&self.configuration, variables),
+            working_dir: self.game_dir.clone(),
+        })
+    }
+
+    pub fn launch(self, version_info: &MinecraftVersion) -> std::io::Result<std::process::Child> {
+        let resolved = self.resolve(version_info, true);
+
+        let mut command = std::process::Command::new(&resolved.program);
+        command.args(&resolved.args);
+        command.arg(LAUNCH_WRAPPER_MAIN_CLASS);
+        command.envs(resolved.env.iter().map(|(key, value)| (key, value)));
+        command.current_dir(&resolved.working_dir);
+        command.stdin(Stdio::piped());
+        command.stdout(Stdio::piped());
+        command.stderr(Stdio::piped());
+
+        let mut child = command.spawn()?;
+
+        let mut stdin = child.stdin.take().expect("stdin present");
+
+        let mut stdin_arguments = String::new();
+
+        for argument in &resolved.game_args {
+            stdin_arguments.push_str("arg\n");
+            stdin_arguments.push_str(argument.to_string_lossy().as_ref());
+            stdin_arguments.push('\n');
+        }
+
+        for (property, value) in &resolved.properties {
+            stdin_arguments.push_str("property\n");
+            stdin_arguments.push_str(property);
+            stdin_arguments.push('\n');
+            stdin_arguments.push_str(&value.to_string_lossy());
+            stdin_arguments.push('\n');
+        }
+
+        stdin_arguments.push_str("launch\n");
+        stdin_arguments.push_str(&resolved.main_class);
+        stdin_arguments.push('\n');
+
+        stdin.write_all(stdin_arguments.as_bytes())?;
+        stdin.flush()?;
+
+        Ok(child)
+    }
+
+    /// Works out everything needed to start the game without starting it. Without the launch wrapper the result
+    /// runs the main class directly, which is what an exported launch script does
+    pub fn resolve(mut self, version_info: &MinecraftVersion, launch_wrapper: bool) -> ResolvedLaunchCommand {
         #[cfg(target_os = "linux")]
         let use_mangohud = self.configuration.linux_wrapper.map(|w| w.use_mangohud).unwrap_or(false);
         #[cfg(target_os = "linux")]
@@ -2094,116 +2771,141 @@ impl LaunchContext {
             wrapping_command.append(split);
         }
 
-        let mut iter = wrapping_command.iter();
-
-        let mut command = if let Some(first) = iter.next() {
-            let mut cmd = std::process::Command::new(first);
-            iter.for_each(|arg| {
-                cmd.arg(arg);
-            });
-            cmd.arg(&*self.java_path);
-            cmd
+        let mut args: Vec<OsString> = Vec::new();
+        let program = if let Some((first, rest)) = wrapping_command.split_first() {
+            args.extend(rest.iter().map(OsString::from));
+            args.push(self.java_path.as_os_str().to_os_string());
+            OsString::from(first)
         } else {
-            std::process::Command::new(&*self.java_path)
+            self.java_path.as_os_str().to_os_string()
         };
 
-
+        let mut env: Vec<(OsString, OsString)> = Vec::new();
         #[cfg(target_os = "linux")] {
             if self.configuration.linux_wrapper.map(|w| w.use_discrete_gpu).unwrap_or(true) {
-                command.env("DRI_PRIME", "1");
+                env.push(("DRI_PRIME".into(), "1".into()));
             }
             if self.configuration.linux_wrapper.map(|w| w.disable_gl_threaded_optimizations).unwrap_or(false) {
-                command.env("__GL_THREADED_OPTIMIZATIONS", "0");
+                env.push(("__GL_THREADED_OPTIMIZATIONS".into(), "0".into()));
             }
         }
 
-        command.current_dir(&self.game_dir);
-        command.stdin(Stdio::piped());
-        command.stdout(Stdio::piped());
-        command.stderr(Stdio::piped());
+        env.extend(self.configuration.environment.iter().map(|(key, value)| (OsString::from(&**key), OsString::from(&**value))));
 
-        self.classpath.push(self.launch_wrapper_path.as_os_str().to_os_string());
+        if launch_wrapper {
+            self.classpath.push(self.launch_wrapper_path.as_os_str().to_os_string());
+        }
 
         if let Some(arguments) = &version_info.arguments {
             self.process_arguments(&arguments.jvm, &mut |arg| {
-                command.arg(arg);
+                args.push(arg.to_os_string());
             });
         } else {
             let mut java_library_path = OsString::new();
             java_library_path.push("-Djava.library.path=");
             java_library_path.push(self.natives_dir.as_os_str());
 
-            command.arg(java_library_path);
-            command.arg("-cp");
-            command.arg(std::env::join_paths(&self.classpath).unwrap());
+            args.push(java_library_path);
+            args.push("-cp".into());
+            args.push(std::env::join_paths(&self.classpath).unwrap());
         }
 
         if let Some(log_configuration) = &self.log_configuration {
-            command.arg(log_configuration);
+            args.push(log_configuration.clone());
         }
 
         if let Some(memory) = &self.configuration.memory && memory.enabled {
-            command.arg(format!("-Xms{}m", memory.min));
-            command.arg(format!("-Xmx{}m", memory.max.max(memory.min).max(128)));
+            args.push(format!("-Xms{}m", memory.min).into());
+            args.push(format!("-Xmx{}m", memory.max.max(memory.min).max(128)).into());
         }
         if let Some(jvm_flags) = &self.configuration.jvm_flags && jvm_flags.enabled {
             if let Ok(split) = shell_words::split(&jvm_flags.flags) {
-                command.args(split);
+                args.extend(split.into_iter().map(OsString::from));
             } else {
-                command.args(jvm_flags.flags.split_whitespace());
+                args.extend(jvm_flags.flags.split_whitespace().map(OsString::from));
             }
         }
 
-        command.arg("com.moulberry.pandora.LaunchWrapper");
-
-        let mut child = command.spawn()?;
-
-        let mut stdin = child.stdin.take().expect("stdin present");
-
-        let mut stdin_arguments = String::new();
+        let mut game_args: Vec<OsString> = Vec::new();
 
         if let Some(arguments) = &version_info.arguments {
             self.process_arguments(&arguments.game, &mut |arg| {
-                stdin_arguments.push_str("arg\n");
-                stdin_arguments.push_str(arg.to_string_lossy().as_ref());
-                stdin_arguments.push('\n');
+                game_args.push(arg.to_os_string());
             });
         }
         if let Some(legacy_arguments) = &version_info.minecraft_arguments {
             for argument in legacy_arguments.split_ascii_whitespace() {
-                stdin_arguments.push_str("arg\n");
-                stdin_arguments.push_str(self.expand_argument(argument).to_string_lossy().as_ref());
-                stdin_arguments.push('\n');
+                game_args.push(self.expand_argument(argument).into_owned());
             }
         }
+        self.process_launch_option_arguments(version_info, &mut |arg| {
+            game_args.push(arg.to_os_string());
+        });
 
-        if let Some(system_libraries) = self.configuration.system_libraries {
+        let mut properties = Vec::new();
+        if let Some(system_libraries) = &self.configuration.system_libraries {
             if system_libraries.override_glfw {
-                if let Some(path) = system_libraries.glfw.get_or_auto(&*AUTO_LIBRARY_PATH_GLFW) {
-                    stdin_arguments.push_str("property\n");
-                    stdin_arguments.push_str("org.lwjgl.glfw.libname\n");
-                    stdin_arguments.push_str(&path.to_string_lossy());
-                    stdin_arguments.push('\n');
+                if let Some(path) = system_libraries.glfw.clone().get_or_auto(&*AUTO_LIBRARY_PATH_GLFW) {
+                    properties.push(("org.lwjgl.glfw.libname".to_string(), path.as_os_str().to_os_string()));
                 }
             }
             if system_libraries.override_openal {
-                if let Some(path) = system_libraries.openal.get_or_auto(&*AUTO_LIBRARY_PATH_OPENAL) {
-                    stdin_arguments.push_str("property\n");
-                    stdin_arguments.push_str("org.lwjgl.openal.libname\n");
-                    stdin_arguments.push_str(&path.to_string_lossy());
-                    stdin_arguments.push('\n');
+                if let Some(path) = system_libraries.openal.clone().get_or_auto(&*AUTO_LIBRARY_PATH_OPENAL) {
+                    properties.push(("org.lwjgl.openal.libname".to_string(), path.as_os_str().to_os_string()));
                 }
             }
         }
 
-        stdin_arguments.push_str("launch\n");
-        stdin_arguments.push_str(version_info.main_class.as_str());
-        stdin_arguments.push('\n');
+        ResolvedLaunchCommand {
+            working_dir: self.game_dir.to_path_buf(),
+            env,
+            program,
+            args,
+            properties,
+            main_class: version_info.main_class.to_string(),
+            game_args,
+            access_token: self.login_info.access_token.as_ref().map(|token| token.secret().to_string()),
+        }
+    }
 
-        stdin.write_all(stdin_arguments.as_bytes())?;
-        stdin.flush()?;
+    /// Arguments for the instance's launch options that the version's own arguments don't cover
+    fn process_launch_option_arguments(&self, version_info: &MinecraftVersion, handler: &mut impl FnMut(&OsStr)) {
+        // Modern versions add these through rules, legacy minecraftArguments don't have them at all
+        if version_info.arguments.is_none() {
+            if let Some((width, height)) = self.rule_context.custom_resolution {
+                (handler)(OsStr::new("--width"));
+                (handler)(OsStr::new(&width.to_string()));
+                (handler)(OsStr::new("--height"));
+                (handler)(OsStr::new(&height.to_string()));
+            }
+            if self.rule_context.is_demo_user {
+                (handler)(OsStr::new("--demo"));
+            }
+        }
 
-        Ok(child)
+        if self.configuration.window.is_some_and(|window| window.fullscreen) {
+            (handler)(OsStr::new("--fullscreen"));
+        }
+
+        // Versions before quick play was added only know how to join a server directly
+        if let Some(QuickPlayLaunch::Multiplayer(target)) = &self.rule_context.quick_play && !supports_quick_play(version_info) {
+            let target = target.to_string_lossy();
+            let (host, port) = split_server_address(&target);
+            (handler)(OsStr::new("--server"));
+            (handler)(OsStr::new(host));
+            (handler)(OsStr::new("--port"));
+            (handler)(OsStr::new(port));
+        }
+
+        if let Some(game_arguments) = &self.configuration.game_arguments && game_arguments.enabled {
+            let split = match shell_words::split(&game_arguments.arguments) {
+                Ok(split) => split,
+                Err(_) => game_arguments.arguments.split_whitespace().map(|f| f.to_string()).collect(),
+            };
+            for argument in split {
+                (handler)(OsStr::new(&argument));
+            }
+        }
     }
 
     fn process_arguments(&self, arguments: &[LaunchArgument], handler: &mut impl FnMut(&OsStr)) {
@@ -2323,6 +3025,31 @@ impl LaunchContext {
     }
 }
 
+/// Splits `host:port` into the host and port, defaulting to 25565. IPv6 addresses only have a port when they're
+/// written in brackets, e.g. `[::1]:25566`, a bare IPv6 address is used as the host as is
+fn split_server_address(target: &str) -> (&str, &str) {
+    if let Some(rest) = target.strip_prefix('[') && let Some((host, rest)) = rest.split_once(']') {
+        return match rest.strip_prefix(':') {
+            Some(port) if port.parse::<u16>().is_ok() => (host, port),
+            _ => (host, "25565"),
+        };
+    }
+    match target.split_once(':') {
+        Some((host, port)) if !port.contains(':') && port.parse::<u16>().is_ok() => (host, port),
+        _ => (target, "25565"),
+    }
+}
+
+fn supports_quick_play(version_info: &MinecraftVersion) -> bool {
+    let Some(arguments) = &version_info.arguments else {
+        return false;
+    };
+    arguments.game.iter().any(|argument| match argument {
+        LaunchArgument::Single(_) => false,
+        LaunchArgument::Ruled(ruled) => ruled.rules.iter().any(|rule| rule.features.as_ref().is_some_and(|features| features.is_quick_play_multiplayer)),
+    })
+}
+
 fn path_is_normal(path: impl AsRef<Path>) -> bool {
     let components = path.as_ref().components();
 
@@ -2363,3 +3090,20 @@ fn expand_forge_argument<'a>(argument: &'a str, map: &FxHashMap<String, OsString
     }
     Cow::Borrowed(OsStr::new(argument))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::split_server_address;
+
+    #[test]
+    fn test_split_server_address() {
+        assert_eq!(split_server_address("mc.example.com"), ("mc.example.com", "25565"));
+        assert_eq!(split_server_address("mc.example.com:25566"), ("mc.example.com", "25566"));
+        assert_eq!(split_server_address("mc.example.com:invalid"), ("mc.example.com:invalid", "25565"));
+        assert_eq!(split_server_address("127.0.0.1:25566"), ("127.0.0.1", "25566"));
+        assert_eq!(split_server_address("2001:db8::1"), ("2001:db8::1", "25565"));
+        assert_eq!(split_server_address("::1"), ("::1", "25565"));
+        assert_eq!(split_server_address("[2001:db8::1]:25566"), ("2001:db8::1", "25566"));
+        assert_eq!(split_server_address("[::1]"), ("::1", "25565"));
+    }
+}
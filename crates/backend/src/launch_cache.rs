@@ -0,0 +1,147 @@
+use std::{collections::BTreeMap, ffi::OsString, path::{Path, PathBuf}, sync::Arc, time::UNIX_EPOCH};
+
+use schema::{loader::Loader, version::MinecraftVersion};
+use serde::{Deserialize, Serialize};
+use ustr::Ustr;
+
+const LAUNCH_CACHE_FILENAME: &str = ".pandora_launch_cache.json";
+
+/// Written next to the assets once every object of an asset index was verified or downloaded
+pub fn assets_complete_marker(assets_dir: &Path, assets_index_name: &str) -> PathBuf {
+    assets_dir.join(format!(".pandora_assets_complete_{assets_index_name}"))
+}
+
+/// Everything that decides how an instance resolves, a cache that was created with a different key is ignored
+#[derive(Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub struct LaunchCacheKey {
+    pub minecraft_version: Ustr,
+    /// Hash of the version json listed in Mojang's version manifest, changes if a version is republished
+    pub version_sha1: Ustr,
+    pub loader: Loader,
+    pub loader_version: Option<Ustr>,
+    /// Profiles can override the Java binary, so switching to another profile resolves the launch again
+    pub launch_profile: Option<Arc<str>>,
+    pub java_defaults: BTreeMap<u32, Arc<Path>>,
+    pub force_external_java: Option<OsString>,
+}
+
+/// A file the cached launch depends on, with the size and modification time it had when its hash was last checked
+#[derive(Serialize, Deserialize, Debug)]
+struct CachedFile {
+    path: PathBuf,
+    size: u64,
+    modified_ms: u64,
+}
+
+impl CachedFile {
+    fn new(path: PathBuf) -> Option<Self> {
+        let (size, modified_ms) = stat(&path)?;
+        Some(Self { path, size, modified_ms })
+    }
+
+    fn is_unchanged(&self) -> bool {
+        stat(&self.path) == Some((self.size, self.modified_ms))
+    }
+}
+
+fn stat(path: &Path) -> Option<(u64, u64)> {
+    let metadata = std::fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?.duration_since(UNIX_EPOCH).ok()?;
+    Some((metadata.len(), modified.as_millis() as u64))
+}
+
+/// The fully resolved launch of an instance. Relaunching an unchanged instance uses it instead of resolving the
+/// version, installing the loader and checking every library again
+#[derive(Serialize, Deserialize, Debug)]
+pub struct LaunchCache {
+    key: LaunchCacheKey,
+    pub version: MinecraftVersion,
+    pub java_path: PathBuf,
+    pub natives_dir: PathBuf,
+    pub assets_index_name: String,
+    pub classpath: Vec<OsString>,
+    pub log_configuration: Option<OsString>,
+    /// Only checked to exist, a full check of the asset objects is left to verifying the instance
+    assets_marker: PathBuf,
+    /// Natives extracted for this launch, only checked to exist since instances with the same libraries share them
+    natives: Vec<PathBuf>,
+    files: Vec<CachedFile>,
+}
+
+impl LaunchCache {
+    /// Returns None if one of the files the launch depends on doesn't exist, there's nothing to cache in that case
+    pub fn new(
+        key: LaunchCacheKey,
+        version: MinecraftVersion,
+        java_path: PathBuf,
+        natives_dir: PathBuf,
+        assets_index_name: String,
+        classpath: Vec<OsString>,
+        log_configuration: Option<OsString>,
+        assets_index: PathBuf,
+        assets_dir: PathBuf,
+        natives: Vec<PathBuf>,
+        dependencies: Vec<PathBuf>,
+    ) -> Option<Self> {
+        let files = std::iter::once(java_path.clone())
+            .chain(std::iter::once(assets_index))
+            .chain(classpath.iter().map(PathBuf::from))
+            .chain(dependencies)
+            .map(CachedFile::new)
+            .collect::<Option<Vec<_>>>()?;
+        let assets_marker = assets_complete_marker(&assets_dir, &assets_index_name);
+
+        Some(Self {
+            key,
+            version,
+            java_path,
+            natives_dir,
+            assets_index_name,
+            classpath,
+            log_configuration,
+            assets_marker,
+            natives,
+            files,
+        })
+    }
+
+    /// Loads the cache of an instance if it has the same key and none of the files it depends on have changed since
+    pub fn load(instance_dir: &Path, key: &LaunchCacheKey) -> Option<Self> {
+        let cache: Self = crate::read_json(&instance_dir.join(LAUNCH_CACHE_FILENAME)).ok()?;
+
+        if cache.key != *key {
+            log::debug!("Launch cache is out of date, resolving launch again");
+            return None;
+        }
+        if let Some(file) = cache.files.iter().find(|file| !file.is_unchanged()) {
+            log::debug!("{:?} changed since the launch was cached, resolving launch again", file.path);
+            return None;
+        }
+        if !cache.natives_dir.is_dir() || cache.natives.iter().any(|path| !path.is_file()) {
+            log::debug!("Natives of the cached launch are missing, resolving launch again");
+            return None;
+        }
+        if !cache.assets_marker.is_file() {
+            log::debug!("Assets of the cached launch are missing, resolving launch again");
+            return None;
+        }
+
+        Some(cache)
+    }
+
+    pub fn save(&self, instance_dir: &Path) {
+        match serde_json::to_vec(self) {
+            Ok(bytes) => {
+                if let Err(error) = crate::write_safe(&instance_dir.join(LAUNCH_CACHE_FILENAME), &bytes) {
+                    log::warn!("Unable to write launch cache: {error}");
+                }
+            },
+            Err(error) => log::warn!("Unable to serialize launch cache: {error}"),
+        }
+    }
+
+    /// Forces the next launch of the instance to resolve everything again
+    pub fn invalidate(instance_dir: &Path) {
+        _ = std::fs::remove_file(instance_dir.join(LAUNCH_CACHE_FILENAME));
+    }
+}
@@ -0,0 +1,107 @@
+use std::sync::Arc;
+
+use auth::models::MinecraftAccessToken;
+use bridge::{
+    instance::InstanceID, message::{LaunchCommandExport, QuickPlayLaunch}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use uuid::Uuid;
+
+use crate::{BackendState, account::MinecraftLoginInfo, instance::ContentFolder, launch::LaunchError};
+
+const LAUNCH_SCRIPT_FILENAME: &str = "launch.sh";
+
+#[derive(thiserror::Error, Debug)]
+pub enum LaunchExportError {
+    #[error("Unable to find instance")]
+    UnknownInstance,
+    #[error("Unknown launch profile {0}")]
+    UnknownLaunchProfile(Arc<str>),
+    #[error("Failed to apply modpacks:\n{0}")]
+    ModpackError(Arc<str>),
+    #[error("Failed to resolve launch command:\n{0}")]
+    LaunchError(#[from] LaunchError),
+    #[error("Failed to write launch script:\n{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+impl BackendState {
+    /// Resolves the command that launches an instance without starting the game. The access token never ends up
+    /// in the output, a placeholder for it is resolved instead
+    pub async fn export_launch_command(&self, id: InstanceID, quick_play: Option<QuickPlayLaunch>, profile: Option<Arc<str>>, export: LaunchCommandExport, modal_action: &ModalAction) -> Result<(), LaunchExportError> {
+        let profile = match profile {
+            Some(name) => Some(self.get_launch_profile(id, &name).ok_or(LaunchExportError::UnknownLaunchProfile(name))?),
+            None => None,
+        };
+
+        let (name, root_path, dot_minecraft, configuration) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(LaunchExportError::UnknownInstance);
+            };
+            let configuration = match &profile {
+                Some(profile) => instance.configuration.get().with_launch_profile(profile),
+                None => instance.configuration.get().clone(),
+            };
+            (instance.name, instance.root_path.clone(), instance.dot_minecraft_path.clone(), configuration)
+        };
+
+        let quick_play = quick_play.or_else(|| profile.as_ref()?.quick_play.as_ref().map(QuickPlayLaunch::from));
+        let login_info = self.placeholder_login_info(profile.as_ref().and_then(|profile| profile.account));
+
+        // Same as the prelaunch of a real launch, except that file syncing is left alone
+        let add_mods = self.prelaunch_apply_modpacks(id, modal_action).await;
+        if let Some(error) = modal_action.error.write().take() {
+            return Err(LaunchExportError::ModpackError(error));
+        }
+        let mods = self.clone().load_instance_content(id, ContentFolder::Mods).await.unwrap_or_else(|| Arc::from([]));
+
+        let tracker = ProgressTracker::new(Arc::from("Resolving launch command"), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+
+        let result = self.launcher.dry_run(name, root_path.clone(), dot_minecraft, configuration,
+            profile.map(|profile| profile.name), quick_play, login_info, add_mods, &mods, &tracker, modal_action).await;
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        let command = result?;
+        match export {
+            LaunchCommandExport::CommandLine(channel) => {
+                _ = channel.send(command.to_command_line().into());
+            },
+            LaunchCommandExport::Script => {
+                let path = root_path.join(LAUNCH_SCRIPT_FILENAME);
+                crate::write_safe(&path, command.to_shell_script().as_bytes())?;
+                #[cfg(unix)]
+                {
+                    use std::os::unix::fs::PermissionsExt;
+                    std::fs::set_permissions(&path, std::fs::Permissions::from_mode(0o755))?;
+                }
+                self.send.send_success(format!("Wrote launch script to {}", path.display()));
+            },
+        }
+
+        Ok(())
+    }
+
+    /// Login info for the account that would be used, with a stand-in access token that the export replaces
+    fn placeholder_login_info(&self, account: Option<Uuid>) -> MinecraftLoginInfo {
+        let mut account_info = self.account_info.write();
+        let account_info = account_info.get();
+
+        let account = account.or(account_info.selected_account)
+            .and_then(|uuid| Some((uuid, account_info.accounts.get(&uuid)?)));
+        match account {
+            Some((uuid, account)) => MinecraftLoginInfo {
+                uuid,
+                username: account.username.clone(),
+                access_token: (!account.offline).then(|| MinecraftAccessToken(Uuid::new_v4().simple().to_string().into())),
+            },
+            None => MinecraftLoginInfo {
+                uuid: Uuid::nil(),
+                username: "Player".into(),
+                access_token: None,
+            },
+        }
+    }
+}
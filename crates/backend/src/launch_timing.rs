@@ -0,0 +1,103 @@
+use std::time::{Duration, Instant};
+
+use bridge::instance::InstanceID;
+use schema::instance::{LaunchPhase, LaunchPhaseTiming, LaunchTimings};
+
+use crate::BackendState;
+
+/// Records how long each phase of a launch takes, so that a slow launch can be attributed to the network, the disk or Forge
+pub struct LaunchTimer {
+    started: Instant,
+    phases: parking_lot::Mutex<Vec<(LaunchPhase, Duration, bool)>>,
+}
+
+impl Default for LaunchTimer {
+    fn default() -> Self {
+        Self {
+            started: Instant::now(),
+            phases: Default::default(),
+        }
+    }
+}
+
+/// Adds the time since it was created to its phase when dropped, including when the launch returns early
+pub struct PhaseTimer<'a> {
+    timer: &'a LaunchTimer,
+    phase: LaunchPhase,
+    concurrent: bool,
+    started: Instant,
+}
+
+impl Drop for PhaseTimer<'_> {
+    fn drop(&mut self) {
+        self.timer.record(self.phase, self.started.elapsed(), self.concurrent);
+    }
+}
+
+impl LaunchTimer {
+    pub fn start(&self, phase: LaunchPhase) -> PhaseTimer<'_> {
+        PhaseTimer {
+            timer: self,
+            phase,
+            concurrent: false,
+            started: Instant::now(),
+        }
+    }
+
+    pub async fn time<F: Future>(&self, phase: LaunchPhase, future: F) -> F::Output {
+        let _timer = self.start(phase);
+        future.await
+    }
+
+    /// Same as `time`, for a phase that is joined with other phases. It's marked as such in the timings
+    pub async fn time_concurrent<F: Future>(&self, phase: LaunchPhase, future: F) -> F::Output {
+        let _timer = PhaseTimer {
+            timer: self,
+            phase,
+            concurrent: true,
+            started: Instant::now(),
+        };
+        future.await
+    }
+
+    /// Phases that are entered more than once, e.g. a loader install that is interrupted by Forge processors, add up
+    pub fn record(&self, phase: LaunchPhase, duration: Duration, concurrent: bool) {
+        let mut phases = self.phases.lock();
+        if let Some((_, existing, existing_concurrent)) = phases.iter_mut().find(|(existing, _, _)| *existing == phase) {
+            *existing += duration;
+            *existing_concurrent |= concurrent;
+        } else {
+            phases.push((phase, duration, concurrent));
+        }
+    }
+
+    pub fn finish(&self) -> LaunchTimings {
+        let timings = LaunchTimings {
+            total_ms: self.started.elapsed().as_millis() as u64,
+            phases: self.phases.lock().iter().map(|(phase, duration, concurrent)| LaunchPhaseTiming {
+                phase: *phase,
+                duration_ms: duration.as_millis() as u64,
+                concurrent: *concurrent,
+            }).collect(),
+        };
+
+        let mut message = format!("Launch took {}ms", timings.total_ms);
+        for timing in &timings.phases {
+            message.push_str(&format!("\n  {}: {}ms{}", timing.phase.name(), timing.duration_ms,
+                if timing.concurrent { " (concurrent)" } else { "" }));
+        }
+        log::info!("{message}");
+
+        timings
+    }
+}
+
+impl BackendState {
+    /// Keeps the timings of a launch for the instance page, whether it started the game, failed or was cancelled
+    pub fn store_launch_timings(&self, id: InstanceID, timer: &LaunchTimer) {
+        let timings = timer.finish();
+        if let Some(instance) = self.instance_state.write().instances.get_mut(id) {
+            instance.stats.modify(|stats| stats.last_launch_timings = Some(timings));
+        }
+    }
+}
@@ -3,11 +3,11 @@ use std::{path::{Path, PathBuf}, sync::Arc};
 use auth::{credentials::AccountCredentials, models::{TokenWithExpiry, XstsToken}, secret::PlatformSecretStorage};
 use bridge::{import::ImportFromOtherLauncherJob, modal_action::{ModalAction, ProgressTracker}};
 use chrono::DateTime;
-use schema::{instance::{InstanceConfiguration, LwjglLibraryPath}, loader::Loader};
+use schema::{instance::{InstanceConfiguration, InstanceStats, LwjglLibraryPath}, loader::Loader};
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::{BackendState, account::BackendAccount, instance::InstanceStats};
+use crate::{BackendState, account::BackendAccount};
 
 
 #[derive(Deserialize)]
@@ -35,6 +35,8 @@ pub fn try_load_from_multimc(instance_cfg: &Path, mmc_pack: &Path) -> Option<(In
             minecraft_version = Some(component.version.into());
         } else if &*component.uid == "net.fabricmc.fabric-loader" {
             loader = Some(Loader::Fabric);
+        } else if &*component.uid == "org.quiltmc.quilt-loader" {
+            loader = Some(Loader::Quilt);
         } else if &*component.uid == "net.minecraftforge" {
             loader = Some(Loader::Forge);
         } else if &*component.uid == "net.neoforged" {
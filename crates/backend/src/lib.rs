@@ -15,22 +15,38 @@ mod backend_handler;
 
 mod account;
 mod arcfactory;
+mod bisect;
+mod content_update;
+mod crash_analyzer;
 mod directories;
+mod disk_usage;
+mod download;
+mod export;
 mod install_content;
 mod instance;
+mod instance_lock;
 mod java_manifest;
+mod java_runtimes;
 mod launch;
+mod launch_cache;
+mod launch_export;
+mod launch_timing;
 mod launch_wrapper;
 mod launcher_import;
 mod lockfile;
 mod log_reader;
 mod metadata;
+mod mirror;
 mod mod_metadata;
 mod id_slab;
+mod offline;
 mod persistent;
 mod shortcut;
+mod snapshot;
 mod syncing;
 mod update;
+mod verify;
+mod zip_writer;
 
 pub(crate) fn is_single_component_path_str(path: &str) -> bool {
     is_single_component_path(std::path::Path::new(path))
@@ -56,6 +72,40 @@ pub(crate) fn check_sha1_hash(path: &Path, expected_hash: [u8; 20]) -> std::io::
     Ok(expected_hash == *actual_hash)
 }
 
+/// CurseForge identifies files by a 32-bit MurmurHash2 (seed 1) of their contents with whitespace bytes removed
+pub(crate) fn curseforge_fingerprint(bytes: &[u8]) -> u32 {
+    const M: u32 = 0x5bd1e995;
+
+    let filtered: Vec<u8> = bytes.iter().copied().filter(|byte| !matches!(byte, 9 | 10 | 13 | 32)).collect();
+
+    let mut hash = 1 ^ (filtered.len() as u32);
+
+    let mut chunks = filtered.chunks_exact(4);
+    for chunk in &mut chunks {
+        let mut k = u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]);
+        k = k.wrapping_mul(M);
+        k ^= k >> 24;
+        k = k.wrapping_mul(M);
+
+        hash = hash.wrapping_mul(M);
+        hash ^= k;
+    }
+
+    let remainder = chunks.remainder();
+    if !remainder.is_empty() {
+        for (index, byte) in remainder.iter().enumerate() {
+            hash ^= (*byte as u32) << (index * 8);
+        }
+        hash = hash.wrapping_mul(M);
+    }
+
+    hash ^= hash >> 13;
+    hash = hash.wrapping_mul(M);
+    hash ^= hash >> 15;
+
+    hash
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum IoOrSerializationError {
     #[error("I/O error: {0}")]
@@ -479,3 +529,19 @@ pub fn join_windows_shell_os(args: &[&OsStr]) -> OsString {
         OsString::from_encoded_bytes_unchecked(string)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn test_curseforge_fingerprint() {
+        assert_eq!(super::curseforge_fingerprint(b""), 1540447798);
+        assert_eq!(super::curseforge_fingerprint(b"a"), 626045324);
+        assert_eq!(super::curseforge_fingerprint(b"abcd"), 3376380438);
+        assert_eq!(super::curseforge_fingerprint(b"hello world"), 2824650221);
+        assert_eq!(super::curseforge_fingerprint(b"\tfabric.mod.json\r\n{ \"id\": \"x\" }\n"), 2167944737);
+
+        // Tabs, newlines, carriage returns and spaces are ignored
+        assert_eq!(super::curseforge_fingerprint(b"a b\tc\r\nd"), super::curseforge_fingerprint(b"abcd"));
+        assert_eq!(super::curseforge_fingerprint(b" \t\r\n"), super::curseforge_fingerprint(b""));
+    }
+}
@@ -6,9 +6,9 @@ use reqwest::RequestBuilder;
 use schema::{
     assets_index::AssetsIndex,
     curseforge::{
-        CURSEFORGE_SEARCH_URL, CurseforgeGetFilesRequest, CurseforgeGetModFilesRequest,
-        CurseforgeGetModFilesResult, CurseforgeSearchRequest, CurseforgeSearchResult,
-        MINECRAFT_GAME_ID,
+        CURSEFORGE_SEARCH_URL, CurseforgeFingerprintsRequest, CurseforgeFingerprintsResult,
+        CurseforgeGetFilesRequest, CurseforgeGetModFilesRequest, CurseforgeGetModFilesResult,
+        CurseforgeSearchRequest, CurseforgeSearchResult, MINECRAFT_GAME_ID,
     },
     fabric_launch::FabricLaunch,
     fabric_loader_manifest::{FABRIC_LOADER_MANIFEST_URL, FabricLoaderManifest},
@@ -17,10 +17,13 @@ use schema::{
     java_runtimes::{JAVA_RUNTIMES_URL, JavaRuntimes},
     maven::MavenMetadataXml,
     modrinth::{
-        MODRINTH_SEARCH_URL, ModrinthLoader, ModrinthProjectVersion,
-        ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest,
-        ModrinthSearchResult, ModrinthVersionFileUpdateResult,
+        MODRINTH_PROJECT_URL, MODRINTH_SEARCH_URL, MODRINTH_VERSION_FILES_URL, ModrinthLoader, ModrinthProjectRequest,
+        ModrinthProjectResult, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest,
+        ModrinthSearchResult, ModrinthVersionFileUpdateResult, ModrinthVersionFilesRequest,
+        ModrinthVersionFilesResult,
     },
+    quilt_launch::QuiltLaunch,
+    quilt_loader_manifest::{QUILT_LOADER_MANIFEST_URL, QuiltLoaderManifest},
     version::MinecraftVersion,
     version_manifest::{MOJANG_VERSION_MANIFEST_URL, MinecraftVersionLink, MinecraftVersionManifest},
 };
@@ -268,6 +271,67 @@ impl MetadataItem for FabricLaunchMetadataItem {
     }
 }
 
+#[derive(Debug)]
+pub struct QuiltLoaderManifestMetadataItem;
+
+impl MetadataItem for QuiltLoaderManifestMetadataItem {
+    type T = QuiltLoaderManifest;
+
+    fn request(&self, client: &reqwest::Client) -> RequestBuilder {
+        client.get(QUILT_LOADER_MANIFEST_URL)
+    }
+
+    fn expires(&self) -> bool {
+        true
+    }
+
+    fn cache_file(&self, metadata_manager: &MetadataManager) -> Option<impl AsRef<Path> + Send + Sync + 'static> {
+        Some(Arc::clone(&metadata_manager.quilt_loader_manifest_cache))
+    }
+
+    fn state(&self, states: &mut MetadataManagerStates) -> MetaLoadStateWrapper<Self::T> {
+        states.quilt_loader_manifest.clone()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+#[derive(Debug)]
+pub struct QuiltLaunchMetadataItem {
+    pub minecraft_version: Ustr,
+    pub loader_version: Ustr,
+}
+
+impl MetadataItem for QuiltLaunchMetadataItem {
+    type T = QuiltLaunch;
+
+    fn request(&self, client: &reqwest::Client) -> RequestBuilder {
+        client.get(format!("{}/{}/{}", QUILT_LOADER_MANIFEST_URL, self.minecraft_version, self.loader_version))
+    }
+
+    fn expires(&self) -> bool {
+        false
+    }
+
+    fn cache_file(&self, metadata_manager: &MetadataManager) -> Option<impl AsRef<Path> + Send + Sync + 'static> {
+        let mut path = metadata_manager.metadata_cache.join("quilt_launch");
+        path.push(self.minecraft_version.as_str());
+        path.push(self.loader_version.as_str());
+        Some(path)
+    }
+
+    fn state(&self, states: &mut MetadataManagerStates) -> MetaLoadStateWrapper<Self::T> {
+        let key = (self.minecraft_version, self.loader_version);
+        states.quilt_launch.entry(key).or_default().clone()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
 #[derive(Debug)]
 pub struct ModrinthSearchMetadataItem<'a>(pub &'a ModrinthSearchRequest);
 
@@ -291,6 +355,29 @@ impl<'a> MetadataItem for ModrinthSearchMetadataItem<'a> {
     }
 }
 
+#[derive(Debug)]
+pub struct ModrinthProjectMetadataItem<'a>(pub &'a ModrinthProjectRequest);
+
+impl<'a> MetadataItem for ModrinthProjectMetadataItem<'a> {
+    type T = ModrinthProjectResult;
+
+    fn request(&self, client: &reqwest::Client) -> RequestBuilder {
+        client.get(format!("{}/{}", MODRINTH_PROJECT_URL, self.0.project_id))
+    }
+
+    fn expires(&self) -> bool {
+        true
+    }
+
+    fn state(&self, states: &mut MetadataManagerStates) -> MetaLoadStateWrapper<Self::T> {
+        states.modrinth_projects.entry(self.0.clone()).or_default().clone()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
 #[derive(Debug)]
 pub struct ModrinthProjectVersionsMetadataItem<'a>(pub &'a ModrinthProjectVersionsRequest);
 
@@ -346,6 +433,29 @@ impl MetadataItem for ModrinthVersionMetadataItem {
     }
 }
 
+#[derive(Debug)]
+pub struct ModrinthVersionFilesMetadataItem<'a>(pub &'a ModrinthVersionFilesRequest);
+
+impl<'a> MetadataItem for ModrinthVersionFilesMetadataItem<'a> {
+    type T = ModrinthVersionFilesResult;
+
+    fn request(&self, client: &reqwest::Client) -> RequestBuilder {
+        client.post(MODRINTH_VERSION_FILES_URL).json(self.0)
+    }
+
+    fn expires(&self) -> bool {
+        true
+    }
+
+    fn state(&self, states: &mut MetadataManagerStates) -> MetaLoadStateWrapper<Self::T> {
+        states.modrinth_version_files.entry(self.0.clone()).or_default().clone()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
 #[derive(Clone, Debug, Serialize, Hash, PartialEq, Eq)]
 pub struct VersionUpdateParameters {
     pub loaders: Arc<[ModrinthLoader]>,
@@ -585,3 +695,28 @@ impl<'a> MetadataItem for CurseforgeGetFilesMetadataItem<'a> {
         Ok(serde_json::from_slice(bytes)?)
     }
 }
+
+#[derive(Debug)]
+pub struct CurseforgeFingerprintsMetadataItem<'a>(pub &'a CurseforgeFingerprintsRequest);
+
+impl<'a> MetadataItem for CurseforgeFingerprintsMetadataItem<'a> {
+    type T = CurseforgeFingerprintsResult;
+
+    fn request(&self, client: &reqwest::Client) -> RequestBuilder {
+        client.post(format!("https://api.curseforge.com/v1/fingerprints/{}", MINECRAFT_GAME_ID))
+            .json(self.0)
+            .header("x-api-key", "$2a$10$YXf6dyJfJZM4zeChdr.RDOvWN.L48AN0dQShQO8/cVc5ho1wA8ZbS")
+    }
+
+    fn expires(&self) -> bool {
+        true
+    }
+
+    fn state(&self, states: &mut MetadataManagerStates) -> MetaLoadStateWrapper<Self::T> {
+        states.curseforge_fingerprints.entry(self.0.clone()).or_default().clone()
+    }
+
+    fn deserialize(bytes: &[u8]) -> Result<Self::T, MetaLoadError> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
@@ -1,5 +1,5 @@
 use std::{
-    collections::{HashMap, VecDeque}, fmt::Display, path::Path, sync::Arc, time::{Duration, Instant}
+    collections::{HashMap, VecDeque}, fmt::Display, path::Path, sync::{Arc, atomic::{AtomicBool, Ordering}}, time::{Duration, Instant}
 };
 
 use bridge::keep_alive::{KeepAlive, KeepAliveHandle};
@@ -7,8 +7,9 @@ use reqwest::StatusCode;
 use schema::{
     assets_index::AssetsIndex,
     curseforge::{
-        CurseforgeGetFilesRequest, CurseforgeGetModFilesRequest, CurseforgeGetModFilesResult,
-        CurseforgeSearchRequest, CurseforgeSearchResult,
+        CurseforgeFingerprintsRequest, CurseforgeFingerprintsResult, CurseforgeGetFilesRequest,
+        CurseforgeGetModFilesRequest, CurseforgeGetModFilesResult, CurseforgeSearchRequest,
+        CurseforgeSearchResult,
     },
     fabric_launch::FabricLaunch,
     fabric_loader_manifest::FabricLoaderManifest,
@@ -16,9 +17,12 @@ use schema::{
     java_runtime_component::JavaRuntimeComponentManifest,
     java_runtimes::JavaRuntimes,
     modrinth::{
-        ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult,
+        ModrinthProjectRequest, ModrinthProjectResult, ModrinthProjectVersion, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult,
         ModrinthSearchRequest, ModrinthSearchResult, ModrinthVersionFileUpdateResult,
+        ModrinthVersionFilesRequest, ModrinthVersionFilesResult,
     },
+    quilt_launch::QuiltLaunch,
+    quilt_loader_manifest::QuiltLoaderManifest,
     version::MinecraftVersion,
     version_manifest::MinecraftVersionManifest,
 };
@@ -27,7 +31,7 @@ use sha1::{Digest, Sha1};
 use tokio::task::JoinHandle;
 use ustr::Ustr;
 
-use crate::metadata::items::{MetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem};
+use crate::{metadata::items::{MetadataItem, ModrinthV3VersionUpdateMetadataItem, ModrinthVersionUpdateMetadataItem}, mirror::MirrorTable};
 
 const DATA_TTL: Duration = Duration::from_secs(5 * 60);
 
@@ -38,20 +42,25 @@ pub struct MetadataManagerStates {
     pub(super) minecraft_version_manifest: MetaLoadStateWrapper<MinecraftVersionManifest>,
     pub(super) mojang_java_runtimes: MetaLoadStateWrapper<JavaRuntimes>,
     pub(super) fabric_loader_manifest: MetaLoadStateWrapper<FabricLoaderManifest>,
+    pub(super) quilt_loader_manifest: MetaLoadStateWrapper<QuiltLoaderManifest>,
     pub(super) neoforge_installer_maven_manifest: MetaLoadStateWrapper<NeoforgeMavenManifest>,
     pub(super) forge_installer_maven_manifest: MetaLoadStateWrapper<ForgeMavenManifest>,
     pub(super) fabric_launch: HashMap<(Ustr, Ustr), MetaLoadStateWrapper<FabricLaunch>>,
+    pub(super) quilt_launch: HashMap<(Ustr, Ustr), MetaLoadStateWrapper<QuiltLaunch>>,
     pub(super) version_info: HashMap<Ustr, MetaLoadStateWrapper<MinecraftVersion>>,
     pub(super) assets_index: HashMap<Ustr, MetaLoadStateWrapper<AssetsIndex>>,
     pub(super) java_runtime_manifests: HashMap<Ustr, MetaLoadStateWrapper<JavaRuntimeComponentManifest>>,
     pub(super) modrinth_search: HashMap<ModrinthSearchRequest, MetaLoadStateWrapper<ModrinthSearchResult>>,
+    pub(super) modrinth_projects: HashMap<ModrinthProjectRequest, MetaLoadStateWrapper<ModrinthProjectResult>>,
     pub(super) modrinth_project_versions: HashMap<ModrinthProjectVersionsRequest, MetaLoadStateWrapper<ModrinthProjectVersionsResult>>,
     pub(super) modrinth_versions: HashMap<Arc<str>, MetaLoadStateWrapper<ModrinthProjectVersion>>,
+    pub(super) modrinth_version_files: HashMap<ModrinthVersionFilesRequest, MetaLoadStateWrapper<ModrinthVersionFilesResult>>,
     pub(super) modrinth_version_v2_updates: HashMap<ModrinthVersionUpdateMetadataItem, MetaLoadStateWrapper<ModrinthVersionFileUpdateResult>>,
     pub(super) modrinth_version_v3_updates: HashMap<ModrinthV3VersionUpdateMetadataItem, MetaLoadStateWrapper<ModrinthVersionFileUpdateResult>>,
     pub(super) curseforge_search: HashMap<CurseforgeSearchRequest, MetaLoadStateWrapper<CurseforgeSearchResult>>,
     pub(super) curseforge_get_mod_files: HashMap<CurseforgeGetModFilesRequest, MetaLoadStateWrapper<CurseforgeGetModFilesResult>>,
     pub(super) curseforge_get_files: HashMap<CurseforgeGetFilesRequest, MetaLoadStateWrapper<CurseforgeGetModFilesResult>>,
+    pub(super) curseforge_fingerprints: HashMap<CurseforgeFingerprintsRequest, MetaLoadStateWrapper<CurseforgeFingerprintsResult>>,
 }
 
 pub struct MetadataManager {
@@ -61,12 +70,15 @@ pub struct MetadataManager {
     pub(super) version_manifest_cache: Arc<Path>,
     pub(super) mojang_java_runtimes_cache: Arc<Path>,
     pub(super) fabric_loader_manifest_cache: Arc<Path>,
+    pub(super) quilt_loader_manifest_cache: Arc<Path>,
     pub(super) neoforge_installer_maven_cache: Arc<Path>,
     pub(super) forge_installer_maven_cache: Arc<Path>,
 
     expiring: tokio::sync::Mutex<VecDeque<(Instant, KeepAlive)>>,
 
     http_client: reqwest::Client,
+    mirrors: Arc<MirrorTable>,
+    offline: AtomicBool,
 }
 
 #[derive(thiserror::Error, Clone, Debug)]
@@ -79,6 +91,7 @@ pub enum MetaLoadError {
     Error(Arc<str>),
     ErrorWithDescription(Arc<str>, Arc<str>),
     NonOK(u16),
+    Offline,
 }
 
 impl Display for MetaLoadError {
@@ -126,6 +139,9 @@ impl Display for MetaLoadError {
                 f.write_fmt(format_args!("Non-OK response: {}", *status_code))
             }
             Self::TokioJoin(error) => f.debug_tuple("TokioJoin").field(error).finish(),
+            Self::Offline => {
+                f.write_str("Not available offline, nothing has been cached yet")
+            }
         }
     }
 }
@@ -164,13 +180,14 @@ pub enum MetaLoadState<T> {
 }
 
 impl MetadataManager {
-    pub fn new(http_client: reqwest::Client, directory: Arc<Path>) -> Self {
+    pub fn new(http_client: reqwest::Client, mirrors: Arc<MirrorTable>, directory: Arc<Path>) -> Self {
         Self {
             states: tokio::sync::Mutex::new(MetadataManagerStates::default()),
 
             version_manifest_cache: directory.join("version_manifest.json").into(),
             mojang_java_runtimes_cache: directory.join("mojang_java_runtimes.json").into(),
             fabric_loader_manifest_cache: directory.join("fabric_loader_manifest.json").into(),
+            quilt_loader_manifest_cache: directory.join("quilt_loader_manifest.json").into(),
             neoforge_installer_maven_cache: directory.join("neoforge_installer_maven.xml").into(),
             forge_installer_maven_cache: directory.join("forge_installer_maven.xml").into(),
             metadata_cache: directory,
@@ -178,9 +195,20 @@ impl MetadataManager {
             expiring: Default::default(),
 
             http_client,
+            mirrors,
+            offline: AtomicBool::new(false),
         }
     }
 
+    pub fn is_offline(&self) -> bool {
+        self.offline.load(Ordering::Relaxed)
+    }
+
+    /// While offline, metadata is only loaded from cache files and never requested
+    pub fn set_offline(&self, offline: bool) {
+        self.offline.store(offline, Ordering::Relaxed);
+    }
+
     pub async fn expire(&self) {
         let now = Instant::now();
 
@@ -198,7 +226,7 @@ impl MetadataManager {
         let wrapper = item.state(&mut *self.states.lock().await);
         let mut wrapper = wrapper.lock().await;
 
-        let is_valid = wrapper.0.as_ref().map(|h| h.is_alive()).unwrap_or(true);
+        let is_valid = wrapper.0.as_ref().map(|h| h.is_alive()).unwrap_or(true) && !self.is_stale_offline_error(&wrapper.1);
         if !is_valid || matches!(wrapper.1, MetaLoadState::Unloaded) {
             if item.expires() {
                 let keep_alive = KeepAlive::new();
@@ -213,6 +241,8 @@ impl MetadataManager {
                 item,
                 cache_file,
                 &self.http_client,
+                &self.mirrors,
+                self.is_offline(),
             );
         }
     }
@@ -225,7 +255,7 @@ impl MetadataManager {
         let wrapper = item.state(&mut *self.states.lock().await);
         let mut wrapper = wrapper.lock().await;
 
-        let is_valid = wrapper.0.as_ref().map(|h| h.is_alive()).unwrap_or(true);
+        let is_valid = wrapper.0.as_ref().map(|h| h.is_alive()).unwrap_or(true) && !self.is_stale_offline_error(&wrapper.1);
         if force_reload || !is_valid || matches!(wrapper.1, MetaLoadState::Unloaded) {
             if item.expires() {
                 let keep_alive = KeepAlive::new();
@@ -240,6 +270,8 @@ impl MetadataManager {
                 item,
                 cache_file,
                 &self.http_client,
+                &self.mirrors,
+                self.is_offline(),
             );
         }
 
@@ -269,15 +301,24 @@ impl MetadataManager {
         }
     }
 
+    /// Items that failed because they weren't cached should be requested again once back online
+    fn is_stale_offline_error<T>(&self, state: &MetaLoadState<T>) -> bool {
+        matches!(state, MetaLoadState::Error(MetaLoadError::Offline)) && !self.is_offline()
+    }
+
     fn inner_start_loading<I: MetadataItem>(
         state: &mut MetaLoadState<I::T>,
         item: &I,
         cache_file: Option<impl AsRef<Path> + Send + Sync + 'static>,
         http_client: &reqwest::Client,
+        mirrors: &Arc<MirrorTable>,
+        offline: bool,
     ) {
         log::debug!("Loading metadata {:?}", item);
 
         let request = item.request(http_client);
+        let http_client = http_client.clone();
+        let mirrors = Arc::clone(mirrors);
         let expected_hash = item.data_hash().and_then(|sha1| {
             let mut expected_hash = [0u8; 20];
             hex::decode_to_slice(sha1.as_str(), &mut expected_hash).ok()?;
@@ -320,7 +361,7 @@ impl MetadataManager {
                     }
                 }).await.unwrap();
                 if let Some(meta) = meta {
-                    if expected_hash.is_some() {
+                    if expected_hash.is_some() || offline {
                         return Ok(Arc::new(meta));
                     } else {
                         file_fallback = Some(Arc::new(meta));
@@ -328,8 +369,13 @@ impl MetadataManager {
                 }
             }
 
+            if offline {
+                log::warn!("Unable to load metadata {:?} while offline, it isn't cached", std::any::type_name::<I::T>());
+                return Err(MetaLoadError::Offline);
+            }
+
             let mut result: Result<Arc<I::T>, MetaLoadError> = async move {
-                let response = request.send().await?;
+                let response = mirrors.send(&http_client, request).await?;
 
                 let status = response.status();
                 if status != StatusCode::OK {
@@ -0,0 +1,66 @@
+use std::sync::Arc;
+
+use reqwest::{RequestBuilder, Response, Url};
+use schema::backend_config::DownloadMirror;
+
+/// Rewrites URLs to point at a mirror, e.g. a regional mirror or a local caching proxy
+#[derive(Default)]
+pub struct MirrorTable {
+    mirrors: parking_lot::RwLock<Arc<[DownloadMirror]>>,
+}
+
+impl MirrorTable {
+    pub fn new(mirrors: &[DownloadMirror]) -> Self {
+        Self {
+            mirrors: parking_lot::RwLock::new(mirrors.into()),
+        }
+    }
+
+    pub fn set(&self, mirrors: &[DownloadMirror]) {
+        *self.mirrors.write() = mirrors.into();
+    }
+
+    /// Returns the URL on the mirror, or None if no mirror covers it. The longest matching origin wins
+    pub fn rewrite(&self, url: &str) -> Option<String> {
+        let mirrors = self.mirrors.read();
+
+        let mut best: Option<(&str, &str)> = None;
+        for mirror in mirrors.iter() {
+            let origin = mirror.origin.trim_end_matches('/');
+            let Some(rest) = url.strip_prefix(origin) else {
+                continue;
+            };
+            // Only match whole path segments so that https://example.com doesn't match https://example.community
+            if !rest.is_empty() && !rest.starts_with(['/', '?']) {
+                continue;
+            }
+            if best.is_none_or(|(best_origin, _)| origin.len() > best_origin.len()) {
+                best = Some((origin, mirror.mirror.trim_end_matches('/')));
+            }
+        }
+
+        let (origin, mirror) = best?;
+        Some(format!("{mirror}{}", &url[origin.len()..]))
+    }
+
+    /// Sends the request to the mirror for its URL, falling back to the origin if the mirror fails
+    pub async fn send(&self, http_client: &reqwest::Client, request: RequestBuilder) -> reqwest::Result<Response> {
+        let request = request.build()?;
+
+        let mirror_url = self.rewrite(request.url().as_str()).and_then(|url| Url::parse(&url).ok());
+        if let Some(mirror_url) = mirror_url && let Some(mut mirror_request) = request.try_clone() {
+            *mirror_request.url_mut() = mirror_url;
+            match http_client.execute(mirror_request).await {
+                Ok(response) if response.status().is_success() => return Ok(response),
+                Ok(response) => {
+                    log::warn!("Mirror {} returned {}, falling back to the origin", response.url(), response.status());
+                },
+                Err(error) => {
+                    log::warn!("Mirror request failed, falling back to the origin: {error}");
+                },
+            }
+        }
+
+        http_client.execute(request).await
+    }
+}
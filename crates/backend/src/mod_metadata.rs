@@ -4,7 +4,7 @@ use std::{
     sync::{atomic::{AtomicBool, Ordering}, Arc},
 };
 
-use bridge::{instance::{ContentUpdateStatus, ContentType, ContentSummary}, safe_path::SafePath};
+use bridge::{dependency::{ContentDependency, ContentDependencyInfo, ContentDependencyKind, ProvidedMod, VersionRequirement}, instance::{ContentUpdateStatus, ContentType, ContentSummary}, safe_path::SafePath};
 use image::{DynamicImage, GenericImageView, imageops::FilterType};
 use indexmap::IndexMap;
 use parking_lot::{RwLock, RwLockReadGuard};
@@ -19,6 +19,7 @@ use schema::{
     loader::Loader,
     modrinth::{ModrinthFile, ModrinthSideRequirement},
     mrpack::ModrinthIndexJson,
+    quilt_mod::{QuiltDependency, QuiltDependencyVersions, QuiltLoaderSection, QuiltModJson, QuiltProvides},
     resourcepack::PackMcmeta,
 };
 use serde::{Deserialize, Serialize};
@@ -72,6 +73,7 @@ pub struct ModMetadataManager {
     cached_curseforge_info: RwLock<FxHashMap<u32, CachedCurseforgeFileInfo>>,
     cached_curseforge_info_dirty: AtomicBool,
     parents_by_missing_curseforge_id: RwLock<FxHashMap<u32, Vec<[u8; 20]>>>,
+    unmatched_curseforge_fingerprints: RwLock<FxHashSet<[u8; 20]>>,
     pub updates: RwLock<FxHashMap<ContentUpdateKey, ContentUpdateAction>>,
 }
 
@@ -109,6 +111,7 @@ impl ModMetadataManager {
             cached_curseforge_info: RwLock::new(cached_curseforge_info),
             cached_curseforge_info_dirty: AtomicBool::new(false),
             parents_by_missing_curseforge_id: Default::default(),
+            unmatched_curseforge_fingerprints: Default::default(),
             updates: Default::default(),
         }
     }
@@ -132,6 +135,31 @@ impl ModMetadataManager {
         }
     }
 
+    /// Counts the content sources and cached CurseForge files whose hash `keep` rejects
+    pub fn count_orphaned_meta(&self, keep: impl Fn(&[u8; 20]) -> bool) -> usize {
+        let sources = self.content_sources.read().hashes().filter(|hash| !keep(hash)).count();
+        let curseforge = self.cached_curseforge_info.read().values().filter(|info| !keep(&info.hash)).count();
+        sources + curseforge
+    }
+
+    /// Removes the content sources and cached CurseForge files whose hash `keep` rejects, returning how many were removed
+    pub fn remove_orphaned_meta(&self, keep: impl Fn(&[u8; 20]) -> bool) -> usize {
+        let sources = self.content_sources.write().retain(&keep, &self.sources_dir);
+
+        let mut cache = self.cached_curseforge_info.write();
+        let cache_len = cache.len();
+        cache.retain(|_, info| keep(&info.hash));
+        let curseforge = cache_len - cache.len();
+        drop(cache);
+
+        if curseforge > 0 {
+            self.cached_curseforge_info_dirty.store(true, Ordering::Relaxed);
+            self.write_changes();
+        }
+
+        sources + curseforge
+    }
+
     pub fn set_cached_curseforge_info(&self, file_id: u32, info: CachedCurseforgeFileInfo) {
         let mut cache = self.cached_curseforge_info.write();
         let changed = cache.get(&file_id).map_or(true, |old| {
@@ -153,6 +181,21 @@ impl ModMetadataManager {
         }
     }
 
+    /// Whether the file with this hash was already looked up by fingerprint without CurseForge knowing it
+    pub fn is_unmatched_curseforge_fingerprint(&self, hash: &[u8; 20]) -> bool {
+        self.unmatched_curseforge_fingerprints.read().contains(hash)
+    }
+
+    pub fn set_unmatched_curseforge_fingerprints(&self, hashes: impl Iterator<Item = [u8; 20]>) {
+        self.unmatched_curseforge_fingerprints.write().extend(hashes);
+    }
+
+    pub fn get_cached_curseforge_file_id(&self, hash: &[u8; 20]) -> Option<u32> {
+        self.cached_curseforge_info.read().iter()
+            .find(|(_, info)| info.hash == *hash)
+            .map(|(&file_id, _)| file_id)
+    }
+
     pub fn write_changes(&self) {
         if !self.cached_curseforge_info_dirty.swap(false, Ordering::Relaxed) {
             return;
@@ -266,6 +309,8 @@ impl ModMetadataManager {
 
         if let Some(file) = archive.by_name("mcmod.info") {
             self.load_legacy_forge_mod(hash, &archive, file)
+        } else if let Some(file) = archive.by_name("quilt.mod.json") && let Some(summary) = self.load_quilt_mod(hash, &archive, file) {
+            Some(summary)
         } else if let Some(file) = archive.by_name("fabric.mod.json") {
             self.load_fabric_mod(hash, &archive, file)
         } else if let Some(file) = archive.by_name("META-INF/mods.toml") {
@@ -276,6 +321,8 @@ impl ModMetadataManager {
             self.load_jarjar(hash, &archive, file)
         } else if let Some(file) = archive.by_name("META-INF/MANIFEST.MF") {
             self.load_from_java_manifest(hash, &archive, file)
+        } else if archive.entries().any(|entry| entry.name.starts_with("shaders/")) {
+            self.load_shader_pack(hash, &archive)
         } else if let Some(file) = archive.by_name("pack.mcmeta") {
             self.load_from_pack_mcmeta(hash, &archive, file)
         } else if allow_children && let Some(file) = archive.by_name("modrinth.index.json") {
@@ -303,21 +350,12 @@ impl ModMetadataManager {
 
         drop(file);
 
-        let name = fabric_mod_json.name.unwrap_or_else(|| Arc::clone(&fabric_mod_json.id));
+        let dependency_info = fabric_dependency_info(&fabric_mod_json, archive);
 
-        let icon = match fabric_mod_json.icon {
-            Some(icon) => match icon {
-                Icon::Single(icon) => Some(icon),
-                Icon::Sizes(hash_map) => {
-                    const DESIRED_SIZE: usize = 64;
-                    hash_map.iter().min_by_key(|size| size.0.abs_diff(DESIRED_SIZE)).map(|e| Arc::clone(e.1))
-                },
-            },
-            None => None,
-        };
+        let name = fabric_mod_json.name.unwrap_or_else(|| Arc::clone(&fabric_mod_json.id));
 
         let mut png_icon: Option<Arc<[u8]>> = None;
-        if let Some(icon) = icon && let Some(icon_file) = archive.by_name(&icon) {
+        if let Some(icon) = fabric_mod_json.icon.and_then(select_icon_path) && let Some(icon_file) = archive.by_name(&icon) {
             png_icon = load_icon(icon_file);
         }
 
@@ -334,7 +372,41 @@ impl ModMetadataManager {
             authors,
             version_str: format!("v{}", fabric_mod_json.version).into(),
             png_icon,
-            extra: ContentType::Fabric
+            extra: ContentType::Fabric,
+            dependency_info: Some(Arc::new(dependency_info)),
+        }))
+    }
+
+    fn load_quilt_mod<R: rc_zip_sync::HasCursor>(self: &Arc<Self>, hash: [u8; 20], archive: &rc_zip_sync::ArchiveHandle<R>, file: EntryHandle<'_, R>) -> Option<Arc<ContentSummary>> {
+        let bytes = file.bytes().ok()?;
+
+        let quilt_mod_json: QuiltModJson = serde_json::from_slice(&bytes).inspect_err(|e| {
+            log::error!("Error parsing quilt.mod.json: {e}");
+        }).ok()?;
+
+        drop(file);
+
+        let loader = quilt_mod_json.quilt_loader;
+        let dependency_info = quilt_dependency_info(&loader, archive);
+        let name = loader.metadata.name.unwrap_or_else(|| Arc::clone(&loader.id));
+
+        let mut png_icon: Option<Arc<[u8]>> = None;
+        if let Some(icon) = loader.metadata.icon.and_then(select_icon_path) && let Some(icon_file) = archive.by_name(&icon) {
+            png_icon = load_icon(icon_file);
+        }
+
+        let contributors: Vec<Person> = loader.metadata.contributors.into_keys().map(Person::Name).collect();
+        let authors = create_authors_string(&contributors).unwrap_or_default().into();
+
+        Some(Arc::new(ContentSummary {
+            id: Some(loader.id),
+            hash,
+            name: Some(name),
+            authors,
+            version_str: format!("v{}", loader.version).into(),
+            png_icon,
+            extra: ContentType::Quilt,
+            dependency_info: Some(Arc::new(dependency_info)),
         }))
     }
 
@@ -374,6 +446,8 @@ impl ModMetadataManager {
             }
         }
 
+        let dependency_info = forge_dependency_info(&mods_toml, &version[1..], archive);
+
         Some(Arc::new(ContentSummary {
             id: Some(first.mod_id.clone()),
             hash,
@@ -382,6 +456,7 @@ impl ModMetadataManager {
             version_str: version.into(),
             png_icon,
             extra,
+            dependency_info: Some(Arc::new(dependency_info)),
         }))
     }
 
@@ -431,6 +506,7 @@ impl ModMetadataManager {
             version_str: version.into(),
             png_icon,
             extra: ContentType::LegacyForge,
+            dependency_info: None,
         }))
     }
 
@@ -532,7 +608,8 @@ impl ModMetadataManager {
                 summaries: summaries.into(),
                 overrides: overrides.into_iter().collect(),
                 dependencies: modrinth_index_json.dependencies,
-            }
+            },
+            dependency_info: None,
         }))
     }
 
@@ -617,7 +694,8 @@ impl ModMetadataManager {
                 summaries: summaries.into(),
                 overrides: overrides.into_iter().collect(),
                 minecraft: manifest_json.minecraft,
-            }
+            },
+            dependency_info: None,
         }))
     }
 
@@ -685,7 +763,8 @@ impl ModMetadataManager {
             authors: author.unwrap_or_default(),
             version_str: version.unwrap_or_default(),
             png_icon: None,
-            extra: ContentType::JavaModule
+            extra: ContentType::JavaModule,
+            dependency_info: None,
         }))
     }
 
@@ -703,6 +782,15 @@ impl ModMetadataManager {
             png_icon = load_icon(icon);
         }
 
+        // Datapacks share the pack.mcmeta format with resource packs, but only contain data/
+        let has_data = archive.entries().any(|entry| entry.name.starts_with("data/"));
+        let has_assets = archive.entries().any(|entry| entry.name.starts_with("assets/"));
+        let extra = if has_data && !has_assets {
+            ContentType::Datapack
+        } else {
+            ContentType::ResourcePack
+        };
+
         Some(Arc::new(ContentSummary {
             id: None,
             hash,
@@ -710,7 +798,26 @@ impl ModMetadataManager {
             authors: "".into(),
             version_str: pack_mcmeta.pack.description,
             png_icon,
-            extra: ContentType::ResourcePack
+            extra,
+            dependency_info: None,
+        }))
+    }
+
+    fn load_shader_pack<R: rc_zip_sync::HasCursor>(self: &Arc<Self>, hash: [u8; 20], archive: &rc_zip_sync::ArchiveHandle<R>) -> Option<Arc<ContentSummary>> {
+        let mut png_icon = None;
+        if let Some(icon) = archive.by_name("pack.png") {
+            png_icon = load_icon(icon);
+        }
+
+        Some(Arc::new(ContentSummary {
+            id: None,
+            hash,
+            name: None,
+            authors: "".into(),
+            version_str: "".into(),
+            png_icon,
+            extra: ContentType::ShaderPack,
+            dependency_info: None,
         }))
     }
 }
@@ -806,6 +913,261 @@ fn crop_to_content(image: DynamicImage) -> DynamicImage {
     }
 }
 
+const MAX_NESTED_JAR_DEPTH: usize = 3;
+
+fn fabric_dependency_info<R: rc_zip_sync::HasCursor>(fabric_mod_json: &FabricModJson, archive: &rc_zip_sync::ArchiveHandle<R>) -> ContentDependencyInfo {
+    let version = Arc::clone(&fabric_mod_json.version);
+
+    let mut provides: Vec<ProvidedMod> = fabric_mod_json.provides.iter().map(|id| ProvidedMod {
+        id: Arc::clone(id),
+        version: Some(Arc::clone(&version)),
+    }).collect();
+    for jar in &fabric_mod_json.jars {
+        load_nested_provides(archive, &jar.file, 0, &mut provides);
+    }
+
+    let mut dependencies = Vec::new();
+    let kinds = [
+        (&fabric_mod_json.depends, ContentDependencyKind::Required),
+        (&fabric_mod_json.recommends, ContentDependencyKind::Recommended),
+        (&fabric_mod_json.breaks, ContentDependencyKind::Breaks),
+    ];
+    for (map, kind) in kinds {
+        for (id, dependency) in map {
+            dependencies.push(ContentDependency {
+                id: Arc::clone(id),
+                kind,
+                versions: fabric_version_requirement(dependency.predicates()),
+            });
+        }
+    }
+
+    ContentDependencyInfo {
+        version: Some(version),
+        provides: provides.into(),
+        dependencies: dependencies.into(),
+    }
+}
+
+fn fabric_version_requirement(predicates: Vec<Arc<str>>) -> VersionRequirement {
+    if predicates.is_empty() || predicates.iter().any(|predicate| predicate.trim() == "*") {
+        VersionRequirement::Any
+    } else {
+        VersionRequirement::Predicates(predicates.into())
+    }
+}
+
+fn quilt_dependency_info<R: rc_zip_sync::HasCursor>(loader: &QuiltLoaderSection, archive: &rc_zip_sync::ArchiveHandle<R>) -> ContentDependencyInfo {
+    let mut provides: Vec<ProvidedMod> = loader.provides.iter().map(|provides| match provides {
+        QuiltProvides::Id(id) => ProvidedMod {
+            id: Arc::clone(id),
+            version: Some(Arc::clone(&loader.version)),
+        },
+        QuiltProvides::Object { id, version } => ProvidedMod {
+            id: Arc::clone(id),
+            version: Some(Arc::clone(version.as_ref().unwrap_or(&loader.version))),
+        },
+    }).collect();
+    for jar in &loader.jars {
+        load_nested_provides(archive, jar, 0, &mut provides);
+    }
+
+    let mut dependencies = Vec::new();
+    let kinds = [
+        (&loader.depends, ContentDependencyKind::Required),
+        (&loader.breaks, ContentDependencyKind::Breaks),
+    ];
+    for (list, kind) in kinds {
+        for dependency in list {
+            push_quilt_dependency(dependency, kind, &mut dependencies);
+        }
+    }
+
+    ContentDependencyInfo {
+        version: Some(Arc::clone(&loader.version)),
+        provides: provides.into(),
+        dependencies: dependencies.into(),
+    }
+}
+
+fn push_quilt_dependency(dependency: &QuiltDependency, kind: ContentDependencyKind, dependencies: &mut Vec<ContentDependency>) {
+    match dependency {
+        QuiltDependency::Id(id) => dependencies.push(ContentDependency {
+            id: Arc::clone(id),
+            kind,
+            versions: VersionRequirement::Any,
+        }),
+        QuiltDependency::Object { id, versions, optional } => {
+            let predicates = match versions {
+                Some(QuiltDependencyVersions::Single(predicate)) => vec![Arc::clone(predicate)],
+                Some(QuiltDependencyVersions::AnyOf(predicates)) => predicates.clone(),
+                Some(QuiltDependencyVersions::Other(_)) | None => Vec::new(),
+            };
+            let kind = if *optional && kind == ContentDependencyKind::Required {
+                ContentDependencyKind::Optional
+            } else {
+                kind
+            };
+            dependencies.push(ContentDependency {
+                id: Arc::clone(id),
+                kind,
+                versions: fabric_version_requirement(predicates),
+            });
+        },
+        QuiltDependency::AnyOf(alternatives) => {
+            if let [dependency] = &alternatives[..] {
+                push_quilt_dependency(dependency, kind, dependencies);
+                return;
+            }
+            // Alternatives can't be expressed as a ContentDependency, so a required choice is recorded
+            // as optional dependencies on each alternative and a choice of breaks is ignored
+            if kind == ContentDependencyKind::Breaks {
+                return;
+            }
+            for dependency in alternatives {
+                push_quilt_dependency(dependency, ContentDependencyKind::Optional, dependencies);
+            }
+        },
+    }
+}
+
+fn forge_dependency_info<R: rc_zip_sync::HasCursor>(mods_toml: &ModsToml, version: &str, archive: &rc_zip_sync::ArchiveHandle<R>) -> ContentDependencyInfo {
+    let version: Arc<str> = version.into();
+
+    // Additional mods declared in the same mods.toml are provided by this file
+    let mut provides: Vec<ProvidedMod> = mods_toml.mods.iter().skip(1).map(|other| ProvidedMod {
+        id: Arc::clone(&other.mod_id),
+        version: Some(other.version.clone()
+            .filter(|other_version| !other_version.contains("${"))
+            .unwrap_or_else(|| Arc::clone(&version))),
+    }).collect();
+    load_jarjar_provides(archive, 0, &mut provides);
+
+    let mut dependencies = Vec::new();
+    for declared in &mods_toml.mods {
+        let Some(list) = mods_toml.dependencies.get(&declared.mod_id) else {
+            continue;
+        };
+        for dependency in list {
+            if dependency.side.as_deref().is_some_and(|side| side.eq_ignore_ascii_case("SERVER")) {
+                continue;
+            }
+
+            let kind = match dependency.dependency_type.as_deref().map(str::to_ascii_lowercase).as_deref() {
+                Some("required") => ContentDependencyKind::Required,
+                Some("optional") => ContentDependencyKind::Optional,
+                Some("incompatible" | "discouraged") => ContentDependencyKind::Breaks,
+                _ if dependency.mandatory == Some(true) => ContentDependencyKind::Required,
+                _ => ContentDependencyKind::Optional,
+            };
+
+            let versions = match &dependency.version_range {
+                Some(range) if !range.trim().is_empty() && range.trim() != "*" => VersionRequirement::MavenRange(Arc::clone(range)),
+                _ => VersionRequirement::Any,
+            };
+
+            dependencies.push(ContentDependency {
+                id: Arc::clone(&dependency.mod_id),
+                kind,
+                versions,
+            });
+        }
+    }
+
+    ContentDependencyInfo {
+        version: Some(version),
+        provides: provides.into(),
+        dependencies: dependencies.into(),
+    }
+}
+
+/// Collects the ids of mods bundled inside a nested jar, so dependencies on them are satisfied
+fn load_nested_provides<R: rc_zip_sync::HasCursor>(archive: &rc_zip_sync::ArchiveHandle<R>, path: &str, depth: usize, provides: &mut Vec<ProvidedMod>) {
+    if depth >= MAX_NESTED_JAR_DEPTH {
+        return;
+    }
+    let Some(entry) = archive.by_name(path) else {
+        return;
+    };
+    let Ok(bytes) = entry.bytes() else {
+        return;
+    };
+    let bytes: &[u8] = &bytes;
+    let Ok(nested) = rc_zip_sync::ReadZip::read_zip(&bytes) else {
+        return;
+    };
+
+    if let Some(file) = nested.by_name("quilt.mod.json")
+        && let Ok(bytes) = file.bytes()
+        && let Ok(quilt_mod_json) = serde_json::from_slice::<QuiltModJson>(&bytes)
+    {
+        let loader = quilt_mod_json.quilt_loader;
+        provides.push(ProvidedMod {
+            id: Arc::clone(&loader.id),
+            version: Some(Arc::clone(&loader.version)),
+        });
+        for provided in &loader.provides {
+            let (QuiltProvides::Id(id) | QuiltProvides::Object { id, .. }) = provided;
+            provides.push(ProvidedMod {
+                id: Arc::clone(id),
+                version: Some(Arc::clone(&loader.version)),
+            });
+        }
+        for jar in &loader.jars {
+            load_nested_provides(&nested, jar, depth + 1, provides);
+        }
+    } else if let Some(file) = nested.by_name("fabric.mod.json")
+        && let Ok(bytes) = file.bytes()
+        && let Ok(fabric_mod_json) = serde_json::from_slice::<FabricModJson>(&bytes)
+    {
+        for id in std::iter::once(&fabric_mod_json.id).chain(fabric_mod_json.provides.iter()) {
+            provides.push(ProvidedMod {
+                id: Arc::clone(id),
+                version: Some(Arc::clone(&fabric_mod_json.version)),
+            });
+        }
+        for jar in &fabric_mod_json.jars {
+            load_nested_provides(&nested, &jar.file, depth + 1, provides);
+        }
+    } else if let Some(file) = nested.by_name("META-INF/mods.toml").or_else(|| nested.by_name("META-INF/neoforge.mods.toml"))
+        && let Ok(bytes) = file.bytes()
+        && let Ok(mods_toml) = toml::from_slice::<ModsToml>(&bytes)
+    {
+        for declared in &mods_toml.mods {
+            provides.push(ProvidedMod {
+                id: Arc::clone(&declared.mod_id),
+                version: declared.version.clone().filter(|version| !version.contains("${")),
+            });
+        }
+        load_jarjar_provides(&nested, depth + 1, provides);
+    }
+}
+
+fn load_jarjar_provides<R: rc_zip_sync::HasCursor>(archive: &rc_zip_sync::ArchiveHandle<R>, depth: usize, provides: &mut Vec<ProvidedMod>) {
+    let Some(file) = archive.by_name("META-INF/jarjar/metadata.json") else {
+        return;
+    };
+    let Ok(bytes) = file.bytes() else {
+        return;
+    };
+    let Ok(metadata) = serde_json::from_slice::<JarJarMetadata>(&bytes) else {
+        return;
+    };
+    for jar in &metadata.jars {
+        load_nested_provides(archive, &jar.path, depth, provides);
+    }
+}
+
+fn select_icon_path(icon: Icon) -> Option<Arc<str>> {
+    match icon {
+        Icon::Single(icon) => Some(icon),
+        Icon::Sizes(hash_map) => {
+            const DESIRED_SIZE: usize = 64;
+            hash_map.iter().min_by_key(|size| size.0.abs_diff(DESIRED_SIZE)).map(|e| Arc::clone(e.1))
+        },
+    }
+}
+
 fn create_authors_string(authors: &[Person]) -> Option<String> {
     if !authors.is_empty() {
         let mut authors_string = "By ".to_owned();
@@ -871,6 +1233,37 @@ impl ContentSources {
         }
     }
 
+    pub fn hashes(&self) -> impl Iterator<Item = [u8; 20]> + '_ {
+        self.by_first_byte.iter().enumerate().flat_map(|(first_byte, values)| {
+            values.iter().map(move |(key, _)| {
+                let mut hash = [0_u8; 20];
+                hash[0] = first_byte as u8;
+                hash[1..].copy_from_slice(key);
+                hash
+            })
+        })
+    }
+
+    /// Removes every entry whose hash `keep` rejects, rewriting the files that changed
+    pub fn retain(&mut self, keep: impl Fn(&[u8; 20]) -> bool, dir: &Path) -> usize {
+        let mut removed = 0;
+        for first_byte in 0..=u8::MAX {
+            let values = &mut self.by_first_byte[first_byte as usize];
+            let len = values.len();
+            values.retain(|(key, _)| {
+                let mut hash = [0_u8; 20];
+                hash[0] = first_byte;
+                hash[1..].copy_from_slice(key);
+                keep(&hash)
+            });
+            if values.len() != len {
+                removed += len - values.len();
+                self.write_to_file(first_byte, dir);
+            }
+        }
+        removed
+    }
+
     pub fn write_all_to_file(&self, dir: &Path) {
         _ = std::fs::create_dir_all(dir);
 
@@ -1080,3 +1473,40 @@ pub enum LegacyContentSource {
     Manual,
     Modrinth,
 }
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use bridge::instance::ContentType;
+
+    use super::ModMetadataManager;
+    use crate::zip_writer::ZipWriter;
+
+    #[test]
+    fn test_exported_modrinth_modpack_round_trip() {
+        let index = br#"{"formatVersion":1,"game":"minecraft","versionId":"1.0.0","name":"Test Pack","files":[],"dependencies":{"minecraft":"1.21.1"}}"#;
+        let options = b"fov:0.5\nrenderDistance:12\n";
+        let config = vec![b'x'; 200_000];
+
+        let mut zip = ZipWriter::new(Vec::new());
+        zip.add_file("modrinth.index.json", &index[..], index.len() as u64).unwrap();
+        zip.add_file("overrides/options.txt", &options[..], options.len() as u64).unwrap();
+        zip.add_file("overrides/config/test.json", config.as_slice(), config.len() as u64).unwrap();
+        let bytes = zip.finish().unwrap();
+
+        let dir = std::env::temp_dir().join(format!("pandora-modpack-round-trip-{}", std::process::id()));
+        let manager = Arc::new(ModMetadataManager::load(dir.join("meta").into(), dir.join("library").into()));
+        let summary = manager.get_bytes(&bytes).unwrap();
+        _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(summary.name.as_deref(), Some("Test Pack"));
+        let ContentType::ModrinthModpack { overrides, dependencies, .. } = &summary.extra else {
+            panic!("expected a Modrinth modpack");
+        };
+        assert_eq!(dependencies.get("minecraft").map(|version| &**version), Some("1.21.1"));
+        assert_eq!(overrides.len(), 2);
+        assert!(overrides.iter().any(|(path, data)| path.as_str() == "options.txt" && &**data == options));
+        assert!(overrides.iter().any(|(path, data)| path.as_str() == "config/test.json" && **data == *config));
+    }
+}
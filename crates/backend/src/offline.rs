@@ -0,0 +1,96 @@
+use std::time::{Duration, Instant};
+
+use auth::{models::MinecraftAccessToken, secret::PlatformSecretStorage};
+use uuid::Uuid;
+
+use crate::{BackendState, account::MinecraftLoginInfo};
+
+const CONNECTIVITY_PROBE_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+const CONNECTIVITY_PROBE_TIMEOUT: Duration = Duration::from_secs(5);
+const CONNECTIVITY_PROBE_MAX_AGE: Duration = Duration::from_secs(5 * 60);
+/// While offline the network is probed again at most this often, every probe can take up to the timeout
+const OFFLINE_PROBE_INTERVAL: Duration = Duration::from_secs(60);
+
+impl BackendState {
+    pub fn is_offline(&self) -> bool {
+        self.meta.is_offline()
+    }
+
+    /// Turns offline mode on if it's forced in the config or the network can't be reached, and off otherwise.
+    /// Returns whether the launcher is now offline
+    pub async fn update_offline_mode(&self) -> bool {
+        let forced = self.config.write().get().offline_mode;
+        let offline = forced || !self.probe_connectivity().await;
+
+        let was_offline = self.meta.is_offline();
+        self.meta.set_offline(offline);
+
+        if offline != was_offline {
+            log::info!("Offline mode is now {}", if offline { "on" } else { "off" });
+            if offline && !forced {
+                self.send.send_warning("Unable to connect to the internet, using offline mode");
+            }
+        }
+
+        offline
+    }
+
+    /// Same as `update_offline_mode`, but skips probing if the last probe is recent enough. A failed probe
+    /// is only retried after a minute, so launching while offline doesn't wait for the timeout every time
+    pub async fn update_offline_mode_if_stale(&self) -> bool {
+        let forced = self.config.write().get().offline_mode;
+        let offline = self.is_offline();
+        if !forced && let Some((probed, reachable)) = *self.last_connectivity_probe.read() {
+            let max_age = if reachable { CONNECTIVITY_PROBE_MAX_AGE } else { OFFLINE_PROBE_INTERVAL };
+            if reachable != offline && probed.elapsed() < max_age {
+                return offline;
+            }
+        }
+        self.update_offline_mode().await
+    }
+
+    async fn probe_connectivity(&self) -> bool {
+        // Behind a firewall the mirror may be the only thing that can be reached
+        let url = self.mirrors.rewrite(CONNECTIVITY_PROBE_URL).unwrap_or_else(|| CONNECTIVITY_PROBE_URL.to_string());
+
+        // Any response at all means the network is reachable, even if it isn't a successful one
+        let reachable = match self.http_client.head(url).timeout(CONNECTIVITY_PROBE_TIMEOUT).send().await {
+            Ok(_) => true,
+            Err(error) => {
+                log::warn!("Connectivity probe failed: {error}");
+                false
+            },
+        };
+        *self.last_connectivity_probe.write() = Some((Instant::now(), reachable));
+        reachable
+    }
+
+    /// Uses the account's cached profile instead of logging in. The cached access token is passed along
+    /// if there is one, singleplayer doesn't need it to be valid
+    pub async fn offline_login_info(&self, uuid: Uuid) -> Option<MinecraftLoginInfo> {
+        let username = {
+            let mut account_info = self.account_info.write();
+            account_info.get().accounts.get(&uuid)?.username.clone()
+        };
+
+        let access_token = match self.secret_storage.get_or_init(PlatformSecretStorage::new).await {
+            Ok(secret_storage) => match secret_storage.read_credentials(uuid).await {
+                Ok(credentials) => credentials.and_then(|credentials| credentials.access_token),
+                Err(error) => {
+                    log::warn!("Unable to read credentials from keychain: {error}");
+                    None
+                },
+            },
+            Err(error) => {
+                log::warn!("Error initializing secret storage: {error}");
+                None
+            },
+        };
+
+        Some(MinecraftLoginInfo {
+            uuid,
+            username,
+            access_token: access_token.map(|token| MinecraftAccessToken(token.token)),
+        })
+    }
+}
@@ -0,0 +1,292 @@
+use std::{
+    io::Write, path::{Path, PathBuf}, sync::Arc, time::SystemTime
+};
+
+use bridge::instance::{InstanceID, InstanceSnapshotSummary, InstanceStatus};
+use schema::instance::{InstanceConfiguration, InstanceSnapshotInfo, InstanceSnapshotReason};
+use sha1::{Digest, Sha1};
+use strum::IntoEnumIterator;
+
+use crate::{BackendState, IoOrSerializationError, instance::ContentFolder};
+
+const SNAPSHOTS_FOLDER: &str = "snapshots";
+const SNAPSHOT_INFO_FILENAME: &str = "snapshot.json";
+const MAX_SNAPSHOTS: usize = 10;
+
+#[derive(thiserror::Error, Debug)]
+pub enum SnapshotError {
+    #[error("Unable to find instance")]
+    UnknownInstance,
+    #[error("Unable to find snapshot {0}")]
+    UnknownSnapshot(Arc<str>),
+    #[error("Unable to restore snapshot while the instance is running")]
+    InstanceRunning,
+    #[error("Unable to read snapshot:\n{0}")]
+    UnableToReadSnapshot(#[from] IoOrSerializationError),
+    #[error("Failed to serialize data:\n{0}")]
+    SerdeJsonError(#[from] serde_json::Error),
+    #[error("Failed to perform I/O operation:\n{0}")]
+    IoError(#[from] std::io::Error),
+}
+
+struct SnapshotSource {
+    root_path: Arc<Path>,
+    dot_minecraft_path: Arc<Path>,
+    content_paths: Vec<(ContentFolder, Arc<Path>)>,
+}
+
+impl BackendState {
+    /// Snapshots the instance's configuration, content file list and `config/` folder. Content files
+    /// are hard linked so a snapshot costs very little space. Identical consecutive snapshots are skipped
+    pub async fn snapshot_instance(&self, id: InstanceID, reason: InstanceSnapshotReason) {
+        let result = match self.snapshot_source(id) {
+            Ok(source) => tokio::task::spawn_blocking(move || create_snapshot(&source, reason)).await
+                .unwrap_or_else(|error| Err(std::io::Error::other(error).into())),
+            Err(error) => Err(error),
+        };
+        if let Err(error) = result {
+            log::error!("Unable to snapshot instance: {error}");
+        }
+    }
+
+    pub fn list_instance_snapshots(&self, id: InstanceID) -> Arc<[InstanceSnapshotSummary]> {
+        let Some(root_path) = self.instance_state.read().instances.get(id).map(|instance| instance.root_path.clone()) else {
+            return Arc::from([]);
+        };
+
+        read_snapshots(&root_path.join(SNAPSHOTS_FOLDER)).into_iter().filter_map(|(path, info)| {
+            let name = path.file_name()?.to_str()?.into();
+            let content_count = ContentFolder::iter()
+                .filter_map(|folder| std::fs::read_dir(folder.path().to_path(&path)).ok())
+                .flat_map(|read_dir| read_dir.flatten())
+                .filter(|entry| is_content_file(entry) && !entry.file_name().as_encoded_bytes().starts_with(b"."))
+                .count();
+            Some(InstanceSnapshotSummary {
+                name,
+                created_unix_ms: info.created_unix_ms,
+                reason: info.reason,
+                content_count,
+            })
+        }).collect()
+    }
+
+    /// Replaces the instance's configuration, content and `config/` folder with the snapshot's.
+    /// The current state is snapshotted first, so a restore can itself be undone
+    pub fn restore_instance_snapshot(&self, id: InstanceID, name: &str) -> Result<(), SnapshotError> {
+        let source = self.snapshot_source(id)?;
+
+        if self.instance_state.read().instances.get(id).is_some_and(|instance| instance.status() == InstanceStatus::Running) {
+            return Err(SnapshotError::InstanceRunning);
+        }
+
+        let snapshot_dir = source.root_path.join(SNAPSHOTS_FOLDER).join(name);
+        if name.contains(['/', '\\', '.']) || !snapshot_dir.join(SNAPSHOT_INFO_FILENAME).is_file() {
+            return Err(SnapshotError::UnknownSnapshot(name.into()));
+        }
+
+        let configuration: InstanceConfiguration = crate::read_json(&snapshot_dir.join("info_v1.json"))?;
+
+        create_snapshot(&source, InstanceSnapshotReason::Restore)?;
+
+        for (folder, content_path) in &source.content_paths {
+            if let Ok(read_dir) = std::fs::read_dir(content_path) {
+                for entry in read_dir.flatten() {
+                    if is_content_file(&entry) {
+                        std::fs::remove_file(entry.path())?;
+                    }
+                }
+            }
+
+            let Ok(read_dir) = std::fs::read_dir(folder.path().to_path(&snapshot_dir)) else {
+                continue;
+            };
+            std::fs::create_dir_all(content_path)?;
+            for entry in read_dir.flatten() {
+                if is_content_file(&entry) {
+                    link_or_copy(&entry.path(), &content_path.join(entry.file_name()))?;
+                }
+            }
+        }
+
+        let config_path = source.dot_minecraft_path.join("config");
+        if config_path.exists() {
+            std::fs::remove_dir_all(&config_path)?;
+        }
+        copy_dir_recursive(&snapshot_dir.join("config"), &config_path)?;
+
+        let mut instance_state = self.instance_state.write();
+        if let Some(instance) = instance_state.instances.get_mut(id) {
+            instance.configuration.modify(|current| {
+                *current = configuration;
+            });
+            for folder in ContentFolder::iter() {
+                instance.content_state[folder].mark_dirty(None);
+            }
+            self.send.send(instance.create_modify_message());
+        }
+        instance_state.reload_immediately.extend(ContentFolder::iter().map(|folder| (id, folder)));
+
+        Ok(())
+    }
+
+    fn snapshot_source(&self, id: InstanceID) -> Result<SnapshotSource, SnapshotError> {
+        let instance_state = self.instance_state.read();
+        let Some(instance) = instance_state.instances.get(id) else {
+            return Err(SnapshotError::UnknownInstance);
+        };
+        Ok(SnapshotSource {
+            root_path: instance.root_path.clone(),
+            dot_minecraft_path: instance.dot_minecraft_path.clone(),
+            content_paths: ContentFolder::iter().map(|folder| (folder, instance.content_state[folder].path.clone())).collect(),
+        })
+    }
+}
+
+fn create_snapshot(source: &SnapshotSource, reason: InstanceSnapshotReason) -> Result<(), SnapshotError> {
+    let snapshots_dir = source.root_path.join(SNAPSHOTS_FOLDER);
+
+    let fingerprint: Arc<str> = snapshot_fingerprint(source).into();
+    if let Some(latest) = read_snapshots(&snapshots_dir).first()
+        && latest.1.fingerprint == fingerprint
+    {
+        log::debug!("Skipping snapshot of {:?}, nothing has changed", source.root_path);
+        return Ok(());
+    }
+
+    let mut created_unix_ms = chrono::Utc::now().timestamp_millis();
+    let mut snapshot_dir = snapshots_dir.join(created_unix_ms.to_string());
+    while snapshot_dir.exists() {
+        created_unix_ms += 1;
+        snapshot_dir = snapshots_dir.join(created_unix_ms.to_string());
+    }
+
+    let result = write_snapshot(source, &snapshot_dir, InstanceSnapshotInfo {
+        created_unix_ms,
+        reason,
+        fingerprint,
+    });
+    if result.is_err() {
+        _ = std::fs::remove_dir_all(&snapshot_dir);
+        return result;
+    }
+
+    for (path, _) in read_snapshots(&snapshots_dir).iter().skip(MAX_SNAPSHOTS) {
+        _ = std::fs::remove_dir_all(path);
+    }
+
+    Ok(())
+}
+
+fn write_snapshot(source: &SnapshotSource, snapshot_dir: &Path, info: InstanceSnapshotInfo) -> Result<(), SnapshotError> {
+    std::fs::create_dir_all(snapshot_dir)?;
+
+    std::fs::copy(source.root_path.join("info_v1.json"), snapshot_dir.join("info_v1.json"))?;
+
+    for (folder, content_path) in &source.content_paths {
+        let Ok(read_dir) = std::fs::read_dir(content_path) else {
+            continue;
+        };
+        let target_dir = folder.path().to_path(snapshot_dir);
+        std::fs::create_dir_all(&target_dir)?;
+        for entry in read_dir.flatten() {
+            if is_content_file(&entry) {
+                link_or_copy(&entry.path(), &target_dir.join(entry.file_name()))?;
+            }
+        }
+    }
+
+    copy_dir_recursive(&source.dot_minecraft_path.join("config"), &snapshot_dir.join("config"))?;
+
+    // Written last, snapshots without info are incomplete and ignored
+    let mut file = std::fs::File::create(snapshot_dir.join(SNAPSHOT_INFO_FILENAME))?;
+    file.write_all(&serde_json::to_vec(&info)?)?;
+
+    Ok(())
+}
+
+/// Returns complete snapshots, newest first
+fn read_snapshots(snapshots_dir: &Path) -> Vec<(PathBuf, InstanceSnapshotInfo)> {
+    let Ok(read_dir) = std::fs::read_dir(snapshots_dir) else {
+        return Vec::new();
+    };
+
+    let mut snapshots: Vec<_> = read_dir.flatten().filter_map(|entry| {
+        let path = entry.path();
+        let info: InstanceSnapshotInfo = crate::read_json(&path.join(SNAPSHOT_INFO_FILENAME)).ok()?;
+        Some((path, info))
+    }).collect();
+    snapshots.sort_by_key(|(_, info)| std::cmp::Reverse(info.created_unix_ms));
+    snapshots
+}
+
+fn snapshot_fingerprint(source: &SnapshotSource) -> String {
+    let mut hasher = Sha1::new();
+
+    if let Ok(info) = std::fs::read(source.root_path.join("info_v1.json")) {
+        hasher.update(&info);
+    }
+
+    let mut hash_file = |relative: &Path, metadata: &std::fs::Metadata| {
+        hasher.update(relative.as_os_str().as_encoded_bytes());
+        hasher.update(metadata.len().to_le_bytes());
+        let modified = metadata.modified().ok()
+            .and_then(|modified| modified.duration_since(SystemTime::UNIX_EPOCH).ok())
+            .map(|duration| duration.as_millis())
+            .unwrap_or_default();
+        hasher.update(modified.to_le_bytes());
+    };
+
+    for (folder, content_path) in &source.content_paths {
+        let Ok(read_dir) = std::fs::read_dir(content_path) else {
+            continue;
+        };
+        let mut entries: Vec<_> = read_dir.flatten().filter(is_content_file).collect();
+        entries.sort_by_key(|entry| entry.file_name());
+        for entry in entries {
+            if let Ok(metadata) = entry.metadata() {
+                hash_file(&folder.path().join(&*entry.file_name().to_string_lossy()).to_path(""), &metadata);
+            }
+        }
+    }
+
+    let config_path = source.dot_minecraft_path.join("config");
+    for entry in walkdir::WalkDir::new(&config_path).sort_by_file_name().into_iter().flatten() {
+        if let Ok(metadata) = entry.metadata() && metadata.is_file() && let Ok(relative) = entry.path().strip_prefix(&config_path) {
+            hash_file(relative, &metadata);
+        }
+    }
+
+    hex::encode(hasher.finalize())
+}
+
+fn is_content_file(entry: &std::fs::DirEntry) -> bool {
+    // pandora.* files are temporary links created when applying modpacks
+    entry.file_type().is_ok_and(|file_type| file_type.is_file())
+        && !entry.file_name().as_encoded_bytes().starts_with(b"pandora.")
+}
+
+fn link_or_copy(from: &Path, to: &Path) -> std::io::Result<()> {
+    if std::fs::hard_link(from, to).is_err() {
+        std::fs::copy(from, to)?;
+    }
+    Ok(())
+}
+
+fn copy_dir_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    if !from.is_dir() {
+        return Ok(());
+    }
+    for entry in walkdir::WalkDir::new(from) {
+        let entry = entry?;
+        let Ok(relative) = entry.path().strip_prefix(from) else {
+            continue;
+        };
+        let target = to.join(relative);
+        if entry.file_type().is_dir() {
+            std::fs::create_dir_all(&target)?;
+        } else if entry.file_type().is_file() {
+            std::fs::copy(entry.path(), &target)?;
+        }
+    }
+    Ok(())
+}
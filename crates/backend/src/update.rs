@@ -4,10 +4,9 @@ use base64::Engine;
 use bridge::{handle::FrontendHandle, message::MessageToFrontend, modal_action::{ModalAction, ProgressTracker}};
 use reqwest::StatusCode;
 use schema::pandora_update::{UpdateInstallType, UpdateManifest, UpdatePrompt};
-use sha1::{Digest, Sha1};
 use rand::RngCore;
 
-use crate::directories::LauncherDirectories;
+use crate::{directories::LauncherDirectories, download::DownloadManager};
 
 fn replace_os_str(filename: &OsStr, from: &str, to: &str) -> OsString {
     let s = filename.to_string_lossy();
@@ -146,8 +145,8 @@ fn determine_macos_app_path(current_exe: &Path) -> Option<&Path> {
     Some(parent3)
 }
 
-pub async fn install_update(http_client: reqwest::Client, dirs: Arc<LauncherDirectories>, send: FrontendHandle, update: UpdatePrompt, modal_action: ModalAction) {
-    if let Err(error) = install_update_inner(http_client, &dirs, send.clone(), update, modal_action.clone()).await {
+pub async fn install_update(downloads: Arc<DownloadManager>, dirs: Arc<LauncherDirectories>, send: FrontendHandle, update: UpdatePrompt, modal_action: ModalAction) {
+    if let Err(error) = install_update_inner(&downloads, &dirs, send.clone(), update, modal_action.clone()).await {
         modal_action.set_error_message(error);
     }
 
@@ -155,7 +154,7 @@ pub async fn install_update(http_client: reqwest::Client, dirs: Arc<LauncherDire
     send.send(MessageToFrontend::Refresh);
 }
 
-async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirectories, send: FrontendHandle, update: UpdatePrompt, modal_action: ModalAction) -> Result<(), Arc<str>> {
+async fn install_update_inner(downloads: &DownloadManager, dirs: &LauncherDirectories, send: FrontendHandle, update: UpdatePrompt, modal_action: ModalAction) -> Result<(), Arc<str>> {
     let title = format!("Downloading Pandora {}", update.new_version);
     let tracker = ProgressTracker::new(title.into(), send.clone());
     modal_action.trackers.push(tracker.clone());
@@ -165,39 +164,11 @@ async fn install_update_inner(http_client: reqwest::Client, dirs: &LauncherDirec
         return Err("Unable to decode sha1 hash".into());
     };
 
-    let Ok(response) = http_client.get(&*update.exe.download).send().await else {
-        return Err("Error making download request".into());
-    };
-
-    if response.status() != StatusCode::OK {
-        return Err("Download URL returned non-200 status code".into());
-    }
-
     tracker.set_total(update.exe.size);
     tracker.notify();
 
-    use futures::StreamExt;
-    let mut stream = response.bytes_stream();
-
-    let mut bytes = Vec::new();
-
-    while let Some(item) = stream.next().await {
-        let Ok(item) = item else {
-            return Err("Error while downloading update".into());
-        };
-
-        bytes.extend_from_slice(&*item);
-        tracker.add_count(item.len());
-        tracker.notify();
-    }
-
-    let mut hasher = Sha1::new();
-    hasher.update(&bytes);
-    let actual_hash = hasher.finalize();
-
-    if expected_hash != *actual_hash {
-        return Err("Hash of downloaded file does not match".into());
-    }
+    let bytes = downloads.download_bytes(&update.exe.download, Some(update.exe.size), Some(expected_hash), Some(&tracker)).await
+        .map_err(|error| format!("Error while downloading update: {error}"))?;
 
     let Some(pubkey) = option_env!("PANDORA_UPDATE_PUBKEY") else {
         return Err("Unable to update, missing PANDORA_UPDATE_PUBKEY at compile time".into());
@@ -0,0 +1,320 @@
+use std::{path::{Path, PathBuf}, sync::Arc};
+
+use bridge::{
+    instance::{InstanceID, InstanceStatus}, modal_action::{ModalAction, ProgressTracker, ProgressTrackerFinishType}
+};
+use rustc_hash::FxHashSet;
+use schema::modrinth::{ModrinthHashAlgorithm, ModrinthVersionFilesRequest};
+use strum::IntoEnumIterator;
+use ustr::Ustr;
+
+use crate::{
+    BackendState, instance::ContentFolder, launch::LaunchError, launch_cache::LaunchCache, metadata::items::ModrinthVersionFilesMetadataItem
+};
+
+const MODRINTH_VERSION_FILES_CHUNK_SIZE: usize = 100;
+const MAX_LISTED_FILES: usize = 5;
+
+#[derive(thiserror::Error, Debug)]
+pub enum VerifyError {
+    #[error("Unable to find instance")]
+    UnknownInstance,
+    #[error("Unable to verify {0} while it is running")]
+    InstanceRunning(Ustr),
+    #[error("Unable to load instance content")]
+    UnableToLoadContent,
+    #[error("Failed to verify game files of {0}:\n{1}")]
+    LaunchError(Ustr, LaunchError),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RepairKind {
+    Missing,
+    Corrupt,
+}
+
+impl RepairKind {
+    /// Determines why a file that failed verification needs to be downloaded again
+    pub fn of(path: &Path) -> Self {
+        if path.exists() {
+            RepairKind::Corrupt
+        } else {
+            RepairKind::Missing
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct RepairedFile {
+    pub path: PathBuf,
+    pub kind: RepairKind,
+}
+
+/// Files that had to be downloaded again because they were missing or didn't match their hash
+#[derive(Debug, Default)]
+pub struct RepairLog {
+    repaired: parking_lot::Mutex<Vec<RepairedFile>>,
+}
+
+impl RepairLog {
+    pub fn push(&self, path: &Path, kind: RepairKind) {
+        log::info!("Downloaded {} file {:?}", if kind == RepairKind::Missing { "missing" } else { "corrupt" }, path);
+        self.repaired.lock().push(RepairedFile { path: path.to_path_buf(), kind });
+    }
+
+    pub fn take(&self) -> Vec<RepairedFile> {
+        std::mem::take(&mut *self.repaired.lock())
+    }
+}
+
+/// Content library files used by a single instance
+struct LibraryFilter {
+    hashes: FxHashSet<[u8; 20]>,
+    #[cfg(unix)]
+    inodes: FxHashSet<(u64, u64)>,
+}
+
+impl LibraryFilter {
+    fn contains(&self, hash: &[u8; 20], _metadata: &std::fs::Metadata) -> bool {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            if self.inodes.contains(&(_metadata.dev(), _metadata.ino())) {
+                return true;
+            }
+        }
+        self.hashes.contains(hash)
+    }
+}
+
+impl BackendState {
+    /// Checks every file needed to launch an instance against its sha1 hash, downloading the ones that are missing or corrupt
+    pub async fn verify_instance(&self, id: InstanceID, modal_action: &ModalAction) -> Result<(), VerifyError> {
+        let repairs = RepairLog::default();
+
+        self.verify_instance_game_files(id, &repairs, modal_action).await?;
+
+        let filter = self.instance_library_filter(id).await?;
+        let unrepaired = self.verify_content_library(Some(&filter), &repairs, modal_action).await;
+
+        self.send_verify_result(repairs.take(), unrepaired, Vec::new());
+        Ok(())
+    }
+
+    /// Verifies the game files of every instance that isn't running, followed by the entire content library
+    pub async fn verify_all_instances(&self, modal_action: &ModalAction) {
+        let repairs = RepairLog::default();
+
+        let instances: Vec<InstanceID> = self.instance_state.read().instances.iter().map(|instance| instance.id).collect();
+
+        let mut errors = Vec::new();
+        for id in instances {
+            if modal_action.has_requested_cancel() {
+                return;
+            }
+            if let Err(error) = self.verify_instance_game_files(id, &repairs, modal_action).await {
+                log::warn!("Unable to verify instance: {error}");
+                errors.push(error);
+            }
+        }
+
+        let unrepaired = self.verify_content_library(None, &repairs, modal_action).await;
+
+        self.send_verify_result(repairs.take(), unrepaired, errors);
+    }
+
+    async fn verify_instance_game_files(&self, id: InstanceID, repairs: &RepairLog, modal_action: &ModalAction) -> Result<(), VerifyError> {
+        let (name, root_path, dot_minecraft_path, configuration) = {
+            let mut instance_state = self.instance_state.write();
+            let Some(instance) = instance_state.instances.get_mut(id) else {
+                return Err(VerifyError::UnknownInstance);
+            };
+            if instance.status() != InstanceStatus::NotRunning {
+                return Err(VerifyError::InstanceRunning(instance.name));
+            }
+            (instance.name, instance.root_path.clone(), instance.dot_minecraft_path.clone(), instance.configuration.get().clone())
+        };
+
+        // Verifying is also how a stale launch cache gets cleared, e.g. if the version metadata changed upstream
+        LaunchCache::invalidate(&root_path);
+
+        let tracker = ProgressTracker::new(format!("Verifying {name}").into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let result = self.launcher.verify_game_files(&dot_minecraft_path, &configuration,
+            &tracker, &modal_action.trackers, repairs).await;
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(result.is_err()));
+        tracker.notify();
+
+        result.map_err(|error| VerifyError::LaunchError(name, error))
+    }
+
+    async fn instance_library_filter(&self, id: InstanceID) -> Result<LibraryFilter, VerifyError> {
+        let mut filter = LibraryFilter {
+            hashes: FxHashSet::default(),
+            #[cfg(unix)]
+            inodes: FxHashSet::default(),
+        };
+
+        for folder in ContentFolder::iter() {
+            let Some(summaries) = self.clone().load_instance_content(id, folder).await else {
+                return Err(VerifyError::UnableToLoadContent);
+            };
+            for summary in summaries.iter() {
+                crate::disk_usage::insert_content_hashes(summary, &mut filter.hashes);
+
+                // A corrupt file no longer has the hash it was installed with, but it's still the same file as the library entry
+                #[cfg(unix)]
+                if let Ok(metadata) = std::fs::metadata(&summary.path) {
+                    use std::os::unix::fs::MetadataExt;
+                    filter.inodes.insert((metadata.dev(), metadata.ino()));
+                }
+            }
+        }
+
+        Ok(filter)
+    }
+
+    /// Checks content library files against the hash in their filename, downloading corrupt ones from Modrinth where possible.
+    /// Returns the files that couldn't be repaired
+    async fn verify_content_library(&self, filter: Option<&LibraryFilter>, repairs: &RepairLog, modal_action: &ModalAction) -> Vec<PathBuf> {
+        let tracker = ProgressTracker::new("Verifying content library".into(), self.send.clone());
+        modal_action.trackers.push(tracker.clone());
+        tracker.notify();
+
+        let mut files = Vec::new();
+        for entry in walkdir::WalkDir::new(&self.directories.content_library_dir).into_iter().filter_map(Result::ok) {
+            let Ok(metadata) = entry.metadata() else {
+                continue;
+            };
+            if !metadata.is_file() {
+                continue;
+            }
+            let Some(hash) = crate::disk_usage::library_file_hash(entry.path()) else {
+                continue;
+            };
+            if filter.is_none_or(|filter| filter.contains(&hash, &metadata)) {
+                files.push((entry.into_path(), hash, metadata.len() as usize));
+            }
+        }
+
+        tracker.set_total(files.iter().map(|(_, _, size)| size).sum());
+        tracker.notify();
+
+        let corrupt = {
+            let tracker = tracker.clone();
+            tokio::task::spawn_blocking(move || {
+                let mut corrupt = Vec::new();
+                for (path, hash, size) in files {
+                    if !crate::check_sha1_hash(&path, hash).unwrap_or(false) {
+                        corrupt.push((path, hash));
+                    }
+                    tracker.add_count(size);
+                    tracker.notify();
+                }
+                corrupt
+            }).await.unwrap()
+        };
+
+        if corrupt.is_empty() {
+            tracker.set_finished(ProgressTrackerFinishType::Normal);
+            tracker.notify();
+            return Vec::new();
+        }
+
+        tracker.set_title("Repairing content library".into());
+        tracker.set_count(0);
+        tracker.set_total(corrupt.len());
+        tracker.notify();
+
+        let mut unrepaired = Vec::new();
+        for chunk in corrupt.chunks(MODRINTH_VERSION_FILES_CHUNK_SIZE) {
+            let hashes = chunk.iter().map(|(_, hash)| Arc::<str>::from(hex::encode(hash))).collect();
+            let versions = match self.meta.fetch(&ModrinthVersionFilesMetadataItem(&ModrinthVersionFilesRequest {
+                hashes,
+                algorithm: ModrinthHashAlgorithm::Sha1,
+            })).await {
+                Ok(result) => Some(result),
+                Err(error) => {
+                    log::warn!("Unable to look up corrupt content on Modrinth: {error}");
+                    None
+                },
+            };
+
+            for (path, hash) in chunk {
+                let sha1 = hex::encode(hash);
+                let url = versions.as_ref()
+                    .and_then(|versions| versions.0.get(sha1.as_str()))
+                    .and_then(|version| version.files.iter().find(|file| *file.hashes.sha1 == *sha1))
+                    .map(|file| file.url.clone());
+
+                let repaired = match url {
+                    Some(url) => self.download_library_file(&url, path, *hash).await,
+                    None => false,
+                };
+                if repaired {
+                    repairs.push(path, RepairKind::Corrupt);
+                } else {
+                    log::warn!("Unable to repair corrupt content library file {:?}", path);
+                    unrepaired.push(path.clone());
+                }
+
+                tracker.add_count(1);
+                tracker.notify();
+            }
+        }
+
+        tracker.set_finished(ProgressTrackerFinishType::from_err(!unrepaired.is_empty()));
+        tracker.notify();
+
+        unrepaired
+    }
+
+    async fn download_library_file(&self, url: &str, path: &Path, hash: [u8; 20]) -> bool {
+        let Ok(bytes) = self.downloads.download_bytes(url, None, Some(hash), None).await else {
+            return false;
+        };
+
+        // Overwrite in place instead of replacing the file so that hard links into instances are repaired too
+        tokio::fs::write(path, &bytes).await.is_ok()
+    }
+
+    fn send_verify_result(&self, repaired: Vec<RepairedFile>, unrepaired: Vec<PathBuf>, errors: Vec<VerifyError>) {
+        fn list(paths: &mut dyn Iterator<Item = &Path>, total: usize) -> String {
+            let mut message = String::new();
+            for path in paths.take(MAX_LISTED_FILES) {
+                message.push_str("\n- ");
+                message.push_str(&path.file_name().unwrap_or(path.as_os_str()).to_string_lossy());
+            }
+            if total > MAX_LISTED_FILES {
+                message.push_str(&format!("\n...and {} more", total - MAX_LISTED_FILES));
+            }
+            message
+        }
+
+        if repaired.is_empty() && unrepaired.is_empty() && errors.is_empty() {
+            self.send.send_success("Verified game files, everything is intact");
+            return;
+        }
+
+        if !repaired.is_empty() {
+            let corrupt = repaired.iter().filter(|file| file.kind == RepairKind::Corrupt).count();
+            let missing = repaired.len() - corrupt;
+            let mut message = format!("Repaired {} files ({corrupt} corrupt, {missing} missing):", repaired.len());
+            message.push_str(&list(&mut repaired.iter().map(|file| file.path.as_path()), repaired.len()));
+            self.send.send_success(message);
+        }
+
+        if !unrepaired.is_empty() {
+            let mut message = format!("Unable to repair {} corrupt content files, reinstall them manually:", unrepaired.len());
+            message.push_str(&list(&mut unrepaired.iter().map(PathBuf::as_path), unrepaired.len()));
+            self.send.send_error(message);
+        }
+
+        for error in errors {
+            self.send.send_error(format!("{error}"));
+        }
+    }
+}
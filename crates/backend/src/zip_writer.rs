@@ -0,0 +1,267 @@
+use std::io::{Error, ErrorKind, Read, Write};
+
+use chrono::{Datelike, Timelike};
+
+const LOCAL_FILE_HEADER_SIGNATURE: u32 = 0x04034b50;
+const DATA_DESCRIPTOR_SIGNATURE: u32 = 0x08074b50;
+const CENTRAL_DIRECTORY_HEADER_SIGNATURE: u32 = 0x02014b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06064b50;
+const ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE: u32 = 0x07064b50;
+const END_OF_CENTRAL_DIRECTORY_SIGNATURE: u32 = 0x06054b50;
+
+const VERSION: u16 = 20;
+const VERSION_ZIP64: u16 = 45;
+const FLAG_DATA_DESCRIPTOR: u16 = 1 << 3;
+const FLAG_UTF8: u16 = 1 << 11;
+
+const METHOD_DEFLATED: u16 = 8;
+
+const ZIP64_EXTRA_FIELD_ID: u16 = 0x0001;
+
+/// Deflating can make a file slightly larger, so files close to 4 GiB are written with zip64 sizes up front
+const ZIP64_ENTRY_THRESHOLD: u64 = 0xF000_0000;
+
+struct CentralDirectoryEntry {
+    name: Box<str>,
+    crc32: u32,
+    compressed_size: u64,
+    uncompressed_size: u64,
+    offset: u64,
+}
+
+impl CentralDirectoryEntry {
+    fn is_zip64(&self) -> bool {
+        self.compressed_size >= u32::MAX as u64 || self.uncompressed_size >= u32::MAX as u64 || self.offset >= u32::MAX as u64
+    }
+}
+
+struct CountingWriter<W: Write> {
+    inner: W,
+    count: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Minimal streaming zip writer, every entry is deflated and zip64 is used when sizes, offsets or the number
+/// of entries don't fit in the regular headers
+pub struct ZipWriter<W: Write> {
+    inner: CountingWriter<W>,
+    entries: Vec<CentralDirectoryEntry>,
+    dos_time: u16,
+    dos_date: u16,
+}
+
+impl<W: Write> ZipWriter<W> {
+    pub fn new(inner: W) -> Self {
+        let now = chrono::Local::now();
+        let dos_time = ((now.hour() << 11) | (now.minute() << 5) | (now.second() / 2)) as u16;
+        let dos_date = (((now.year().max(1980) - 1980) as u32) << 9 | (now.month() << 5) | now.day()) as u16;
+
+        Self {
+            inner: CountingWriter { inner, count: 0 },
+            entries: Vec::new(),
+            dos_time,
+            dos_date,
+        }
+    }
+
+    /// Streams `reader` into the zip, `size_hint` is the expected uncompressed size
+    pub fn add_file(&mut self, name: &str, mut reader: impl Read, size_hint: u64) -> std::io::Result<()> {
+        if name.len() > u16::MAX as usize {
+            return Err(Error::new(ErrorKind::InvalidInput, "filename is too long"));
+        }
+
+        let offset = self.inner.count;
+        let zip64 = size_hint >= ZIP64_ENTRY_THRESHOLD;
+
+        // Sizes and crc aren't known until the file has been written, they follow it in a data descriptor
+        let mut header = Vec::with_capacity(50 + name.len());
+        header.extend_from_slice(&LOCAL_FILE_HEADER_SIGNATURE.to_le_bytes());
+        header.extend_from_slice(&(if zip64 { VERSION_ZIP64 } else { VERSION }).to_le_bytes());
+        header.extend_from_slice(&(FLAG_UTF8 | FLAG_DATA_DESCRIPTOR).to_le_bytes());
+        header.extend_from_slice(&METHOD_DEFLATED.to_le_bytes());
+        header.extend_from_slice(&self.dos_time.to_le_bytes());
+        header.extend_from_slice(&self.dos_date.to_le_bytes());
+        header.extend_from_slice(&0u32.to_le_bytes()); // crc32
+        if zip64 {
+            header.extend_from_slice(&u32::MAX.to_le_bytes());
+            header.extend_from_slice(&u32::MAX.to_le_bytes());
+        } else {
+            header.extend_from_slice(&0u32.to_le_bytes());
+            header.extend_from_slice(&0u32.to_le_bytes());
+        }
+        header.extend_from_slice(&(name.len() as u16).to_le_bytes());
+        header.extend_from_slice(&(if zip64 { 20u16 } else { 0u16 }).to_le_bytes());
+        header.extend_from_slice(name.as_bytes());
+        if zip64 {
+            header.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+            header.extend_from_slice(&16u16.to_le_bytes());
+            header.extend_from_slice(&0u64.to_le_bytes());
+            header.extend_from_slice(&0u64.to_le_bytes());
+        }
+        self.inner.write_all(&header)?;
+
+        let data_offset = self.inner.count;
+        let mut hasher = crc32fast::Hasher::new();
+        let mut uncompressed_size = 0u64;
+        let mut encoder = flate2::write::DeflateEncoder::new(&mut self.inner, flate2::Compression::default());
+        let mut buffer = vec![0u8; 64 * 1024];
+        loop {
+            let read = match reader.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(error) if error.kind() == ErrorKind::Interrupted => continue,
+                Err(error) => return Err(error),
+            };
+            hasher.update(&buffer[..read]);
+            encoder.write_all(&buffer[..read])?;
+            uncompressed_size += read as u64;
+        }
+        encoder.finish()?;
+
+        let compressed_size = self.inner.count - data_offset;
+        let crc32 = hasher.finalize();
+
+        if !zip64 && (compressed_size >= u32::MAX as u64 || uncompressed_size >= u32::MAX as u64) {
+            return Err(Error::new(ErrorKind::Other, format!("{name} grew while it was being added to the zip")));
+        }
+
+        let mut descriptor = Vec::with_capacity(24);
+        descriptor.extend_from_slice(&DATA_DESCRIPTOR_SIGNATURE.to_le_bytes());
+        descriptor.extend_from_slice(&crc32.to_le_bytes());
+        if zip64 {
+            descriptor.extend_from_slice(&compressed_size.to_le_bytes());
+            descriptor.extend_from_slice(&uncompressed_size.to_le_bytes());
+        } else {
+            descriptor.extend_from_slice(&(compressed_size as u32).to_le_bytes());
+            descriptor.extend_from_slice(&(uncompressed_size as u32).to_le_bytes());
+        }
+        self.inner.write_all(&descriptor)?;
+
+        self.entries.push(CentralDirectoryEntry {
+            name: name.into(),
+            crc32,
+            compressed_size,
+            uncompressed_size,
+            offset,
+        });
+
+        Ok(())
+    }
+
+    pub fn finish(mut self) -> std::io::Result<W> {
+        let central_directory_offset = self.inner.count;
+
+        for entry in &self.entries {
+            let zip64 = entry.is_zip64();
+            let version = if zip64 { VERSION_ZIP64 } else { VERSION };
+
+            let mut header = Vec::with_capacity(46 + entry.name.len() + 28);
+            header.extend_from_slice(&CENTRAL_DIRECTORY_HEADER_SIGNATURE.to_le_bytes());
+            header.extend_from_slice(&version.to_le_bytes());
+            header.extend_from_slice(&version.to_le_bytes());
+            header.extend_from_slice(&(FLAG_UTF8 | FLAG_DATA_DESCRIPTOR).to_le_bytes());
+            header.extend_from_slice(&METHOD_DEFLATED.to_le_bytes());
+            header.extend_from_slice(&self.dos_time.to_le_bytes());
+            header.extend_from_slice(&self.dos_date.to_le_bytes());
+            header.extend_from_slice(&entry.crc32.to_le_bytes());
+            if zip64 {
+                header.extend_from_slice(&u32::MAX.to_le_bytes());
+                header.extend_from_slice(&u32::MAX.to_le_bytes());
+            } else {
+                header.extend_from_slice(&(entry.compressed_size as u32).to_le_bytes());
+                header.extend_from_slice(&(entry.uncompressed_size as u32).to_le_bytes());
+            }
+            header.extend_from_slice(&(entry.name.len() as u16).to_le_bytes());
+            header.extend_from_slice(&(if zip64 { 28u16 } else { 0u16 }).to_le_bytes()); // extra field length
+            header.extend_from_slice(&0u16.to_le_bytes()); // comment length
+            header.extend_from_slice(&0u16.to_le_bytes()); // disk number
+            header.extend_from_slice(&0u16.to_le_bytes()); // internal attributes
+            header.extend_from_slice(&0u32.to_le_bytes()); // external attributes
+            if zip64 {
+                header.extend_from_slice(&u32::MAX.to_le_bytes());
+            } else {
+                header.extend_from_slice(&(entry.offset as u32).to_le_bytes());
+            }
+            header.extend_from_slice(entry.name.as_bytes());
+            if zip64 {
+                header.extend_from_slice(&ZIP64_EXTRA_FIELD_ID.to_le_bytes());
+                header.extend_from_slice(&24u16.to_le_bytes());
+                header.extend_from_slice(&entry.uncompressed_size.to_le_bytes());
+                header.extend_from_slice(&entry.compressed_size.to_le_bytes());
+                header.extend_from_slice(&entry.offset.to_le_bytes());
+            }
+            self.inner.write_all(&header)?;
+        }
+
+        let central_directory_size = self.inner.count - central_directory_offset;
+        let entry_count = self.entries.len() as u64;
+
+        let mut end = Vec::with_capacity(98);
+        if entry_count >= u16::MAX as u64 || central_directory_size >= u32::MAX as u64 || central_directory_offset >= u32::MAX as u64 {
+            let zip64_end_offset = self.inner.count;
+
+            end.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+            end.extend_from_slice(&44u64.to_le_bytes()); // size of the remaining record
+            end.extend_from_slice(&VERSION_ZIP64.to_le_bytes());
+            end.extend_from_slice(&VERSION_ZIP64.to_le_bytes());
+            end.extend_from_slice(&0u32.to_le_bytes()); // disk number
+            end.extend_from_slice(&0u32.to_le_bytes()); // disk with central directory
+            end.extend_from_slice(&entry_count.to_le_bytes());
+            end.extend_from_slice(&entry_count.to_le_bytes());
+            end.extend_from_slice(&central_directory_size.to_le_bytes());
+            end.extend_from_slice(&central_directory_offset.to_le_bytes());
+
+            end.extend_from_slice(&ZIP64_END_OF_CENTRAL_DIRECTORY_LOCATOR_SIGNATURE.to_le_bytes());
+            end.extend_from_slice(&0u32.to_le_bytes()); // disk with zip64 end of central directory
+            end.extend_from_slice(&zip64_end_offset.to_le_bytes());
+            end.extend_from_slice(&1u32.to_le_bytes()); // total disks
+        }
+
+        let entry_count = entry_count.min(u16::MAX as u64) as u16;
+        end.extend_from_slice(&END_OF_CENTRAL_DIRECTORY_SIGNATURE.to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // disk number
+        end.extend_from_slice(&0u16.to_le_bytes()); // disk with central directory
+        end.extend_from_slice(&entry_count.to_le_bytes());
+        end.extend_from_slice(&entry_count.to_le_bytes());
+        end.extend_from_slice(&(central_directory_size.min(u32::MAX as u64) as u32).to_le_bytes());
+        end.extend_from_slice(&(central_directory_offset.min(u32::MAX as u64) as u32).to_le_bytes());
+        end.extend_from_slice(&0u16.to_le_bytes()); // comment length
+
+        self.inner.write_all(&end)?;
+        self.inner.flush()?;
+
+        Ok(self.inner.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rc_zip_sync::ReadZip;
+
+    use super::ZipWriter;
+
+    #[test]
+    fn test_zip64_entry_count() {
+        let mut zip = ZipWriter::new(Vec::new());
+        for index in 0..70_000 {
+            zip.add_file(&format!("overrides/config/{index}.txt"), index.to_string().as_bytes(), 5).unwrap();
+        }
+        let bytes = zip.finish().unwrap();
+
+        let archive = bytes.as_slice().read_zip().unwrap();
+        assert_eq!(archive.entries().count(), 70_000);
+        let entry = archive.by_name("overrides/config/69999.txt").unwrap();
+        assert_eq!(entry.bytes().unwrap(), b"69999");
+    }
+}
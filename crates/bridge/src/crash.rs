@@ -0,0 +1,43 @@
+use std::{path::Path, sync::Arc};
+
+use ustr::Ustr;
+
+use crate::instance::{InstanceContentID, InstanceID};
+
+/// Result of analyzing the files left behind after the game exited abnormally
+#[derive(Debug, Clone)]
+pub struct CrashAnalysis {
+    pub instance: InstanceID,
+    pub instance_name: Ustr,
+    pub report_path: Arc<Path>,
+    pub report_kind: CrashReportKind,
+    /// Short description of the crash, e.g. the crash report's description and exception
+    pub description: Option<Arc<str>>,
+    /// Mods that are likely responsible, most likely first
+    pub suspects: Arc<[CrashSuspect]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrashReportKind {
+    CrashReport,
+    JvmFatalError,
+    GameLog,
+}
+
+#[derive(Debug, Clone)]
+pub struct CrashSuspect {
+    pub content_id: InstanceContentID,
+    pub name: Arc<str>,
+    pub filename: Arc<str>,
+    pub reasons: Arc<[CrashSuspectReason]>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CrashSuspectReason {
+    /// The report names the mod directly, e.g. "Suspected Mods" or "Mod loading issue for"
+    NamedInReport,
+    /// A mixin from the mod failed to apply
+    MixinFailure,
+    /// Classes from the mod appear in the stack trace
+    StackTrace,
+}
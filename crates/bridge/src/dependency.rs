@@ -0,0 +1,529 @@
+use std::{cmp::Ordering, fmt::Display, sync::Arc};
+
+use rustc_hash::FxHashMap;
+use schema::{auxiliary::AuxDisabledChildren, loader::Loader};
+
+use crate::instance::{ContentSummary, ContentType, InstanceContentSummary};
+
+/// Dependency metadata declared by a mod's `fabric.mod.json`, `quilt.mod.json` or `mods.toml`
+#[derive(Debug, Clone, Default)]
+pub struct ContentDependencyInfo {
+    pub version: Option<Arc<str>>,
+    /// Other mod ids this file satisfies, including mods nested inside it
+    pub provides: Arc<[ProvidedMod]>,
+    pub dependencies: Arc<[ContentDependency]>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ProvidedMod {
+    pub id: Arc<str>,
+    pub version: Option<Arc<str>>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentDependency {
+    pub id: Arc<str>,
+    pub kind: ContentDependencyKind,
+    pub versions: VersionRequirement,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentDependencyKind {
+    /// Must be installed with a matching version
+    Required,
+    /// Should be installed
+    Recommended,
+    /// Only needs a matching version when installed
+    Optional,
+    /// Must not be installed with a matching version
+    Breaks,
+}
+
+#[derive(Debug, Clone)]
+pub enum VersionRequirement {
+    Any,
+    /// Fabric/Quilt style predicates, matches if any predicate matches
+    Predicates(Arc<[Arc<str>]>),
+    /// Maven version range as used by Forge/NeoForge, e.g. `[1.20,1.21)`
+    MavenRange(Arc<str>),
+}
+
+impl Display for VersionRequirement {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VersionRequirement::Any => f.write_str("*"),
+            VersionRequirement::Predicates(predicates) => f.write_str(&predicates.join(" || ")),
+            VersionRequirement::MavenRange(range) => f.write_str(range),
+        }
+    }
+}
+
+impl VersionRequirement {
+    /// Returns None when the version or requirement can't be understood
+    pub fn matches(&self, version: &str) -> Option<bool> {
+        match self {
+            VersionRequirement::Any => Some(true),
+            VersionRequirement::Predicates(predicates) => {
+                let mut any_understood = false;
+                for predicate in predicates.iter() {
+                    match matches_predicate(version, predicate) {
+                        Some(true) => return Some(true),
+                        Some(false) => any_understood = true,
+                        None => {},
+                    }
+                }
+                if any_understood { Some(false) } else { None }
+            },
+            VersionRequirement::MavenRange(range) => matches_maven_range(version, range),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ContentIssue {
+    pub name: Arc<str>,
+    pub filename: Arc<str>,
+    pub kind: ContentIssueKind,
+}
+
+#[derive(Debug, Clone)]
+pub enum ContentIssueKind {
+    MissingDependency { id: Arc<str>, required: Arc<str> },
+    MissingRecommendation { id: Arc<str> },
+    WrongDependencyVersion { id: Arc<str>, required: Arc<str>, found: Arc<str> },
+    Incompatible { id: Arc<str>, found: Arc<str> },
+    WrongMinecraftVersion { required: Arc<str> },
+    WrongLoaderVersion { id: Arc<str>, required: Arc<str> },
+    WrongLoader,
+    Duplicate { id: Arc<str> },
+}
+
+impl ContentIssue {
+    pub fn is_error(&self) -> bool {
+        !matches!(self.kind, ContentIssueKind::MissingRecommendation { .. })
+    }
+}
+
+impl Display for ContentIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = &self.name;
+        match &self.kind {
+            ContentIssueKind::MissingDependency { id, required } => write!(f, "{name} requires {id} {required}, which is not installed"),
+            ContentIssueKind::MissingRecommendation { id } => write!(f, "{name} recommends {id}, which is not installed"),
+            ContentIssueKind::WrongDependencyVersion { id, required, found } => write!(f, "{name} requires {id} {required}, but {found} is installed"),
+            ContentIssueKind::Incompatible { id, found } => write!(f, "{name} is incompatible with {id} {found}"),
+            ContentIssueKind::WrongMinecraftVersion { required } => write!(f, "{name} requires Minecraft {required}"),
+            ContentIssueKind::WrongLoaderVersion { id, required } => write!(f, "{name} requires {id} {required}"),
+            ContentIssueKind::WrongLoader => write!(f, "{name} ({}) is not made for this loader", self.filename),
+            ContentIssueKind::Duplicate { id } => write!(f, "{name} is installed more than once ({id})"),
+        }
+    }
+}
+
+/// Ids that are provided by the game or loader rather than by a mod file
+const BUILTIN_IDS: &[&str] = &[
+    "java", "minecraft", "fabricloader", "fabric-loader", "quilt_loader", "forge", "neoforge",
+    "javafml", "lowcodefml", "mclanguage", "fml", "mixinextras",
+];
+
+/// Builds a dependency graph of the enabled mods and reports missing, incompatible or mismatched dependencies
+pub fn analyze_content(
+    content: &[InstanceContentSummary],
+    minecraft_version: &str,
+    loader: Loader,
+    loader_version: Option<&str>,
+) -> Vec<ContentIssue> {
+    let enabled: Vec<(&InstanceContentSummary, &ContentDependencyInfo)> = content.iter()
+        .filter(|summary| summary.enabled)
+        .filter_map(|summary| Some((summary, summary.content_summary.dependency_info.as_deref()?)))
+        .collect();
+
+    let mut issues = Vec::new();
+
+    let mut provided: FxHashMap<&str, Vec<Option<&str>>> = FxHashMap::default();
+    let mut top_level: FxHashMap<&str, usize> = FxHashMap::default();
+    for (summary, info) in &enabled {
+        if let Some(id) = summary.content_summary.id.as_deref() {
+            provided.entry(id).or_default().push(info.version.as_deref());
+            *top_level.entry(id).or_default() += 1;
+        }
+        for provides in info.provides.iter() {
+            provided.entry(&provides.id).or_default().push(provides.version.as_deref());
+        }
+    }
+
+    // Mods inside enabled modpacks are added when launching
+    for summary in content.iter().filter(|summary| summary.enabled) {
+        let children: Vec<(&ContentSummary, Option<&str>)> = match &summary.content_summary.extra {
+            ContentType::ModrinthModpack { downloads, summaries, .. } => summaries.iter().enumerate()
+                .filter_map(|(index, child)| Some((child.as_deref()?, downloads.get(index).map(|download| &*download.path))))
+                .collect(),
+            ContentType::CurseforgeModpack { summaries, .. } => summaries.iter()
+                .filter_map(|(child, file_info)| Some((child.as_deref()?, file_info.as_ref().map(|file_info| &*file_info.filename))))
+                .collect(),
+            _ => continue,
+        };
+        for (child, filename) in children {
+            if is_child_disabled(&summary.disabled_children, child, filename) {
+                continue;
+            }
+            let info = child.dependency_info.as_deref();
+            if let Some(id) = child.id.as_deref() {
+                provided.entry(id).or_default().push(info.and_then(|info| info.version.as_deref()));
+            }
+            for provides in info.iter().flat_map(|info| info.provides.iter()) {
+                provided.entry(&provides.id).or_default().push(provides.version.as_deref());
+            }
+        }
+    }
+
+    let loader_id = match loader {
+        Loader::Fabric => Some("fabricloader"),
+        Loader::Quilt => Some("quilt_loader"),
+        Loader::Forge => Some("forge"),
+        Loader::NeoForge => Some("neoforge"),
+        Loader::Vanilla | Loader::Unknown => None,
+    };
+    // Forge loader versions are sometimes prefixed with the Minecraft version
+    let loader_version = loader_version.map(|version| {
+        version.strip_prefix(minecraft_version).and_then(|version| version.strip_prefix('-')).unwrap_or(version)
+    });
+
+    for (summary, info) in &enabled {
+        let name = summary.content_summary.name.clone()
+            .or_else(|| summary.content_summary.id.clone())
+            .unwrap_or_else(|| summary.filename.clone());
+        let mut push_issue = |kind| {
+            issues.push(ContentIssue {
+                name: name.clone(),
+                filename: summary.filename.clone(),
+                kind,
+            });
+        };
+
+        if !is_loader_compatible(loader, &summary.content_summary.extra) {
+            push_issue(ContentIssueKind::WrongLoader);
+            continue;
+        }
+
+        if let Some(id) = summary.content_summary.id.as_deref() && top_level.get(id).is_some_and(|count| *count > 1) {
+            push_issue(ContentIssueKind::Duplicate { id: id.into() });
+        }
+
+        for dependency in info.dependencies.iter() {
+            let id = &*dependency.id;
+            let required: Arc<str> = dependency.versions.to_string().into();
+
+            if id == "minecraft" {
+                if dependency.kind == ContentDependencyKind::Required
+                    && dependency.versions.matches(minecraft_version) == Some(false)
+                {
+                    push_issue(ContentIssueKind::WrongMinecraftVersion { required });
+                }
+                continue;
+            }
+
+            if Some(id) == loader_id {
+                if dependency.kind == ContentDependencyKind::Required
+                    && let Some(loader_version) = loader_version
+                    && dependency.versions.matches(loader_version) == Some(false)
+                {
+                    push_issue(ContentIssueKind::WrongLoaderVersion { id: dependency.id.clone(), required });
+                }
+                continue;
+            }
+
+            if BUILTIN_IDS.contains(&id) {
+                continue;
+            }
+
+            let versions = provided.get(id);
+            let matching = versions.map(|versions| versions.iter().any(|version| match version {
+                Some(version) => dependency.versions.matches(version) != Some(false),
+                None => true,
+            }));
+            let found: Arc<str> = versions
+                .and_then(|versions| versions.iter().flatten().next())
+                .map(|version| Arc::from(*version))
+                .unwrap_or_else(|| Arc::from("?"));
+
+            match dependency.kind {
+                ContentDependencyKind::Required => match matching {
+                    None => push_issue(ContentIssueKind::MissingDependency { id: dependency.id.clone(), required }),
+                    Some(false) => push_issue(ContentIssueKind::WrongDependencyVersion { id: dependency.id.clone(), required, found }),
+                    Some(true) => {},
+                },
+                ContentDependencyKind::Recommended => if matching.is_none() {
+                    push_issue(ContentIssueKind::MissingRecommendation { id: dependency.id.clone() });
+                },
+                ContentDependencyKind::Optional => if matching == Some(false) {
+                    push_issue(ContentIssueKind::WrongDependencyVersion { id: dependency.id.clone(), required, found });
+                },
+                ContentDependencyKind::Breaks => if matching == Some(true) {
+                    push_issue(ContentIssueKind::Incompatible { id: dependency.id.clone(), found });
+                },
+            }
+        }
+    }
+
+    issues
+}
+
+fn is_child_disabled(disabled_children: &AuxDisabledChildren, child: &ContentSummary, filename: Option<&str>) -> bool {
+    child.id.as_ref().is_some_and(|id| disabled_children.disabled_ids.contains(id))
+        || child.name.as_ref().is_some_and(|name| disabled_children.disabled_names.contains(name))
+        || filename.is_some_and(|filename| {
+            disabled_children.disabled_filenames.contains(filename) || disabled_children.deleted_filenames.contains(filename)
+        })
+}
+
+fn is_loader_compatible(loader: Loader, content_type: &ContentType) -> bool {
+    match content_type {
+        ContentType::Fabric => matches!(loader, Loader::Fabric | Loader::Quilt | Loader::Unknown),
+        ContentType::Quilt => matches!(loader, Loader::Quilt | Loader::Unknown),
+        ContentType::Forge => matches!(loader, Loader::Forge | Loader::NeoForge | Loader::Unknown),
+        ContentType::NeoForge => matches!(loader, Loader::NeoForge | Loader::Unknown),
+        ContentType::LegacyForge => matches!(loader, Loader::Forge | Loader::Unknown),
+        _ => true,
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum VersionPart<'a> {
+    Number(u64),
+    Text(&'a str),
+    Wildcard,
+}
+
+struct Version<'a> {
+    release: Vec<VersionPart<'a>>,
+    pre_release: Option<&'a str>,
+}
+
+fn parse_version(version: &str) -> Option<Version<'_>> {
+    let version = version.trim().trim_start_matches('v');
+    let version = version.split_once('+').map_or(version, |(version, _)| version);
+    let (release, pre_release) = match version.split_once('-') {
+        Some((release, pre_release)) => (release, Some(pre_release)),
+        None => (version, None),
+    };
+
+    let release: Vec<_> = release.split('.').map(|part| {
+        if part == "x" || part == "X" || part == "*" {
+            VersionPart::Wildcard
+        } else if let Ok(number) = part.parse() {
+            VersionPart::Number(number)
+        } else {
+            VersionPart::Text(part)
+        }
+    }).collect();
+
+    // Versions such as snapshots ("24w14a") can't be compared
+    if !matches!(release.first(), Some(VersionPart::Number(_))) {
+        return None;
+    }
+
+    Some(Version { release, pre_release: pre_release.filter(|pre_release| !pre_release.is_empty()) })
+}
+
+fn compare_versions(a: &Version, b: &Version) -> Ordering {
+    let len = a.release.len().max(b.release.len());
+    for index in 0..len {
+        let part_a = a.release.get(index).unwrap_or(&VersionPart::Number(0));
+        let part_b = b.release.get(index).unwrap_or(&VersionPart::Number(0));
+        let ordering = match (part_a, part_b) {
+            (VersionPart::Wildcard, _) | (_, VersionPart::Wildcard) => return Ordering::Equal,
+            (VersionPart::Number(a), VersionPart::Number(b)) => a.cmp(b),
+            (VersionPart::Number(_), VersionPart::Text(_)) => Ordering::Greater,
+            (VersionPart::Text(_), VersionPart::Number(_)) => Ordering::Less,
+            (VersionPart::Text(a), VersionPart::Text(b)) => a.cmp(b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+
+    match (a.pre_release, b.pre_release) {
+        (None, None) => Ordering::Equal,
+        (None, Some(_)) => Ordering::Greater,
+        (Some(_), None) => Ordering::Less,
+        (Some(a), Some(b)) => lexical_pre_release_cmp(a, b),
+    }
+}
+
+fn lexical_pre_release_cmp(a: &str, b: &str) -> Ordering {
+    for (part_a, part_b) in a.split('.').zip(b.split('.')) {
+        let ordering = match (part_a.parse::<u64>(), part_b.parse::<u64>()) {
+            (Ok(a), Ok(b)) => a.cmp(&b),
+            _ => part_a.cmp(part_b),
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+    }
+    a.split('.').count().cmp(&b.split('.').count())
+}
+
+/// Matches a space separated list of predicates such as `>=1.2 <2`, `~1.20`, `^3.0.0` or `1.20.x`
+fn matches_predicate(version: &str, predicate: &str) -> Option<bool> {
+    let parsed = parse_version(version)?;
+
+    for predicate in predicate.split_whitespace() {
+        if predicate == "*" {
+            continue;
+        }
+
+        let (operator, target) = ["<=", ">=", "<", ">", "=", "~", "^"].iter()
+            .find_map(|operator| predicate.strip_prefix(operator).map(|target| (*operator, target)))
+            .unwrap_or(("=", predicate));
+        // A trailing '-' means pre-releases of the target are allowed, an empty pre-release sorts before all of them
+        let mut parsed_target = parse_version(target.trim_end_matches('-'))?;
+        if target.ends_with('-') && parsed_target.pre_release.is_none() {
+            parsed_target.pre_release = Some("");
+        }
+        let target = parsed_target;
+
+        let ordering = compare_versions(&parsed, &target);
+        let matches = match operator {
+            "<=" => ordering != Ordering::Greater,
+            ">=" => ordering != Ordering::Less,
+            "<" => ordering == Ordering::Less,
+            ">" => ordering == Ordering::Greater,
+            "=" => ordering == Ordering::Equal,
+            "~" | "^" => {
+                // ~ allows changes after the minor version, ^ after the major version
+                let fixed = if operator == "~" { 2 } else { 1 };
+                ordering != Ordering::Less && target.release.iter().take(fixed).enumerate().all(|(index, part)| {
+                    parsed.release.get(index).unwrap_or(&VersionPart::Number(0)) == part
+                })
+            },
+            _ => unreachable!(),
+        };
+        if !matches {
+            return Some(false);
+        }
+    }
+
+    Some(true)
+}
+
+/// Matches a Maven version range such as `[1.20,1.21)`, `[47,)` or `[1.0]`. Several ranges
+/// can be separated by commas. A plain version is only a recommendation, so it always matches
+fn matches_maven_range(version: &str, range: &str) -> Option<bool> {
+    let range = range.trim();
+    if range.is_empty() || range == "*" || !range.starts_with(['[', '(']) {
+        return Some(true);
+    }
+    if range.contains("${") {
+        return None;
+    }
+
+    let parsed = parse_version(version)?;
+
+    let mut rest = range;
+    let mut any_understood = false;
+    while let Some(start) = rest.find(['[', '(']) {
+        let Some(end) = rest[start..].find([']', ')']).map(|end| start + end) else {
+            break;
+        };
+        let lower_inclusive = rest.as_bytes()[start] == b'[';
+        let upper_inclusive = rest.as_bytes()[end] == b']';
+        let inner = &rest[start + 1..end];
+        rest = &rest[end + 1..];
+
+        let (lower, upper) = match inner.split_once(',') {
+            Some((lower, upper)) => (lower.trim(), upper.trim()),
+            None => (inner.trim(), inner.trim()),
+        };
+
+        let lower_ok = if lower.is_empty() {
+            Some(true)
+        } else {
+            parse_version(lower).map(|lower| {
+                let ordering = compare_versions(&parsed, &lower);
+                ordering == Ordering::Greater || (lower_inclusive && ordering == Ordering::Equal)
+            })
+        };
+        let upper_ok = if upper.is_empty() {
+            Some(true)
+        } else {
+            parse_version(upper).map(|upper| {
+                let ordering = compare_versions(&parsed, &upper);
+                ordering == Ordering::Less || (upper_inclusive && ordering == Ordering::Equal)
+            })
+        };
+
+        match (lower_ok, upper_ok) {
+            (Some(true), Some(true)) => return Some(true),
+            (Some(_), Some(_)) => any_understood = true,
+            _ => {},
+        }
+    }
+
+    if any_understood { Some(false) } else { None }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{matches_maven_range, matches_predicate, parse_version, VersionPart};
+
+    #[test]
+    fn test_parse_version() {
+        let version = parse_version("v1.20.4-beta.2+build.7").unwrap();
+        assert_eq!(version.release, [VersionPart::Number(1), VersionPart::Number(20), VersionPart::Number(4)]);
+        assert_eq!(version.pre_release, Some("beta.2"));
+
+        let version = parse_version("1.20.x").unwrap();
+        assert_eq!(version.release, [VersionPart::Number(1), VersionPart::Number(20), VersionPart::Wildcard]);
+        assert_eq!(version.pre_release, None);
+
+        assert!(parse_version("24w14a").is_none());
+        assert!(parse_version("").is_none());
+    }
+
+    #[test]
+    fn test_matches_predicate() {
+        let cases = [
+            ("1.20.4", ">=1.20", Some(true)),
+            ("1.19.2", ">=1.20", Some(false)),
+            ("1.20.4", ">=1.20 <1.21", Some(true)),
+            ("1.21", ">=1.20 <1.21", Some(false)),
+            ("1.20.6", "~1.20.4", Some(true)),
+            ("1.21.0", "~1.20.4", Some(false)),
+            ("1.20.3", "~1.20.4", Some(false)),
+            ("3.4.1", "^3.0.0", Some(true)),
+            ("4.0.0", "^3.0.0", Some(false)),
+            ("0.15.11", "*", Some(true)),
+            ("1.20.1", "1.20.x", Some(true)),
+            ("1.21.1", "1.20.x", Some(false)),
+            ("1.0.0-beta.1", ">=1.0.0-", Some(true)),
+            ("1.0.0-beta.1", ">=1.0.0", Some(false)),
+            ("24w14a", ">=1.20", None),
+        ];
+        for (version, predicate, expected) in cases {
+            assert_eq!(matches_predicate(version, predicate), expected, "{version} {predicate}");
+        }
+    }
+
+    #[test]
+    fn test_matches_maven_range() {
+        let cases = [
+            ("1.20.4", "[1.20,1.21)", Some(true)),
+            ("1.21", "[1.20,1.21)", Some(false)),
+            ("1.20", "(1.20,1.21)", Some(false)),
+            ("1.21", "[1.20,1.21]", Some(true)),
+            ("47.2.0", "[47,)", Some(true)),
+            ("46.0.1", "[47,)", Some(false)),
+            ("1.0", "[1.0]", Some(true)),
+            ("1.1", "[1.0]", Some(false)),
+            ("1.5", "[1.0,1.2),[1.4,1.6)", Some(true)),
+            ("1.3", "[1.0,1.2),[1.4,1.6)", Some(false)),
+            ("1.0", "1.20", Some(true)),
+            ("1.0", "[${mc_version},)", None),
+            ("24w14a", "[1.20,)", None),
+        ];
+        for (version, range, expected) in cases {
+            assert_eq!(matches_maven_range(version, range), expected, "{version} {range}");
+        }
+    }
+}
@@ -0,0 +1,49 @@
+use std::{path::Path, sync::Arc};
+
+use ustr::Ustr;
+
+use crate::instance::InstanceID;
+
+/// Disk usage of the launcher's data, with hard-linked files only counted once
+#[derive(Debug, Clone)]
+pub struct DiskUsageReport {
+    pub instances: Arc<[InstanceDiskUsage]>,
+    pub content_library_files: usize,
+    pub content_library_bytes: u64,
+    pub content_meta_bytes: u64,
+    /// Size of everything above, counting files shared between instances and the content library once
+    pub total_bytes: u64,
+    /// Content library files that no instance uses anymore
+    pub orphaned_library: Arc<[DiskUsageEntry]>,
+    /// Content source entries for files that are no longer in the content library or any instance
+    pub orphaned_meta_entries: usize,
+    /// Extracted natives that haven't been used recently
+    pub old_natives: Arc<[DiskUsageEntry]>,
+}
+
+impl DiskUsageReport {
+    pub fn reclaimable_bytes(&self) -> u64 {
+        self.orphaned_library.iter().chain(self.old_natives.iter()).map(|entry| entry.bytes).sum()
+    }
+
+    pub fn has_cleanup(&self) -> bool {
+        !self.orphaned_library.is_empty() || !self.old_natives.is_empty() || self.orphaned_meta_entries > 0
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct InstanceDiskUsage {
+    pub id: InstanceID,
+    pub name: Ustr,
+    /// Size of every file in the instance
+    pub total_bytes: u64,
+    /// Size of the files that aren't shared with the content library or other instances,
+    /// i.e. roughly how much space deleting the instance would free
+    pub unique_bytes: u64,
+}
+
+#[derive(Debug, Clone)]
+pub struct DiskUsageEntry {
+    pub path: Arc<Path>,
+    pub bytes: u64,
+}
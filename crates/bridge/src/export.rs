@@ -0,0 +1,16 @@
+use strum::{Display, EnumIter};
+
+#[derive(Debug, Display, Clone, Copy, PartialEq, Eq, EnumIter)]
+pub enum InstanceExportFormat {
+    Modrinth,
+    Curseforge,
+}
+
+impl InstanceExportFormat {
+    pub fn extension(self) -> &'static str {
+        match self {
+            InstanceExportFormat::Modrinth => "mrpack",
+            InstanceExportFormat::Curseforge => "zip",
+        }
+    }
+}
@@ -5,12 +5,13 @@ use schema::{
     auxiliary::AuxDisabledChildren,
     content::ContentSource,
     curseforge::{CachedCurseforgeFileInfo, CurseforgeModpackFile, CurseforgeModpackMinecraft},
+    instance::InstanceSnapshotReason,
     loader::Loader,
     modification::ModrinthModpackFileDownload,
 };
 use ustr::Ustr;
 
-use crate::safe_path::SafePath;
+use crate::{dependency::ContentDependencyInfo, safe_path::SafePath};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct InstanceID {
@@ -66,6 +67,34 @@ pub struct WorldDatapackSummary {
     pub enabled: bool,
 }
 
+#[derive(Debug, Clone)]
+pub struct InstanceSnapshotSummary {
+    pub name: Arc<str>,
+    pub created_unix_ms: i64,
+    pub reason: InstanceSnapshotReason,
+    pub content_count: usize,
+}
+
+#[derive(Debug, Clone)]
+pub struct InstanceBisectStatus {
+    pub step: usize,
+    pub candidates: usize,
+    /// Mods enabled for the current step, including required dependencies
+    pub enabled: Arc<[Arc<str>]>,
+    /// Once finished, the mods the problem was narrowed down to. Empty if none of the mods caused it
+    pub result: Option<Arc<[Arc<str>]>>,
+}
+
+impl InstanceBisectStatus {
+    pub fn estimated_steps_left(&self) -> usize {
+        if self.result.is_some() || self.candidates <= 1 {
+            0
+        } else {
+            self.candidates.next_power_of_two().trailing_zeros() as usize
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct InstanceServerSummary {
     pub name: Arc<str>,
@@ -96,11 +125,13 @@ pub struct ContentSummary {
     pub authors: Arc<str>,
     pub png_icon: Option<Arc<[u8]>>,
     pub extra: ContentType,
+    pub dependency_info: Option<Arc<ContentDependencyInfo>>,
 }
 
 #[derive(Debug, Clone)]
 pub enum ContentType {
     Fabric,
+    Quilt,
     LegacyForge,
     Forge,
     NeoForge,
@@ -118,6 +149,8 @@ pub enum ContentType {
         minecraft: CurseforgeModpackMinecraft,
     },
     ResourcePack,
+    ShaderPack,
+    Datapack,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -134,7 +167,7 @@ pub enum ContentUpdateStatus {
 impl ContentUpdateStatus {
     pub fn can_update(&self) -> bool {
         match self {
-            ContentUpdateStatus::Modrinth => true,
+            ContentUpdateStatus::Modrinth | ContentUpdateStatus::Curseforge => true,
             _ => false,
         }
     }
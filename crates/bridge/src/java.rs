@@ -0,0 +1,17 @@
+use std::{path::Path, sync::Arc};
+
+/// A Java runtime found on this machine, either installed on the system or downloaded from Mojang
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JavaRuntimeInfo {
+    /// Path to the java binary
+    pub path: Arc<Path>,
+    pub major_version: u32,
+    pub version: Arc<str>,
+    pub vendor: Arc<str>,
+}
+
+impl JavaRuntimeInfo {
+    pub fn label(&self) -> String {
+        format!("Java {} ({} {}) - {}", self.major_version, self.vendor, self.version, self.path.display())
+    }
+}
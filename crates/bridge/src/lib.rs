@@ -1,9 +1,14 @@
 pub mod account;
+pub mod crash;
+pub mod dependency;
+pub mod disk_usage;
+pub mod export;
 pub mod game_output;
 pub mod handle;
 pub mod import;
 pub mod install;
 pub mod instance;
+pub mod java;
 pub mod keep_alive;
 pub mod message;
 pub mod meta;
@@ -3,11 +3,12 @@ use std::{
 };
 
 use schema::{
-    backend_config::{BackendConfig, ProxyConfig, SyncTargets},
+    backend_config::{BackendConfig, DownloadConfig, DownloadMirror, ProxyConfig, SyncTargets},
     instance::{
-        InstanceConfiguration, InstanceJvmBinaryConfiguration, InstanceJvmFlagsConfiguration,
-        InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration, InstanceSystemLibrariesConfiguration,
-        InstanceWrapperCommandConfiguration,
+        InstanceConfiguration, InstanceGameArgumentsConfiguration, InstanceHooksConfiguration, InstanceJvmBinaryConfiguration,
+        InstanceLaunchProfile, InstanceQuickPlayTarget, InstanceJvmFlagsConfiguration,
+        InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration, InstanceStats,
+        InstanceSystemLibrariesConfiguration, InstanceWindowConfiguration, InstanceWrapperCommandConfiguration,
     },
     loader::Loader,
     pandora_update::{UpdateManifest, UpdateManifestExe, UpdatePrompt},
@@ -17,16 +18,21 @@ use uuid::Uuid;
 
 use crate::{
     account::Account,
+    crash::CrashAnalysis,
+    disk_usage::DiskUsageReport,
+    export::InstanceExportFormat,
     game_output::GameOutputLogLevel,
     import::{ImportFromOtherLaunchers, OtherLauncher},
     install::ContentInstall,
     instance::{
-        InstanceContentID, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceStatus,
-        InstanceWorldSummary, WorldDatapackSummary,
+        InstanceBisectStatus, InstanceContentID, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceSnapshotSummary,
+        InstanceStatus, InstanceWorldSummary, WorldDatapackSummary,
     },
+    java::JavaRuntimeInfo,
     keep_alive::{KeepAlive, KeepAliveHandle},
     meta::{MetadataRequest, MetadataResult},
     modal_action::ModalAction,
+    safe_path::SafePath,
 };
 
 #[derive(Debug)]
@@ -82,6 +88,34 @@ pub enum MessageToBackend {
         id: InstanceID,
         wrapper_command: InstanceWrapperCommandConfiguration,
     },
+    SetInstanceWindow {
+        id: InstanceID,
+        window: InstanceWindowConfiguration,
+    },
+    SetInstanceGameArguments {
+        id: InstanceID,
+        game_arguments: InstanceGameArgumentsConfiguration,
+    },
+    SetInstanceDemo {
+        id: InstanceID,
+        demo: bool,
+    },
+    SetInstanceAutoJoinServer {
+        id: InstanceID,
+        server: Option<Arc<str>>,
+    },
+    SetInstanceLaunchProfiles {
+        id: InstanceID,
+        launch_profiles: Vec<InstanceLaunchProfile>,
+    },
+    SetInstanceEnvironment {
+        id: InstanceID,
+        environment: BTreeMap<Arc<str>, Arc<str>>,
+    },
+    SetInstanceHooks {
+        id: InstanceID,
+        hooks: InstanceHooksConfiguration,
+    },
     SetInstanceJvmFlags {
         id: InstanceID,
         jvm_flags: InstanceJvmFlagsConfiguration,
@@ -104,6 +138,7 @@ pub enum MessageToBackend {
     StartInstance {
         id: InstanceID,
         quick_play: Option<QuickPlayLaunch>,
+        profile: Option<Arc<str>>,
         allow_running_instance: bool,
         modal_action: ModalAction,
     },
@@ -134,6 +169,12 @@ pub enum MessageToBackend {
     RequestLoadResourcePacks {
         id: InstanceID,
     },
+    RequestLoadShaderPacks {
+        id: InstanceID,
+    },
+    RequestLoadDatapacks {
+        id: InstanceID,
+    },
     SetContentEnabled {
         id: InstanceID,
         content_ids: Vec<InstanceContentID>,
@@ -169,9 +210,15 @@ pub enum MessageToBackend {
     UpdateContent {
         instance: InstanceID,
         content_id: InstanceContentID,
+        /// Snapshot the instance before updating, only set for the first update of a batch
+        snapshot: bool,
         modal_action: ModalAction,
     },
     Sleep5s,
+    /// Replies once every message sent before it has been handled
+    Flush {
+        channel: tokio::sync::oneshot::Sender<()>,
+    },
     ReadLog {
         path: Arc<Path>,
         send: tokio::sync::mpsc::Sender<Arc<str>>,
@@ -198,6 +245,71 @@ pub enum MessageToBackend {
         path: Arc<Path>,
         modal_action: ModalAction,
     },
+    ExportInstance {
+        id: InstanceID,
+        format: InstanceExportFormat,
+        name: Arc<str>,
+        version: Arc<str>,
+        overrides: Arc<[SafePath]>,
+        output: Arc<Path>,
+        modal_action: ModalAction,
+    },
+    CreateInstanceSnapshot {
+        id: InstanceID,
+    },
+    GetInstanceSnapshots {
+        id: InstanceID,
+        channel: tokio::sync::oneshot::Sender<Arc<[InstanceSnapshotSummary]>>,
+    },
+    RestoreInstanceSnapshot {
+        id: InstanceID,
+        snapshot: Arc<str>,
+        modal_action: ModalAction,
+    },
+    GenerateInstanceLock {
+        id: InstanceID,
+        modal_action: ModalAction,
+    },
+    RestoreInstanceFromLock {
+        id: InstanceID,
+        remove_extra: bool,
+        modal_action: ModalAction,
+    },
+    StartBisect {
+        id: InstanceID,
+    },
+    ReportBisectResult {
+        id: InstanceID,
+        /// The step being reported, the report is ignored if that step was already resolved
+        step: usize,
+        good: bool,
+    },
+    StopBisect {
+        id: InstanceID,
+    },
+    RequestBisectStatus {
+        id: InstanceID,
+    },
+    VerifyInstance {
+        id: InstanceID,
+        modal_action: ModalAction,
+    },
+    ExportLaunchCommand {
+        id: InstanceID,
+        quick_play: Option<QuickPlayLaunch>,
+        profile: Option<Arc<str>>,
+        export: LaunchCommandExport,
+        modal_action: ModalAction,
+    },
+    VerifyAllInstances {
+        modal_action: ModalAction,
+    },
+    GetDiskUsage {
+        channel: tokio::sync::oneshot::Sender<Option<Arc<DiskUsageReport>>>,
+    },
+    CleanupDiskUsage {
+        modal_action: ModalAction,
+    },
     AddNewAccount {
         modal_action: ModalAction,
     },
@@ -214,10 +326,30 @@ pub enum MessageToBackend {
     SetOpenGameOutputAfterLaunching {
         value: bool,
     },
+    SetBackgroundUpdateChecks {
+        value: bool,
+    },
+    SetOfflineMode {
+        value: bool,
+    },
+    GetJavaRuntimes {
+        rescan: bool,
+        channel: tokio::sync::oneshot::Sender<Arc<[JavaRuntimeInfo]>>,
+    },
+    SetDefaultJavaRuntime {
+        major_version: u32,
+        path: Option<Arc<Path>>,
+    },
     SetProxyConfiguration {
         config: ProxyConfig,
         password: Option<String>,
     },
+    SetDownloadConfiguration {
+        config: DownloadConfig,
+    },
+    SetDownloadMirrors {
+        mirrors: Vec<DownloadMirror>,
+    },
     CreateInstanceShortcut {
         id: InstanceID,
         path: PathBuf,
@@ -285,10 +417,13 @@ pub enum MessageToFrontend {
         root_path: Arc<Path>,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        stats: InstanceStats,
         worlds_state: Arc<AtomicBridgeDataLoadState>,
         servers_state: Arc<AtomicBridgeDataLoadState>,
         mods_state: Arc<AtomicBridgeDataLoadState>,
         resource_packs_state: Arc<AtomicBridgeDataLoadState>,
+        shader_packs_state: Arc<AtomicBridgeDataLoadState>,
+        datapacks_state: Arc<AtomicBridgeDataLoadState>,
     },
     InstanceRemoved {
         id: InstanceID,
@@ -300,6 +435,7 @@ pub enum MessageToFrontend {
         root_path: Arc<Path>,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        stats: InstanceStats,
         status: InstanceStatus,
     },
     InstanceWorldsUpdated {
@@ -323,6 +459,14 @@ pub enum MessageToFrontend {
         id: InstanceID,
         resource_packs: Arc<[InstanceContentSummary]>,
     },
+    InstanceShaderPacksUpdated {
+        id: InstanceID,
+        shader_packs: Arc<[InstanceContentSummary]>,
+    },
+    InstanceDatapacksUpdated {
+        id: InstanceID,
+        datapacks: Arc<[InstanceContentSummary]>,
+    },
     CreateGameOutputWindow {
         id: usize,
         keep_alive: KeepAlive,
@@ -357,6 +501,13 @@ pub enum MessageToFrontend {
     MinecraftProfileResult {
         profile: MinecraftProfileInfo,
     },
+    CrashAnalyzed {
+        analysis: Arc<CrashAnalysis>,
+    },
+    InstanceBisectUpdated {
+        id: InstanceID,
+        status: Option<InstanceBisectStatus>,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -445,6 +596,23 @@ pub enum QuickPlayLaunch {
     Realms(OsString),
 }
 
+pub enum LaunchCommandExport {
+    /// Sends back the command on a single line
+    CommandLine(tokio::sync::oneshot::Sender<Arc<str>>),
+    /// Writes a launch.sh script into the instance folder
+    Script,
+}
+
+impl From<&InstanceQuickPlayTarget> for QuickPlayLaunch {
+    fn from(target: &InstanceQuickPlayTarget) -> Self {
+        match target {
+            InstanceQuickPlayTarget::Singleplayer(world) => QuickPlayLaunch::Singleplayer((&**world).into()),
+            InstanceQuickPlayTarget::Multiplayer(server) => QuickPlayLaunch::Multiplayer((&**server).into()),
+            InstanceQuickPlayTarget::Realms(realm) => QuickPlayLaunch::Realms((&**realm).into()),
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub enum EmbeddedOrRaw {
     Embedded(Arc<str>),
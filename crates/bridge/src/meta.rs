@@ -1,14 +1,16 @@
 use std::sync::Arc;
 
-use schema::{curseforge::{CurseforgeGetModFilesRequest, CurseforgeGetModFilesResult, CurseforgeSearchRequest, CurseforgeSearchResult}, fabric_loader_manifest::FabricLoaderManifest, forge::{ForgeMavenManifest, NeoforgeMavenManifest}, modrinth::{ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult}, version_manifest::MinecraftVersionManifest};
+use schema::{curseforge::{CurseforgeGetModFilesRequest, CurseforgeGetModFilesResult, CurseforgeSearchRequest, CurseforgeSearchResult}, fabric_loader_manifest::FabricLoaderManifest, forge::{ForgeMavenManifest, NeoforgeMavenManifest}, modrinth::{ModrinthProjectRequest, ModrinthProjectResult, ModrinthProjectVersionsRequest, ModrinthProjectVersionsResult, ModrinthSearchRequest, ModrinthSearchResult}, quilt_loader_manifest::QuiltLoaderManifest, version_manifest::MinecraftVersionManifest};
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord)]
 pub enum MetadataRequest {
     MinecraftVersionManifest,
     FabricLoaderManifest,
+    QuiltLoaderManifest,
     ForgeMavenManifest,
     NeoforgeMavenManifest,
     ModrinthSearch(ModrinthSearchRequest),
+    ModrinthProject(ModrinthProjectRequest),
     ModrinthProjectVersions(ModrinthProjectVersionsRequest),
     CurseforgeSearch(CurseforgeSearchRequest),
     CurseforgeGetModFiles(CurseforgeGetModFilesRequest),
@@ -18,9 +20,11 @@ pub enum MetadataRequest {
 pub enum MetadataResult {
     MinecraftVersionManifest(Arc<MinecraftVersionManifest>),
     FabricLoaderManifest(Arc<FabricLoaderManifest>),
+    QuiltLoaderManifest(Arc<QuiltLoaderManifest>),
     ForgeMavenManifest(Arc<ForgeMavenManifest>),
     NeoforgeMavenManifest(Arc<NeoforgeMavenManifest>),
     ModrinthSearchResult(Arc<ModrinthSearchResult>),
+    ModrinthProjectResult(Arc<ModrinthProjectResult>),
     ModrinthProjectVersionsResult(Arc<ModrinthProjectVersionsResult>),
     CurseforgeSearchResult(Arc<CurseforgeSearchResult>),
     CurseforgeGetModFilesResult(Arc<CurseforgeGetModFilesResult>),
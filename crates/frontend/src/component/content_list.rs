@@ -176,10 +176,12 @@ impl ContentListDelegate {
                                 let mut updating = updating.lock();
                                 let delegate = this.delegate_mut();
                                 if delegate.is_selected(element_id) {
+                                    let mut snapshot = true;
                                     for summary in &delegate.content {
                                         if delegate.is_selected(summary.filename_hash) && summary.update.can_update(delegate.for_loader, delegate.for_version) {
                                             updating.insert(summary.filename_hash);
-                                            crate::root::update_single_mod(id, summary.id, &backend_handle, window, cx);
+                                            crate::root::update_single_mod(id, summary.id, snapshot, &backend_handle, window, cx);
+                                            snapshot = false;
                                         }
                                     }
                                     delegate.selected.clear();
@@ -187,7 +189,7 @@ impl ContentListDelegate {
                                     delegate.last_clicked_non_range = None;
                                 } else {
                                     updating.insert(element_id);
-                                    crate::root::update_single_mod(id, content_id, &backend_handle, window, cx);
+                                    crate::root::update_single_mod(id, content_id, true, &backend_handle, window, cx);
                                 }
                             })
                         })
@@ -504,6 +506,7 @@ impl ContentListDelegate {
             authors: "".into(),
             png_icon: None,
             extra: ContentType::Fabric,
+            dependency_info: None,
         });
 
         for modification in new_content.iter() {
@@ -21,9 +21,9 @@ const GRAY: Hsla = Hsla {
 use crate::{
     entity::{
         DataEntities,
-        instance::{InstanceAddedEvent, InstanceEntry, InstanceModifiedEvent, InstanceRemovedEvent},
+        instance::{InstanceAddedEvent, InstanceEntries, InstanceEntry, InstanceModifiedEvent, InstanceRemovedEvent},
     },
-    interface_config::InterfaceConfig,
+    interface_config::{InstancesSortMode, InterfaceConfig},
     modals,
     png_render_cache, root, ts, ui,
 };
@@ -31,6 +31,7 @@ use crate::{
 pub struct InstanceList {
     columns: Vec<Column>,
     items: Vec<InstanceEntry>,
+    instances: Entity<InstanceEntries>,
     backend_handle: BackendHandle,
     _instance_added_subscription: Subscription,
     _instance_removed_subscription: Subscription,
@@ -46,6 +47,7 @@ impl InstanceList {
                 &instances,
                 |table: &mut TableState<InstanceList>, _, event, cx| {
                     table.delegate_mut().items.insert(0, event.instance.clone());
+                    table.delegate_mut().sort_items(InterfaceConfig::get(cx).instances_sort_mode);
                     cx.notify();
                 },
             );
@@ -60,31 +62,58 @@ impl InstanceList {
                         table.delegate_mut().items.iter_mut().find(|entry| entry.id == event.instance.id)
                     {
                         *entry = event.instance.clone();
+                        table.delegate_mut().sort_items(InterfaceConfig::get(cx).instances_sort_mode);
                         cx.notify();
                     }
                 });
-            let instance_list = Self {
+            let mut instance_list = Self {
                 columns: vec![
                     Column::new("controls", "").width(150.).fixed_left().movable(false).resizable(false),
                     Column::new("name", "Name").width(150.).fixed_left().sortable().resizable(true),
                     Column::new("version", "Version").width(150.).fixed_left().sortable().resizable(true),
                     Column::new("loader", "Loader").width(150.).fixed_left().resizable(true),
+                    Column::new("last_played", "Last Played").width(150.).fixed_left().sortable().resizable(true),
+                    Column::new("playtime", "Playtime").width(100.).fixed_left().sortable().resizable(true),
                     Column::new("remove", "").width(44.).fixed_left().movable(false).resizable(false),
                 ],
                 items,
+                instances,
                 backend_handle: data.backend_handle.clone(),
                 _instance_added_subscription,
                 _instance_removed_subscription,
                 _instance_modified_subscription,
             };
+            instance_list.sort_items(InterfaceConfig::get(cx).instances_sort_mode);
             TableState::new(instance_list, window, cx)
         })
     }
 
+    pub fn set_sort_mode(&mut self, mode: InstancesSortMode, cx: &App) {
+        self.items = self.instances.read(cx).entries.values().map(|i| i.read(cx).clone()).collect();
+        self.sort_items(mode);
+    }
+
+    fn sort_items(&mut self, mode: InstancesSortMode) {
+        match mode {
+            InstancesSortMode::Default => {},
+            InstancesSortMode::RecentlyPlayed => {
+                self.items.sort_by(|a, b| b.stats.last_played_unix_ms.cmp(&a.stats.last_played_unix_ms))
+            },
+            InstancesSortMode::MostPlayed => {
+                self.items.sort_by(|a, b| b.stats.total_playtime_secs.cmp(&a.stats.total_playtime_secs))
+            },
+        }
+    }
+
     pub fn render_card(&self, index: usize, cx: &mut App) -> Div {
         let item = &self.items[index];
         let loader_and_version =
             format!("{} {}", item.configuration.loader.name(), item.configuration.minecraft_version.as_str(),);
+        let playtime = if item.stats.session_count == 0 {
+            ts!("instance.playtime.never_played")
+        } else {
+            ts!("instance.playtime.played", time = format_playtime(item.stats.total_playtime_secs))
+        };
 
         let icon_element = if let Some(icon) = item.icon.clone() {
             let transform = png_render_cache::ImageTransformation::Resize { width: 64, height: 64 };
@@ -212,7 +241,8 @@ impl InstanceList {
                                         .child(edit_icon.clone().size_4()),
                                 ),
                         )
-                        .child(div().text_color(GRAY).text_xs().child(loader_and_version.clone())),
+                        .child(div().text_color(GRAY).text_xs().child(loader_and_version.clone()))
+                        .child(div().text_color(GRAY).text_xs().child(playtime)),
                 ),
             )
             .child(
@@ -227,7 +257,7 @@ impl InstanceList {
                             InstanceStatus::NotRunning => {
                                 Button::new(("start", index)).flex_grow().small().success().label(ts!("instance.start.label")).on_click(
                                     move |_, window, cx| {
-                                        root::start_instance(id, name.clone(), None, false, &backend_handle, window, cx);
+                                        root::start_instance(id, name.clone(), None, None, false, &backend_handle, window, cx);
                                     },
                                 )
                             },
@@ -301,6 +331,14 @@ impl TableDelegate for InstanceList {
                         &b.configuration.minecraft_version,
                     ),
                 }),
+                "last_played" => self.items.sort_by(|a, b| match sort {
+                    ColumnSort::Descending => b.stats.last_played_unix_ms.cmp(&a.stats.last_played_unix_ms),
+                    _ => a.stats.last_played_unix_ms.cmp(&b.stats.last_played_unix_ms),
+                }),
+                "playtime" => self.items.sort_by(|a, b| match sort {
+                    ColumnSort::Descending => b.stats.total_playtime_secs.cmp(&a.stats.total_playtime_secs),
+                    _ => a.stats.total_playtime_secs.cmp(&b.stats.total_playtime_secs),
+                }),
                 _ => {},
             }
         }
@@ -330,7 +368,7 @@ impl TableDelegate for InstanceList {
                             let id = item.id;
                             let backend_handle = backend_handle.clone();
                             move |_, window, cx| {
-                                root::start_instance(id, name.clone(), None, false, &backend_handle, window, cx);
+                                root::start_instance(id, name.clone(), None, None, false, &backend_handle, window, cx);
                             }
                         }))
                         .child(Button::new(("view", row_ix)).w(relative(0.5)).small().info().label(ts!("instance.view")).on_click({
@@ -343,6 +381,11 @@ impl TableDelegate for InstanceList {
                         .into_any_element()
                 },
                 "loader" => item.configuration.loader.name().into_any_element(),
+                "last_played" => match item.stats.last_played_unix_ms {
+                    Some(last_played) => format_last_played(last_played).into_any_element(),
+                    None => ts!("instance.playtime.never_played").into_any_element(),
+                },
+                "playtime" => format_playtime(item.stats.total_playtime_secs).into_any_element(),
                 "remove" => {
                     let backend_handle = self.backend_handle.clone();
                     let id = item.id;
@@ -375,3 +418,20 @@ impl TableDelegate for InstanceList {
         }
     }
 }
+
+fn format_playtime(secs: u64) -> String {
+    let hours = secs / 3600;
+    let minutes = (secs % 3600) / 60;
+    if hours > 0 {
+        format!("{hours}h {minutes}m")
+    } else {
+        format!("{minutes}m")
+    }
+}
+
+fn format_last_played(unix_ms: i64) -> String {
+    let Some(date_time) = chrono::DateTime::from_timestamp_millis(unix_ms) else {
+        return String::new();
+    };
+    format!("{}", date_time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M"))
+}
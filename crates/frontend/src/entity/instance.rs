@@ -1,7 +1,7 @@
 use std::{path::Path, sync::Arc};
 
 use bridge::{
-    instance::{InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceStatus, InstanceWorldSummary, WorldDatapackSummary},
+    instance::{InstanceBisectStatus, InstanceContentSummary, InstanceID, InstanceServerSummary, InstanceStatus, InstanceWorldSummary, WorldDatapackSummary},
     message::AtomicBridgeDataLoadState,
 };
 use gpui::{prelude::*, *};
@@ -9,7 +9,7 @@ use gpui_component::select::SelectItem;
 use indexmap::IndexMap;
 use parking_lot::RwLock;
 use rustc_hash::FxHashMap;
-use schema::{instance::InstanceConfiguration, loader::Loader};
+use schema::{instance::{InstanceConfiguration, InstanceStats}, loader::Loader};
 
 pub struct InstanceEntries {
     pub entries: IndexMap<InstanceID, Entity<InstanceEntry>>,
@@ -24,10 +24,13 @@ impl InstanceEntries {
         root_path: Arc<Path>,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        stats: InstanceStats,
         worlds_state: Arc<AtomicBridgeDataLoadState>,
         servers_state: Arc<AtomicBridgeDataLoadState>,
         mods_state: Arc<AtomicBridgeDataLoadState>,
         resource_packs_state: Arc<AtomicBridgeDataLoadState>,
+        shader_packs_state: Arc<AtomicBridgeDataLoadState>,
+        datapacks_state: Arc<AtomicBridgeDataLoadState>,
         cx: &mut App,
     ) {
         entity.update(cx, |entries, cx| {
@@ -39,6 +42,7 @@ impl InstanceEntries {
                 root_path,
                 dot_minecraft_folder,
                 configuration,
+                stats,
                 status: InstanceStatus::NotRunning,
                 worlds_state,
                 worlds: cx.new(|_| [].into()),
@@ -49,6 +53,11 @@ impl InstanceEntries {
                 mods: cx.new(|_| [].into()),
                 resource_packs_state,
                 resource_packs: cx.new(|_| [].into()),
+                shader_packs_state,
+                shader_packs: cx.new(|_| [].into()),
+                datapacks_state,
+                datapacks: cx.new(|_| [].into()),
+                bisect: cx.new(|_| None),
             };
             instance.title = instance.create_title().into();
 
@@ -96,6 +105,7 @@ impl InstanceEntries {
         root_path: Arc<Path>,
         dot_minecraft_folder: Arc<Path>,
         configuration: InstanceConfiguration,
+        stats: InstanceStats,
         status: InstanceStatus,
         cx: &mut App,
     ) {
@@ -107,6 +117,7 @@ impl InstanceEntries {
                     instance.root_path = root_path.clone();
                     instance.dot_minecraft_folder = dot_minecraft_folder.clone();
                     instance.configuration = configuration.clone();
+                    instance.stats = stats.clone();
                     instance.status = status;
                     instance.title = instance.create_title().into();
                     cx.notify();
@@ -177,6 +188,19 @@ impl InstanceEntries {
         });
     }
 
+    pub fn set_bisect(entity: &Entity<Self>, id: InstanceID, status: Option<InstanceBisectStatus>, cx: &mut App) {
+        entity.update(cx, |entries, cx| {
+            if let Some(instance) = entries.entries.get_mut(&id) {
+                instance.update(cx, |instance, cx| {
+                    instance.bisect.update(cx, |existing_status, cx| {
+                        *existing_status = status;
+                        cx.notify();
+                    })
+                });
+            }
+        });
+    }
+
     pub fn set_resource_packs(
         entity: &Entity<Self>,
         id: InstanceID,
@@ -195,6 +219,42 @@ impl InstanceEntries {
         });
     }
 
+    pub fn set_shader_packs(
+        entity: &Entity<Self>,
+        id: InstanceID,
+        shader_packs: Arc<[InstanceContentSummary]>,
+        cx: &mut App,
+    ) {
+        entity.update(cx, |entries, cx| {
+            if let Some(instance) = entries.entries.get_mut(&id) {
+                instance.update(cx, |instance, cx| {
+                    instance.shader_packs.update(cx, |existing_shader_packs, cx| {
+                        *existing_shader_packs = shader_packs;
+                        cx.notify();
+                    })
+                });
+            }
+        });
+    }
+
+    pub fn set_datapacks(
+        entity: &Entity<Self>,
+        id: InstanceID,
+        datapacks: Arc<[InstanceContentSummary]>,
+        cx: &mut App,
+    ) {
+        entity.update(cx, |entries, cx| {
+            if let Some(instance) = entries.entries.get_mut(&id) {
+                instance.update(cx, |instance, cx| {
+                    instance.datapacks.update(cx, |existing_datapacks, cx| {
+                        *existing_datapacks = datapacks;
+                        cx.notify();
+                    })
+                });
+            }
+        });
+    }
+
     pub fn move_to_top(entity: &Entity<Self>, id: InstanceID, cx: &mut App) {
         entity.update(cx, |entries, cx| {
             if let Some(index) = entries.entries.get_index_of(&id) {
@@ -218,6 +278,7 @@ pub struct InstanceEntry {
     pub root_path: Arc<Path>,
     pub dot_minecraft_folder: Arc<Path>,
     pub configuration: InstanceConfiguration,
+    pub stats: InstanceStats,
     pub status: InstanceStatus,
     pub worlds_state: Arc<AtomicBridgeDataLoadState>,
     pub worlds: Entity<Arc<[InstanceWorldSummary]>>,
@@ -228,6 +289,11 @@ pub struct InstanceEntry {
     pub mods: Entity<Arc<[InstanceContentSummary]>>,
     pub resource_packs_state: Arc<AtomicBridgeDataLoadState>,
     pub resource_packs: Entity<Arc<[InstanceContentSummary]>>,
+    pub shader_packs_state: Arc<AtomicBridgeDataLoadState>,
+    pub shader_packs: Entity<Arc<[InstanceContentSummary]>>,
+    pub datapacks_state: Arc<AtomicBridgeDataLoadState>,
+    pub datapacks: Entity<Arc<[InstanceContentSummary]>>,
+    pub bisect: Entity<Option<InstanceBisectStatus>>,
 }
 
 impl SelectItem for InstanceEntry {
@@ -64,6 +64,8 @@ pub struct InterfaceConfig {
     pub hide_server_addresses: bool,
     #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub instances_view_mode: InstancesViewMode,
+    #[serde(default, deserialize_with = "schema::try_deserialize")]
+    pub instances_sort_mode: InstancesSortMode,
     /// Per-instance datapack world: key = dot_minecraft_folder path, value = world folder name.
     #[serde(default, deserialize_with = "schema::try_deserialize")]
     pub datapack_world_by_instance: HashMap<String, String>,
@@ -102,6 +104,7 @@ impl Default for InterfaceConfig {
             hide_usernames: Default::default(),
             hide_server_addresses: Default::default(),
             instances_view_mode: Default::default(),
+            instances_sort_mode: Default::default(),
             datapack_world_by_instance: Default::default(),
             instance_subpage: Default::default()
         }
@@ -150,6 +153,25 @@ impl InstancesViewMode {
     }
 }
 
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, strum::EnumIter)]
+#[serde(rename_all = "snake_case")]
+pub enum InstancesSortMode {
+    #[default]
+    Default,
+    RecentlyPlayed,
+    MostPlayed,
+}
+
+impl InstancesSortMode {
+    pub fn name(self) -> SharedString {
+        match self {
+            InstancesSortMode::Default => ts!("instance.sort.default").into(),
+            InstancesSortMode::RecentlyPlayed => ts!("instance.sort.recently_played").into(),
+            InstancesSortMode::MostPlayed => ts!("instance.sort.most_played").into(),
+        }
+    }
+}
+
 impl InterfaceConfig {
     pub fn init(cx: &mut App, path: Arc<Path>) {
         cx.set_global(InterfaceConfigHolder {
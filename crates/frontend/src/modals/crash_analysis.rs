@@ -0,0 +1,119 @@
+use std::sync::Arc;
+
+use bridge::{
+    crash::{CrashAnalysis, CrashReportKind, CrashSuspectReason}, handle::BackendHandle, instance::InstanceContentID, message::MessageToBackend
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme as _, Disableable, Sizable, WindowExt, button::{Button, ButtonVariants}, dialog::Dialog, h_flex, scroll::ScrollableElement, v_flex
+};
+
+use crate::{root, ts};
+
+struct CrashAnalysisModalState {
+    analysis: Arc<CrashAnalysis>,
+    backend_handle: BackendHandle,
+    disabled: Vec<InstanceContentID>,
+}
+
+impl CrashAnalysisModalState {
+    pub fn render(&mut self, modal: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let (radius, border, muted_foreground) = {
+            let theme = cx.theme();
+            (theme.radius, theme.border, theme.muted_foreground)
+        };
+
+        let source = match self.analysis.report_kind {
+            CrashReportKind::CrashReport => ts!("instance.crash.source.crash_report"),
+            CrashReportKind::JvmFatalError => ts!("instance.crash.source.jvm_error"),
+            CrashReportKind::GameLog => ts!("instance.crash.source.game_log"),
+        };
+
+        let mut content = v_flex().gap_3().child(source);
+
+        if let Some(description) = &self.analysis.description {
+            content = content.child(div()
+                .p_2()
+                .border_1()
+                .rounded(radius)
+                .border_color(border)
+                .font_family("Roboto Mono")
+                .text_sm()
+                .max_h_32()
+                .overflow_y_scrollbar()
+                .child(SharedString::from(description.clone())));
+        }
+
+        if self.analysis.suspects.is_empty() {
+            content = content.child(ts!("instance.crash.no_suspects"));
+        } else {
+            let mut list = v_flex().gap_1();
+            for (index, suspect) in self.analysis.suspects.iter().enumerate() {
+                let reasons = suspect.reasons.iter().map(|reason| match reason {
+                    CrashSuspectReason::NamedInReport => ts!("instance.crash.reason.named"),
+                    CrashSuspectReason::MixinFailure => ts!("instance.crash.reason.mixin"),
+                    CrashSuspectReason::StackTrace => ts!("instance.crash.reason.stack_trace"),
+                }).collect::<Vec<_>>().join(", ");
+
+                let content_id = suspect.content_id;
+                let disabled = self.disabled.contains(&content_id);
+
+                list = list.child(h_flex()
+                    .gap_3()
+                    .p_2()
+                    .border_1()
+                    .rounded(radius)
+                    .border_color(border)
+                    .child(v_flex()
+                        .flex_1()
+                        .child(div().font_medium().child(SharedString::from(suspect.name.clone())))
+                        .child(div().text_sm().text_color(muted_foreground)
+                            .child(ts!("instance.crash.suspect_details", file = suspect.filename, reasons = reasons))))
+                    .child(Button::new(("disable", index)).small().warning()
+                        .label(if disabled { ts!("instance.crash.disabled") } else { ts!("instance.crash.disable") })
+                        .disabled(disabled)
+                        .on_click(cx.listener(move |this, _, _, cx| {
+                            this.backend_handle.send(MessageToBackend::SetContentEnabled {
+                                id: this.analysis.instance,
+                                content_ids: vec![content_id],
+                                enabled: false,
+                            });
+                            this.disabled.push(content_id);
+                            cx.notify();
+                        }))));
+            }
+            content = content.child(div().font_medium().child(ts!("instance.crash.likely_cause"))).child(list);
+        }
+
+        modal
+            .title(ts!("instance.crash.title", name = self.analysis.instance_name))
+            .child(content)
+            .footer(h_flex().gap_2().w_full()
+                .child(Button::new("close").flex_1().label(ts!("common.ok"))
+                    .on_click(|_, window, cx| window.close_dialog(cx)))
+                .child(Button::new("upload").flex_1().success().label(ts!("instance.crash.upload"))
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        window.close_dialog(cx);
+                        root::upload_log_file(this.analysis.report_path.clone(), &this.backend_handle, window, cx);
+                    }))))
+    }
+}
+
+pub fn open_crash_analysis(
+    analysis: Arc<CrashAnalysis>,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let state = cx.new(|_| CrashAnalysisModalState {
+        analysis,
+        backend_handle,
+        disabled: Vec::new(),
+    });
+
+    window.open_dialog(cx, move |modal, window, cx| {
+        cx.update_entity(&state, |state, cx| {
+            state.render(modal, window, cx)
+        })
+    });
+}
@@ -261,12 +261,18 @@ impl CreateInstanceModalState {
                         .label(ts!("modrinth.category.neoforge"))
                         .selected(self.selected_loader == Loader::NeoForge),
                 )
+                .child(
+                    Button::new("loader-quilt")
+                        .label(ts!("modrinth.category.quilt"))
+                        .selected(self.selected_loader == Loader::Quilt),
+                )
                 .on_click(cx.listener(move |this, selected: &Vec<usize>, _, _| {
                     match selected.first() {
                         Some(0) => this.selected_loader = Loader::Vanilla,
                         Some(1) => this.selected_loader = Loader::Fabric,
                         Some(2) => this.selected_loader = Loader::Forge,
                         Some(3) => this.selected_loader = Loader::NeoForge,
+                        Some(4) => this.selected_loader = Loader::Quilt,
                         _ => {},
                     };
                 }))
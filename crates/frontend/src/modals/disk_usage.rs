@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use bridge::{
+    disk_usage::DiskUsageReport, handle::BackendHandle, message::MessageToBackend, modal_action::ModalAction
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme as _, Disableable, WindowExt, button::{Button, ButtonVariants}, dialog::Dialog, h_flex, scroll::ScrollableElement, spinner::Spinner, v_flex
+};
+
+use crate::{modals, ts};
+
+struct DiskUsageModalState {
+    backend_handle: BackendHandle,
+    report: Option<Option<Arc<DiskUsageReport>>>,
+    _get_report_task: Task<()>,
+}
+
+impl DiskUsageModalState {
+    pub fn new(backend_handle: BackendHandle, cx: &mut Context<Self>) -> Self {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        let get_report_task = cx.spawn(async move |state, cx| {
+            let report = recv.await.ok().flatten();
+            let _ = state.update(cx, move |state, cx| {
+                state.report = Some(report);
+                cx.notify();
+            });
+        });
+
+        backend_handle.send(MessageToBackend::GetDiskUsage { channel: send });
+
+        Self {
+            backend_handle,
+            report: None,
+            _get_report_task: get_report_task,
+        }
+    }
+
+    pub fn render(&mut self, modal: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let (radius, border, muted_foreground) = {
+            let theme = cx.theme();
+            (theme.radius, theme.border, theme.muted_foreground)
+        };
+
+        let report = match &self.report {
+            None => {
+                return modal
+                    .title(ts!("disk_usage.title"))
+                    .child(h_flex().gap_3().child(ts!("disk_usage.scanning")).child(Spinner::new()));
+            },
+            Some(None) => {
+                return modal
+                    .title(ts!("disk_usage.title"))
+                    .child(ts!("disk_usage.failed"))
+                    .footer(Button::new("close").flex_1().label(ts!("common.ok"))
+                        .on_click(|_, window, cx| window.close_dialog(cx)));
+            },
+            Some(Some(report)) => report.clone(),
+        };
+
+        let mut instances = v_flex().gap_1();
+        for instance in report.instances.iter() {
+            instances = instances.child(h_flex()
+                .gap_3()
+                .p_2()
+                .border_1()
+                .rounded(radius)
+                .border_color(border)
+                .child(v_flex()
+                    .flex_1()
+                    .child(div().font_medium().child(SharedString::from(instance.name.as_str())))
+                    .child(div().text_sm().text_color(muted_foreground)
+                        .child(ts!("disk_usage.instance_details", unique = format_bytes(instance.unique_bytes)))))
+                .child(format_bytes(instance.total_bytes)));
+        }
+
+        let orphaned_bytes: u64 = report.orphaned_library.iter().map(|entry| entry.bytes).sum();
+        let natives_bytes: u64 = report.old_natives.iter().map(|entry| entry.bytes).sum();
+
+        let content = v_flex()
+            .gap_3()
+            .child(ts!("disk_usage.total", size = format_bytes(report.total_bytes)))
+            .child(div().max_h_64().overflow_y_scrollbar().child(instances))
+            .child(v_flex()
+                .gap_1()
+                .child(ts!("disk_usage.content_library", num = report.content_library_files, size = format_bytes(report.content_library_bytes)))
+                .child(ts!("disk_usage.content_meta", size = format_bytes(report.content_meta_bytes))))
+            .child(div().font_medium().child(ts!("disk_usage.cleanup")))
+            .child(v_flex()
+                .gap_1()
+                .text_sm()
+                .text_color(muted_foreground)
+                .child(ts!("disk_usage.orphaned_library", num = report.orphaned_library.len(), size = format_bytes(orphaned_bytes)))
+                .child(ts!("disk_usage.orphaned_meta", num = report.orphaned_meta_entries))
+                .child(ts!("disk_usage.old_natives", num = report.old_natives.len(), size = format_bytes(natives_bytes))));
+
+        modal
+            .title(ts!("disk_usage.title"))
+            .child(content)
+            .footer(h_flex().gap_2().w_full()
+                .child(Button::new("close").flex_1().label(ts!("common.ok"))
+                    .on_click(|_, window, cx| window.close_dialog(cx)))
+                .child(Button::new("cleanup").flex_1().warning()
+                    .label(ts!("disk_usage.move_to_trash", size = format_bytes(report.reclaimable_bytes())))
+                    .disabled(!report.has_cleanup())
+                    .on_click(cx.listener(|this, _, window, cx| {
+                        let modal_action = ModalAction::default();
+                        this.backend_handle.send(MessageToBackend::CleanupDiskUsage {
+                            modal_action: modal_action.clone(),
+                        });
+                        window.close_dialog(cx);
+                        modals::generic::show_modal(window, cx, ts!("disk_usage.cleaning_up"), ts!("disk_usage.cleanup_error"), modal_action);
+                    }))))
+    }
+}
+
+fn format_bytes(bytes: u64) -> SharedString {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return format!("{bytes} B").into();
+    }
+
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{value:.1} {}", UNITS[unit]).into()
+}
+
+pub fn open_disk_usage(
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let state = cx.new(|cx| DiskUsageModalState::new(backend_handle, cx));
+
+    window.open_dialog(cx, move |modal, window, cx| {
+        cx.update_entity(&state, |state, cx| {
+            state.render(modal, window, cx)
+        })
+    });
+}
@@ -0,0 +1,181 @@
+use std::{path::Path, sync::Arc};
+
+use bridge::{
+    export::InstanceExportFormat, handle::BackendHandle, instance::InstanceID, message::MessageToBackend, modal_action::ModalAction, safe_path::SafePath
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    Selectable, WindowExt, checkbox::Checkbox, button::{Button, ButtonGroup, ButtonVariants}, dialog::Dialog, h_flex, input::{Input, InputState}, scroll::ScrollableElement, v_flex
+};
+
+use crate::{entity::instance::InstanceEntry, modals, ts};
+
+const DEFAULT_INCLUDED: &[&str] = &["config", "options.txt"];
+const ALWAYS_EXCLUDED: &[&str] = &["mods", "resourcepacks", "shaderpacks", "datapacks", "logs", "crash-reports", "natives"];
+
+struct ExportInstanceModalState {
+    id: InstanceID,
+    instance_name: SharedString,
+    backend_handle: BackendHandle,
+    format: InstanceExportFormat,
+    name_input_state: Entity<InputState>,
+    version_input_state: Entity<InputState>,
+    entries: Vec<(SharedString, bool)>,
+}
+
+impl ExportInstanceModalState {
+    pub fn new(instance: &InstanceEntry, backend_handle: BackendHandle, window: &mut Window, cx: &mut Context<Self>) -> Self {
+        let instance_name = instance.name.clone();
+        let name_input_state = cx.new(|cx| InputState::new(window, cx).default_value(instance_name.clone()));
+        let version_input_state = cx.new(|cx| InputState::new(window, cx).default_value("1.0.0"));
+
+        let mut entries = Vec::new();
+        if let Ok(read_dir) = std::fs::read_dir(&instance.dot_minecraft_folder) {
+            for entry in read_dir {
+                let Ok(entry) = entry else {
+                    continue;
+                };
+                let Some(filename) = entry.file_name().to_str().map(str::to_string) else {
+                    continue;
+                };
+                if filename.starts_with('.') || filename.starts_with("pandora.") || ALWAYS_EXCLUDED.contains(&filename.as_str()) {
+                    continue;
+                }
+                let included = DEFAULT_INCLUDED.contains(&filename.as_str());
+                entries.push((SharedString::from(filename), included));
+            }
+        }
+        entries.sort_by(|(a, _), (b, _)| lexical_sort::natural_lexical_cmp(a, b));
+
+        Self {
+            id: instance.id,
+            instance_name,
+            backend_handle,
+            format: InstanceExportFormat::Modrinth,
+            name_input_state,
+            version_input_state,
+            entries,
+        }
+    }
+
+    pub fn render(&mut self, modal: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let mut include_list = v_flex().gap_1();
+        if self.entries.is_empty() {
+            include_list = include_list.child(ts!("instance.export.no_files"));
+        }
+        for (index, (filename, included)) in self.entries.iter().enumerate() {
+            include_list = include_list.child(
+                Checkbox::new(("include", index))
+                    .label(filename.clone())
+                    .checked(*included)
+                    .on_click(cx.listener(move |this, value, _, cx| {
+                        if let Some((_, included)) = this.entries.get_mut(index) {
+                            *included = *value;
+                            cx.notify();
+                        }
+                    })),
+            );
+        }
+
+        let format_button_group = ButtonGroup::new("format")
+            .outline()
+            .child(
+                Button::new("format-modrinth")
+                    .label(ts!("modrinth.name"))
+                    .selected(self.format == InstanceExportFormat::Modrinth),
+            )
+            .child(
+                Button::new("format-curseforge")
+                    .label(ts!("curseforge.name"))
+                    .selected(self.format == InstanceExportFormat::Curseforge),
+            )
+            .on_click(cx.listener(move |this, selected: &Vec<usize>, _, cx| {
+                match selected.first() {
+                    Some(0) => this.format = InstanceExportFormat::Modrinth,
+                    Some(1) => this.format = InstanceExportFormat::Curseforge,
+                    _ => {},
+                };
+                cx.notify();
+            }));
+
+        let content = v_flex()
+            .gap_3()
+            .child(crate::labelled(ts!("instance.export.format"), format_button_group))
+            .child(crate::labelled(ts!("instance.export.name"), Input::new(&self.name_input_state)))
+            .child(crate::labelled(ts!("instance.export.version"), Input::new(&self.version_input_state)))
+            .child(crate::labelled(ts!("instance.export.include"), div().max_h_64().overflow_y_scrollbar().child(include_list)));
+
+        modal
+            .title(ts!("instance.export.title", name = self.instance_name))
+            .child(content)
+            .footer(h_flex().gap_2().w_full()
+                .child(Button::new("cancel").flex_1().label(ts!("common.cancel"))
+                    .on_click(|_, window, cx| window.close_dialog(cx)))
+                .child(Button::new("export").flex_1().success().label(ts!("instance.export.confirm"))
+                    .on_click(cx.listener(move |this, _, window, cx| {
+                        let mut name = this.name_input_state.read(cx).value().clone();
+                        if name.trim_ascii().is_empty() {
+                            name = this.instance_name.clone();
+                        }
+                        let mut version = this.version_input_state.read(cx).value().clone();
+                        if version.trim_ascii().is_empty() {
+                            version = "1.0.0".into();
+                        }
+
+                        let overrides: Arc<[SafePath]> = this.entries.iter()
+                            .filter(|(_, included)| *included)
+                            .filter_map(|(filename, _)| SafePath::new(filename))
+                            .collect();
+
+                        let user_dirs = directories::UserDirs::new();
+                        let directory = user_dirs.as_ref()
+                            .and_then(directories::UserDirs::desktop_dir).unwrap_or(Path::new("."));
+                        let suggested_name = format!("{}.{}", name.trim_ascii(), this.format.extension());
+
+                        let receiver = cx.prompt_for_new_path(directory, Some(&suggested_name));
+
+                        let id = this.id;
+                        let format = this.format;
+                        let backend_handle = this.backend_handle.clone();
+                        window.spawn(cx, async move |cx| {
+                            let Ok(Ok(Some(path))) = receiver.await else {
+                                return;
+                            };
+
+                            let modal_action = ModalAction::default();
+                            backend_handle.send(MessageToBackend::ExportInstance {
+                                id,
+                                format,
+                                name: name.trim_ascii().into(),
+                                version: version.trim_ascii().into(),
+                                overrides,
+                                output: path.into(),
+                                modal_action: modal_action.clone(),
+                            });
+
+                            _ = cx.update(move |window, cx| {
+                                modals::generic::show_modal(window, cx, ts!("instance.export.progress"), ts!("instance.export.error"), modal_action);
+                            });
+                        }).detach();
+
+                        window.close_dialog(cx);
+                    }))))
+    }
+}
+
+pub fn open_export_instance(
+    instance: &InstanceEntry,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let state = cx.new(|cx| {
+        ExportInstanceModalState::new(instance, backend_handle, window, cx)
+    });
+
+    window.open_dialog(cx, move |modal, window, cx| {
+        cx.update_entity(&state, |state, cx| {
+            state.render(modal, window, cx)
+        })
+    });
+}
@@ -0,0 +1,139 @@
+use std::sync::Arc;
+
+use bridge::{
+    handle::BackendHandle, instance::{InstanceID, InstanceSnapshotSummary}, message::MessageToBackend, modal_action::ModalAction
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme as _, Sizable, WindowExt, button::{Button, ButtonVariants}, dialog::Dialog, h_flex, scroll::ScrollableElement, spinner::Spinner, v_flex
+};
+use schema::instance::InstanceSnapshotReason;
+
+use crate::{entity::instance::InstanceEntry, modals, ts};
+
+struct InstanceSnapshotsModalState {
+    id: InstanceID,
+    instance_name: SharedString,
+    backend_handle: BackendHandle,
+    snapshots: Option<Arc<[InstanceSnapshotSummary]>>,
+    _get_snapshots_task: Task<()>,
+}
+
+impl InstanceSnapshotsModalState {
+    pub fn new(instance: &InstanceEntry, backend_handle: BackendHandle, cx: &mut Context<Self>) -> Self {
+        let mut this = Self {
+            id: instance.id,
+            instance_name: instance.name.clone(),
+            backend_handle,
+            snapshots: None,
+            _get_snapshots_task: Task::ready(()),
+        };
+        this.get_snapshots(cx);
+        this
+    }
+
+    fn get_snapshots(&mut self, cx: &mut Context<Self>) {
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self._get_snapshots_task = cx.spawn(async move |state, cx| {
+            let snapshots = recv.await.unwrap_or_else(|_| Arc::from([]));
+            let _ = state.update(cx, move |state, cx| {
+                state.snapshots = Some(snapshots);
+                cx.notify();
+            });
+        });
+
+        self.backend_handle.send(MessageToBackend::GetInstanceSnapshots {
+            id: self.id,
+            channel: send,
+        });
+    }
+
+    pub fn render(&mut self, modal: Dialog, _window: &mut Window, cx: &mut Context<Self>) -> Dialog {
+        let (radius, border, muted_foreground) = {
+            let theme = cx.theme();
+            (theme.radius, theme.border, theme.muted_foreground)
+        };
+
+        let content = match &self.snapshots {
+            None => h_flex().gap_3().child(ts!("instance.snapshots.loading")).child(Spinner::new()).into_any_element(),
+            Some(snapshots) if snapshots.is_empty() => h_flex().child(ts!("instance.snapshots.none")).into_any_element(),
+            Some(snapshots) => {
+                let mut list = v_flex().gap_1();
+                for (index, snapshot) in snapshots.iter().enumerate() {
+                    let reason = match snapshot.reason {
+                        InstanceSnapshotReason::Manual => ts!("instance.snapshots.reason.manual"),
+                        InstanceSnapshotReason::UpdateContent => ts!("instance.snapshots.reason.update_content"),
+                        InstanceSnapshotReason::MinecraftVersion => ts!("instance.snapshots.reason.minecraft_version"),
+                        InstanceSnapshotReason::Loader => ts!("instance.snapshots.reason.loader"),
+                        InstanceSnapshotReason::Modpack => ts!("instance.snapshots.reason.modpack"),
+                        InstanceSnapshotReason::Restore => ts!("instance.snapshots.reason.restore"),
+                        InstanceSnapshotReason::Lockfile => ts!("instance.snapshots.reason.lockfile"),
+                    };
+
+                    let name = snapshot.name.clone();
+                    list = list.child(h_flex()
+                        .gap_3()
+                        .p_2()
+                        .border_1()
+                        .rounded(radius)
+                        .border_color(border)
+                        .child(v_flex()
+                            .flex_1()
+                            .child(div().font_medium().child(format_created(snapshot.created_unix_ms)))
+                            .child(div().text_sm().text_color(muted_foreground)
+                                .child(ts!("instance.snapshots.details", reason = reason, num = snapshot.content_count))))
+                        .child(Button::new(("restore", index)).label(ts!("instance.snapshots.restore")).small().warning()
+                            .on_click(cx.listener(move |this, _, window, cx| {
+                                let modal_action = ModalAction::default();
+                                this.backend_handle.send(MessageToBackend::RestoreInstanceSnapshot {
+                                    id: this.id,
+                                    snapshot: name.clone(),
+                                    modal_action: modal_action.clone(),
+                                });
+                                window.close_dialog(cx);
+                                modals::generic::show_notification(window, cx, ts!("instance.snapshots.restore_error"), modal_action);
+                            }))));
+                }
+                div().max_h_96().overflow_y_scrollbar().child(list).into_any_element()
+            },
+        };
+
+        modal
+            .title(ts!("instance.snapshots.title", name = self.instance_name))
+            .child(v_flex().gap_3().child(ts!("instance.snapshots.description")).child(content))
+            .footer(h_flex().gap_2().w_full()
+                .child(Button::new("close").flex_1().label(ts!("common.ok"))
+                    .on_click(|_, window, cx| window.close_dialog(cx)))
+                .child(Button::new("take").flex_1().success().label(ts!("instance.snapshots.take"))
+                    .on_click(cx.listener(|this, _, _, cx| {
+                        this.backend_handle.send(MessageToBackend::CreateInstanceSnapshot { id: this.id });
+                        this.snapshots = None;
+                        this.get_snapshots(cx);
+                        cx.notify();
+                    }))))
+    }
+}
+
+fn format_created(unix_ms: i64) -> String {
+    let Some(date_time) = chrono::DateTime::from_timestamp_millis(unix_ms) else {
+        return String::new();
+    };
+    format!("{}", date_time.with_timezone(&chrono::Local).format("%Y-%m-%d %H:%M:%S"))
+}
+
+pub fn open_instance_snapshots(
+    instance: &InstanceEntry,
+    backend_handle: BackendHandle,
+    window: &mut Window,
+    cx: &mut App,
+) {
+    let state = cx.new(|cx| {
+        InstanceSnapshotsModalState::new(instance, backend_handle, cx)
+    });
+
+    window.open_dialog(cx, move |modal, window, cx| {
+        cx.update_entity(&state, |state, cx| {
+            state.render(modal, window, cx)
+        })
+    });
+}
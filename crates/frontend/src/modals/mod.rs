@@ -1,8 +1,12 @@
+pub mod crash_analysis;
 pub mod create_instance;
 pub mod curseforge_install;
 pub mod delete_instance;
 pub mod delete_skin;
+pub mod disk_usage;
+pub mod export_instance;
 pub mod generic;
+pub mod instance_snapshots;
 pub mod modrinth_install;
 pub mod modrinth_install_auto;
 pub mod rename_instance;
@@ -76,6 +76,7 @@ pub fn perform_datapack_install(
             ModrinthLoader::Fabric => loader_hint = Loader::Fabric,
             ModrinthLoader::Forge => loader_hint = Loader::Forge,
             ModrinthLoader::NeoForge => loader_hint = Loader::NeoForge,
+            ModrinthLoader::Quilt => loader_hint = Loader::Quilt,
             _ => {},
         }
     }
@@ -1133,6 +1134,8 @@ impl InstallDialog {
                                         ModrinthLoader::Fabric => loader_hint = Loader::Fabric,
                                         ModrinthLoader::Forge => loader_hint = Loader::Forge,
                                         ModrinthLoader::NeoForge => loader_hint = Loader::NeoForge,
+                                        ModrinthLoader::Quilt => loader_hint = Loader::Quilt,
+            ModrinthLoader::Quilt => loader_hint = Loader::Quilt,
                                         _ => {},
                                     }
                                 }
@@ -1,6 +1,6 @@
 use std::{path::Path, sync::Arc};
 
-use bridge::{handle::BackendHandle, message::{BackendConfigWithPassword, MessageToBackend}};
+use bridge::{handle::BackendHandle, java::JavaRuntimeInfo, message::{BackendConfigWithPassword, MessageToBackend}, modal_action::ModalAction};
 use gpui::{prelude::FluentBuilder, *};
 use gpui_component::{
     button::{Button, ButtonVariants},
@@ -13,7 +13,7 @@ use gpui_component::{
     tab::{Tab, TabBar},
     v_flex, ActiveTheme, Disableable, Sizable, ThemeRegistry,
 };
-use schema::backend_config::{BackendConfig, ProxyConfig, ProxyProtocol};
+use schema::backend_config::{BackendConfig, DownloadConfig, DownloadMirror, ProxyConfig, ProxyProtocol};
 
 use crate::{entity::DataEntities, icon::PandoraIcon, interface_config::InterfaceConfig, ts};
 
@@ -22,6 +22,7 @@ enum SettingsTab {
     #[default]
     Interface,
     Network,
+    Java,
 }
 
 struct Settings {
@@ -41,6 +42,11 @@ struct Settings {
     proxy_username_input: Entity<InputState>,
     proxy_password_input: Entity<InputState>,
     proxy_password_changed: bool,
+    download_concurrency_input: Entity<InputState>,
+    download_bandwidth_input: Entity<InputState>,
+    mirrors_input: Entity<InputState>,
+    java_runtimes: Option<Arc<[JavaRuntimeInfo]>>,
+    get_java_runtimes_task: Option<Task<()>>,
 }
 
 pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut App) -> impl Fn(Sheet, &mut Window, &mut App) -> Sheet + 'static {
@@ -85,6 +91,11 @@ pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut A
             state
         });
 
+        let download_concurrency_input = cx.new(|cx| InputState::new(window, cx).placeholder(DownloadConfig::DEFAULT_MAX_CONCURRENT.to_string()));
+        let download_bandwidth_input = cx.new(|cx| InputState::new(window, cx).placeholder(ts!("settings.downloads.unlimited")));
+        let mirrors_input = cx.new(|cx| InputState::new(window, cx).auto_grow(2, 8)
+            .placeholder("https://piston-meta.mojang.com https://mirror.example.com/piston-meta"));
+
         let mut settings = Settings {
             selected_tab: SettingsTab::Interface,
             theme_folder,
@@ -101,6 +112,11 @@ pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut A
             proxy_username_input,
             proxy_password_input,
             proxy_password_changed: false,
+            download_concurrency_input,
+            download_bandwidth_input,
+            mirrors_input,
+            java_runtimes: None,
+            get_java_runtimes_task: None,
         };
 
         cx.subscribe(&settings.proxy_protocol_select, Settings::on_proxy_protocol_changed).detach();
@@ -108,6 +124,9 @@ pub fn build_settings_sheet(data: &DataEntities, window: &mut Window, cx: &mut A
         cx.subscribe(&settings.proxy_port_input, Settings::on_proxy_input_changed).detach();
         cx.subscribe(&settings.proxy_username_input, Settings::on_proxy_input_changed).detach();
         cx.subscribe(&settings.proxy_password_input, Settings::on_proxy_password_changed).detach();
+        cx.subscribe(&settings.download_concurrency_input, Settings::on_download_input_changed).detach();
+        cx.subscribe(&settings.download_bandwidth_input, Settings::on_download_input_changed).detach();
+        cx.subscribe(&settings.mirrors_input, Settings::on_mirrors_input_changed).detach();
 
         settings.update_backend_configuration(window, cx);
 
@@ -160,6 +179,22 @@ impl Settings {
                     });
                 }
 
+                // Unset limits are left empty so that the placeholder shows the default
+                let downloads = result.config.downloads;
+                settings.download_concurrency_input.update(cx, |input, cx| {
+                    input.set_value(if downloads.max_concurrent == 0 { String::new() } else { downloads.max_concurrent.to_string() }, window, cx);
+                });
+                settings.download_bandwidth_input.update(cx, |input, cx| {
+                    input.set_value(if downloads.bandwidth_limit_kib == 0 { String::new() } else { downloads.bandwidth_limit_kib.to_string() }, window, cx);
+                });
+                let mirrors = result.config.mirrors.iter()
+                    .map(|mirror| format!("{} {}", mirror.origin, mirror.mirror))
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                settings.mirrors_input.update(cx, |input, cx| {
+                    input.set_value(mirrors, window, cx);
+                });
+
                 settings.backend_config = Some(result.config);
                 settings.get_configuration_task = None;
                 cx.notify();
@@ -176,6 +211,27 @@ impl Settings {
         });
     }
 
+    fn load_java_runtimes(&mut self, rescan: bool, cx: &mut Context<Self>) {
+        if self.get_java_runtimes_task.is_some() {
+            return;
+        }
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        self.get_java_runtimes_task = Some(cx.spawn(async move |page, cx| {
+            let result = recv.await.ok();
+            let _ = page.update(cx, move |settings, cx| {
+                settings.java_runtimes = Some(result.unwrap_or_else(|| Arc::from([])));
+                settings.get_java_runtimes_task = None;
+                cx.notify();
+            });
+        }));
+
+        self.backend_handle.send(MessageToBackend::GetJavaRuntimes {
+            rescan,
+            channel: send,
+        });
+    }
+
     fn on_proxy_protocol_changed(
         &mut self,
         _state: Entity<SelectState<Vec<&'static str>>>,
@@ -216,6 +272,68 @@ impl Settings {
         }
     }
 
+    fn on_download_input_changed(
+        &mut self,
+        _state: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Blur = event {
+            self.save_download_config(cx);
+        }
+    }
+
+    fn save_download_config(&mut self, cx: &mut Context<Self>) {
+        let config = DownloadConfig {
+            max_concurrent: self.download_concurrency_input.read(cx).value().parse().unwrap_or(0),
+            bandwidth_limit_kib: self.download_bandwidth_input.read(cx).value().parse().unwrap_or(0),
+        };
+
+        if let Some(backend_config) = &mut self.backend_config {
+            if backend_config.downloads == config {
+                return;
+            }
+            backend_config.downloads = config;
+        }
+
+        self.backend_handle.send(MessageToBackend::SetDownloadConfiguration { config });
+    }
+
+    fn on_mirrors_input_changed(
+        &mut self,
+        _state: Entity<InputState>,
+        event: &InputEvent,
+        cx: &mut Context<Self>,
+    ) {
+        if let InputEvent::Blur = event {
+            self.save_mirrors(cx);
+        }
+    }
+
+    /// Each line is an origin followed by the mirror to use for it, separated by whitespace
+    fn save_mirrors(&mut self, cx: &mut Context<Self>) {
+        let mirrors: Vec<DownloadMirror> = self.mirrors_input.read(cx).value().lines()
+            .filter_map(|line| {
+                let mut parts = line.split_whitespace();
+                let origin = parts.next()?;
+                let mirror = parts.next()?;
+                Some(DownloadMirror {
+                    origin: origin.into(),
+                    mirror: mirror.into(),
+                })
+            })
+            .collect();
+
+        if let Some(backend_config) = &mut self.backend_config {
+            if backend_config.mirrors == mirrors {
+                return;
+            }
+            backend_config.mirrors = mirrors.clone();
+        }
+
+        self.backend_handle.send(MessageToBackend::SetDownloadMirrors { mirrors });
+    }
+
     fn get_proxy_config(&self, cx: &App) -> ProxyConfig {
         let protocol_name = self.proxy_protocol_select.read(cx).selected_value()
             .map(|s| *s)
@@ -296,7 +414,24 @@ impl Settings {
                             InterfaceConfig::get_mut(cx).quick_delete_skins = *value;
                         }))
                     )
-            );
+            )
+            .child(crate::labelled(ts!("settings.storage.title"),
+                v_flex().gap_2()
+                    .child(Button::new("disk-usage").info().icon(PandoraIcon::HardDrive).label(ts!("settings.storage.disk_usage")).on_click({
+                        let backend_handle = self.backend_handle.clone();
+                        move |_, window, cx| {
+                            crate::modals::disk_usage::open_disk_usage(backend_handle.clone(), window, cx);
+                        }
+                    }))
+                    .child(Button::new("verify-all").info().label(ts!("settings.storage.verify_all")).on_click({
+                        let backend_handle = self.backend_handle.clone();
+                        move |_, window, cx| {
+                            let modal_action = ModalAction::default();
+                            backend_handle.send(MessageToBackend::VerifyAllInstances { modal_action: modal_action.clone() });
+                            crate::modals::generic::show_modal(window, cx, ts!("settings.storage.verifying"), ts!("settings.storage.verify_error"), modal_action);
+                        }
+                    }))
+            ));
 
         if let Some(backend_config) = &self.backend_config {
             div = div
@@ -328,6 +463,21 @@ impl Settings {
                                 InterfaceConfig::get_mut(cx).quit_on_main_closed = *value;
                             }))
                 ))
+                .child(crate::labelled(
+                    ts!("settings.updates.title"),
+                    Checkbox::new("background-update-checks")
+                        .label(ts!("settings.updates.background_check"))
+                        .checked(backend_config.background_update_checks)
+                        .on_click(cx.listener({
+                            let backend_handle = self.backend_handle.clone();
+                            move |settings, value, window, cx| {
+                                backend_handle.send(MessageToBackend::SetBackgroundUpdateChecks {
+                                    value: *value
+                                });
+                                settings.update_backend_configuration(window, cx);
+                            }
+                        }))
+                ))
         } else {
             div = div.child(Spinner::new().large());
         }
@@ -354,11 +504,58 @@ impl Settings {
     fn render_network_tab(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
         let proxy_enabled = self.proxy_enabled;
         let proxy_auth_enabled = self.proxy_auth_enabled;
+        let offline_mode = self.backend_config.as_ref().map(|backend_config| backend_config.offline_mode);
 
         v_flex()
             .px_4()
             .py_3()
             .gap_3()
+            .when_some(offline_mode, |this, offline_mode| {
+                this.child(crate::labelled(
+                    ts!("settings.offline.title"),
+                    v_flex().gap_2()
+                        .child(Checkbox::new("offline-mode")
+                            .label(ts!("settings.offline.force"))
+                            .checked(offline_mode)
+                            .on_click(cx.listener({
+                                let backend_handle = self.backend_handle.clone();
+                                move |settings, value, window, cx| {
+                                    backend_handle.send(MessageToBackend::SetOfflineMode {
+                                        value: *value
+                                    });
+                                    settings.update_backend_configuration(window, cx);
+                                }
+                            })))
+                        .child(div()
+                            .text_sm()
+                            .text_color(cx.theme().muted_foreground)
+                            .child(ts!("settings.offline.note")))
+                ))
+            })
+            .child(crate::labelled(
+                ts!("settings.downloads.title"),
+                v_flex().gap_2()
+                    .child(h_flex().gap_2()
+                        .child(v_flex().gap_1().flex_1()
+                            .child(ts!("settings.downloads.max_concurrent"))
+                            .child(NumberInput::new(&self.download_concurrency_input)))
+                        .child(v_flex().gap_1().flex_1()
+                            .child(ts!("settings.downloads.bandwidth_limit"))
+                            .child(NumberInput::new(&self.download_bandwidth_input))))
+                    .child(div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(ts!("settings.downloads.note")))
+            ))
+            .child(crate::labelled(
+                ts!("settings.mirrors.title"),
+                v_flex().gap_2()
+                    .child(Input::new(&self.mirrors_input))
+                    .child(div()
+                        .text_sm()
+                        .text_color(cx.theme().muted_foreground)
+                        .child(ts!("settings.mirrors.note")))
+            ))
             .child(crate::labelled(
                 ts!("settings.proxy.title"),
                 v_flex().gap_2()
@@ -413,6 +610,72 @@ impl Settings {
                 .text_color(cx.theme().muted_foreground)
                 .child(ts!("settings.proxy.launcher_only_note")))
     }
+
+    fn render_java_tab(&mut self, _window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
+        if self.java_runtimes.is_none() {
+            self.load_java_runtimes(false, cx);
+        }
+
+        let scanning = self.get_java_runtimes_task.is_some();
+
+        let mut content = v_flex()
+            .px_4()
+            .py_3()
+            .gap_3()
+            .child(Button::new("rescan-java").info().icon(PandoraIcon::RefreshCcw).label(ts!("settings.java.rescan"))
+                .disabled(scanning)
+                .on_click(cx.listener(|settings, _, _, cx| {
+                    settings.load_java_runtimes(true, cx);
+                    cx.notify();
+                })));
+
+        let (Some(runtimes), Some(backend_config)) = (&self.java_runtimes, &self.backend_config) else {
+            return content.child(Spinner::new().large());
+        };
+        if scanning {
+            content = content.child(Spinner::new().large());
+        }
+        if runtimes.is_empty() {
+            return content.child(ts!("settings.java.none_found"));
+        }
+
+        let muted_foreground = cx.theme().muted_foreground;
+
+        let mut major_versions: Vec<u32> = runtimes.iter().map(|runtime| runtime.major_version).collect();
+        major_versions.dedup();
+
+        for major_version in major_versions {
+            let default = backend_config.java_defaults.get(&major_version);
+
+            let mut list = v_flex().gap_2();
+            for (index, runtime) in runtimes.iter().enumerate().filter(|(_, runtime)| runtime.major_version == major_version) {
+                let is_default = default.is_some_and(|default| *default == runtime.path);
+                list = list.child(v_flex()
+                    .child(Checkbox::new(("java-default", index))
+                        .label(SharedString::from(format!("{} {}", runtime.vendor, runtime.version)))
+                        .checked(is_default)
+                        .on_click(cx.listener({
+                            let backend_handle = self.backend_handle.clone();
+                            let path = runtime.path.clone();
+                            move |settings, value, window, cx| {
+                                backend_handle.send(MessageToBackend::SetDefaultJavaRuntime {
+                                    major_version,
+                                    path: value.then(|| path.clone()),
+                                });
+                                settings.update_backend_configuration(window, cx);
+                            }
+                        })))
+                    .child(div().text_sm().text_color(muted_foreground).child(SharedString::from(runtime.path.to_string_lossy().into_owned()))));
+            }
+
+            content = content.child(crate::labelled(ts!("settings.java.default_for", version = major_version), list));
+        }
+
+        content.child(div()
+            .text_sm()
+            .text_color(muted_foreground)
+            .child(ts!("settings.java.default_note")))
+    }
 }
 impl Render for Settings {
     fn render(&mut self, window: &mut Window, cx: &mut Context<Self>) -> impl IntoElement {
@@ -423,14 +686,17 @@ impl Render for Settings {
             .selected_index(match selected_tab {
                 SettingsTab::Interface => 0,
                 SettingsTab::Network => 1,
+                SettingsTab::Java => 2,
             })
             .underline()
             .child(Tab::new().label(ts!("settings.interface")))
             .child(Tab::new().label(ts!("settings.network")))
+            .child(Tab::new().label(ts!("settings.java.title")))
             .on_click(cx.listener(|settings, index, _window, cx| {
                 settings.selected_tab = match index {
                     0 => SettingsTab::Interface,
                     1 => SettingsTab::Network,
+                    2 => SettingsTab::Java,
                     _ => SettingsTab::Interface,
                 };
                 cx.notify();
@@ -439,6 +705,7 @@ impl Render for Settings {
         let content = match selected_tab {
             SettingsTab::Interface => self.render_interface_tab(window, cx).into_any_element(),
             SettingsTab::Network => self.render_network_tab(window, cx).into_any_element(),
+            SettingsTab::Java => self.render_java_tab(window, cx).into_any_element(),
         };
 
         v_flex()
@@ -639,11 +639,12 @@ impl CurseforgeSearchPage {
                                 NubAction::ErrorCheckingForUpdates => {}
                                 NubAction::UpToDate => {}
                                 NubAction::Update(ids) => {
-                                    for id in ids {
+                                    for (index, id) in ids.iter().enumerate() {
                                         let modal_action = ModalAction::default();
                                         data.backend_handle.send(MessageToBackend::UpdateContent {
                                             instance: install_for.unwrap(),
                                             content_id: *id,
+                                            snapshot: index == 0,
                                             modal_action: modal_action.clone(),
                                         });
                                         crate::modals::generic::show_notification(
@@ -943,11 +944,13 @@ impl Render for CurseforgeSearchPage {
                 .child(Button::new("fabric").label(ts!("modrinth.category.fabric")).selected(self.filter_loaders.contains(Loader::Fabric)))
                 .child(Button::new("forge").label(ts!("modrinth.category.forge")).selected(self.filter_loaders.contains(Loader::Forge)))
                 .child(Button::new("neoforge").label(ts!("modrinth.category.neoforge")).selected(self.filter_loaders.contains(Loader::NeoForge)))
+                .child(Button::new("quilt").label(ts!("modrinth.category.quilt")).selected(self.filter_loaders.contains(Loader::Quilt)))
                 .on_click(cx.listener(|page, clicked: &Vec<usize>, window, cx| {
                     page.set_filter_loaders(clicked.iter().filter_map(|index| match index {
                         0 => Some(Loader::Fabric),
                         1 => Some(Loader::Forge),
                         2 => Some(Loader::NeoForge),
+                        3 => Some(Loader::Quilt),
                         _ => None
                     }).collect(), window, cx);
                 })))
@@ -0,0 +1,189 @@
+use std::{path::{Path, PathBuf}, sync::{atomic::Ordering, Arc}};
+
+use bridge::{
+    handle::BackendHandle,
+    install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget},
+    instance::InstanceID,
+    message::{AtomicBridgeDataLoadState, MessageToBackend},
+    serial::AtomicOptionSerial,
+};
+use gpui::{prelude::*, *};
+use gpui_component::{
+    ActiveTheme as _, Sizable, WindowExt,
+    button::{Button, ButtonVariants},
+    h_flex,
+    input::SelectAll,
+    list::ListState,
+    notification::{Notification, NotificationType},
+    v_flex,
+};
+use schema::{content::ContentSource, loader::Loader};
+use ustr::Ustr;
+
+use crate::{component::content_list::ContentListDelegate, entity::instance::InstanceEntry, ts};
+
+pub struct InstanceDatapacksSubpage {
+    instance: InstanceID,
+    instance_loader: Loader,
+    instance_version: Ustr,
+    backend_handle: BackendHandle,
+    datapacks_state: Arc<AtomicBridgeDataLoadState>,
+    datapack_list: Entity<ListState<ContentListDelegate>>,
+    load_serial: AtomicOptionSerial,
+    _add_from_file_task: Option<Task<()>>,
+}
+
+impl InstanceDatapacksSubpage {
+    pub fn new(
+        instance: &Entity<InstanceEntry>,
+        backend_handle: BackendHandle,
+        window: &mut gpui::Window,
+        cx: &mut gpui::Context<Self>,
+    ) -> Self {
+        let instance = instance.read(cx);
+        let instance_loader = instance.configuration.loader;
+        let instance_version = instance.configuration.minecraft_version;
+        let instance_id = instance.id;
+
+        let datapacks_state = Arc::clone(&instance.datapacks_state);
+
+        let mut datapacks_list_delegate = ContentListDelegate::new(instance_id, backend_handle.clone(), instance_loader, instance_version);
+        datapacks_list_delegate.set_content(instance.datapacks.read(cx));
+
+        let datapacks = instance.datapacks.clone();
+
+        let datapack_list = cx.new(move |cx| {
+            cx.observe(&datapacks, |list: &mut ListState<ContentListDelegate>, datapacks, cx| {
+                let actual_datapacks = datapacks.read(cx);
+                list.delegate_mut().set_content(actual_datapacks);
+                cx.notify();
+            })
+            .detach();
+
+            ListState::new(datapacks_list_delegate, window, cx)
+                .selectable(false)
+                .searchable(true)
+        });
+
+        Self {
+            instance: instance_id,
+            instance_loader,
+            instance_version,
+            backend_handle,
+            datapacks_state,
+            datapack_list,
+            load_serial: AtomicOptionSerial::default(),
+            _add_from_file_task: None,
+        }
+    }
+
+    fn install_paths(&self, paths: &[PathBuf], window: &mut Window, cx: &mut App) {
+        let content_install = ContentInstall {
+            target: InstallTarget::Instance(self.instance),
+            loader_hint: self.instance_loader,
+            version_hint: Some(self.instance_version.into()),
+            datapack_world: None,
+            files: paths
+                .into_iter()
+                .filter_map(|path| {
+                    Some(ContentInstallFile {
+                        replace_old: None,
+                        path: bridge::install::ContentInstallPath::Raw(
+                            Path::new("datapacks").join(path.file_name()?).into(),
+                        ),
+                        download: ContentDownload::File { path: path.clone() },
+                        content_source: ContentSource::Manual,
+                    })
+                })
+                .collect(),
+        };
+        crate::root::start_install(content_install, &self.backend_handle, window, cx);
+    }
+}
+
+impl Render for InstanceDatapacksSubpage {
+    fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
+        let theme = cx.theme();
+
+        let state = self.datapacks_state.load(Ordering::SeqCst);
+        if state.should_send_load_request() {
+            self.backend_handle
+                .send_with_serial(MessageToBackend::RequestLoadDatapacks { id: self.instance }, &self.load_serial);
+        }
+
+        let header = h_flex()
+            .gap_3()
+            .mb_1()
+            .ml_1()
+            .child(div().text_lg().child(ts!("instance.content.datapacks")))
+            .child(Button::new("update").label("Check for updates").success().compact().small().on_click({
+                let backend_handle = self.backend_handle.clone();
+                let instance_id = self.instance;
+                move |_, window, cx| {
+                    crate::root::start_update_check(instance_id, &backend_handle, window, cx);
+                }
+            }))
+            .child(Button::new("addfile").label(ts!("instance.content.install.from_file")).success().compact().small().on_click({
+                cx.listener(move |this, _, window, cx| {
+                    let receiver = cx.prompt_for_paths(PathPromptOptions {
+                        files: true,
+                        directories: false,
+                        multiple: true,
+                        prompt: Some(ts!("instance.content.install.select_datapacks")),
+                    });
+
+                    let entity = cx.entity();
+                    let add_from_file_task = window.spawn(cx, async move |cx| {
+                        let Ok(result) = receiver.await else {
+                            return;
+                        };
+                        _ = cx.update_window_entity(&entity, move |this, window, cx| match result {
+                            Ok(Some(paths)) => {
+                                this.install_paths(&paths, window, cx);
+                            },
+                            Ok(None) => {},
+                            Err(error) => {
+                                let error = format!("{}", error);
+                                let notification =
+                                    Notification::new().autohide(false).with_type(NotificationType::Error).title(error);
+                                window.push_notification(notification, cx);
+                            },
+                        });
+                    });
+                    this._add_from_file_task = Some(add_from_file_task);
+                })
+            }));
+
+        v_flex().p_4().size_full().child(header).child(
+            div()
+                .id("datapack-list-area")
+                .drag_over(|style, _: &ExternalPaths, _, cx| style.bg(cx.theme().accent))
+                .on_drop(cx.listener(|this, paths: &ExternalPaths, window, cx| {
+                    this.install_paths(paths.paths(), window, cx);
+                }))
+                .size_full()
+                .border_1()
+                .rounded(theme.radius)
+                .border_color(theme.border)
+                .child(self.datapack_list.clone())
+                .on_click({
+                    let datapack_list = self.datapack_list.clone();
+                    move |_, _, cx| {
+                        cx.update_entity(&datapack_list, |list, _| {
+                            list.delegate_mut().clear_selection();
+                        })
+                    }
+                })
+                .key_context("Input")
+                .on_action({
+                    let datapack_list = self.datapack_list.clone();
+                    move |_: &SelectAll, _, cx| {
+                        cx.update_entity(&datapack_list, |list, cx| {
+                            list.delegate_mut().select_all();
+                            cx.notify();
+                        })
+                    }
+                }),
+        )
+    }
+}
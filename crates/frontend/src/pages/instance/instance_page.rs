@@ -1,3 +1,5 @@
+use std::sync::Arc;
+
 use bridge::{
     handle::BackendHandle,
     instance::{InstanceID, InstanceStatus},
@@ -5,12 +7,12 @@ use bridge::{
 };
 use gpui::{prelude::*, *};
 use gpui_component::{
-    WindowExt, button::{Button, ButtonGroup, ButtonVariants}, h_flex, tab::{Tab, TabBar}, v_flex
+    WindowExt, button::{Button, ButtonGroup, ButtonVariants, DropdownButton}, h_flex, menu::PopupMenuItem, tab::{Tab, TabBar}, v_flex
 };
 use serde::{Deserialize, Serialize};
 
 use crate::{
-    entity::{DataEntities, instance::InstanceEntry}, icon::PandoraIcon, interface_config::InterfaceConfig, pages::{instance::{logs_subpage::InstanceLogsSubpage, mods_subpage::InstanceModsSubpage, quickplay_subpage::InstanceQuickplaySubpage, resource_packs_subpage::InstanceResourcePacksSubpage, settings_subpage::InstanceSettingsSubpage}, page::{Page, page_layout}}, root, ts
+    entity::{DataEntities, instance::InstanceEntry}, icon::PandoraIcon, interface_config::InterfaceConfig, pages::{instance::{datapacks_subpage::InstanceDatapacksSubpage, logs_subpage::InstanceLogsSubpage, mods_subpage::InstanceModsSubpage, quickplay_subpage::InstanceQuickplaySubpage, resource_packs_subpage::InstanceResourcePacksSubpage, settings_subpage::InstanceSettingsSubpage, shader_packs_subpage::InstanceShaderPacksSubpage}, page::{Page, page_layout}}, root, ts
 };
 
 pub struct InstancePage {
@@ -45,11 +47,34 @@ impl Page for InstancePage {
 
         let button = match instance.status {
             InstanceStatus::NotRunning => {
-                Button::new("start_instance").success().icon(PandoraIcon::Play).label(ts!("instance.start.label")).on_click(
+                let start_button = Button::new("start_instance").success().icon(PandoraIcon::Play).label(ts!("instance.start.label")).on_click({
+                    let name = name.clone();
+                    let backend_handle = backend_handle.clone();
                     move |_, window, cx| {
-                        root::start_instance(id, name.clone(), None, false, &backend_handle, window, cx);
-                    },
-                ).into_any_element()
+                        root::start_instance(id, name.clone(), None, None, false, &backend_handle, window, cx);
+                    }
+                });
+
+                let profiles: Arc<[Arc<str>]> = instance.configuration.launch_profiles.iter().map(|profile| profile.name.clone()).collect();
+                if profiles.is_empty() {
+                    start_button.into_any_element()
+                } else {
+                    DropdownButton::new("start_instance_profile")
+                        .success()
+                        .button(start_button)
+                        .dropdown_menu(move |mut menu, _, _| {
+                            for profile in profiles.iter() {
+                                let name = name.clone();
+                                let backend_handle = backend_handle.clone();
+                                let profile = profile.clone();
+                                menu = menu.item(PopupMenuItem::new(SharedString::from(profile.to_string())).on_click(move |_, window, cx| {
+                                    root::start_instance(id, name.clone(), None, Some(profile.clone()), false, &backend_handle, window, cx);
+                                }));
+                            }
+                            menu
+                        })
+                        .into_any_element()
+                }
             },
             InstanceStatus::Launching => {
                 Button::new("launching").warning().icon(PandoraIcon::Loader).label(ts!("instance.start.starting")).into_any_element()
@@ -109,6 +134,7 @@ impl Page for InstancePage {
                                                                 id,
                                                                 name.clone(),
                                                                 None,
+                                                                None,
                                                                 true,
                                                                 &backend_handle,
                                                                 window,
@@ -134,7 +160,43 @@ impl Page for InstancePage {
             }
         });
 
-        h_flex().gap_3().child(button).child(open_dot_minecraft_button)
+        let last_launch_button = instance.stats.last_launch_timings.clone().map(|timings| {
+            Button::new("last_launch_timings")
+                .icon(PandoraIcon::ChartNoAxesCombined)
+                .label(ts!("instance.launch_timings.last_launch", time = format_duration_ms(timings.total_ms)))
+                .on_click(move |_, window, cx| {
+                    let timings = timings.clone();
+                    window.open_dialog(cx, move |dialog, _, _| {
+                        let phases = timings.phases.iter().map(|timing| {
+                            let mut name = ts!(format!("instance.launch_timings.phase.{}", timing.phase.id()));
+                            if timing.concurrent {
+                                name = ts!("instance.launch_timings.concurrent", phase = name);
+                            }
+                            h_flex()
+                                .gap_4()
+                                .justify_between()
+                                .child(name)
+                                .child(format_duration_ms(timing.duration_ms))
+                        });
+
+                        dialog
+                            .title(ts!("instance.launch_timings.title"))
+                            .child(v_flex()
+                                .gap_1()
+                                .children(phases)
+                                .child(h_flex()
+                                    .gap_4()
+                                    .justify_between()
+                                    .font_bold()
+                                    .child(ts!("instance.launch_timings.total"))
+                                    .child(format_duration_ms(timings.total_ms)))
+                                .child(div().h_2())
+                                .child(ts!("instance.launch_timings.note")))
+                    });
+                })
+        });
+
+        h_flex().gap_3().child(button).children(last_launch_button).child(open_dot_minecraft_button)
     }
 
     fn scrollable(&self, _cx: &App) -> bool {
@@ -154,7 +216,9 @@ impl Render for InstancePage {
             InstanceSubpage::Logs(_) => 1,
             InstanceSubpage::Mods(_) => 2,
             InstanceSubpage::ResourcePacks(_) => 3,
-            InstanceSubpage::Settings(_) => 4,
+            InstanceSubpage::ShaderPacks(_) => 4,
+            InstanceSubpage::Datapacks(_) => 5,
+            InstanceSubpage::Settings(_) => 6,
         };
 
         let tab_bar = TabBar::new("bar")
@@ -165,6 +229,8 @@ impl Render for InstancePage {
             .child(Tab::new().label(ts!("instance.logs.title")))
             .child(Tab::new().label(ts!("instance.content.mods")))
             .child(Tab::new().label(ts!("instance.content.resourcepacks")))
+            .child(Tab::new().label(ts!("instance.content.shaders")))
+            .child(Tab::new().label(ts!("instance.content.datapacks")))
             .child(Tab::new().label(ts!("settings.title")))
             .on_click(cx.listener(|_, index, _, cx| {
                 let page_type = match *index {
@@ -172,7 +238,9 @@ impl Render for InstancePage {
                     1 => InstanceSubpageType::Logs,
                     2 => InstanceSubpageType::Mods,
                     3 => InstanceSubpageType::ResourcePacks,
-                    4 => InstanceSubpageType::Settings,
+                    4 => InstanceSubpageType::ShaderPacks,
+                    5 => InstanceSubpageType::Datapacks,
+                    6 => InstanceSubpageType::Settings,
                     _ => {
                         return;
                     },
@@ -193,6 +261,14 @@ impl Render for InstancePage {
     }
 }
 
+fn format_duration_ms(ms: u64) -> String {
+    if ms < 1000 {
+        format!("{ms}ms")
+    } else {
+        format!("{:.1}s", ms as f64 / 1000.0)
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
 pub enum InstanceSubpageType {
@@ -201,6 +277,8 @@ pub enum InstanceSubpageType {
     Logs,
     Mods,
     ResourcePacks,
+    ShaderPacks,
+    Datapacks,
     Settings,
 }
 
@@ -226,6 +304,12 @@ impl InstanceSubpageType {
             InstanceSubpageType::ResourcePacks => InstanceSubpage::ResourcePacks(cx.new(|cx| {
                 InstanceResourcePacksSubpage::new(instance, backend_handle, window, cx)
             })),
+            InstanceSubpageType::ShaderPacks => InstanceSubpage::ShaderPacks(cx.new(|cx| {
+                InstanceShaderPacksSubpage::new(instance, backend_handle, window, cx)
+            })),
+            InstanceSubpageType::Datapacks => InstanceSubpage::Datapacks(cx.new(|cx| {
+                InstanceDatapacksSubpage::new(instance, backend_handle, window, cx)
+            })),
             InstanceSubpageType::Settings => InstanceSubpage::Settings(cx.new(|cx| {
                 InstanceSettingsSubpage::new(instance, data, backend_handle, window, cx)
             })),
@@ -239,6 +323,8 @@ pub enum InstanceSubpage {
     Logs(Entity<InstanceLogsSubpage>),
     Mods(Entity<InstanceModsSubpage>),
     ResourcePacks(Entity<InstanceResourcePacksSubpage>),
+    ShaderPacks(Entity<InstanceShaderPacksSubpage>),
+    Datapacks(Entity<InstanceDatapacksSubpage>),
     Settings(Entity<InstanceSettingsSubpage>),
 }
 
@@ -249,6 +335,8 @@ impl InstanceSubpage {
             InstanceSubpage::Logs(_) => InstanceSubpageType::Logs,
             InstanceSubpage::Mods(_) => InstanceSubpageType::Mods,
             InstanceSubpage::ResourcePacks(_) => InstanceSubpageType::ResourcePacks,
+            InstanceSubpage::ShaderPacks(_) => InstanceSubpageType::ShaderPacks,
+            InstanceSubpage::Datapacks(_) => InstanceSubpageType::Datapacks,
             InstanceSubpage::Settings(_) => InstanceSubpageType::Settings,
         }
     }
@@ -259,6 +347,8 @@ impl InstanceSubpage {
             Self::Logs(entity) => entity.into_any_element(),
             Self::Mods(entity) => entity.into_any_element(),
             Self::ResourcePacks(entity) => entity.into_any_element(),
+            Self::ShaderPacks(entity) => entity.into_any_element(),
+            Self::Datapacks(entity) => entity.into_any_element(),
             Self::Settings(entity) => entity.into_any_element(),
         }
     }
@@ -1,9 +1,10 @@
 use std::{path::{Path, PathBuf}, sync::{atomic::Ordering, Arc}};
 
 use bridge::{
+    dependency::{ContentIssue, ContentIssueKind},
     handle::BackendHandle,
     install::{ContentDownload, ContentInstall, ContentInstallFile, InstallTarget},
-    instance::{InstanceContentID, InstanceContentSummary, InstanceID},
+    instance::{InstanceBisectStatus, InstanceContentID, InstanceContentSummary, InstanceID},
     message::{AtomicBridgeDataLoadState, MessageToBackend},
     serial::AtomicOptionSerial,
 };
@@ -14,6 +15,7 @@ use gpui_component::{
     h_flex,
     input::SelectAll,
     list::ListState,
+    scroll::ScrollableElement,
     notification::{Notification, NotificationType},
     v_flex,
 };
@@ -26,8 +28,12 @@ pub struct InstanceModsSubpage {
     instance: InstanceID,
     instance_loader: Loader,
     instance_version: Ustr,
+    instance_loader_version: Option<Ustr>,
     instance_name: SharedString,
     backend_handle: BackendHandle,
+    issues: Arc<[ContentIssue]>,
+    show_all_issues: bool,
+    bisect: Entity<Option<InstanceBisectStatus>>,
     mods_state: Arc<AtomicBridgeDataLoadState>,
     mod_list: Entity<ListState<ContentListDelegate>>,
     load_serial: AtomicOptionSerial,
@@ -44,6 +50,7 @@ impl InstanceModsSubpage {
         let instance = instance.read(cx);
         let instance_loader = instance.configuration.loader;
         let instance_version = instance.configuration.minecraft_version;
+        let instance_loader_version = instance.configuration.preferred_loader_version;
         let instance_id = instance.id;
         let instance_name = instance.name.clone();
 
@@ -53,6 +60,15 @@ impl InstanceModsSubpage {
         mods_list_delegate.set_content(instance.mods.read(cx));
 
         let mods = instance.mods.clone();
+        let bisect = instance.bisect.clone();
+
+        let issues = analyze_mods(mods.read(cx), instance_loader, instance_version, instance_loader_version);
+        cx.observe(&mods, move |this: &mut Self, mods, cx| {
+            this.issues = analyze_mods(mods.read(cx), this.instance_loader, this.instance_version, this.instance_loader_version);
+            cx.notify();
+        }).detach();
+        cx.observe(&bisect, |_, _, cx| cx.notify()).detach();
+        backend_handle.send(MessageToBackend::RequestBisectStatus { id: instance_id });
 
         let mod_list = cx.new(move |cx| {
             cx.observe(&mods, |list: &mut ListState<ContentListDelegate>, mods, cx| {
@@ -69,8 +85,12 @@ impl InstanceModsSubpage {
             instance: instance_id,
             instance_loader,
             instance_version,
+            instance_loader_version,
             instance_name,
             backend_handle,
+            issues,
+            show_all_issues: false,
+            bisect,
             mods_state,
             mod_list,
             load_serial: AtomicOptionSerial::default(),
@@ -100,10 +120,141 @@ impl InstanceModsSubpage {
         };
         crate::root::start_install(content_install, &self.backend_handle, window, cx);
     }
+
+    fn render_bisect(&self, cx: &mut gpui::Context<Self>) -> Option<AnyElement> {
+        let status = self.bisect.read(cx).clone()?;
+
+        let (radius, info, muted_foreground) = {
+            let theme = cx.theme();
+            (theme.radius, theme.info, theme.muted_foreground)
+        };
+
+        let content = if let Some(result) = &status.result {
+            let message = if result.is_empty() {
+                ts!("instance.content.bisect.no_result")
+            } else {
+                ts!("instance.content.bisect.result", mods = result.join(", "))
+            };
+            v_flex()
+                .gap_1()
+                .child(div().font_medium().child(ts!("instance.content.bisect.finished")))
+                .child(message)
+                .child(div().text_color(muted_foreground).child(ts!("instance.content.bisect.restored")))
+                .child(h_flex().gap_2().child(Button::new("bisect-done").label(ts!("common.ok")).compact().small().on_click(cx.listener(|this, _, _, _| {
+                    this.backend_handle.send(MessageToBackend::StopBisect { id: this.instance });
+                }))))
+        } else {
+            let step = status.step;
+            v_flex()
+                .gap_1()
+                .child(div().font_medium().child(ts!("instance.content.bisect.step", step = status.step, num = status.estimated_steps_left())))
+                .child(ts!("instance.content.bisect.instructions", enabled = status.enabled.len(), candidates = status.candidates))
+                .child(div().text_color(muted_foreground).child(ts!("instance.content.bisect.crash_hint")))
+                .child(h_flex()
+                    .gap_2()
+                    .child(Button::new("bisect-good").label(ts!("instance.content.bisect.good")).success().compact().small().on_click(cx.listener(move |this, _, _, _| {
+                        this.backend_handle.send(MessageToBackend::ReportBisectResult { id: this.instance, step, good: true });
+                    })))
+                    .child(Button::new("bisect-bad").label(ts!("instance.content.bisect.bad")).danger().compact().small().on_click(cx.listener(move |this, _, _, _| {
+                        this.backend_handle.send(MessageToBackend::ReportBisectResult { id: this.instance, step, good: false });
+                    })))
+                    .child(Button::new("bisect-stop").label(ts!("instance.content.bisect.stop")).compact().small().on_click(cx.listener(|this, _, _, _| {
+                        this.backend_handle.send(MessageToBackend::StopBisect { id: this.instance });
+                    }))))
+        };
+
+        Some(div()
+            .mb_2()
+            .px_4()
+            .py_2()
+            .text_sm()
+            .border_1()
+            .rounded(radius)
+            .border_color(info)
+            .bg(info.opacity(0.08))
+            .child(content)
+            .into_any_element())
+    }
+
+    fn render_issues(&self, cx: &mut gpui::Context<Self>) -> Option<AnyElement> {
+        if self.issues.is_empty() {
+            return None;
+        }
+
+        const COLLAPSED_ISSUES: usize = 3;
+        let shown = if self.show_all_issues { self.issues.len() } else { COLLAPSED_ISSUES.min(self.issues.len()) };
+
+        let (radius, warning, red, muted_foreground) = {
+            let theme = cx.theme();
+            (theme.radius, theme.warning, theme.red, theme.muted_foreground)
+        };
+
+        let mut list = v_flex().gap_0p5();
+        for issue in self.issues.iter().take(shown) {
+            let color = if issue.is_error() { red } else { muted_foreground };
+            list = list.child(div().text_color(color).child(issue_message(issue)));
+        }
+
+        let toggle_label = if self.show_all_issues {
+            Some(ts!("instance.content.issues.show_less"))
+        } else if self.issues.len() > COLLAPSED_ISSUES {
+            Some(ts!("instance.content.issues.show_all"))
+        } else {
+            None
+        };
+
+        Some(v_flex()
+            .mb_2()
+            .px_4()
+            .py_2()
+            .gap_1()
+            .text_sm()
+            .border_1()
+            .rounded(radius)
+            .border_color(warning)
+            .bg(warning.opacity(0.08))
+            .child(h_flex()
+                .gap_3()
+                .child(div().flex_1().font_medium().child(ts!("instance.content.issues.title", num = self.issues.len())))
+                .when_some(toggle_label, |this, label| {
+                    this.child(Button::new("toggle-issues").label(label).compact().small().on_click(cx.listener(|this, _, _, cx| {
+                        this.show_all_issues = !this.show_all_issues;
+                        cx.notify();
+                    })))
+                }))
+            .child(div().max_h_64().overflow_y_scrollbar().child(list))
+            .when(!self.show_all_issues && self.issues.len() > shown, |this| {
+                this.child(div().text_color(muted_foreground).child(ts!("instance.content.issues.more", num = self.issues.len() - shown)))
+            })
+            .into_any_element())
+    }
+}
+
+fn analyze_mods(mods: &[InstanceContentSummary], loader: Loader, minecraft_version: Ustr, loader_version: Option<Ustr>) -> Arc<[ContentIssue]> {
+    let mut issues = bridge::dependency::analyze_content(mods, minecraft_version.as_str(), loader, loader_version.as_ref().map(Ustr::as_str));
+    issues.sort_by_key(|issue| !issue.is_error());
+    issues.into()
+}
+
+fn issue_message(issue: &ContentIssue) -> SharedString {
+    let name = &issue.name;
+    match &issue.kind {
+        ContentIssueKind::MissingDependency { id, required } => ts!("instance.content.issues.missing_dependency", name = name, id = id, required = required),
+        ContentIssueKind::MissingRecommendation { id } => ts!("instance.content.issues.missing_recommendation", name = name, id = id),
+        ContentIssueKind::WrongDependencyVersion { id, required, found } => ts!("instance.content.issues.wrong_dependency_version", name = name, id = id, required = required, found = found),
+        ContentIssueKind::Incompatible { id, found } => ts!("instance.content.issues.incompatible", name = name, id = id, found = found),
+        ContentIssueKind::WrongMinecraftVersion { required } => ts!("instance.content.issues.wrong_minecraft_version", name = name, required = required),
+        ContentIssueKind::WrongLoaderVersion { id, required } => ts!("instance.content.issues.wrong_loader_version", name = name, id = id, required = required),
+        ContentIssueKind::WrongLoader => ts!("instance.content.issues.wrong_loader", name = name, file = issue.filename),
+        ContentIssueKind::Duplicate { id } => ts!("instance.content.issues.duplicate", name = name, id = id),
+    }
 }
 
 impl Render for InstanceModsSubpage {
     fn render(&mut self, _window: &mut gpui::Window, cx: &mut gpui::Context<Self>) -> impl gpui::IntoElement {
+        let bisect = self.render_bisect(cx);
+        let issues = self.render_issues(cx);
+        let bisecting = self.bisect.read(cx).is_some();
         let theme = cx.theme();
 
         let state = self.mods_state.load(Ordering::SeqCst);
@@ -117,6 +268,11 @@ impl Render for InstanceModsSubpage {
             .mb_1()
             .ml_1()
             .child(div().text_lg().child("Mods"))
+            .when(!bisecting, |this| {
+                this.child(Button::new("bisect").label(ts!("instance.content.bisect.start")).tooltip(ts!("instance.content.bisect.start_tooltip")).compact().small().on_click(cx.listener(|this, _, _, _| {
+                    this.backend_handle.send(MessageToBackend::StartBisect { id: this.instance });
+                })))
+            })
             .child(Button::new("update").label("Check for updates").success().compact().small().on_click({
                 let backend_handle = self.backend_handle.clone();
                 let instance_id = self.instance;
@@ -173,7 +329,7 @@ impl Render for InstanceModsSubpage {
                 })
             }));
 
-        v_flex().p_4().size_full().child(header).child(
+        v_flex().p_4().size_full().child(header).children(bisect).children(issues).child(
             div()
                 .id("mod-list-area")
                 .drag_over(|style, _: &ExternalPaths, _, cx| style.bg(cx.theme().accent))
@@ -295,6 +295,7 @@ impl ListDelegate for WorldsListDelegate {
                                 id,
                                 name.clone(),
                                 Some(QuickPlayLaunch::Singleplayer(target.clone())),
+                                None,
                                 false,
                                 &backend_handle,
                                 window,
@@ -443,6 +444,7 @@ impl ListDelegate for ServersListDelegate {
                                 id,
                                 name.clone(),
                                 Some(QuickPlayLaunch::Multiplayer(target.clone())),
+                                None,
                                 false,
                                 &backend_handle,
                                 window,
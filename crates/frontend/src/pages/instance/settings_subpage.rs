@@ -1,6 +1,6 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
-use bridge::{handle::BackendHandle, instance::InstanceID, message::MessageToBackend, meta::MetadataRequest};
+use bridge::{handle::BackendHandle, instance::InstanceID, java::JavaRuntimeInfo, message::{LaunchCommandExport, MessageToBackend}, meta::MetadataRequest, modal_action::ModalAction};
 use gpui::{prelude::*, *};
 use gpui_component::{
     ActiveTheme as _, Disableable, Sizable, WindowExt,
@@ -17,11 +17,13 @@ use schema::{
     fabric_loader_manifest::FabricLoaderManifest,
     forge::{ForgeMavenManifest, NeoforgeMavenManifest},
     instance::{
-        AUTO_LIBRARY_PATH_GLFW, AUTO_LIBRARY_PATH_OPENAL, InstanceJvmBinaryConfiguration,
+        AUTO_LIBRARY_PATH_GLFW, AUTO_LIBRARY_PATH_OPENAL, InstanceGameArgumentsConfiguration, InstanceHooksConfiguration, InstanceJvmBinaryConfiguration,
+        InstanceLaunchProfile, InstanceQuickPlayTarget,
         InstanceJvmFlagsConfiguration, InstanceLinuxWrapperConfiguration, InstanceMemoryConfiguration,
-        InstanceSystemLibrariesConfiguration, InstanceWrapperCommandConfiguration, LwjglLibraryPath,
+        InstanceSystemLibrariesConfiguration, InstanceWindowConfiguration, InstanceWrapperCommandConfiguration, LwjglLibraryPath,
     },
     loader::Loader,
+    quilt_loader_manifest::QuiltLoaderManifest,
     version_manifest::MinecraftVersionManifest,
 };
 use strum::IntoEnumIterator;
@@ -61,6 +63,12 @@ pub struct InstanceSettingsSubpage {
     loader_versions_state: TypelessFrontendMetadataResult,
     loader_version_select_state: Entity<SelectState<SearchableVec<&'static str>>>,
     disable_file_syncing: bool,
+    custom_window_size: bool,
+    window_width_input_state: Entity<InputState>,
+    window_height_input_state: Entity<InputState>,
+    fullscreen: bool,
+    demo: bool,
+    auto_join_server_input_state: Entity<InputState>,
 
     memory_override_enabled: bool,
     memory_min_input_state: Entity<InputState>,
@@ -69,8 +77,17 @@ pub struct InstanceSettingsSubpage {
     wrapper_command_input_state: Entity<InputState>,
     jvm_flags_enabled: bool,
     jvm_flags_input_state: Entity<InputState>,
+    game_arguments_enabled: bool,
+    game_arguments_input_state: Entity<InputState>,
+    environment_input_state: Entity<InputState>,
+    pre_launch_hook_input_state: Entity<InputState>,
+    post_exit_hook_input_state: Entity<InputState>,
+    launch_profiles: Vec<InstanceLaunchProfile>,
+    new_launch_profile_input_state: Entity<InputState>,
     jvm_binary_enabled: bool,
     jvm_binary_path: Option<PathLabel>,
+    java_runtimes: Arc<[JavaRuntimeInfo]>,
+    java_runtime_select_state: Entity<SelectState<SearchableVec<SharedString>>>,
 
     instance_root_label: PathLabel,
 
@@ -95,6 +112,7 @@ pub struct InstanceSettingsSubpage {
     backend_handle: BackendHandle,
     _observe_loader_version_subscription: Option<Subscription>,
     _select_file_task: Task<()>,
+    _get_java_runtimes_task: Task<()>,
 }
 
 impl InstanceSettingsSubpage {
@@ -110,11 +128,18 @@ impl InstanceSettingsSubpage {
         let loader = entry.configuration.loader;
         let preferred_loader_version = entry.configuration.preferred_loader_version.map(|s| s.as_str()).unwrap_or("Latest");
         let disable_file_syncing = entry.configuration.disable_file_syncing;
+        let game_window = entry.configuration.window.unwrap_or_default();
+        let demo = entry.configuration.demo;
+        let auto_join_server = entry.configuration.auto_join_server.clone().unwrap_or_default();
 
         let memory = entry.configuration.memory.unwrap_or_default();
         let wrapper_command = entry.configuration.wrapper_command.clone().unwrap_or_default();
         let jvm_flags = entry.configuration.jvm_flags.clone().unwrap_or_default();
         let jvm_binary = entry.configuration.jvm_binary.clone().unwrap_or_default();
+        let game_arguments = entry.configuration.game_arguments.clone().unwrap_or_default();
+        let environment = entry.configuration.environment.iter().map(|(key, value)| format!("{key}={value}")).collect::<Vec<_>>().join("\n");
+        let hooks = entry.configuration.hooks.clone().unwrap_or_default();
+        let launch_profiles = entry.configuration.launch_profiles.clone();
         #[cfg(target_os = "linux")]
         let linux_wrapper = entry.configuration.linux_wrapper.unwrap_or_default();
         let system_libraries = entry.configuration.system_libraries.clone().unwrap_or_default();
@@ -172,6 +197,18 @@ impl InstanceSettingsSubpage {
         cx.subscribe_in(&memory_max_input_state, window, Self::on_memory_step).detach();
         cx.subscribe(&memory_max_input_state, Self::on_memory_changed).detach();
 
+        let window_width_input_state = cx.new(|cx| InputState::new(window, cx).default_value(game_window.width.to_string()));
+        cx.subscribe_in(&window_width_input_state, window, Self::on_window_size_step).detach();
+        cx.subscribe(&window_width_input_state, Self::on_window_size_changed).detach();
+        let window_height_input_state = cx.new(|cx| InputState::new(window, cx).default_value(game_window.height.to_string()));
+        cx.subscribe_in(&window_height_input_state, window, Self::on_window_size_step).detach();
+        cx.subscribe(&window_height_input_state, Self::on_window_size_changed).detach();
+
+        let auto_join_server_input_state = cx.new(|cx| {
+            InputState::new(window, cx).placeholder("play.example.com:25565").default_value(auto_join_server)
+        });
+        cx.subscribe(&auto_join_server_input_state, Self::on_auto_join_server_changed).detach();
+
         let wrapper_command_input_state = cx.new(|cx| {
             InputState::new(window, cx).auto_grow(1, 8).default_value(wrapper_command.flags)
         });
@@ -181,6 +218,40 @@ impl InstanceSettingsSubpage {
             cx.new(|cx| InputState::new(window, cx).auto_grow(1, 8).default_value(jvm_flags.flags));
         cx.subscribe(&jvm_flags_input_state, Self::on_jvm_flags_changed).detach();
 
+        let game_arguments_input_state =
+            cx.new(|cx| InputState::new(window, cx).auto_grow(1, 8).default_value(game_arguments.arguments));
+        cx.subscribe(&game_arguments_input_state, Self::on_game_arguments_changed).detach();
+
+        let environment_input_state = cx.new(|cx| {
+            InputState::new(window, cx).auto_grow(2, 8).placeholder("KEY=VALUE").default_value(environment)
+        });
+        cx.subscribe(&environment_input_state, Self::on_environment_changed).detach();
+
+        let pre_launch_hook_input_state =
+            cx.new(|cx| InputState::new(window, cx).auto_grow(1, 4).default_value(hooks.pre_launch));
+        cx.subscribe(&pre_launch_hook_input_state, Self::on_hooks_changed).detach();
+        let post_exit_hook_input_state =
+            cx.new(|cx| InputState::new(window, cx).auto_grow(1, 4).default_value(hooks.post_exit));
+        cx.subscribe(&post_exit_hook_input_state, Self::on_hooks_changed).detach();
+
+        let new_launch_profile_input_state =
+            cx.new(|cx| InputState::new(window, cx).placeholder(ts!("instance.launch_profiles.name")));
+
+        let java_runtime_select_state =
+            cx.new(|cx| SelectState::new(SearchableVec::new(Vec::new()), None, window, cx).searchable(true));
+        cx.subscribe(&java_runtime_select_state, Self::on_java_runtime_selected).detach();
+
+        let (send, recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::GetJavaRuntimes { rescan: false, channel: send });
+        let get_java_runtimes_task = cx.spawn_in(window, async move |page, cx| {
+            let Ok(runtimes) = recv.await else {
+                return;
+            };
+            let _ = page.update_in(cx, move |page, window, cx| {
+                page.update_java_runtimes(runtimes, window, cx);
+            });
+        });
+
         let mut page = Self {
             data: data.clone(),
             instance: instance.clone(),
@@ -192,6 +263,12 @@ impl InstanceSettingsSubpage {
             loader_select_state,
             loader_version_select_state,
             disable_file_syncing,
+            custom_window_size: game_window.custom_size,
+            window_width_input_state,
+            window_height_input_state,
+            fullscreen: game_window.fullscreen,
+            demo,
+            auto_join_server_input_state,
             memory_override_enabled: memory.enabled,
             memory_min_input_state,
             memory_max_input_state,
@@ -199,8 +276,17 @@ impl InstanceSettingsSubpage {
             wrapper_command_input_state,
             jvm_flags_enabled: jvm_flags.enabled,
             jvm_flags_input_state,
+            game_arguments_enabled: game_arguments.enabled,
+            game_arguments_input_state,
+            environment_input_state,
+            pre_launch_hook_input_state,
+            post_exit_hook_input_state,
+            launch_profiles,
+            new_launch_profile_input_state,
             jvm_binary_enabled: jvm_binary.enabled,
             jvm_binary_path: jvm_binary.path.clone().map(|path| PathLabel::new(path, false)),
+            java_runtimes: Arc::from([]),
+            java_runtime_select_state,
             override_glfw_enabled: system_libraries.override_glfw,
             override_glfw_path: glfw_path.map(|path| PathLabel::new(path, false)),
             override_openal_enabled: system_libraries.override_openal,
@@ -223,6 +309,7 @@ impl InstanceSettingsSubpage {
             loader_versions_state: TypelessFrontendMetadataResult::Loading,
             _observe_loader_version_subscription: None,
             _select_file_task: Task::ready(()),
+            _get_java_runtimes_task: get_java_runtimes_task,
         };
         page.update_minecraft_versions(minecraft_versions, window, cx);
         page.update_loader_versions(window, cx);
@@ -295,6 +382,14 @@ impl InstanceSettingsSubpage {
                 window,
                 cx,
             ),
+            Loader::Quilt => self.update_loader_versions_for_loader(
+                MetadataRequest::QuiltLoaderManifest,
+                |manifest: &QuiltLoaderManifest| {
+                    std::iter::once("Latest").chain(manifest.0.iter().map(|s| s.version.as_str())).collect()
+                },
+                window,
+                cx,
+            ),
             Loader::Forge => self.update_loader_versions_for_loader(
                 MetadataRequest::ForgeMavenManifest,
                 |manifest: &ForgeMavenManifest| {
@@ -453,6 +548,47 @@ impl InstanceSettingsSubpage {
         });
     }
 
+    fn update_java_runtimes(&mut self, runtimes: Arc<[JavaRuntimeInfo]>, window: &mut Window, cx: &mut Context<Self>) {
+        let labels: Vec<SharedString> = runtimes.iter().map(|runtime| SharedString::from(runtime.label())).collect();
+        let selected = self.jvm_binary_path.as_ref().and_then(|path| {
+            let path = path.path();
+            runtimes.iter().position(|runtime| runtime.path == path).map(|index| labels[index].clone())
+        });
+
+        self.java_runtime_select_state.update(cx, |select_state, cx| {
+            select_state.set_items(SearchableVec::new(labels), window, cx);
+            if let Some(selected) = selected {
+                select_state.set_selected_value(&selected, window, cx);
+            }
+        });
+
+        self.java_runtimes = runtimes;
+        cx.notify();
+    }
+
+    pub fn on_java_runtime_selected(
+        &mut self,
+        _state: Entity<SelectState<SearchableVec<SharedString>>>,
+        event: &SelectEvent<SearchableVec<SharedString>>,
+        cx: &mut Context<Self>,
+    ) {
+        let SelectEvent::Confirm(value) = event;
+        let Some(value) = value else {
+            return;
+        };
+        let Some(runtime) = self.java_runtimes.iter().find(|runtime| runtime.label() == value.as_str()) else {
+            return;
+        };
+
+        self.jvm_binary_enabled = true;
+        self.jvm_binary_path = Some(PathLabel::new(runtime.path.clone(), false));
+        self.backend_handle.send(MessageToBackend::SetInstanceJvmBinary {
+            id: self.instance_id,
+            jvm_binary: self.get_jvm_binary_configuration()
+        });
+        cx.notify();
+    }
+
     pub fn on_memory_step(
         &mut self,
         state: &Entity<InputState>,
@@ -502,6 +638,79 @@ impl InstanceSettingsSubpage {
         }
     }
 
+    pub fn on_window_size_step(
+        &mut self,
+        state: &Entity<InputState>,
+        event: &NumberInputEvent,
+        window: &mut Window,
+        cx: &mut Context<Self>,
+    ) {
+        match event {
+            NumberInputEvent::Step(step_action) => {
+                let Ok(value) = state.read(cx).value().parse::<u32>() else {
+                    return;
+                };
+                let value = match step_action {
+                    gpui_component::input::StepAction::Decrement => value.saturating_sub(1).max(1),
+                    gpui_component::input::StepAction::Increment => value.saturating_add(1),
+                };
+                state.update(cx, |input, cx| {
+                    input.set_value(value.to_string(), window, cx);
+                })
+            },
+        }
+    }
+
+    pub fn on_window_size_changed(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceWindow {
+                id: self.instance_id,
+                window: self.get_window_configuration(cx),
+            });
+        }
+    }
+
+    fn get_window_configuration(&self, cx: &App) -> InstanceWindowConfiguration {
+        let width = self.window_width_input_state.read(cx).value().parse::<u32>().unwrap_or(InstanceWindowConfiguration::DEFAULT_WIDTH);
+        let height = self.window_height_input_state.read(cx).value().parse::<u32>().unwrap_or(InstanceWindowConfiguration::DEFAULT_HEIGHT);
+
+        InstanceWindowConfiguration {
+            custom_size: self.custom_window_size,
+            width: width.max(1),
+            height: height.max(1),
+            fullscreen: self.fullscreen,
+        }
+    }
+
+    pub fn on_auto_join_server_changed(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            let server = self.auto_join_server_input_state.read(cx).value();
+            let server = server.trim();
+            self.backend_handle.send(MessageToBackend::SetInstanceAutoJoinServer {
+                id: self.instance_id,
+                server: (!server.is_empty()).then(|| server.into()),
+            });
+        }
+    }
+
+    pub fn on_game_arguments_changed(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceGameArguments {
+                id: self.instance_id,
+                game_arguments: self.get_game_arguments_configuration(cx),
+            });
+        }
+    }
+
+    fn get_game_arguments_configuration(&self, cx: &App) -> InstanceGameArgumentsConfiguration {
+        let arguments = self.game_arguments_input_state.read(cx).value();
+
+        InstanceGameArgumentsConfiguration {
+            enabled: self.game_arguments_enabled,
+            arguments: arguments.into(),
+        }
+    }
+
     pub fn on_wrapper_command_changed(
         &mut self,
         _: Entity<InputState>,
@@ -543,6 +752,76 @@ impl InstanceSettingsSubpage {
         }
     }
 
+    pub fn on_environment_changed(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceEnvironment {
+                id: self.instance_id,
+                environment: self.get_environment(cx),
+            });
+        }
+    }
+
+    fn get_environment(&self, cx: &App) -> BTreeMap<Arc<str>, Arc<str>> {
+        let environment = self.environment_input_state.read(cx).value();
+
+        environment.lines()
+            .filter_map(|line| line.split_once('='))
+            .filter(|(key, _)| !key.trim().is_empty())
+            .map(|(key, value)| (key.trim().into(), value.into()))
+            .collect()
+    }
+
+    pub fn on_hooks_changed(&mut self, _: Entity<InputState>, event: &InputEvent, cx: &mut Context<Self>) {
+        if let InputEvent::Change = event {
+            self.backend_handle.send(MessageToBackend::SetInstanceHooks {
+                id: self.instance_id,
+                hooks: self.get_hooks_configuration(cx),
+            });
+        }
+    }
+
+    fn get_hooks_configuration(&self, cx: &App) -> InstanceHooksConfiguration {
+        InstanceHooksConfiguration {
+            pre_launch: self.pre_launch_hook_input_state.read(cx).value().into(),
+            post_exit: self.post_exit_hook_input_state.read(cx).value().into(),
+        }
+    }
+
+    /// Saves the current memory, JVM, wrapper command, account and auto-join settings as a new launch profile
+    fn save_launch_profile(&mut self, window: &mut Window, cx: &mut Context<Self>) {
+        let name = self.new_launch_profile_input_state.read(cx).value().trim().to_string();
+        if name.is_empty() {
+            return;
+        }
+
+        let auto_join_server = self.auto_join_server_input_state.read(cx).value().trim().to_string();
+        let profile = InstanceLaunchProfile {
+            name: name.into(),
+            memory: self.memory_override_enabled.then(|| self.get_memory_configuration(cx)),
+            jvm_flags: self.jvm_flags_enabled.then(|| self.get_jvm_flags_configuration(cx)),
+            jvm_binary: self.jvm_binary_enabled.then(|| self.get_jvm_binary_configuration()),
+            wrapper_command: self.wrapper_command_enabled.then(|| self.get_wrapper_command_configuration(cx)),
+            account: self.data.accounts.read(cx).selected_account_uuid,
+            quick_play: (!auto_join_server.is_empty()).then(|| InstanceQuickPlayTarget::Multiplayer(auto_join_server.into())),
+        };
+
+        self.launch_profiles.retain(|existing| existing.name != profile.name);
+        self.launch_profiles.push(profile);
+        self.send_launch_profiles();
+
+        self.new_launch_profile_input_state.update(cx, |input, cx| {
+            input.set_value("", window, cx);
+        });
+        cx.notify();
+    }
+
+    fn send_launch_profiles(&self) {
+        self.backend_handle.send(MessageToBackend::SetInstanceLaunchProfiles {
+            id: self.instance_id,
+            launch_profiles: self.launch_profiles.clone(),
+        });
+    }
+
     fn get_jvm_binary_configuration(&self) -> InstanceJvmBinaryConfiguration {
         InstanceJvmBinaryConfiguration {
             enabled: self.jvm_binary_enabled,
@@ -638,6 +917,8 @@ impl Render for InstanceSettingsSubpage {
         let memory_override_enabled = self.memory_override_enabled;
         let wrapper_command_enabled = self.wrapper_command_enabled;
         let jvm_flags_enabled = self.jvm_flags_enabled;
+        let game_arguments_enabled = self.game_arguments_enabled;
+        let custom_window_size = self.custom_window_size;
         let jvm_binary_enabled = self.jvm_binary_enabled;
 
         let mut basic_content = v_flex()
@@ -699,6 +980,7 @@ impl Render for InstanceSettingsSubpage {
                                 Loader::Fabric => "Fabric Version: ",
                                 Loader::Forge => "Forge Version: ",
                                 Loader::NeoForge => "NeoForge Version: ",
+                                Loader::Quilt => "Quilt Version: ",
                                 Loader::Vanilla | Loader::Unknown => "Loader Version: ",
                             })
                             .w_full(),
@@ -727,6 +1009,58 @@ impl Render for InstanceSettingsSubpage {
                             disable_file_syncing: *value,
                         });
                     })),
+            ))
+            .child(crate::labelled(
+                ts!("instance.game.label"),
+                v_flex()
+                    .gap_1()
+                    .child(Checkbox::new("custom_window_size").label(ts!("instance.game.custom_window_size")).checked(custom_window_size).on_click(
+                        cx.listener(|page, value, _, cx| {
+                            if page.custom_window_size != *value {
+                                page.custom_window_size = *value;
+                                page.backend_handle.send(MessageToBackend::SetInstanceWindow {
+                                    id: page.instance_id,
+                                    window: page.get_window_configuration(cx),
+                                });
+                                cx.notify();
+                            }
+                        }),
+                    ))
+                    .child(
+                        h_flex()
+                            .gap_1()
+                            .child(NumberInput::new(&self.window_width_input_state).small().disabled(!custom_window_size))
+                            .child("×")
+                            .child(NumberInput::new(&self.window_height_input_state).small().disabled(!custom_window_size)),
+                    )
+                    .child(Checkbox::new("fullscreen").label(ts!("instance.game.fullscreen")).checked(self.fullscreen).on_click(
+                        cx.listener(|page, value, _, cx| {
+                            if page.fullscreen != *value {
+                                page.fullscreen = *value;
+                                page.backend_handle.send(MessageToBackend::SetInstanceWindow {
+                                    id: page.instance_id,
+                                    window: page.get_window_configuration(cx),
+                                });
+                                cx.notify();
+                            }
+                        }),
+                    ))
+                    .child(Checkbox::new("demo").label(ts!("instance.game.demo")).checked(self.demo).on_click(
+                        cx.listener(|page, value, _, cx| {
+                            if page.demo != *value {
+                                page.demo = *value;
+                                page.backend_handle.send(MessageToBackend::SetInstanceDemo {
+                                    id: page.instance_id,
+                                    demo: *value,
+                                });
+                                cx.notify();
+                            }
+                        }),
+                    )),
+            ))
+            .child(crate::labelled(
+                ts!("instance.game.auto_join_server"),
+                Input::new(&self.auto_join_server_input_state),
             ));
 
         let runtime_content = v_flex()
@@ -787,6 +1121,23 @@ impl Render for InstanceSettingsSubpage {
                     ))
                     .child(Input::new(&self.jvm_flags_input_state).disabled(!jvm_flags_enabled)),
             )
+            .child(
+                v_flex()
+                    .gap_1()
+                    .child(Checkbox::new("game_arguments").label(ts!("instance.game_arguments")).checked(game_arguments_enabled).on_click(
+                        cx.listener(|page, value, _, cx| {
+                            if page.game_arguments_enabled != *value {
+                                page.game_arguments_enabled = *value;
+                                page.backend_handle.send(MessageToBackend::SetInstanceGameArguments {
+                                    id: page.instance_id,
+                                    game_arguments: page.get_game_arguments_configuration(cx),
+                                });
+                                cx.notify();
+                            }
+                        }),
+                    ))
+                    .child(Input::new(&self.game_arguments_input_state).disabled(!game_arguments_enabled)),
+            )
             .child(v_flex()
                 .gap_1()
                 .child(Checkbox::new("jvm_binary").label(ts!("instance.jvm_binary")).checked(jvm_binary_enabled).on_click(cx.listener(|page, value, _, cx| {
@@ -808,6 +1159,9 @@ impl Render for InstanceSettingsSubpage {
                         });
                     }, window, cx);
                 })))
+                .child(Select::new(&self.java_runtime_select_state)
+                    .placeholder(ts!("instance.select_java_runtime"))
+                    .disabled(!jvm_binary_enabled || self.java_runtimes.is_empty()))
             )
             .child(v_flex()
                 .gap_1()
@@ -865,7 +1219,44 @@ impl Render for InstanceSettingsSubpage {
                     }
                 })))
                 .child(Input::new(&self.wrapper_command_input_state).disabled(!wrapper_command_enabled))
-            );
+            )
+            .child(crate::labelled(ts!("instance.environment"), Input::new(&self.environment_input_state)))
+            .child(v_flex()
+                .gap_2()
+                .child(crate::labelled(ts!("instance.hooks.pre_launch"), Input::new(&self.pre_launch_hook_input_state)))
+                .child(crate::labelled(ts!("instance.hooks.post_exit"), Input::new(&self.post_exit_hook_input_state)))
+                .child(div().text_sm().text_color(theme.muted_foreground).child(ts!("instance.hooks.note")))
+            )
+            .child(crate::labelled(
+                ts!("instance.launch_profiles.title"),
+                v_flex()
+                    .gap_1()
+                    .children(self.launch_profiles.iter().enumerate().map(|(index, profile)| {
+                        h_flex()
+                            .gap_2()
+                            .child(div().flex_1().child(SharedString::from(profile.name.to_string())))
+                            .child(Button::new(("remove_launch_profile", index)).icon(PandoraIcon::Trash2).small().danger().on_click(
+                                cx.listener(move |page, _, _, cx| {
+                                    if index < page.launch_profiles.len() {
+                                        page.launch_profiles.remove(index);
+                                        page.send_launch_profiles();
+                                        cx.notify();
+                                    }
+                                }),
+                            ))
+                    }))
+                    .child(
+                        h_flex()
+                            .gap_2()
+                            .child(div().flex_1().child(Input::new(&self.new_launch_profile_input_state)))
+                            .child(Button::new("save_launch_profile").icon(PandoraIcon::Plus).label(ts!("instance.launch_profiles.save")).on_click(
+                                cx.listener(|page, _, window, cx| {
+                                    page.save_launch_profile(window, cx);
+                                }),
+                            )),
+                    )
+                    .child(div().text_sm().text_color(theme.muted_foreground).child(ts!("instance.launch_profiles.note"))),
+            ));
 
         #[cfg(target_os = "linux")]
         let runtime_content = runtime_content.child(
@@ -992,6 +1383,98 @@ impl Render for InstanceSettingsSubpage {
                     .detach();
                 }
             }))
+            .child(Button::new("export").label(ts!("instance.export.label")).success().on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let instance = instance.read(cx).clone();
+                    crate::modals::export_instance::open_export_instance(&instance, backend_handle.clone(), window, cx);
+                }
+            }))
+            .child(Button::new("snapshots").label(ts!("instance.snapshots.label")).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let instance = instance.read(cx).clone();
+                    crate::modals::instance_snapshots::open_instance_snapshots(&instance, backend_handle.clone(), window, cx);
+                }
+            }))
+            .child(Button::new("verify").label(ts!("instance.verify.label")).tooltip(ts!("instance.verify.tooltip")).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let id = instance.read(cx).id;
+                    let modal_action = ModalAction::default();
+                    backend_handle.send(MessageToBackend::VerifyInstance { id, modal_action: modal_action.clone() });
+                    crate::modals::generic::show_modal(window, cx, ts!("instance.verify.verifying"), ts!("instance.verify.error"), modal_action);
+                }
+            }))
+            .child(Button::new("copy_launch_command").label(ts!("instance.launch_command.copy")).tooltip(ts!("instance.launch_command.tooltip")).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let id = instance.read(cx).id;
+                    let modal_action = ModalAction::default();
+                    let (send, recv) = tokio::sync::oneshot::channel();
+                    backend_handle.send(MessageToBackend::ExportLaunchCommand {
+                        id,
+                        quick_play: None,
+                        profile: None,
+                        export: LaunchCommandExport::CommandLine(send),
+                        modal_action: modal_action.clone(),
+                    });
+                    crate::modals::generic::show_modal(window, cx, ts!("instance.launch_command.resolving"), ts!("instance.launch_command.error"), modal_action);
+                    cx.spawn(async move |cx| {
+                        let Ok(command) = recv.await else {
+                            return;
+                        };
+                        _ = cx.update(|cx| {
+                            cx.write_to_clipboard(ClipboardItem::new_string(command.to_string()));
+                        });
+                    })
+                    .detach();
+                }
+            }))
+            .child(Button::new("export_launch_script").label(ts!("instance.launch_command.export")).tooltip(ts!("instance.launch_command.tooltip")).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let id = instance.read(cx).id;
+                    let modal_action = ModalAction::default();
+                    backend_handle.send(MessageToBackend::ExportLaunchCommand {
+                        id,
+                        quick_play: None,
+                        profile: None,
+                        export: LaunchCommandExport::Script,
+                        modal_action: modal_action.clone(),
+                    });
+                    crate::modals::generic::show_modal(window, cx, ts!("instance.launch_command.resolving"), ts!("instance.launch_command.error"), modal_action);
+                }
+            }))
+            .child(Button::new("write_lock").label(ts!("instance.lock.write")).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |_: &ClickEvent, window, cx| {
+                    let id = instance.read(cx).id;
+                    let modal_action = ModalAction::default();
+                    backend_handle.send(MessageToBackend::GenerateInstanceLock { id, modal_action: modal_action.clone() });
+                    crate::modals::generic::show_modal(window, cx, ts!("instance.lock.writing"), ts!("instance.lock.write_error"), modal_action);
+                }
+            }))
+            .child(Button::new("restore_lock").label(ts!("instance.lock.restore")).tooltip(ts!("instance.lock.restore_tooltip")).on_click({
+                let instance = self.instance.clone();
+                let backend_handle = self.backend_handle.clone();
+                move |click: &ClickEvent, window, cx| {
+                    let id = instance.read(cx).id;
+                    let modal_action = ModalAction::default();
+                    backend_handle.send(MessageToBackend::RestoreInstanceFromLock {
+                        id,
+                        remove_extra: click.modifiers().shift,
+                        modal_action: modal_action.clone(),
+                    });
+                    crate::modals::generic::show_modal(window, cx, ts!("instance.lock.restoring"), ts!("instance.lock.restore_error"), modal_action);
+                }
+            }))
             .child(Button::new("delete").label(ts!("instance.delete_instance")).danger().on_click({
                 let instance = self.instance.clone();
                 let backend_handle = self.backend_handle.clone();
@@ -6,12 +6,13 @@ use gpui_component::{
 use strum::IntoEnumIterator;
 
 use crate::{
-    component::{instance_list::InstanceList, named_dropdown::{NamedDropdown, NamedDropdownItem}, responsive_grid::ResponsiveGrid}, entity::{DataEntities, instance::InstanceEntries, metadata::FrontendMetadata}, icon::PandoraIcon, interface_config::{InstancesViewMode, InterfaceConfig}, pages::page::{Page, page_layout}, ts, ui::PageType
+    component::{instance_list::InstanceList, named_dropdown::{NamedDropdown, NamedDropdownItem}, responsive_grid::ResponsiveGrid}, entity::{DataEntities, instance::InstanceEntries, metadata::FrontendMetadata}, icon::PandoraIcon, interface_config::{InstancesSortMode, InstancesViewMode, InterfaceConfig}, pages::page::{Page, page_layout}, ts, ui::PageType
 };
 
 pub struct InstancesPage {
     instance_table: Entity<TableState<InstanceList>>,
     view_dropdown: Entity<SelectState<NamedDropdown<InstancesViewMode>>>,
+    sort_dropdown: Entity<SelectState<NamedDropdown<InstancesSortMode>>>,
 
     metadata: Entity<FrontendMetadata>,
     instances: Entity<InstanceEntries>,
@@ -40,9 +41,32 @@ impl InstancesPage {
             InterfaceConfig::get_mut(cx).instances_view_mode = view;
         }).detach();
 
+        let sort_dropdown = cx.new(|cx| {
+            let items = InstancesSortMode::iter().map(|sort| {
+                NamedDropdownItem { name: sort.name(), item: sort }
+            }).collect::<Vec<_>>();
+            let current_sort = InterfaceConfig::get(cx).instances_sort_mode;
+            let row = items.iter().position(|v| v.item == current_sort).unwrap_or(0);
+            let delegate = NamedDropdown::new(items);
+            SelectState::new(delegate, Some(IndexPath::new(row)), window, cx)
+        });
+        cx.subscribe(&sort_dropdown, |this, _, event: &SelectEvent<NamedDropdown<InstancesSortMode>>, cx| {
+            let SelectEvent::Confirm(Some(value)) = event else {
+                return;
+            };
+            let sort = value.item;
+
+            InterfaceConfig::get_mut(cx).instances_sort_mode = sort;
+            this.instance_table.update(cx, |table, cx| {
+                table.delegate_mut().set_sort_mode(sort, cx);
+                cx.notify();
+            });
+        }).detach();
+
         Self {
             instance_table,
             view_dropdown,
+            sort_dropdown,
             metadata: data.metadata.clone(),
             instances: data.instances.clone(),
             backend_handle: data.backend_handle.clone(),
@@ -61,8 +85,9 @@ impl Page for InstancesPage {
                     this.backend_handle.clone(), window, cx);
             }));
         let select_view = Select::new(&self.view_dropdown).title_prefix(format!("{}: ", ts!("instance.view")));
+        let select_sort = Select::new(&self.sort_dropdown).title_prefix(format!("{}: ", ts!("instance.sort.label")));
 
-        h_flex().gap_3().child(create_instance).child(select_view)
+        h_flex().gap_3().child(create_instance).child(select_sort).child(select_view)
     }
 
     fn scrollable(&self, cx: &App) -> bool {
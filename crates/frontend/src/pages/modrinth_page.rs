@@ -116,8 +116,10 @@ pub struct ModrinthSearchPage {
     image_cache: Entity<RetainAllImageCache>,
     mods_load_state: Option<(Arc<AtomicBridgeDataLoadState>, AtomicOptionSerial)>,
     resource_packs_load_state: Option<(Arc<AtomicBridgeDataLoadState>, AtomicOptionSerial)>,
+    shader_packs_load_state: Option<(Arc<AtomicBridgeDataLoadState>, AtomicOptionSerial)>,
     _instance_mods_subscription: Option<Subscription>,
     _instance_resourcepacks_subscription: Option<Subscription>,
+    _instance_shaderpacks_subscription: Option<Subscription>,
     _instance_worlds_subscription: Option<Subscription>,
     _refresh_trigger_subscription: Subscription,
     _shader_refresh_task: Option<Task<()>>,
@@ -159,8 +161,10 @@ impl ModrinthSearchPage {
 
         let mut mods_load_state = None;
         let mut resource_packs_load_state = None;
+        let mut shader_packs_load_state = None;
         let mut _instance_mods_subscription = None;
         let mut _instance_resourcepacks_subscription = None;
+        let mut _instance_shaderpacks_subscription = None;
         let mut _instance_worlds_subscription = None;
         let mut _shader_refresh_task = None;
 
@@ -181,7 +185,10 @@ impl ModrinthSearchPage {
                 let resource_packs_load_state = Some((instance.resource_packs_state.clone(), AtomicOptionSerial::default()));
 
                 let mods_entity = instance.mods.clone();
+                shader_packs_load_state = Some((instance.shader_packs_state.clone(), AtomicOptionSerial::default()));
+
                 let resource_packs_entity = instance.resource_packs.clone();
+                let shader_packs_entity = instance.shader_packs.clone();
                 let worlds_entity = instance.worlds.clone();
 
                 _instance_mods_subscription = Some(cx.observe(&mods_entity, |page, _mods, cx| {
@@ -194,6 +201,11 @@ impl ModrinthSearchPage {
                     cx.notify();
                 }));
 
+                _instance_shaderpacks_subscription = Some(cx.observe(&shader_packs_entity, |page, _shader_packs, cx| {
+                    page.refill_installed_content_from_instance(cx);
+                    cx.notify();
+                }));
+
                 _instance_worlds_subscription = Some(cx.observe(&worlds_entity, |page, _worlds, cx| {
                     page.refill_installed_content_from_instance(cx);
                     cx.notify();
@@ -246,8 +258,10 @@ impl ModrinthSearchPage {
             image_cache: RetainAllImageCache::new(cx),
             mods_load_state,
             resource_packs_load_state,
+            shader_packs_load_state,
             _instance_mods_subscription,
             _instance_resourcepacks_subscription,
+            _instance_shaderpacks_subscription,
             _instance_worlds_subscription,
             _refresh_trigger_subscription,
             _shader_refresh_task,
@@ -418,6 +432,19 @@ impl ModrinthSearchPage {
                     }
                 }
 
+                let shader_packs = instance.shader_packs.read(cx);
+                for summary in shader_packs.iter() {
+                    if let ContentSource::ModrinthProject { project } = &summary.content_source {
+                        let project_key: Arc<str> = Arc::from(project.to_lowercase().as_str());
+                        let installed = self.installed_shaders_by_project.entry(project_key).or_default();
+                        installed.push(InstalledContent {
+                            content_id: summary.id,
+                            status: summary.update,
+                            mod_id: summary.content_summary.id.clone(),
+                        });
+                    }
+                }
+
                 let shaderpacks_path = instance.dot_minecraft_folder.join("shaderpacks");
                 if shaderpacks_path.exists() {
                     if let Ok(entries) = std::fs::read_dir(&shaderpacks_path) {
@@ -923,11 +950,12 @@ impl ModrinthSearchPage {
                                         );
                                     },
                                     PrimaryAction::Update(ref ids) => {
-                                        for id in ids {
+                                        for (index, id) in ids.iter().enumerate() {
                                             let modal_action = ModalAction::default();
                                             data.backend_handle.send(MessageToBackend::UpdateContent {
                                                 instance: install_for.unwrap(),
                                                 content_id: *id,
+                                                snapshot: index == 0,
                                                 modal_action: modal_action.clone()
                                             });
                                             crate::modals::generic::show_notification(window, cx,
@@ -970,11 +998,12 @@ impl ModrinthSearchPage {
                                 NubAction::ErrorCheckingForUpdates => {},
                                 NubAction::UpToDate => {},
                                 NubAction::Update(ids) => {
-                                    for id in ids {
+                                    for (index, id) in ids.iter().enumerate() {
                                         let modal_action = ModalAction::default();
                                         data.backend_handle.send(MessageToBackend::UpdateContent {
                                             instance: install_for.unwrap(),
                                             content_id: *id,
+                                            snapshot: index == 0,
                                             modal_action: modal_action.clone()
                                         });
                                         crate::modals::generic::show_notification(window, cx,
@@ -1314,6 +1343,14 @@ impl Render for ModrinthSearchPage {
                 self.data.backend_handle.send_with_serial(MessageToBackend::RequestLoadResourcePacks { id: install_for }, load_serial);
             }
         }
+        if let Some((shader_packs_state, load_serial)) = &self.shader_packs_load_state
+            && let Some(install_for) = self.install_for
+        {
+            let state = shader_packs_state.load(std::sync::atomic::Ordering::SeqCst);
+            if state.should_send_load_request() {
+                self.data.backend_handle.send_with_serial(MessageToBackend::RequestLoadShaderPacks { id: install_for }, load_serial);
+            }
+        }
 
         let list = h_flex()
             .image_cache(self.image_cache.clone())
@@ -1405,11 +1442,13 @@ impl Render for ModrinthSearchPage {
                 .child(Button::new("fabric").label(ts!("modrinth.category.fabric")).selected(self.filter_loaders.contains(Loader::Fabric)))
                 .child(Button::new("forge").label(ts!("modrinth.category.forge")).selected(self.filter_loaders.contains(Loader::Forge)))
                 .child(Button::new("neoforge").label(ts!("modrinth.category.neoforge")).selected(self.filter_loaders.contains(Loader::NeoForge)))
+                .child(Button::new("quilt").label(ts!("modrinth.category.quilt")).selected(self.filter_loaders.contains(Loader::Quilt)))
                 .on_click(cx.listener(|page, clicked: &Vec<usize>, window, cx| {
                     page.set_filter_loaders(clicked.iter().filter_map(|index| match index {
                         0 => Some(Loader::Fabric),
                         1 => Some(Loader::Forge),
                         2 => Some(Loader::NeoForge),
+                        3 => Some(Loader::Quilt),
                         _ => None
                     }).collect(), window, cx);
                 })))
@@ -84,10 +84,13 @@ impl Processor {
                 root_path,
                 dot_minecraft_folder,
                 configuration,
+                stats,
                 worlds_state,
                 servers_state,
                 mods_state,
                 resource_packs_state,
+                shader_packs_state,
+                datapacks_state,
             } => {
                 InstanceEntries::add(
                     &self.data.instances,
@@ -97,10 +100,13 @@ impl Processor {
                     root_path,
                     dot_minecraft_folder,
                     configuration,
+                    stats,
                     worlds_state,
                     servers_state,
                     mods_state,
                     resource_packs_state,
+                    shader_packs_state,
+                    datapacks_state,
                     cx,
                 );
             },
@@ -114,6 +120,7 @@ impl Processor {
                 root_path,
                 dot_minecraft_folder,
                 configuration,
+                stats,
                 status,
             } => {
                 if status == InstanceStatus::Running {
@@ -145,6 +152,7 @@ impl Processor {
                     root_path,
                     dot_minecraft_folder,
                     configuration,
+                    stats,
                     status,
                     cx,
                 );
@@ -164,6 +172,12 @@ impl Processor {
             MessageToFrontend::InstanceResourcePacksUpdated { id, resource_packs } => {
                 InstanceEntries::set_resource_packs(&self.data.instances, id, resource_packs, cx);
             },
+            MessageToFrontend::InstanceShaderPacksUpdated { id, shader_packs } => {
+                InstanceEntries::set_shader_packs(&self.data.instances, id, shader_packs, cx);
+            },
+            MessageToFrontend::InstanceDatapacksUpdated { id, datapacks } => {
+                InstanceEntries::set_datapacks(&self.data.instances, id, datapacks, cx);
+            },
             MessageToFrontend::AddNotification { .. } => {
                 self.with_main_window(message, cx, |_, message, window, cx| {
                     let MessageToFrontend::AddNotification {
@@ -269,6 +283,23 @@ impl Processor {
                     cx,
                 );
             },
+            MessageToFrontend::InstanceBisectUpdated { id, status } => {
+                InstanceEntries::set_bisect(&self.data.instances, id, status, cx);
+            },
+            MessageToFrontend::CrashAnalyzed { .. } => {
+                self.with_main_window(message, cx, |this, message, window, cx| {
+                    let MessageToFrontend::CrashAnalyzed { analysis } = message else {
+                        unreachable!();
+                    };
+
+                    crate::modals::crash_analysis::open_crash_analysis(
+                        analysis,
+                        this.data.backend_handle.clone(),
+                        window,
+                        cx,
+                    );
+                });
+            },
         }
     }
 }
@@ -108,6 +108,7 @@ pub fn start_instance(
     id: InstanceID,
     name: SharedString,
     quick_play: Option<QuickPlayLaunch>,
+    profile: Option<Arc<str>>,
     allow_running_instance: bool,
     backend_handle: &BackendHandle,
     window: &mut Window,
@@ -118,6 +119,7 @@ pub fn start_instance(
     backend_handle.send(MessageToBackend::StartInstance {
         id,
         quick_play,
+        profile,
         allow_running_instance,
         modal_action: modal_action.clone(),
     });
@@ -162,6 +164,7 @@ pub fn start_update_check(
 pub fn update_single_mod(
     instance: InstanceID,
     mod_id: InstanceContentID,
+    snapshot: bool,
     backend_handle: &BackendHandle,
     window: &mut Window,
     cx: &mut App,
@@ -171,6 +174,7 @@ pub fn update_single_mod(
     backend_handle.send(MessageToBackend::UpdateContent {
         instance,
         content_id: mod_id,
+        snapshot,
         modal_action: modal_action.clone(),
     });
 
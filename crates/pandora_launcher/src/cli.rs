@@ -0,0 +1,648 @@
+use std::{
+    path::{Path, PathBuf}, sync::{mpsc, Arc}, time::{Duration, Instant}
+};
+
+use bridge::{
+    export::InstanceExportFormat, handle::{BackendHandle, FrontendReceiver}, import::OtherLauncher, install::{ContentDownload, ContentInstall, ContentInstallFile, ContentInstallPath, InstallTarget}, instance::{ContentUpdateStatus, InstanceContentID, InstanceContentSummary, InstanceID, InstanceStatus}, message::{BridgeNotificationType, LaunchCommandExport, MessageToBackend, MessageToFrontend, QuickPlayLaunch}, meta::{MetadataRequest, MetadataResult}, modal_action::ModalAction, safe_path::SafePath
+};
+use clap::{Subcommand, ValueEnum};
+use schema::{content::ContentSource, instance::InstanceConfiguration, loader::Loader, modrinth::ModrinthProjectRequest};
+
+use crate::run_modal_action;
+
+const DEFAULT_EXPORT_INCLUDED: &[&str] = &["config", "options.txt"];
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List all instances
+    List,
+    /// Create a new instance
+    Create {
+        name: String,
+        /// Minecraft version, e.g. 1.21.1
+        version: String,
+        #[arg(long, value_enum, default_value_t = CliLoader::Vanilla)]
+        loader: CliLoader,
+    },
+    /// Launch an instance
+    Launch {
+        instance: String,
+        /// Folder name of a singleplayer world to join on startup
+        #[arg(long, conflicts_with = "server")]
+        world: Option<String>,
+        /// Address of a server to join on startup
+        #[arg(long)]
+        server: Option<String>,
+        /// Name of the launch profile to use
+        #[arg(long)]
+        profile: Option<String>,
+        /// Print the command that would launch the game instead of launching it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Install a Modrinth project (by id, slug or URL) or a local file into an instance
+    Install {
+        instance: String,
+        target: String,
+        /// Folder to install local files into
+        #[arg(long, value_enum, default_value_t = CliContentFolder::Mods)]
+        folder: CliContentFolder,
+        /// Don't install required dependencies
+        #[arg(long)]
+        no_dependencies: bool,
+    },
+    /// Check for updates and update all content in an instance
+    Update {
+        instance: String,
+    },
+    /// Export an instance as a modpack
+    Export {
+        instance: String,
+        #[arg(long, value_enum, default_value_t = CliExportFormat::Modrinth)]
+        format: CliExportFormat,
+        /// Output file, defaults to <name>.<extension> in the current directory
+        #[arg(long, short)]
+        output: Option<PathBuf>,
+        /// Modpack name, defaults to the instance name
+        #[arg(long)]
+        name: Option<String>,
+        /// Modpack version
+        #[arg(long, default_value = "1.0.0")]
+        version: String,
+        /// Files or folders inside .minecraft to include, defaults to config and options.txt
+        #[arg(long)]
+        include: Vec<String>,
+    },
+    /// Import from another launcher's default location
+    Import {
+        launcher: CliOtherLauncher,
+        /// Also import accounts
+        #[arg(long)]
+        accounts: bool,
+        /// Don't import instances
+        #[arg(long)]
+        no_instances: bool,
+    },
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CliLoader {
+    Vanilla,
+    Fabric,
+    Forge,
+    #[value(name = "neoforge")]
+    NeoForge,
+    Quilt,
+}
+
+impl From<CliLoader> for Loader {
+    fn from(value: CliLoader) -> Self {
+        match value {
+            CliLoader::Vanilla => Loader::Vanilla,
+            CliLoader::Fabric => Loader::Fabric,
+            CliLoader::Forge => Loader::Forge,
+            CliLoader::NeoForge => Loader::NeoForge,
+            CliLoader::Quilt => Loader::Quilt,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CliContentFolder {
+    Mods,
+    #[value(name = "resourcepacks")]
+    ResourcePacks,
+    #[value(name = "shaderpacks")]
+    ShaderPacks,
+    Datapacks,
+}
+
+impl CliContentFolder {
+    fn path(self) -> &'static str {
+        match self {
+            CliContentFolder::Mods => "mods",
+            CliContentFolder::ResourcePacks => "resourcepacks",
+            CliContentFolder::ShaderPacks => "shaderpacks",
+            CliContentFolder::Datapacks => "datapacks",
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CliExportFormat {
+    Modrinth,
+    #[value(name = "curseforge")]
+    Curseforge,
+}
+
+impl From<CliExportFormat> for InstanceExportFormat {
+    fn from(value: CliExportFormat) -> Self {
+        match value {
+            CliExportFormat::Modrinth => InstanceExportFormat::Modrinth,
+            CliExportFormat::Curseforge => InstanceExportFormat::Curseforge,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy)]
+pub enum CliOtherLauncher {
+    Prism,
+    #[value(name = "curseforge")]
+    CurseForge,
+    Modrinth,
+    #[value(name = "multimc")]
+    MultiMC,
+    #[value(name = "atlauncher")]
+    ATLauncher,
+}
+
+impl From<CliOtherLauncher> for OtherLauncher {
+    fn from(value: CliOtherLauncher) -> Self {
+        match value {
+            CliOtherLauncher::Prism => OtherLauncher::Prism,
+            CliOtherLauncher::CurseForge => OtherLauncher::CurseForge,
+            CliOtherLauncher::Modrinth => OtherLauncher::Modrinth,
+            CliOtherLauncher::MultiMC => OtherLauncher::MultiMC,
+            CliOtherLauncher::ATLauncher => OtherLauncher::ATLauncher,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct CliInstance {
+    pub id: InstanceID,
+    pub name: String,
+    pub dot_minecraft_folder: Arc<Path>,
+    pub configuration: InstanceConfiguration,
+    pub status: InstanceStatus,
+}
+
+enum CliEvent {
+    Message(MessageToFrontend),
+    Flushed,
+}
+
+/// Runs the backend without a window, tracking instances from the messages it sends
+pub struct CliSession {
+    pub backend_handle: BackendHandle,
+    events: mpsc::Receiver<CliEvent>,
+    instances: Vec<CliInstance>,
+}
+
+impl CliSession {
+    /// Starts the backend and blocks until every instance has been loaded
+    pub fn start(launcher_dir: PathBuf) -> Self {
+        let (backend_recv, backend_handle, frontend_recv, frontend_handle) = bridge::handle::create_pair();
+
+        backend::start(launcher_dir, frontend_handle, backend_handle.clone(), backend_recv);
+
+        let (flush_send, flush_recv) = tokio::sync::oneshot::channel();
+        backend_handle.send(MessageToBackend::Flush { channel: flush_send });
+
+        let (event_send, events) = mpsc::channel();
+        std::thread::spawn(move || forward_messages(frontend_recv, flush_recv, event_send));
+
+        let mut session = Self {
+            backend_handle,
+            events,
+            instances: Vec::new(),
+        };
+
+        while let Ok(event) = session.events.recv() {
+            match event {
+                CliEvent::Message(message) => session.handle_message(&message),
+                CliEvent::Flushed => break,
+            }
+        }
+
+        session
+    }
+
+    pub fn find_instance(&self, name: &str) -> Option<CliInstance> {
+        self.instances.iter().find(|instance| instance.name == name)
+            .or_else(|| self.instances.iter().find(|instance| instance.name.eq_ignore_ascii_case(name)))
+            .cloned()
+    }
+
+    /// Processes messages until `done` returns true, returning false if the timeout elapsed first
+    fn wait_for(&mut self, timeout: Duration, mut done: impl FnMut(&MessageToFrontend) -> bool) -> bool {
+        let deadline = Instant::now() + timeout;
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            let Ok(event) = self.events.recv_timeout(remaining) else {
+                return false;
+            };
+            if let CliEvent::Message(message) = event {
+                self.handle_message(&message);
+                if done(&message) {
+                    return true;
+                }
+            }
+        }
+    }
+
+    /// Looks up a Modrinth project by id or slug, returning its id
+    fn fetch_modrinth_project_id(&mut self, project: Arc<str>) -> Result<Arc<str>, Arc<str>> {
+        let request = MetadataRequest::ModrinthProject(ModrinthProjectRequest { project_id: project });
+        self.backend_handle.send(MessageToBackend::RequestMetadata { request: request.clone(), force_reload: false });
+
+        let mut project_id = None;
+        self.wait_for(Duration::from_secs(30), |message| match message {
+            MessageToFrontend::MetadataResult { request: received, result, .. } if *received == request => {
+                project_id = Some(match result {
+                    Ok(MetadataResult::ModrinthProjectResult(project)) => Ok(project.id.clone()),
+                    Ok(_) => Err("Wrong metadata type".into()),
+                    Err(error) => Err(error.clone()),
+                });
+                true
+            },
+            _ => false,
+        });
+        project_id.unwrap_or_else(|| Err("Timed out waiting for Modrinth".into()))
+    }
+
+    fn handle_message(&mut self, message: &MessageToFrontend) {
+        match message {
+            MessageToFrontend::InstanceAdded { id, name, dot_minecraft_folder, configuration, .. } => {
+                self.instances.push(CliInstance {
+                    id: *id,
+                    name: name.to_string(),
+                    dot_minecraft_folder: dot_minecraft_folder.clone(),
+                    configuration: configuration.clone(),
+                    status: InstanceStatus::NotRunning,
+                });
+            },
+            MessageToFrontend::InstanceRemoved { id } => {
+                self.instances.retain(|instance| instance.id != *id);
+            },
+            MessageToFrontend::InstanceModified { id, name, dot_minecraft_folder, configuration, status, .. } => {
+                if let Some(instance) = self.instances.iter_mut().find(|instance| instance.id == *id) {
+                    instance.name = name.to_string();
+                    instance.dot_minecraft_folder = dot_minecraft_folder.clone();
+                    instance.configuration = configuration.clone();
+                    instance.status = *status;
+                }
+            },
+            _ => {},
+        }
+    }
+}
+
+fn forward_messages(mut frontend_recv: FrontendReceiver, flush_recv: tokio::sync::oneshot::Receiver<()>, events: mpsc::Sender<CliEvent>) {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .expect("Failed to initialize Tokio runtime");
+
+    runtime.block_on(async move {
+        let mut flush_recv = Some(flush_recv);
+        loop {
+            // Biased so that everything sent before the flush reply is forwarded first
+            let event = match &mut flush_recv {
+                Some(flush_recv) => tokio::select! {
+                    biased;
+                    message = frontend_recv.recv() => message.map(CliEvent::Message),
+                    _ = flush_recv => Some(CliEvent::Flushed),
+                },
+                None => frontend_recv.recv().await.map(CliEvent::Message),
+            };
+            let Some(event) = event else {
+                return;
+            };
+
+            match &event {
+                CliEvent::Flushed => flush_recv = None,
+                CliEvent::Message(MessageToFrontend::AddNotification { notification_type, message }) => {
+                    match notification_type {
+                        BridgeNotificationType::Error => eprintln!("Error: {message}"),
+                        BridgeNotificationType::Warning => eprintln!("Warning: {message}"),
+                        BridgeNotificationType::Success | BridgeNotificationType::Info => println!("{message}"),
+                    }
+                },
+                CliEvent::Message(_) => {},
+            }
+
+            if events.send(event).is_err() {
+                return;
+            }
+        }
+    });
+}
+
+/// Runs a subcommand to completion, returning the process exit code
+pub fn run(command: Command, launcher_dir: PathBuf, working_dir: PathBuf) -> i32 {
+    let mut session = CliSession::start(launcher_dir);
+
+    let find_instance = |session: &CliSession, name: &str| {
+        let instance = session.find_instance(name);
+        if instance.is_none() {
+            eprintln!("Error: Unable to find instance {name}");
+        }
+        instance
+    };
+
+    match command {
+        Command::List => {
+            for instance in &session.instances {
+                let running = if instance.status == InstanceStatus::NotRunning { "" } else { " (running)" };
+                println!("{}\t{}\t{}{}", instance.name, instance.configuration.minecraft_version,
+                    instance.configuration.loader.name(), running);
+            }
+            0
+        },
+        Command::Create { name, version, loader } => {
+            let existing: Vec<InstanceID> = session.instances.iter().map(|instance| instance.id).collect();
+            session.backend_handle.send(MessageToBackend::CreateInstance {
+                name: name.as_str().into(),
+                version: version.as_str().into(),
+                loader: loader.into(),
+                icon: None,
+            });
+
+            // The instance folder may not end up with the requested name, so accept any new instance
+            let mut created = None;
+            let finished = session.wait_for(Duration::from_secs(30), |message| match message {
+                MessageToFrontend::InstanceAdded { id, name: added, .. } if !existing.contains(id) => {
+                    created = Some(added.clone());
+                    true
+                },
+                MessageToFrontend::AddNotification { notification_type: BridgeNotificationType::Error | BridgeNotificationType::Warning, .. } => true,
+                _ => false,
+            });
+
+            if let Some(created) = created {
+                println!("Created instance {created}");
+                0
+            } else {
+                if !finished {
+                    eprintln!("Error: Timed out waiting for instance {name} to be created");
+                }
+                1
+            }
+        },
+        Command::Launch { instance, world, server, profile, dry_run } => {
+            let Some(instance) = find_instance(&session, &instance) else {
+                return 1;
+            };
+
+            let quick_play = if let Some(world) = world {
+                Some(QuickPlayLaunch::Singleplayer(world.into()))
+            } else {
+                server.map(|server| QuickPlayLaunch::Multiplayer(server.into()))
+            };
+
+            if dry_run {
+                let modal_action = ModalAction::default();
+                let (send, recv) = tokio::sync::oneshot::channel();
+                session.backend_handle.send(MessageToBackend::ExportLaunchCommand {
+                    id: instance.id,
+                    quick_play,
+                    profile: profile.map(Into::into),
+                    export: LaunchCommandExport::CommandLine(send),
+                    modal_action: modal_action.clone(),
+                });
+                if !run_modal_action(modal_action, true) {
+                    return 1;
+                }
+                return match recv.blocking_recv() {
+                    Ok(command) => {
+                        println!("{command}");
+                        0
+                    },
+                    Err(_) => 1,
+                };
+            }
+
+            println!("Starting instance {}", instance.name);
+            let modal_action = ModalAction::default();
+            session.backend_handle.send(MessageToBackend::StartInstance {
+                id: instance.id,
+                quick_play,
+                profile: profile.map(Into::into),
+                allow_running_instance: false,
+                modal_action: modal_action.clone(),
+            });
+            let success = run_modal_action(modal_action, true);
+            std::thread::sleep(Duration::from_millis(100));
+            if success { 0 } else { 1 }
+        },
+        Command::Install { instance, target, folder, no_dependencies } => {
+            let Some(instance) = find_instance(&session, &instance) else {
+                return 1;
+            };
+
+            let target_path = working_dir.join(&target);
+            let file = if target_path.is_file() {
+                let Some(file_name) = target_path.file_name() else {
+                    eprintln!("Error: Invalid file {target}");
+                    return 1;
+                };
+                ContentInstallFile {
+                    replace_old: None,
+                    path: ContentInstallPath::Raw(Path::new(folder.path()).join(file_name).into()),
+                    download: ContentDownload::File { path: target_path },
+                    content_source: ContentSource::Manual,
+                }
+            } else if let Some(project) = parse_modrinth_project(&target) {
+                // The project may have been given as a slug, installing checks versions against the real id
+                let project_id = match session.fetch_modrinth_project_id(project) {
+                    Ok(project_id) => project_id,
+                    Err(error) => {
+                        eprintln!("Error: Unable to find Modrinth project {target}: {error}");
+                        return 1;
+                    },
+                };
+                ContentInstallFile {
+                    replace_old: None,
+                    path: ContentInstallPath::Automatic,
+                    download: ContentDownload::Modrinth {
+                        project_id: project_id.clone(),
+                        version_id: None,
+                        install_dependencies: !no_dependencies,
+                    },
+                    content_source: ContentSource::ModrinthProject { project: project_id },
+                }
+            } else {
+                eprintln!("Error: {target} is not a file, Modrinth project id or Modrinth URL");
+                return 1;
+            };
+
+            let modal_action = ModalAction::default();
+            session.backend_handle.send(MessageToBackend::InstallContent {
+                content: ContentInstall {
+                    target: InstallTarget::Instance(instance.id),
+                    loader_hint: instance.configuration.loader,
+                    version_hint: Some(instance.configuration.minecraft_version.as_str().into()),
+                    datapack_world: None,
+                    files: [file].into(),
+                },
+                modal_action: modal_action.clone(),
+            });
+            if run_modal_action(modal_action, true) { 0 } else { 1 }
+        },
+        Command::Update { instance } => {
+            let Some(instance) = find_instance(&session, &instance) else {
+                return 1;
+            };
+            let id = instance.id;
+            let loader = instance.configuration.loader;
+            let version = instance.configuration.minecraft_version;
+
+            let modal_action = ModalAction::default();
+            session.backend_handle.send(MessageToBackend::UpdateCheck {
+                instance: id,
+                modal_action: modal_action.clone(),
+            });
+            if !run_modal_action(modal_action, true) {
+                return 1;
+            }
+
+            session.backend_handle.send(MessageToBackend::RequestLoadMods { id });
+            session.backend_handle.send(MessageToBackend::RequestLoadResourcePacks { id });
+            session.backend_handle.send(MessageToBackend::RequestLoadShaderPacks { id });
+            session.backend_handle.send(MessageToBackend::RequestLoadDatapacks { id });
+
+            // Content loaded during the check itself doesn't have update statuses yet, so only
+            // count a folder once every entry in it has been checked
+            let mut checked: [Option<Arc<[InstanceContentSummary]>>; 4] = Default::default();
+            let all_checked = session.wait_for(Duration::from_secs(30), |message| {
+                let (index, content) = match message {
+                    MessageToFrontend::InstanceModsUpdated { id: updated, mods } if *updated == id => (0, mods),
+                    MessageToFrontend::InstanceResourcePacksUpdated { id: updated, resource_packs } if *updated == id => (1, resource_packs),
+                    MessageToFrontend::InstanceShaderPacksUpdated { id: updated, shader_packs } if *updated == id => (2, shader_packs),
+                    MessageToFrontend::InstanceDatapacksUpdated { id: updated, datapacks } if *updated == id => (3, datapacks),
+                    _ => return false,
+                };
+                if content.iter().all(|summary| summary.update.status_if_matches(loader, version) != ContentUpdateStatus::Unknown) {
+                    checked[index] = Some(content.clone());
+                }
+                checked.iter().all(Option::is_some)
+            });
+            if !all_checked {
+                eprintln!("Error: Timed out waiting for instance content to load");
+                return 1;
+            }
+
+            let updatable: Vec<(InstanceContentID, Arc<str>)> = checked.iter().flatten()
+                .flat_map(|content| content.iter())
+                .filter(|summary| matches!(summary.update.status_if_matches(loader, version),
+                    ContentUpdateStatus::Modrinth | ContentUpdateStatus::Curseforge))
+                .map(|summary| (summary.id, summary.filename.clone()))
+                .collect();
+
+            if updatable.is_empty() {
+                println!("Everything is up-to-date");
+                return 0;
+            }
+
+            // Send every update up front, content ids may be invalidated once the first one is installed
+            let mut modal_actions = Vec::new();
+            for (index, (content_id, filename)) in updatable.into_iter().enumerate() {
+                let modal_action = ModalAction::default();
+                session.backend_handle.send(MessageToBackend::UpdateContent {
+                    instance: id,
+                    content_id,
+                    snapshot: index == 0,
+                    modal_action: modal_action.clone(),
+                });
+                modal_actions.push((filename, modal_action));
+            }
+
+            let mut code = 0;
+            for (filename, modal_action) in modal_actions {
+                println!("Updating {filename}");
+                if !run_modal_action(modal_action, true) {
+                    code = 1;
+                }
+            }
+            code
+        },
+        Command::Export { instance, format, output, name, version, include } => {
+            let Some(instance) = find_instance(&session, &instance) else {
+                return 1;
+            };
+
+            let format: InstanceExportFormat = format.into();
+            let name = name.unwrap_or_else(|| instance.name.clone());
+            let output = working_dir.join(output.unwrap_or_else(|| format!("{}.{}", name, format.extension()).into()));
+
+            let include = if include.is_empty() {
+                DEFAULT_EXPORT_INCLUDED.iter()
+                    .filter(|path| instance.dot_minecraft_folder.join(path).exists())
+                    .map(|path| path.to_string())
+                    .collect()
+            } else {
+                include
+            };
+
+            let mut overrides = Vec::new();
+            for path in &include {
+                let Some(safe_path) = SafePath::new(path) else {
+                    eprintln!("Error: Invalid include path {path}");
+                    return 1;
+                };
+                overrides.push(safe_path);
+            }
+
+            let modal_action = ModalAction::default();
+            session.backend_handle.send(MessageToBackend::ExportInstance {
+                id: instance.id,
+                format,
+                name: name.as_str().into(),
+                version: version.as_str().into(),
+                overrides: overrides.into(),
+                output: output.as_path().into(),
+                modal_action: modal_action.clone(),
+            });
+            if run_modal_action(modal_action, true) {
+                println!("Exported to {}", output.display());
+                0
+            } else {
+                1
+            }
+        },
+        Command::Import { launcher, accounts, no_instances } => {
+            let modal_action = ModalAction::default();
+            session.backend_handle.send(MessageToBackend::ImportFromOtherLauncher {
+                launcher: launcher.into(),
+                import_accounts: accounts,
+                import_instances: !no_instances,
+                modal_action: modal_action.clone(),
+            });
+            if run_modal_action(modal_action, true) { 0 } else { 1 }
+        },
+    }
+}
+
+/// Accepts a Modrinth project id, slug or project page URL
+fn parse_modrinth_project(target: &str) -> Option<Arc<str>> {
+    if let Some(url) = target.strip_prefix("https://").or_else(|| target.strip_prefix("http://")) {
+        let url = url.strip_prefix("www.").unwrap_or(url);
+        let path = url.strip_prefix("modrinth.com/")?;
+
+        // modrinth.com/<project type>/<slug>/...
+        let mut segments = path.split(['/', '?', '#']).filter(|segment| !segment.is_empty());
+        let _project_type = segments.next()?;
+        return segments.next().map(Arc::from);
+    }
+
+    let valid = !target.is_empty() && target.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_' || c == '.');
+    valid.then(|| Arc::from(target))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_modrinth_project() {
+        assert_eq!(parse_modrinth_project("AANobbMI").as_deref(), Some("AANobbMI"));
+        assert_eq!(parse_modrinth_project("fabric-api").as_deref(), Some("fabric-api"));
+        assert_eq!(parse_modrinth_project("https://modrinth.com/mod/sodium").as_deref(), Some("sodium"));
+        assert_eq!(parse_modrinth_project("https://www.modrinth.com/mod/sodium/versions?g=1.21").as_deref(), Some("sodium"));
+        assert_eq!(parse_modrinth_project("http://modrinth.com/shader/complementary-reimagined#gallery").as_deref(), Some("complementary-reimagined"));
+        assert_eq!(parse_modrinth_project("https://modrinth.com/mod"), None);
+        assert_eq!(parse_modrinth_project("https://curseforge.com/minecraft/mc-mods/jei"), None);
+        assert_eq!(parse_modrinth_project("not a project"), None);
+        assert_eq!(parse_modrinth_project(""), None);
+    }
+}
@@ -7,7 +7,6 @@ use std::sync::Arc;
 use std::fmt::Write;
 use std::time::SystemTime;
 
-use bridge::message::MessageToFrontend;
 use bridge::modal_action::ModalAction;
 use clap::Parser;
 use fern::colors::ColoredLevelConfig;
@@ -21,8 +20,12 @@ struct Args {
     /// Instance to launch, instead of opening the launcher
     #[arg(long)]
     run_instance: Option<String>,
+
+    #[command(subcommand)]
+    command: Option<cli::Command>,
 }
 
+pub mod cli;
 pub mod panic;
 
 fn main() {
@@ -36,6 +39,7 @@ fn main() {
     };
 
     let launcher_dir = data_dir.join("PandoraLauncher");
+    let working_dir = std::env::current_dir().unwrap_or_default();
     _ = std::env::set_current_dir(&launcher_dir);
 
     let log_path = launcher_dir.join("launcher.log");
@@ -44,7 +48,7 @@ fn main() {
         _ = std::fs::rename(log_path, old_log_path);
     }
 
-    if let Err(error) = setup_logging(log::LevelFilter::Debug) {
+    if let Err(error) = setup_logging(log::LevelFilter::Debug, args.command.is_none()) {
         eprintln!("Unable to enable logging: {error:?}");
     }
 
@@ -53,31 +57,28 @@ fn main() {
 
     panic::install_logging_hook();
 
-    if let Some(run_instance) = args.run_instance {
-        let (backend_recv, backend_handle, mut frontend_recv, frontend_handle) = bridge::handle::create_pair();
-
-        backend::start(launcher_dir.clone(), frontend_handle, backend_handle.clone(), backend_recv);
-
-        while let Some(message) = frontend_recv.try_recv() {
-            if let MessageToFrontend::InstanceAdded { id, name, .. } = message {
-                if name.as_str() == run_instance.as_str() {
-                    println!("Starting instance {}", run_instance);
-                    let modal_action = ModalAction::default();
-                    backend_handle.send(bridge::message::MessageToBackend::StartInstance {
-                        id,
-                        quick_play: None,
-                        allow_running_instance: false,
-                        modal_action: modal_action.clone()
-                    });
-                    run_modal_action(modal_action);
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                    return;
-                }
-            }
-        }
-
-        show_error(format!("Unable to find instance {}", run_instance));
-        std::process::exit(1);
+    if let Some(command) = args.command {
+        let code = cli::run(command, launcher_dir, working_dir);
+        std::process::exit(code);
+    } else if let Some(run_instance) = args.run_instance {
+        let session = cli::CliSession::start(launcher_dir.clone());
+
+        let Some(instance) = session.find_instance(&run_instance) else {
+            show_error(format!("Unable to find instance {}", run_instance));
+            std::process::exit(1);
+        };
+
+        println!("Starting instance {}", run_instance);
+        let modal_action = ModalAction::default();
+        session.backend_handle.send(bridge::message::MessageToBackend::StartInstance {
+            id: instance.id,
+            quick_play: None,
+            profile: None,
+            allow_running_instance: false,
+            modal_action: modal_action.clone()
+        });
+        run_modal_action(modal_action, false);
+        std::thread::sleep(std::time::Duration::from_millis(100));
     } else {
         run_gui(launcher_dir);
     }
@@ -93,7 +94,7 @@ fn show_error(error: String) {
         .show();
 }
 
-fn run_modal_action(modal_action: ModalAction) {
+fn run_modal_action(modal_action: ModalAction, headless: bool) -> bool {
     let m = MultiProgress::new();
     let sty = ProgressStyle::with_template(
         "[{elapsed_precise}] {bar:40.cyan/blue} {msg}",
@@ -108,8 +109,14 @@ fn run_modal_action(modal_action: ModalAction) {
         std::thread::sleep(std::time::Duration::from_millis(100));
 
         if let Some(error) = &*modal_action.error.read() {
-            show_error(error.to_string());
-            return;
+            if headless {
+                _ = m.clear();
+                log::error!("{}", error);
+                eprintln!("Error: {}", error);
+            } else {
+                show_error(error.to_string());
+            }
+            return false;
         }
 
         if modal_action.refcnt() <= 1 {
@@ -117,13 +124,13 @@ fn run_modal_action(modal_action: ModalAction) {
         }
 
         if modal_action.get_finished_at().is_some() {
-            return;
+            return true;
         }
 
         if let Some(visit_url) = &*modal_action.visit_url.write() {
             if opened.insert(visit_url.url.clone()) {
                 _ = m.println(format!("Open this URL in your browser to continue: {}", visit_url.url));
-                let open = DialogBuilder::message()
+                let open = headless || DialogBuilder::message()
                     .set_title("Open URL")
                     .set_text(&visit_url.message)
                     .confirm()
@@ -132,7 +139,7 @@ fn run_modal_action(modal_action: ModalAction) {
                 if open {
                     _ = open::that_detached(&*visit_url.url);
                 } else {
-                    return;
+                    return false;
                 }
             }
         }
@@ -206,7 +213,7 @@ fn run_gui(launcher_dir: PathBuf) {
     frontend::start(launcher_dir.clone(), panic_message, deadlock_message, backend_handle, frontend_recv);
 }
 
-fn setup_logging(level: log::LevelFilter) -> Result<(), fern::InitError> {
+fn setup_logging(level: log::LevelFilter, log_to_stdout: bool) -> Result<(), fern::InitError> {
     let base_config = fern::Dispatch::new()
         .level_for("pandora_launcher", level)
         .level_for("auth", level)
@@ -246,10 +253,12 @@ fn setup_logging(level: log::LevelFilter) -> Result<(), fern::InitError> {
         })
         .chain(std::io::stdout());
 
-    base_config
-        .chain(file_config)
-        .chain(stdout_config)
-        .apply()?;
+    let base_config = base_config.chain(file_config);
+    if log_to_stdout {
+        base_config.chain(stdout_config).apply()?;
+    } else {
+        base_config.apply()?;
+    }
 
     Ok(())
 }
@@ -1,4 +1,4 @@
-use std::{collections::BTreeSet, sync::Arc};
+use std::{collections::{BTreeMap, BTreeSet}, path::Path, sync::Arc};
 
 use enumset::{EnumSet, EnumSetType};
 use serde::{Deserialize, Serialize};
@@ -10,7 +10,53 @@ pub struct BackendConfig {
     #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
     pub dont_open_game_output_when_launching: bool,
     #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub background_update_checks: bool,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
     pub proxy: ProxyConfig,
+    /// Always launch from local caches without using the network, even when connected
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub offline_mode: bool,
+    /// Java runtime to use for each major version when an instance doesn't pick one itself
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub java_defaults: BTreeMap<u32, Arc<Path>>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub downloads: DownloadConfig,
+    #[serde(default, skip_serializing_if = "Vec::is_empty", deserialize_with = "crate::try_deserialize")]
+    pub mirrors: Vec<DownloadMirror>,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+pub struct DownloadConfig {
+    /// Maximum number of simultaneous connections, 0 to use the default
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub max_concurrent: u32,
+    /// Combined download speed limit in KiB/s, 0 for unlimited
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub bandwidth_limit_kib: u32,
+}
+
+impl DownloadConfig {
+    // More connections than this tends to run into rate limits
+    pub const DEFAULT_MAX_CONCURRENT: u32 = 8;
+
+    pub fn max_concurrent(&self) -> usize {
+        if self.max_concurrent == 0 {
+            Self::DEFAULT_MAX_CONCURRENT as usize
+        } else {
+            self.max_concurrent as usize
+        }
+    }
+
+    pub fn bandwidth_limit_bytes(&self) -> u64 {
+        self.bandwidth_limit_kib as u64 * 1024
+    }
+}
+
+/// Requests to URLs starting with `origin` are sent to `mirror` instead, falling back to `origin` if the mirror fails
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DownloadMirror {
+    pub origin: Arc<str>,
+    pub mirror: Arc<str>,
 }
 
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
@@ -1,5 +1,6 @@
 use std::{collections::HashMap, sync::Arc};
 
+use indexmap::IndexMap;
 use serde::Deserialize;
 
 #[derive(Deserialize, Debug)]
@@ -11,17 +12,39 @@ pub struct FabricModJson {
     // pub description: Option<Arc<str>>,
     pub authors: Option<Vec<Person>>,
     pub icon: Option<Icon>,
-    // #[serde(alias = "requires")]
-    // pub depends: Option<HashMap<Arc<str>, Dependency>>,
-    // pub breaks: Option<HashMap<Arc<str>, Dependency>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub provides: Vec<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub jars: Vec<FabricNestedJar>,
+    #[serde(default, alias = "requires", deserialize_with = "crate::try_deserialize")]
+    pub depends: IndexMap<Arc<str>, FabricDependency>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub recommends: IndexMap<Arc<str>, FabricDependency>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub breaks: IndexMap<Arc<str>, FabricDependency>,
 }
 
-// #[derive(Deserialize, Debug)]
-// #[serde(untagged)]
-// enum Dependency {
-//     Single(Arc<str>),
-//     Multiple(Vec<Arc<str>>)
-// }
+#[derive(Deserialize, Debug)]
+pub struct FabricNestedJar {
+    pub file: Arc<str>,
+}
+
+/// Version predicates, the dependency matches if any of them match
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum FabricDependency {
+    Single(Arc<str>),
+    Multiple(Vec<Arc<str>>)
+}
+
+impl FabricDependency {
+    pub fn predicates(&self) -> Vec<Arc<str>> {
+        match self {
+            FabricDependency::Single(predicate) => vec![predicate.clone()],
+            FabricDependency::Multiple(predicates) => predicates.clone(),
+        }
+    }
+}
 
 #[derive(Deserialize, Debug)]
 #[serde(untagged)]
@@ -1,4 +1,4 @@
-use std::{path::Path, sync::Arc};
+use std::{collections::BTreeMap, path::Path, sync::Arc};
 
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
@@ -19,6 +19,14 @@ pub struct InstanceConfiguration {
     pub memory: Option<InstanceMemoryConfiguration>,
     #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_wrapper_command_configuration")]
     pub wrapper_command: Option<InstanceWrapperCommandConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_window_configuration")]
+    pub window: Option<InstanceWindowConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_game_arguments_configuration")]
+    pub game_arguments: Option<InstanceGameArgumentsConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub demo: bool,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub auto_join_server: Option<Arc<str>>,
     #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_jvm_flags_configuration")]
     pub jvm_flags: Option<InstanceJvmFlagsConfiguration>,
     #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_jvm_binary_configuration")]
@@ -27,6 +35,12 @@ pub struct InstanceConfiguration {
     pub linux_wrapper: Option<InstanceLinuxWrapperConfiguration>,
     #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_system_libraries_configuration")]
     pub system_libraries: Option<InstanceSystemLibrariesConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "BTreeMap::is_empty")]
+    pub environment: BTreeMap<Arc<str>, Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "is_default_hooks_configuration")]
+    pub hooks: Option<InstanceHooksConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Vec::is_empty")]
+    pub launch_profiles: Vec<InstanceLaunchProfile>,
     #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
     pub instance_fallback_icon: Option<Ustr>,
     #[serde(default, deserialize_with = "crate::try_deserialize")]
@@ -44,15 +58,186 @@ impl InstanceConfiguration {
             preferred_account: None,
             memory: None,
             wrapper_command: None,
+            window: None,
+            game_arguments: None,
+            demo: false,
+            auto_join_server: None,
             jvm_flags: None,
             jvm_binary: None,
             linux_wrapper: None,
             system_libraries: None,
+            environment: BTreeMap::new(),
+            hooks: None,
+            launch_profiles: Vec::new(),
             instance_fallback_icon: None,
             disable_file_syncing: false,
             created_shortcuts: Vec::new(),
         }
     }
+
+    pub fn launch_profile(&self, name: &str) -> Option<&InstanceLaunchProfile> {
+        self.launch_profiles.iter().find(|profile| &*profile.name == name)
+    }
+
+    /// The configuration with everything the profile overrides replaced
+    pub fn with_launch_profile(&self, profile: &InstanceLaunchProfile) -> Self {
+        let mut configuration = self.clone();
+        if profile.memory.is_some() {
+            configuration.memory = profile.memory;
+        }
+        if profile.jvm_flags.is_some() {
+            configuration.jvm_flags = profile.jvm_flags.clone();
+        }
+        if profile.jvm_binary.is_some() {
+            configuration.jvm_binary = profile.jvm_binary.clone();
+        }
+        if profile.wrapper_command.is_some() {
+            configuration.wrapper_command = profile.wrapper_command.clone();
+        }
+        if profile.account.is_some() {
+            configuration.preferred_account = profile.account;
+        }
+        configuration
+    }
+}
+
+/// A named set of overrides for launching the same instance in a different way, e.g. with a debug agent or another account
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstanceLaunchProfile {
+    pub name: Arc<str>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub memory: Option<InstanceMemoryConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub jvm_flags: Option<InstanceJvmFlagsConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub jvm_binary: Option<InstanceJvmBinaryConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub wrapper_command: Option<InstanceWrapperCommandConfiguration>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub account: Option<Uuid>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub quick_play: Option<InstanceQuickPlayTarget>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InstanceQuickPlayTarget {
+    Singleplayer(Arc<str>),
+    Multiplayer(Arc<str>),
+    Realms(Arc<str>),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct InstanceStats {
+    pub total_playtime_secs: u64,
+    pub session_count: u64,
+    #[serde(default)]
+    pub last_played_unix_ms: Option<i64>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "Vec::is_empty")]
+    pub recent_sessions: Vec<InstanceSession>,
+    #[serde(default, deserialize_with = "crate::try_deserialize", skip_serializing_if = "crate::skip_if_none")]
+    pub last_launch_timings: Option<LaunchTimings>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct InstanceSession {
+    pub started_unix_ms: i64,
+    pub duration_secs: u64,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none")]
+    pub exit_code: Option<i32>,
+}
+
+impl InstanceSession {
+    pub fn crashed(&self) -> bool {
+        self.exit_code.is_some_and(|code| code != 0)
+    }
+}
+
+/// How long each phase of a launch took. Some phases run concurrently, so they can add up to more than the total
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct LaunchTimings {
+    pub total_ms: u64,
+    pub phases: Vec<LaunchPhaseTiming>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LaunchPhaseTiming {
+    pub phase: LaunchPhase,
+    pub duration_ms: u64,
+    /// Ran at the same time as other phases
+    #[serde(default, skip_serializing_if = "crate::skip_if_default")]
+    pub concurrent: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum LaunchPhase {
+    Login,
+    FileSyncing,
+    ModpackApplication,
+    LaunchCache,
+    VersionResolution,
+    LoaderInstall,
+    ForgeProcessors,
+    JavaRuntime,
+    Libraries,
+    Assets,
+    NativesExtraction,
+    PreLaunchCommand,
+    ProcessStart,
+}
+
+impl LaunchPhase {
+    pub fn id(self) -> &'static str {
+        match self {
+            LaunchPhase::Login => "login",
+            LaunchPhase::FileSyncing => "file_syncing",
+            LaunchPhase::ModpackApplication => "modpack_application",
+            LaunchPhase::LaunchCache => "launch_cache",
+            LaunchPhase::VersionResolution => "version_resolution",
+            LaunchPhase::LoaderInstall => "loader_install",
+            LaunchPhase::ForgeProcessors => "forge_processors",
+            LaunchPhase::JavaRuntime => "java_runtime",
+            LaunchPhase::Libraries => "libraries",
+            LaunchPhase::Assets => "assets",
+            LaunchPhase::NativesExtraction => "natives_extraction",
+            LaunchPhase::PreLaunchCommand => "pre_launch_command",
+            LaunchPhase::ProcessStart => "process_start",
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            LaunchPhase::Login => "Login",
+            LaunchPhase::FileSyncing => "File syncing",
+            LaunchPhase::ModpackApplication => "Modpack application",
+            LaunchPhase::LaunchCache => "Launch cache check",
+            LaunchPhase::VersionResolution => "Version resolution",
+            LaunchPhase::LoaderInstall => "Loader install",
+            LaunchPhase::ForgeProcessors => "Forge processors",
+            LaunchPhase::JavaRuntime => "Java runtime",
+            LaunchPhase::Libraries => "Libraries",
+            LaunchPhase::Assets => "Assets",
+            LaunchPhase::NativesExtraction => "Natives extraction",
+            LaunchPhase::PreLaunchCommand => "Pre-launch command",
+            LaunchPhase::ProcessStart => "Process start",
+        }
+    }
+}
+
+impl InstanceStats {
+    pub const MAX_RECENT_SESSIONS: usize = 10;
+
+    pub fn start_session(&mut self, started_unix_ms: i64) {
+        self.session_count += 1;
+        self.last_played_unix_ms = Some(started_unix_ms);
+    }
+
+    pub fn end_session(&mut self, session: InstanceSession) {
+        self.total_playtime_secs += session.duration_secs;
+        self.recent_sessions.insert(0, session);
+        self.recent_sessions.truncate(Self::MAX_RECENT_SESSIONS);
+    }
 }
 
 #[derive(Serialize, Deserialize, Debug, Copy, Clone)]
@@ -115,6 +300,70 @@ fn is_default_jvm_flags_configuration(config: &Option<InstanceJvmFlagsConfigurat
     }
 }
 
+#[derive(Serialize, Deserialize, Debug, Copy, Clone)]
+pub struct InstanceWindowConfiguration {
+    pub custom_size: bool,
+    pub width: u32,
+    pub height: u32,
+    pub fullscreen: bool,
+}
+
+impl InstanceWindowConfiguration {
+    pub const DEFAULT_WIDTH: u32 = 854;
+    pub const DEFAULT_HEIGHT: u32 = 480;
+}
+
+impl Default for InstanceWindowConfiguration {
+    fn default() -> Self {
+        Self {
+            custom_size: false,
+            width: Self::DEFAULT_WIDTH,
+            height: Self::DEFAULT_HEIGHT,
+            fullscreen: false,
+        }
+    }
+}
+
+fn is_default_window_configuration(config: &Option<InstanceWindowConfiguration>) -> bool {
+    if let Some(config) = config {
+        !config.custom_size
+            && !config.fullscreen
+            && config.width == InstanceWindowConfiguration::DEFAULT_WIDTH
+            && config.height == InstanceWindowConfiguration::DEFAULT_HEIGHT
+    } else {
+        true
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InstanceGameArgumentsConfiguration {
+    pub enabled: bool,
+    pub arguments: Arc<str>,
+}
+
+fn is_default_game_arguments_configuration(config: &Option<InstanceGameArgumentsConfiguration>) -> bool {
+    if let Some(config) = config {
+        !config.enabled && config.arguments.trim_ascii().is_empty()
+    } else {
+        true
+    }
+}
+
+/// Commands run before the game is started and after it exits
+#[derive(Serialize, Deserialize, Debug, Clone, Default)]
+pub struct InstanceHooksConfiguration {
+    pub pre_launch: Arc<str>,
+    pub post_exit: Arc<str>,
+}
+
+fn is_default_hooks_configuration(config: &Option<InstanceHooksConfiguration>) -> bool {
+    if let Some(config) = config {
+        config.pre_launch.trim_ascii().is_empty() && config.post_exit.trim_ascii().is_empty()
+    } else {
+        true
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, Default)]
 pub struct InstanceJvmBinaryConfiguration {
     pub enabled: bool,
@@ -234,3 +483,23 @@ fn get_shared_library_path_for_name(name: &str) -> Option<Arc<Path>> {
 
     None
 }
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum InstanceSnapshotReason {
+    Manual,
+    UpdateContent,
+    MinecraftVersion,
+    Loader,
+    Modpack,
+    Restore,
+    Lockfile,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct InstanceSnapshotInfo {
+    pub created_unix_ms: i64,
+    pub reason: InstanceSnapshotReason,
+    /// Hash of the snapshotted files, used to avoid taking identical snapshots
+    pub fingerprint: Arc<str>,
+}
@@ -0,0 +1,33 @@
+use std::sync::Arc;
+
+use serde::{Deserialize, Serialize};
+use ustr::Ustr;
+
+use crate::{auxiliary::AuxDisabledChildren, content::ContentSource, loader::Loader};
+
+/// Contents of `pandora.lock.json`, a reproducible manifest of an instance's content
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceLock {
+    pub minecraft_version: Ustr,
+    pub loader: Loader,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none")]
+    pub loader_version: Option<Ustr>,
+    #[serde(default)]
+    pub files: Vec<InstanceLockFile>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InstanceLockFile {
+    /// Path relative to `.minecraft`, without the `.disabled` suffix
+    pub path: Arc<str>,
+    pub sha1: Arc<str>,
+    pub size: u64,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub source: ContentSource,
+    #[serde(default = "crate::default_true")]
+    pub enabled: bool,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none")]
+    pub curseforge_file_id: Option<u32>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_default", deserialize_with = "crate::try_deserialize")]
+    pub disabled_children: AuxDisabledChildren,
+}
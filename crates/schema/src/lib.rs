@@ -11,6 +11,7 @@ pub mod fabric_mod;
 pub mod forge;
 pub mod forge_mod;
 pub mod instance;
+pub mod instance_lock;
 pub mod java_runtime_component;
 pub mod java_runtimes;
 pub mod loader;
@@ -20,6 +21,9 @@ pub mod modification;
 pub mod modrinth;
 pub mod mrpack;
 pub mod pandora_update;
+pub mod quilt_launch;
+pub mod quilt_loader_manifest;
+pub mod quilt_mod;
 pub mod resourcepack;
 pub mod server_status;
 pub mod text_component;
@@ -34,6 +38,18 @@ where
     Ok(T::deserialize(serde_json::Value::deserialize(deserializer)?).unwrap_or_default())
 }
 
+/// Deserializes a list, skipping the entries that don't match `T` instead of dropping the whole list
+pub fn try_deserialize_each<'de, T, D>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    T: Deserialize<'de>,
+    D: serde::Deserializer<'de>,
+{
+    let serde_json::Value::Array(values) = serde_json::Value::deserialize(deserializer)? else {
+        return Ok(Vec::new());
+    };
+    Ok(values.into_iter().filter_map(|value| T::deserialize(value).ok()).collect())
+}
+
 pub fn skip_if_default<T: Default + PartialEq>(value: &T) -> bool {
     value == &T::default()
 }
@@ -1,20 +1,21 @@
 use std::sync::Arc;
 
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::modrinth::{ModrinthHashes, ModrinthSideRequirement};
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "camelCase")]
 pub struct ModrinthModpackFileDownload {
     pub path: Arc<str>,
     pub hashes: ModrinthHashes,
+    #[serde(skip_serializing_if = "crate::skip_if_none")]
     pub env: Option<ModrinthEnv>,
     pub downloads: Arc<[Arc<str>]>,
     pub file_size: usize,
 }
 
-#[derive(Debug, Clone, Copy, Deserialize)]
+#[derive(Debug, Clone, Copy, Deserialize, Serialize)]
 pub struct ModrinthEnv {
     pub client: ModrinthSideRequirement,
 }
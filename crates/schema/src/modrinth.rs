@@ -1,4 +1,4 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use serde::{Deserialize, Serialize};
 use ustr::Ustr;
@@ -66,7 +66,7 @@ pub struct ModrinthHit {
     // pub featured_gallery: Option<Arc<str>>,
 }
 
-#[derive(PartialEq, Eq, Debug, Copy, Clone, Deserialize)]
+#[derive(PartialEq, Eq, Debug, Copy, Clone, Deserialize, Serialize)]
 #[serde(rename_all = "lowercase")]
 pub enum ModrinthSideRequirement {
     Required,
@@ -143,6 +143,7 @@ pub enum ModrinthLoader {
     Fabric,
     Forge,
     NeoForge,
+    Quilt,
     // Resourcepacks
     Minecraft,
     // Shaders
@@ -159,7 +160,7 @@ pub enum ModrinthLoader {
 impl ModrinthLoader {
     pub fn install_directory(self) -> Option<&'static str> {
         match self {
-            ModrinthLoader::Fabric | ModrinthLoader::Forge | ModrinthLoader::NeoForge => Some("mods"),
+            ModrinthLoader::Fabric | ModrinthLoader::Forge | ModrinthLoader::NeoForge | ModrinthLoader::Quilt => Some("mods"),
             ModrinthLoader::Minecraft => Some("resourcepacks"),
             ModrinthLoader::Iris | ModrinthLoader::Optifine => Some("shaderpacks"),
             ModrinthLoader::Canvas => Some("resourcepacks"),
@@ -173,6 +174,7 @@ impl ModrinthLoader {
             Self::Fabric => "Fabric",
             Self::Forge => "Forge",
             Self::NeoForge => "NeoForge",
+            Self::Quilt => "Quilt",
             Self::Minecraft => "Minecraft",
             Self::Iris => "Iris",
             Self::Optifine => "Optifine",
@@ -187,6 +189,7 @@ impl ModrinthLoader {
             Self::Fabric => "fabric",
             Self::Forge => "forge",
             Self::NeoForge => "neoforge",
+            Self::Quilt => "quilt",
             Self::Minecraft => "minecraft",
             Self::Iris => "iris",
             Self::Optifine => "optifine",
@@ -201,6 +204,7 @@ impl ModrinthLoader {
             "Fabric" | "fabric" => Self::Fabric,
             "Forge" | "forge" => Self::Forge,
             "NeoForge" | "neoforge" => Self::NeoForge,
+            "Quilt" | "quilt" => Self::Quilt,
             "Minecraft" | "minecraft" => Self::Minecraft,
             "Iris" | "iris" => Self::Iris,
             "Optifine" | "optifine" => Self::Optifine,
@@ -242,14 +246,34 @@ pub struct ModrinthFile {
     pub size: usize,
 }
 
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct ModrinthHashes {
     pub sha1: Arc<str>,
+    #[serde(default, skip_serializing_if = "crate::skip_if_none")]
+    pub sha512: Option<Arc<str>>,
 }
 
 #[derive(Debug, Clone, Deserialize)]
 pub struct ModrinthVersionFileUpdateResult(pub ModrinthProjectVersion);
 
+pub const MODRINTH_VERSION_FILES_URL: &str = "https://api.modrinth.com/v2/version_files";
+
+#[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct ModrinthVersionFilesRequest {
+    pub hashes: Arc<[Arc<str>]>,
+    pub algorithm: ModrinthHashAlgorithm,
+}
+
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModrinthHashAlgorithm {
+    Sha1,
+    Sha512,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct ModrinthVersionFilesResult(pub HashMap<Arc<str>, ModrinthProjectVersion>);
+
 pub const MODRINTH_PROJECT_URL: &str = "https://api.modrinth.com/v2/project";
 
 #[derive(Debug, Clone, Hash, PartialEq, Eq, PartialOrd, Ord, Serialize)]
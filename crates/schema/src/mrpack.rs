@@ -1,7 +1,7 @@
 use std::sync::Arc;
 
 use indexmap::IndexMap;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 use crate::{fabric_mod::Person, modification::ModrinthModpackFileDownload};
 
@@ -20,3 +20,16 @@ pub struct ModrinthIndexJson {
     #[serde(default, deserialize_with = "crate::try_deserialize")]
     pub author: Option<Person>,
 }
+
+#[derive(Serialize, Debug)]
+#[serde(rename_all = "camelCase")]
+pub struct ModrinthIndexJsonOutput {
+    pub format_version: u32,
+    pub game: &'static str,
+    pub version_id: Arc<str>,
+    pub name: Arc<str>,
+    #[serde(skip_serializing_if = "crate::skip_if_none")]
+    pub summary: Option<Arc<str>>,
+    pub files: Vec<ModrinthModpackFileDownload>,
+    pub dependencies: IndexMap<&'static str, Arc<str>>,
+}
@@ -0,0 +1,50 @@
+use std::sync::Arc;
+
+use serde::Deserialize;
+use ustr::Ustr;
+
+use crate::quilt_loader_manifest::QuiltLoaderVersion;
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLaunch {
+    pub loader: Option<QuiltLoaderVersion>,
+    pub hashed: Option<QuiltMappingsVersion>,
+    pub intermediary: Option<QuiltMappingsVersion>,
+    #[serde(rename = "launcherMeta")]
+    pub launcher_meta: QuiltLaunchLauncherMeta,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltMappingsVersion {
+    pub maven: Ustr,
+    pub version: Ustr,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLaunchLauncherMeta {
+    pub version: u32,
+    pub libraries: QuiltLaunchLibraries,
+    #[serde(rename = "mainClass")]
+    pub main_class: QuiltLaunchMainClasses,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLaunchLibraries {
+    pub client: Arc<[QuiltLaunchLibrary]>,
+    pub common: Arc<[QuiltLaunchLibrary]>,
+    pub server: Arc<[QuiltLaunchLibrary]>,
+    #[serde(default)]
+    pub development: Arc<[QuiltLaunchLibrary]>,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLaunchLibrary {
+    pub name: Ustr,
+    pub url: Ustr,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLaunchMainClasses {
+    pub client: Ustr,
+    pub server: Ustr,
+}
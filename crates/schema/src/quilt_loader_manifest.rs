@@ -0,0 +1,22 @@
+use serde::Deserialize;
+use ustr::Ustr;
+
+pub const QUILT_LOADER_MANIFEST_URL: &str = "https://meta.quiltmc.org/v3/versions/loader";
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLoaderManifest(pub Vec<QuiltLoaderVersion>);
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLoaderVersion {
+    pub separator: Ustr,
+    pub build: usize,
+    pub maven: Ustr,
+    pub version: Ustr,
+}
+
+impl QuiltLoaderVersion {
+    // Quilt meta doesn't provide a stable flag, pre-releases are marked with a suffix (e.g. 0.30.0-beta.5)
+    pub fn is_stable(&self) -> bool {
+        !self.version.contains('-')
+    }
+}
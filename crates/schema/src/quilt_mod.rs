@@ -0,0 +1,137 @@
+use std::sync::Arc;
+
+use indexmap::IndexMap;
+use serde::Deserialize;
+
+use crate::fabric_mod::Icon;
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltModJson {
+    pub schema_version: u32,
+    pub quilt_loader: QuiltLoaderSection,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct QuiltLoaderSection {
+    pub id: Arc<str>,
+    pub version: Arc<str>,
+    #[serde(default)]
+    pub metadata: QuiltModMetadata,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub provides: Vec<QuiltProvides>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub jars: Vec<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize_each")]
+    pub depends: Vec<QuiltDependency>,
+    #[serde(default, deserialize_with = "crate::try_deserialize_each")]
+    pub breaks: Vec<QuiltDependency>,
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum QuiltProvides {
+    Id(Arc<str>),
+    Object {
+        id: Arc<str>,
+        version: Option<Arc<str>>,
+    },
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum QuiltDependency {
+    Id(Arc<str>),
+    Object {
+        id: Arc<str>,
+        #[serde(default)]
+        versions: Option<QuiltDependencyVersions>,
+        #[serde(default)]
+        optional: bool,
+    },
+    /// Alternatives, any one of them satisfies the dependency
+    AnyOf(Vec<QuiltDependency>),
+}
+
+#[derive(Deserialize, Debug)]
+#[serde(untagged)]
+pub enum QuiltDependencyVersions {
+    Single(Arc<str>),
+    AnyOf(Vec<Arc<str>>),
+    /// `{ "all": [...] }` / `{ "any": [...] }` objects aren't evaluated
+    Other(serde_json::Value),
+}
+
+#[derive(Deserialize, Debug, Default)]
+pub struct QuiltModMetadata {
+    pub name: Option<Arc<str>>,
+    // pub description: Option<Arc<str>>,
+    #[serde(default, deserialize_with = "crate::try_deserialize")]
+    pub contributors: IndexMap<Arc<str>, serde_json::Value>,
+    pub icon: Option<Icon>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // From Quilt Standard Libraries' qsl_base, with an alternatives entry and an invalid entry added
+    const QUILT_MOD_JSON: &str = r#"{
+        "schema_version": 1,
+        "quilt_loader": {
+            "group": "org.quiltmc.qsl.core",
+            "id": "quilt_base",
+            "version": "7.0.0+0.92.0-1.20.1",
+            "metadata": {
+                "name": "Quilt Base API",
+                "description": "The base module of the Quilt Standard Libraries.",
+                "contributors": {
+                    "The Quilt Project": "Owner"
+                },
+                "icon": "assets/quilt_base/icon.png"
+            },
+            "intermediate_mappings": "net.fabricmc:intermediary",
+            "load_type": "always",
+            "provides": [
+                "quilted_fabric_api_base"
+            ],
+            "depends": [
+                {
+                    "id": "quilt_loader",
+                    "versions": ">=0.19.1"
+                },
+                "minecraft",
+                [
+                    { "id": "fabric-api", "versions": ">=0.83.0" },
+                    { "id": "quilted_fabric_api" }
+                ],
+                { "versions": "missing id" }
+            ],
+            "breaks": [
+                { "id": "sodium", "versions": "<0.4.0", "reason": "Incompatible rendering changes" }
+            ]
+        },
+        "mixin": "quilt_base.mixins.json"
+    }"#;
+
+    #[test]
+    fn test_quilt_mod_dependencies() {
+        let quilt_mod: QuiltModJson = serde_json::from_str(QUILT_MOD_JSON).unwrap();
+        let loader = quilt_mod.quilt_loader;
+
+        assert_eq!(&*loader.id, "quilt_base");
+        assert_eq!(loader.metadata.name.as_deref(), Some("Quilt Base API"));
+        assert!(matches!(&loader.provides[..], [QuiltProvides::Id(id)] if &**id == "quilted_fabric_api_base"));
+
+        assert_eq!(loader.depends.len(), 3);
+        assert!(matches!(&loader.depends[0], QuiltDependency::Object { id, versions: Some(QuiltDependencyVersions::Single(versions)), optional: false }
+            if &**id == "quilt_loader" && &**versions == ">=0.19.1"));
+        assert!(matches!(&loader.depends[1], QuiltDependency::Id(id) if &**id == "minecraft"));
+        let QuiltDependency::AnyOf(alternatives) = &loader.depends[2] else {
+            panic!("expected alternatives");
+        };
+        assert_eq!(alternatives.len(), 2);
+        assert!(matches!(&alternatives[1], QuiltDependency::Object { id, versions: None, .. } if &**id == "quilted_fabric_api"));
+
+        assert!(matches!(&loader.breaks[..], [QuiltDependency::Object { id, .. }] if &**id == "sodium"));
+    }
+}
@@ -1,12 +1,12 @@
 use std::{collections::HashMap, sync::Arc};
 
 use relative_path::RelativePathBuf;
-use serde::{Deserialize, Deserializer};
+use serde::{Deserialize, Deserializer, Serialize};
 use ustr::Ustr;
 
 use crate::version_manifest::MinecraftVersionType;
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct MinecraftVersion {
@@ -28,14 +28,15 @@ pub struct MinecraftVersion {
     pub r#type: MinecraftVersionType,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct LaunchArguments {
     pub game: Arc<[LaunchArgument]>,
     pub jvm: Arc<[LaunchArgument]>,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Serialize, Clone, Debug)]
+#[serde(untagged)]
 pub enum LaunchArgument {
     Single(LaunchArgumentValue),
     Ruled(LaunchArgumentRuled),
@@ -54,21 +55,21 @@ impl<'de> Deserialize<'de> for LaunchArgument {
     }
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct LaunchArgumentRuled {
     pub rules: Arc<[Rule]>,
     pub value: LaunchArgumentValue,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(untagged)]
 pub enum LaunchArgumentValue {
     Single(Ustr),
     Multiple(Arc<[Ustr]>),
 }
 
-#[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct Rule {
     pub action: RuleAction,
@@ -76,14 +77,14 @@ pub struct Rule {
     pub os: Option<RuleOs>,
 }
 
-#[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum RuleAction {
     Allow,
     Disallow,
 }
 
-#[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct RuleFeatures {
     #[serde(default)]
@@ -100,7 +101,7 @@ pub struct RuleFeatures {
     pub is_quick_play_realms: bool,
 }
 
-#[derive(Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct RuleOs {
     pub name: Option<OsName>,
@@ -109,7 +110,7 @@ pub struct RuleOs {
     pub version: Option<Ustr>,
 }
 
-#[derive(Deserialize, Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum OsName {
     Linux,
@@ -117,14 +118,14 @@ pub enum OsName {
     Windows,
 }
 
-#[derive(Deserialize, Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, Hash, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum OsArch {
     Arm64,
     X86,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct AssetIndexLink {
@@ -135,7 +136,7 @@ pub struct AssetIndexLink {
     pub url: Ustr,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameDownloads {
     pub client: VersionDownloadLink,
@@ -146,7 +147,7 @@ pub struct GameDownloads {
     pub windows_server: Option<VersionDownloadLink>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct VersionDownloadLink {
     pub sha1: Ustr,
@@ -154,7 +155,7 @@ pub struct VersionDownloadLink {
     pub url: Ustr,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 #[serde(rename_all = "camelCase")]
 pub struct JavaVersion {
@@ -162,7 +163,7 @@ pub struct JavaVersion {
     pub major_version: u32,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibrary {
     pub downloads: GameLibraryDownloads,
@@ -177,7 +178,7 @@ pub struct GameLibrary {
     pub extract: Option<GameLibraryExtractOptions>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibraryDownloads {
     pub artifact: Option<GameLibraryArtifact>,
@@ -186,7 +187,7 @@ pub struct GameLibraryDownloads {
     pub classifiers: Option<HashMap<Ustr, GameLibraryArtifact>>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibraryArtifact {
     pub path: Ustr, // todo: this should be a safepath to avoid traversal?
@@ -195,19 +196,19 @@ pub struct GameLibraryArtifact {
     pub url: Ustr,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLibraryExtractOptions {
     pub exclude: Option<Arc<[RelativePathBuf]>>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLogging {
     pub client: Option<GameLoggingTarget>,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLoggingTarget {
     pub argument: Ustr,
@@ -215,7 +216,7 @@ pub struct GameLoggingTarget {
     pub r#type: GameLoggingType,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub struct GameLoggingFile {
     pub id: Ustr,
@@ -224,14 +225,14 @@ pub struct GameLoggingFile {
     pub url: Ustr,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[cfg_attr(debug_assertions, serde(deny_unknown_fields))]
 pub enum GameLoggingType {
     #[serde(rename = "log4j2-xml")]
     Log4j2Xml,
 }
 
-#[derive(Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug)]
 #[serde(rename_all = "camelCase")]
 pub struct PartialMinecraftVersion {
     pub inherits_from: Option<Ustr>,
@@ -315,3 +316,68 @@ impl PartialMinecraftVersion {
         version
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VERSION_JSON: &str = r#"{
+        "arguments": {
+            "game": [
+                "--username",
+                "${auth_player_name}",
+                {
+                    "rules": [{ "action": "allow", "features": { "has_custom_resolution": true } }],
+                    "value": ["--width", "${resolution_width}"]
+                }
+            ],
+            "jvm": [
+                {
+                    "rules": [{ "action": "allow", "os": { "name": "osx" } }],
+                    "value": "-XstartOnFirstThread"
+                },
+                "-cp",
+                "${classpath}"
+            ]
+        },
+        "assetIndex": { "id": "17", "sha1": "aaaa", "size": 1, "totalSize": 2, "url": "https://example.com/17.json" },
+        "assets": "17",
+        "complianceLevel": 1,
+        "downloads": { "client": { "sha1": "bbbb", "size": 3, "url": "https://example.com/client.jar" } },
+        "id": "1.21",
+        "javaVersion": { "component": "java-runtime-delta", "majorVersion": 21 },
+        "libraries": [
+            {
+                "downloads": { "artifact": { "path": "a/b/c.jar", "sha1": "cccc", "size": 4, "url": "https://example.com/c.jar" } },
+                "name": "a:b:c",
+                "rules": [{ "action": "allow", "os": { "name": "linux", "arch": "arm64" } }]
+            }
+        ],
+        "logging": {
+            "client": {
+                "argument": "-Dlog4j.configurationFile=${path}",
+                "file": { "id": "client-1.12.xml", "sha1": "dddd", "size": 5, "url": "https://example.com/client-1.12.xml" },
+                "type": "log4j2-xml"
+            }
+        },
+        "mainClass": "net.minecraft.client.main.Main",
+        "minimumLauncherVersion": 21,
+        "releaseTime": "2024-06-13T08:24:03+00:00",
+        "time": "2024-06-13T08:24:03+00:00",
+        "type": "release"
+    }"#;
+
+    #[test]
+    fn test_version_round_trip() {
+        let version: MinecraftVersion = serde_json::from_str(VERSION_JSON).unwrap();
+        let serialized = serde_json::to_value(&version).unwrap();
+        let round_tripped: MinecraftVersion = serde_json::from_value(serialized.clone()).unwrap();
+        assert_eq!(serde_json::to_value(&round_tripped).unwrap(), serialized);
+
+        let arguments = round_tripped.arguments.unwrap();
+        assert!(matches!(&arguments.game[0], LaunchArgument::Single(LaunchArgumentValue::Single(value)) if value.as_str() == "--username"));
+        assert!(matches!(&arguments.game[2], LaunchArgument::Ruled(ruled) if matches!(&ruled.value, LaunchArgumentValue::Multiple(values) if values.len() == 2)));
+        assert!(matches!(&arguments.jvm[0], LaunchArgument::Ruled(ruled) if ruled.rules[0].os.as_ref().and_then(|os| os.name) == Some(OsName::Osx)));
+        assert_eq!(round_tripped.libraries[0].rules.as_ref().unwrap()[0].os.as_ref().unwrap().arch, Some(OsArch::Arm64));
+    }
+}